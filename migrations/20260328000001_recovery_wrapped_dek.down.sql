@@ -0,0 +1,4 @@
+ALTER TABLE users
+    DROP CONSTRAINT IF EXISTS users_recovery_wrap_complete,
+    DROP COLUMN IF EXISTS recovery_wrap_params,
+    DROP COLUMN IF EXISTS recovery_wrapped_dek;
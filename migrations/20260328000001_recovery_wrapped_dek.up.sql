@@ -0,0 +1,21 @@
+-- Recovery-key wrapping of the per-user DEK
+--
+-- `wrapped_dek` is encrypted under a KEK derived from the user's password,
+-- so a forgotten-password reset used to leave every `*_enc` column
+-- unreadable. These columns hold a second, independent wrapping of the
+-- same DEK under a KEK derived from a user-held recovery key (generated
+-- and shown once by the client, never sent to the server).
+--
+-- During a reset the client fetches this wrapping with the reset token,
+-- unwraps the DEK with the recovery key, re-wraps it under the new
+-- password and submits the new `wrapped_dek` alongside the new password.
+--
+-- `recovery_wrap_params` mirrors `dek_wrap_params`: an opaque JSON object
+-- with the KDF parameters, salt and wrap nonce. Both columns are NULL
+-- until the user sets up a recovery key.
+
+ALTER TABLE users
+    ADD COLUMN recovery_wrapped_dek BYTEA,
+    ADD COLUMN recovery_wrap_params JSONB,
+    ADD CONSTRAINT users_recovery_wrap_complete
+        CHECK ((recovery_wrapped_dek IS NULL) = (recovery_wrap_params IS NULL));
@@ -0,0 +1,13 @@
+CREATE OR REPLACE FUNCTION transaction_immutability_guard()
+    RETURNS TRIGGER
+    LANGUAGE plpgsql AS
+$$
+BEGIN
+    IF current_setting('piggy_pulse.allow_ledger_mutations', true) = 'on' THEN
+        RETURN COALESCE(OLD, NEW);
+    END IF;
+    RAISE EXCEPTION 'ledger rows are immutable';
+END;
+$$;
+
+DROP TABLE IF EXISTS dek_rotation;
@@ -0,0 +1,44 @@
+-- In-progress DEK rotations
+--
+-- `POST /v2/auth/rotate-dek` re-encrypts every `*_enc` column a user owns
+-- from the old DEK to a new one in committed batches, then swaps
+-- `users.wrapped_dek`. If the request dies part-way, some rows are under
+-- the new key and some under the old one; this row records the rotation so
+-- it can be resumed, and pins which new DEK it is rotating to.
+--
+-- new_wrapped_dek / new_dek_wrap_params are the client's wrap of the new
+-- DEK, kept here so a client that lost the new DEK mid-rotation can
+-- unwrap it again. new_dek_check is a fixed plaintext encrypted under the
+-- new DEK; a resumed request must present a DEK that decrypts it, so a
+-- rotation can never end up spread across two different new keys.
+--
+-- The row is deleted in the same transaction that swaps users.wrapped_dek.
+
+CREATE TABLE dek_rotation (
+    user_id             UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    new_wrapped_dek     BYTEA NOT NULL,
+    new_dek_wrap_params JSONB NOT NULL,
+    new_dek_check       BYTEA NOT NULL,
+    started_at          TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at          TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Rotation rewrites ledger ciphertext in place under the
+-- `piggy_pulse.allow_ledger_mutations` bypass. The bypassed guard returned
+-- COALESCE(OLD, NEW), which is right for DELETE but turns a BEFORE UPDATE
+-- into a silent no-op (the row keeps its OLD values). Return NEW for
+-- UPDATE so bypassed updates actually apply.
+CREATE OR REPLACE FUNCTION transaction_immutability_guard()
+    RETURNS TRIGGER
+    LANGUAGE plpgsql AS
+$$
+BEGIN
+    IF current_setting('piggy_pulse.allow_ledger_mutations', true) = 'on' THEN
+        IF TG_OP = 'DELETE' THEN
+            RETURN OLD;
+        END IF;
+        RETURN NEW;
+    END IF;
+    RAISE EXCEPTION 'ledger rows are immutable';
+END;
+$$;
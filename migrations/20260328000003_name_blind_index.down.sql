@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS vendor_user_id_name_bidx_key;
+DROP INDEX IF EXISTS category_user_id_name_bidx_key;
+DROP INDEX IF EXISTS account_user_id_name_bidx_key;
+
+ALTER TABLE vendor DROP COLUMN IF EXISTS name_bidx;
+ALTER TABLE category DROP COLUMN IF EXISTS name_bidx;
+ALTER TABLE account DROP COLUMN IF EXISTS name_bidx;
@@ -0,0 +1,20 @@
+-- Blind indexes for entity names
+--
+-- Name uniqueness per user was dropped with the plaintext name columns
+-- (20260327000008) and enforced by decrypting every name inside the write
+-- transaction. name_bidx holds HMAC-SHA256 of the lowercased name under a
+-- key derived from the user's DEK, so Postgres can enforce uniqueness and
+-- answer exact-name lookups without seeing the name.
+--
+-- Existing rows start NULL (the server has no DEK at migration time) and
+-- are backfilled the next time the user writes or looks up a name in that
+-- table. NULLs are distinct, so they never collide. DEK rotation clears the
+-- column for the same reason: the index key changes with the DEK.
+
+ALTER TABLE account ADD COLUMN name_bidx BYTEA;
+ALTER TABLE category ADD COLUMN name_bidx BYTEA;
+ALTER TABLE vendor ADD COLUMN name_bidx BYTEA;
+
+CREATE UNIQUE INDEX account_user_id_name_bidx_key ON account (user_id, name_bidx);
+CREATE UNIQUE INDEX category_user_id_name_bidx_key ON category (user_id, name_bidx);
+CREATE UNIQUE INDEX vendor_user_id_name_bidx_key ON vendor (user_id, name_bidx);
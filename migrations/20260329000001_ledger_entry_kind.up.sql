@@ -0,0 +1,44 @@
+-- Ledger entry kinds
+--
+-- Every ledger row is one of:
+--   original    first row of a logical transaction
+--   reversal    brings the running sum to zero ahead of a correction
+--   correction  carries the edited metadata and amount
+--   void        compensating row written by a delete
+--
+-- Amounts are encrypted, so the kind cannot be read off the row's sign; it
+-- is recorded explicitly on insert. Existing rows are classified from the
+-- shape the write paths leave behind: an update writes its reversal and
+-- correction in one database transaction, so both share `created_at`
+-- (now() is the transaction start time), while a delete writes a single
+-- row on its own.
+
+ALTER TABLE transaction ADD COLUMN entry_kind TEXT;
+
+SET LOCAL piggy_pulse.allow_ledger_mutations = 'on';
+
+WITH ordered AS (
+    SELECT id,
+           seq,
+           created_at,
+           row_number() OVER w AS n,
+           lag(created_at) OVER w AS prev_created_at,
+           lead(created_at) OVER w AS next_created_at
+    FROM transaction
+    WINDOW w AS (PARTITION BY id ORDER BY seq)
+)
+UPDATE transaction t
+SET entry_kind = CASE
+        WHEN o.n = 1 THEN 'original'
+        WHEN o.next_created_at = o.created_at THEN 'reversal'
+        WHEN o.prev_created_at = o.created_at THEN 'correction'
+        ELSE 'void'
+    END
+FROM ordered o
+WHERE t.id = o.id
+  AND t.seq = o.seq;
+
+ALTER TABLE transaction ALTER COLUMN entry_kind SET NOT NULL;
+ALTER TABLE transaction
+    ADD CONSTRAINT transaction_entry_kind_check
+        CHECK (entry_kind IN ('original', 'reversal', 'correction', 'void'));
@@ -0,0 +1,4 @@
+ALTER TABLE transaction DROP CONSTRAINT transaction_entry_kind_check;
+ALTER TABLE transaction
+    ADD CONSTRAINT transaction_entry_kind_check
+        CHECK (entry_kind IN ('original', 'reversal', 'correction', 'void'));
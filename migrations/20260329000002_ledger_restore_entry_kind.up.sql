@@ -0,0 +1,7 @@
+-- Restoring a deleted transaction appends a row that re-applies the
+-- amount its void cancelled.
+
+ALTER TABLE transaction DROP CONSTRAINT transaction_entry_kind_check;
+ALTER TABLE transaction
+    ADD CONSTRAINT transaction_entry_kind_check
+        CHECK (entry_kind IN ('original', 'reversal', 'correction', 'void', 'restore'));
@@ -0,0 +1,30 @@
+-- Split lines
+--
+-- A ledger row may allocate its amount across several categories. Each
+-- line belongs to exactly one ledger row (id, seq) and, like the row, is
+-- never rewritten: a correction writes fresh lines on its correction row,
+-- and compensating rows (reversal, void, restore) carry the negation of
+-- the lines they cancel, so every row's lines sum to that row's amount.
+--
+-- `amount_enc` and `memo_enc` are bound to the line's own `id`, which
+-- keeps the table rotatable by the same id-keyed sweep as the other
+-- encrypted tables.
+
+CREATE TABLE transaction_split (
+    id             UUID     PRIMARY KEY,
+    user_id        UUID     NOT NULL REFERENCES users (id) ON DELETE RESTRICT,
+    transaction_id UUID     NOT NULL,
+    seq            BIGINT   NOT NULL,
+    position       SMALLINT NOT NULL,
+    category_id    UUID     NOT NULL REFERENCES category (id) ON DELETE CASCADE,
+    amount_enc     BYTEA    NOT NULL,
+    memo_enc       BYTEA,
+    FOREIGN KEY (transaction_id, seq) REFERENCES transaction (id, seq) ON DELETE CASCADE,
+    UNIQUE (transaction_id, seq, position)
+);
+
+CREATE INDEX idx_transaction_split_user_category ON transaction_split (user_id, category_id);
+
+CREATE TRIGGER transaction_split_immutability
+    BEFORE UPDATE OR DELETE ON transaction_split
+    FOR EACH ROW EXECUTE FUNCTION transaction_immutability_guard();
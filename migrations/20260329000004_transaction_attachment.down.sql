@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS attachment_chunk;
+DROP TABLE IF EXISTS attachment;
@@ -0,0 +1,36 @@
+-- Transaction attachments
+--
+-- Receipts and invoices linked to a logical transaction. Each attachment
+-- has its own random content key, sealed with the user's DEK in
+-- `content_key_enc`; the file is encrypted under that key in fixed-size
+-- chunks, so DEK rotation only re-seals the key and never touches the
+-- chunks.
+--
+-- Chunks live either in `attachment_chunk` or in a single file at
+-- `blob_path`, depending on the storage backend at upload time.
+
+CREATE TABLE attachment (
+    id               UUID        PRIMARY KEY,
+    user_id          UUID        NOT NULL REFERENCES users (id) ON DELETE RESTRICT,
+    transaction_id   UUID        NOT NULL REFERENCES logical_transaction_state (id) ON DELETE CASCADE,
+    file_name_enc    BYTEA       NOT NULL,
+    content_type_enc BYTEA       NOT NULL,
+    content_key_enc  BYTEA       NOT NULL,
+    size_bytes       BIGINT      NOT NULL CHECK (size_bytes > 0),
+    chunk_count      INTEGER     NOT NULL CHECK (chunk_count > 0),
+    storage          TEXT        NOT NULL CHECK (storage IN ('postgres', 'filesystem')),
+    blob_path        TEXT,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK ((storage = 'filesystem') = (blob_path IS NOT NULL))
+);
+
+CREATE INDEX idx_attachment_user_transaction ON attachment (user_id, transaction_id, created_at);
+
+-- Encrypted under the attachment's content key, not the DEK, so the
+-- column is deliberately not named `*_enc`.
+CREATE TABLE attachment_chunk (
+    attachment_id UUID    NOT NULL REFERENCES attachment (id) ON DELETE CASCADE,
+    idx           INTEGER NOT NULL,
+    ciphertext    BYTEA   NOT NULL,
+    PRIMARY KEY (attachment_id, idx)
+);
@@ -0,0 +1,28 @@
+-- Transaction tags
+--
+-- Free-form labels a user can put on any number of transactions. Like
+-- vendors, the label fields are encrypted and uniqueness per user goes
+-- through the `name_bidx` blind index.
+--
+-- Links are keyed on the logical transaction id, not on a ledger row, so a
+-- correction (which writes new seqs under the same id) keeps its tags.
+
+CREATE TABLE tag (
+    id         UUID        PRIMARY KEY,
+    user_id    UUID        NOT NULL REFERENCES users (id) ON DELETE RESTRICT,
+    name_enc   BYTEA       NOT NULL,
+    name_bidx  BYTEA,
+    color_enc  BYTEA       NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX tag_user_id_name_bidx_key ON tag (user_id, name_bidx);
+
+CREATE TABLE transaction_tag (
+    transaction_id UUID NOT NULL REFERENCES logical_transaction_state (id) ON DELETE CASCADE,
+    tag_id         UUID NOT NULL REFERENCES tag (id) ON DELETE CASCADE,
+    user_id        UUID NOT NULL REFERENCES users (id) ON DELETE RESTRICT,
+    PRIMARY KEY (transaction_id, tag_id)
+);
+
+CREATE INDEX idx_transaction_tag_tag ON transaction_tag (tag_id);
@@ -0,0 +1,4 @@
+ALTER TABLE logical_transaction_state
+    DROP COLUMN IF EXISTS reconciliation_id,
+    DROP COLUMN IF EXISTS cleared_status;
+DROP TABLE IF EXISTS reconciliation;
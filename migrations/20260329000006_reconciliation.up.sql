@@ -0,0 +1,29 @@
+-- Cleared status and statement reconciliation
+--
+-- Each logical transaction is uncleared, cleared (seen on a statement) or
+-- reconciled. The status lives on `logical_transaction_state` so it
+-- survives corrections, which only append ledger rows.
+--
+-- A reconciliation compares an account's cleared balance with a bank
+-- statement's ending balance, stored encrypted. Finishing it marks the
+-- account's cleared transactions reconciled; those refuse correction and
+-- deletion until the user unlocks them. An account has at most one open
+-- reconciliation.
+
+CREATE TABLE reconciliation (
+    id                    UUID        PRIMARY KEY,
+    user_id               UUID        NOT NULL REFERENCES users (id) ON DELETE RESTRICT,
+    account_id            UUID        NOT NULL REFERENCES account (id) ON DELETE CASCADE,
+    statement_date        DATE        NOT NULL,
+    statement_balance_enc BYTEA       NOT NULL,
+    created_at            TIMESTAMPTZ NOT NULL DEFAULT now(),
+    finished_at           TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX reconciliation_open_account_key ON reconciliation (account_id) WHERE finished_at IS NULL;
+CREATE INDEX idx_reconciliation_user_account ON reconciliation (user_id, account_id, created_at);
+
+ALTER TABLE logical_transaction_state
+    ADD COLUMN cleared_status TEXT NOT NULL DEFAULT 'uncleared'
+        CHECK (cleared_status IN ('uncleared', 'cleared', 'reconciled')),
+    ADD COLUMN reconciliation_id UUID REFERENCES reconciliation (id) ON DELETE SET NULL;
@@ -0,0 +1,8 @@
+-- Setting an account balance by hand appends an uncategorized ledger row
+-- carrying the signed difference, so the ledger keeps accounting for the
+-- balance.
+
+ALTER TABLE transaction DROP CONSTRAINT transaction_entry_kind_check;
+ALTER TABLE transaction
+    ADD CONSTRAINT transaction_entry_kind_check
+        CHECK (entry_kind IN ('original', 'reversal', 'correction', 'void', 'restore', 'adjustment'));
@@ -0,0 +1,5 @@
+-- The balance an account was opened with, so its current balance can be
+-- replayed from the ledger. NULL on accounts created before this column;
+-- ledger verification derives and stores it on first run.
+
+ALTER TABLE account ADD COLUMN opening_balance_enc BYTEA;
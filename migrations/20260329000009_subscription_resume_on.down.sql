@@ -0,0 +1,2 @@
+ALTER TABLE subscription DROP CONSTRAINT subscription_resume_on_paused_check;
+ALTER TABLE subscription DROP COLUMN resume_on;
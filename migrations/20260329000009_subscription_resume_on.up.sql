@@ -0,0 +1,7 @@
+-- A paused subscription may carry the date it resumes on; the
+-- advance-subscriptions cron job reactivates it from that day.
+
+ALTER TABLE subscription ADD COLUMN resume_on DATE;
+ALTER TABLE subscription
+    ADD CONSTRAINT subscription_resume_on_paused_check
+        CHECK (resume_on IS NULL OR status = 'paused');
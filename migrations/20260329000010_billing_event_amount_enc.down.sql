@@ -0,0 +1,12 @@
+-- Events recorded since the upgrade have no plaintext amount to fall
+-- back to.
+DELETE FROM subscription_billing_event WHERE amount IS NULL;
+
+DROP INDEX IF EXISTS subscription_billing_event_transaction_key;
+ALTER TABLE subscription_billing_event
+    DROP CONSTRAINT subscription_billing_event_amount_check,
+    DROP COLUMN amount_enc,
+    ALTER COLUMN amount SET NOT NULL;
+
+DROP INDEX IF EXISTS idx_billing_event_user_id;
+ALTER TABLE subscription_billing_event DROP COLUMN user_id;
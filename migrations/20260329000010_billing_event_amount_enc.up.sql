@@ -0,0 +1,33 @@
+-- Subscription billing events
+--
+-- The table predates encryption at rest and still holds its amount in
+-- plaintext. amount_enc is the envelope that replaces it. The server has
+-- no DEK at migration time, so existing rows keep `amount` until the
+-- owner next reads their billing history or exports a backup; exactly
+-- one of the two columns is set on every row.
+--
+-- user_id is denormalised from the subscription so the row fits the
+-- envelope AAD (table, column, id, user) and DEK rotation like every
+-- other encrypted table.
+
+ALTER TABLE subscription_billing_event ADD COLUMN user_id UUID;
+UPDATE subscription_billing_event e
+SET user_id = s.user_id
+FROM subscription s
+WHERE s.id = e.subscription_id;
+ALTER TABLE subscription_billing_event
+    ALTER COLUMN user_id SET NOT NULL,
+    ADD CONSTRAINT subscription_billing_event_user_id_fkey
+        FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE;
+CREATE INDEX idx_billing_event_user_id ON subscription_billing_event (user_id);
+
+ALTER TABLE subscription_billing_event
+    ADD COLUMN amount_enc BYTEA,
+    ALTER COLUMN amount DROP NOT NULL,
+    ADD CONSTRAINT subscription_billing_event_amount_check
+        CHECK ((amount IS NULL) <> (amount_enc IS NULL));
+
+-- A transaction is billed to a subscription at most once.
+CREATE UNIQUE INDEX subscription_billing_event_transaction_key
+    ON subscription_billing_event (subscription_id, transaction_id)
+    WHERE transaction_id IS NOT NULL;
@@ -0,0 +1,2 @@
+ALTER TABLE subscription DROP COLUMN last_posted_on;
+ALTER TABLE subscription DROP COLUMN auto_post_account_id;
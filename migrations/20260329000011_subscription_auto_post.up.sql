@@ -0,0 +1,9 @@
+-- A subscription with an auto-post account has its due charges posted to
+-- that account as ledger transactions the next time its owner unlocks a
+-- session. The cron job has no DEK and leaves these subscriptions'
+-- next_charge_date alone: it stays on the first charge not yet posted.
+-- last_posted_on is the date of the most recent posted charge.
+
+ALTER TABLE subscription
+    ADD COLUMN auto_post_account_id UUID REFERENCES account (id) ON DELETE SET NULL,
+    ADD COLUMN last_posted_on DATE;
@@ -0,0 +1,4 @@
+ALTER TABLE dek_rotation
+    DROP CONSTRAINT IF EXISTS dek_rotation_recovery_wrap_complete,
+    DROP COLUMN IF EXISTS new_recovery_wrap_params,
+    DROP COLUMN IF EXISTS new_recovery_wrapped_dek;
@@ -0,0 +1,15 @@
+-- Recovery-key wrap of the new DEK during a rotation
+--
+-- `users.recovery_wrapped_dek` wraps the DEK itself, so a rotation that
+-- only swapped `wrapped_dek` left the recovery key unwrapping the retired
+-- key. The client now sends a recovery wrap of the new DEK with the
+-- rotation; it is staged here and swapped in the same UPDATE as
+-- `wrapped_dek`. When nothing is staged (the user had no recovery key when
+-- the rotation started) the recovery columns are cleared instead, and the
+-- client is told to enroll again.
+
+ALTER TABLE dek_rotation
+    ADD COLUMN new_recovery_wrapped_dek BYTEA,
+    ADD COLUMN new_recovery_wrap_params JSONB,
+    ADD CONSTRAINT dek_rotation_recovery_wrap_complete
+        CHECK ((new_recovery_wrapped_dek IS NULL) = (new_recovery_wrap_params IS NULL));
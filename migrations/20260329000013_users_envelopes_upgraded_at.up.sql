@@ -0,0 +1,14 @@
+-- When every envelope of a user was last known to be a bound v1 envelope
+--
+-- Legacy (header-less, unbound) envelopes open without associated data,
+-- so while they are accepted, any legacy ciphertext of the user can be
+-- copied into another cell and still decrypt. Once a user's envelopes have
+-- all been upgraded (a full sweep on unlock, or a DEK rotation, which
+-- rewrites everything as v1), this is set and legacy envelopes are
+-- rejected from then on.
+--
+-- Existing users start NULL and are swept on their next unlock. New users
+-- never had legacy envelopes, so the column defaults to the signup time.
+
+ALTER TABLE users ADD COLUMN envelopes_upgraded_at TIMESTAMPTZ;
+ALTER TABLE users ALTER COLUMN envelopes_upgraded_at SET DEFAULT now();
@@ -1,8 +1,8 @@
-use piggy_pulse::{Config, cleanup_expired_tokens, generate_periods};
+use piggy_pulse::{Config, advance_subscriptions, cleanup_expired_tokens, generate_periods};
 use tracing_subscriber::EnvFilter;
 
 fn print_usage(bin_name: &str) {
-    eprintln!("Usage: {bin_name} <generate-periods|cleanup-tokens>");
+    eprintln!("Usage: {bin_name} <generate-periods|cleanup-tokens|advance-subscriptions>");
 }
 
 fn init_tracing(log_level: &str, json_format: bool) {
@@ -25,7 +25,7 @@ async fn main() {
     let command = args.next();
 
     let cmd = match command.as_deref() {
-        Some(cmd @ ("generate-periods" | "cleanup-tokens")) if args.next().is_none() => cmd,
+        Some(cmd @ ("generate-periods" | "cleanup-tokens" | "advance-subscriptions")) if args.next().is_none() => cmd,
         _ => {
             print_usage(&bin_name);
             std::process::exit(2);
@@ -64,6 +64,18 @@ async fn main() {
                 std::process::exit(1);
             }
         },
+        "advance-subscriptions" => match advance_subscriptions(&config).await {
+            Ok(result) => {
+                println!(
+                    "Subscription advancement completed: subscriptions_resumed={}, subscriptions_advanced={}",
+                    result.subscriptions_resumed, result.subscriptions_advanced
+                );
+            }
+            Err(err) => {
+                eprintln!("Cron job failed: {err}");
+                std::process::exit(1);
+            }
+        },
         _ => unreachable!(),
     }
 }
@@ -65,6 +65,89 @@ pub struct Config {
     pub two_factor: TwoFactorConfig,
     #[serde(default)]
     pub login_rate_limit: LoginRateLimitConfig,
+    #[serde(default)]
+    pub dek_store: DekStoreConfig,
+    #[serde(default)]
+    pub attachments: AttachmentConfig,
+}
+
+/// Where unlocked session DEKs live between requests.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DekStoreBackend {
+    /// In-process map. DEKs are lost on restart and not shared between instances.
+    #[default]
+    InMemory,
+    /// Redis-protocol server shared by every instance.
+    Redis,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct DekStoreConfig {
+    #[serde(default)]
+    pub backend: DekStoreBackend,
+    /// Connection URL for the `redis` backend (e.g., "redis://127.0.0.1:6379/0")
+    #[serde(default = "default_dek_store_redis_url")]
+    pub redis_url: String,
+    /// Hex-encoded 32-byte key that seals DEKs before they leave the process (generate with: openssl rand -hex 32)
+    #[serde(default = "default_dek_store_sealing_key")]
+    pub sealing_key: String,
+    /// Prefix for every key written to the networked store
+    #[serde(default = "default_dek_store_redis_key_prefix")]
+    pub redis_key_prefix: String,
+    /// Seconds an unlocked DEK may go unused before the device must unlock again
+    #[serde(default = "default_dek_store_idle_timeout_seconds")]
+    pub idle_timeout_seconds: i64,
+}
+
+pub const INSECURE_DEFAULT_DEK_STORE_SEALING_KEY: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+fn default_dek_store_redis_url() -> String {
+    "redis://127.0.0.1:6379".to_string()
+}
+
+fn default_dek_store_sealing_key() -> String {
+    // WARNING: This is a placeholder key for development only
+    // Set PIGGY_PULSE_DEK_STORE__SEALING_KEY environment variable in production
+    INSECURE_DEFAULT_DEK_STORE_SEALING_KEY.to_string()
+}
+
+fn default_dek_store_redis_key_prefix() -> String {
+    "piggy-pulse:dek:".to_string()
+}
+
+fn default_dek_store_idle_timeout_seconds() -> i64 {
+    60 * 30
+}
+
+impl Default for DekStoreConfig {
+    fn default() -> Self {
+        Self {
+            backend: DekStoreBackend::default(),
+            redis_url: default_dek_store_redis_url(),
+            sealing_key: default_dek_store_sealing_key(),
+            redis_key_prefix: default_dek_store_redis_key_prefix(),
+            idle_timeout_seconds: default_dek_store_idle_timeout_seconds(),
+        }
+    }
+}
+
+impl DekStoreConfig {
+    pub fn sealing_key_is_default(&self) -> bool {
+        self.sealing_key.eq_ignore_ascii_case(INSECURE_DEFAULT_DEK_STORE_SEALING_KEY)
+    }
+
+    pub fn parse_sealing_key(&self) -> Result<[u8; 32], String> {
+        let sealing_key_bytes = hex::decode(&self.sealing_key).map_err(|e| format!("Invalid sealing key configuration: {}", e))?;
+
+        if sealing_key_bytes.len() != 32 {
+            return Err("Sealing key must be exactly 32 bytes (64 hex chars)".to_string());
+        }
+
+        let mut sealing_key = [0u8; 32];
+        sealing_key.copy_from_slice(&sealing_key_bytes);
+        Ok(sealing_key)
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone)]
@@ -166,6 +249,47 @@ pub struct LoginRateLimitConfig {
     pub frontend_unlock_url: String,
 }
 
+/// Where encrypted attachment chunks are written.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentBackend {
+    /// Chunk rows in the `attachment_chunk` table.
+    #[default]
+    Postgres,
+    /// One file per attachment under `path`.
+    Filesystem,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct AttachmentConfig {
+    #[serde(default)]
+    pub backend: AttachmentBackend,
+    /// Root directory for the `filesystem` backend
+    #[serde(default = "default_attachment_path")]
+    pub path: String,
+    /// Largest accepted upload, in bytes
+    #[serde(default = "default_attachment_max_size_bytes")]
+    pub max_size_bytes: u64,
+}
+
+fn default_attachment_path() -> String {
+    "data/attachments".to_string()
+}
+
+fn default_attachment_max_size_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+impl Default for AttachmentConfig {
+    fn default() -> Self {
+        Self {
+            backend: AttachmentBackend::default(),
+            path: default_attachment_path(),
+            max_size_bytes: default_attachment_max_size_bytes(),
+        }
+    }
+}
+
 impl Default for LoginRateLimitConfig {
     fn default() -> Self {
         Self {
@@ -369,3 +493,52 @@ mod rate_limit_tests {
         assert_eq!(config.lockout_duration_minutes, 60);
     }
 }
+
+#[cfg(test)]
+mod dek_store_tests {
+    use super::*;
+
+    #[test]
+    fn test_dek_store_config_defaults_to_in_memory() {
+        let config = DekStoreConfig::default();
+        assert_eq!(config.backend, DekStoreBackend::InMemory);
+        assert!(config.sealing_key_is_default());
+    }
+
+    #[test]
+    fn test_dek_store_backend_parses_snake_case() {
+        let config: DekStoreConfig = toml::from_str("backend = \"redis\"").expect("valid config");
+        assert_eq!(config.backend, DekStoreBackend::Redis);
+        assert_eq!(config.redis_key_prefix, "piggy-pulse:dek:");
+    }
+
+    #[test]
+    fn test_parse_sealing_key_rejects_wrong_length() {
+        let config = DekStoreConfig {
+            sealing_key: "abcd".to_string(),
+            ..DekStoreConfig::default()
+        };
+
+        let err = config.parse_sealing_key().expect_err("short key should fail");
+        assert_eq!(err, "Sealing key must be exactly 32 bytes (64 hex chars)");
+    }
+}
+
+#[cfg(test)]
+mod attachment_tests {
+    use super::*;
+
+    #[test]
+    fn test_attachment_config_defaults_to_postgres() {
+        let config = AttachmentConfig::default();
+        assert_eq!(config.backend, AttachmentBackend::Postgres);
+        assert_eq!(config.max_size_bytes, 10 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_attachment_backend_parses_snake_case() {
+        let config: AttachmentConfig = toml::from_str("backend = \"filesystem\"\npath = \"/var/lib/piggy-pulse\"").expect("valid config");
+        assert_eq!(config.backend, AttachmentBackend::Filesystem);
+        assert_eq!(config.path, "/var/lib/piggy-pulse");
+    }
+}
@@ -8,6 +8,12 @@ pub struct GeneratePeriodsResult {
     pub periods_created: i64,
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct AdvanceSubscriptionsResult {
+    pub subscriptions_resumed: u64,
+    pub subscriptions_advanced: u64,
+}
+
 pub async fn cleanup_expired_tokens(config: &Config) -> Result<(), String> {
     let pool = init_pool(&config.database, config.logging.slow_query_ms)
         .await
@@ -46,3 +52,22 @@ pub async fn generate_periods(config: &Config) -> Result<GeneratePeriodsResult,
         periods_created: result.periods_created,
     })
 }
+
+pub async fn advance_subscriptions(config: &Config) -> Result<AdvanceSubscriptionsResult, String> {
+    let pool = init_pool(&config.database, config.logging.slow_query_ms)
+        .await
+        .map_err(|err| format!("Failed to initialize database pool: {err}"))?;
+
+    let repo = PostgresRepository { pool: pool.clone() };
+    let result = repo
+        .advance_subscriptions(chrono::Utc::now().date_naive())
+        .await
+        .map_err(|err| format!("Failed to advance subscriptions: {err:?}"))?;
+
+    pool.close().await;
+
+    Ok(AdvanceSubscriptionsResult {
+        subscriptions_resumed: result.resumed,
+        subscriptions_advanced: result.advanced,
+    })
+}
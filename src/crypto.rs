@@ -8,13 +8,22 @@
 //! from the session store per request and uses it to encrypt on write and
 //! decrypt on read.
 //!
+//! Column values are stored as v1 envelopes that carry a version/algorithm
+//! header and are authenticated against the cell they live in (`EnvelopeAad`).
+//! Envelopes written before the header existed are readable only until every
+//! envelope of the user has been upgraded (`users.envelopes_upgraded_at`);
+//! the `Dek` carries that state, see `Dek::with_legacy_envelopes`.
+//!
 //! This module hosts the primitive types and pure functions. Higher-level
 //! wiring (session store, request guard, unlock endpoint) lives in
 //! `auth.rs` and `routes/v2/auth/unlock.rs`.
 
-use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::aead::{Aead, KeyInit, Payload};
 use aes_gcm::{Aes256Gcm, Nonce};
+use hmac::{Hmac, Mac};
 use rand::Rng;
+use sha2::Sha256;
+use uuid::Uuid;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
 /// The per-user Data Encryption Key.
@@ -29,13 +38,23 @@ use zeroize::{Zeroize, ZeroizeOnDrop};
 /// the enclosing struct — the custom clippy lint in CI will reject it, but
 /// defense in depth means we don't rely on the lint alone.
 #[derive(ZeroizeOnDrop)]
-pub struct Dek([u8; 32]);
+pub struct Dek {
+    key: [u8; 32],
+    /// Whether the owner may still have legacy (unbound) envelopes on disk.
+    /// Off by default: a legacy envelope is only accepted for a user whose
+    /// envelopes have not all been upgraded yet.
+    #[zeroize(skip)]
+    legacy_envelopes: bool,
+}
 
 impl Dek {
     /// Construct from 32 raw bytes. Caller is responsible for ensuring the
     /// bytes came from a CSPRNG or a verified unwrap operation.
     pub fn from_bytes(bytes: [u8; 32]) -> Self {
-        Dek(bytes)
+        Dek {
+            key: bytes,
+            legacy_envelopes: false,
+        }
     }
 
     /// Generate a fresh random DEK from the OS CSPRNG. Used at user signup
@@ -43,58 +62,87 @@ impl Dek {
     pub fn generate() -> Self {
         let mut bytes = [0u8; 32];
         rand::rng().fill_bytes(&mut bytes);
-        Dek(bytes)
+        Dek::from_bytes(bytes)
     }
 
     /// Copy the DEK into a new owned `Dek`. Named awkwardly on purpose: every
     /// call site should be a conscious decision to hold two copies.
     pub fn clone_for_request(&self) -> Self {
-        Dek(self.0)
+        Dek {
+            key: self.key,
+            legacy_envelopes: self.legacy_envelopes,
+        }
+    }
+
+    /// Set whether `decrypt_bytes_for` accepts legacy envelopes. Callers
+    /// pass whether the owner's envelopes are still awaiting their upgrade;
+    /// once they have all been upgraded, an unbound envelope in a column can
+    /// only have been planted there, e.g. restored from an old snapshot.
+    pub fn with_legacy_envelopes(mut self, accepted: bool) -> Self {
+        self.legacy_envelopes = accepted;
+        self
+    }
+
+    pub fn accepts_legacy_envelopes(&self) -> bool {
+        self.legacy_envelopes
     }
 
     /// Expose the raw bytes. Only intended for wrapping (encrypting the DEK
     /// with a KEK) and for writing into a session store. Never log the result.
     pub fn as_bytes(&self) -> &[u8; 32] {
-        &self.0
+        &self.key
     }
 
     fn cipher(&self) -> Aes256Gcm {
-        Aes256Gcm::new((&self.0).into())
+        Aes256Gcm::new((&self.key).into())
     }
 
-    /// Encrypt an arbitrary byte slice. Returns the envelope
-    /// `nonce (12B) || ciphertext || tag (16B)`.
+    /// Encrypt an arbitrary byte slice into an unbound legacy envelope
+    /// `nonce (12B) || ciphertext || tag (16B)`. Only for values that do not
+    /// live in a `*_enc` column (session sealing, rotation checks); column
+    /// values go through the `*_for` methods so they carry their AAD.
     pub fn encrypt_bytes(&self, plaintext: &[u8]) -> Result<Vec<u8>, CryptoError> {
-        let mut nonce_bytes = [0u8; 12];
-        rand::rng().fill_bytes(&mut nonce_bytes);
-        let nonce = Nonce::try_from(nonce_bytes.as_slice()).expect("nonce is always 12 bytes");
-        let ciphertext = self.cipher().encrypt(&nonce, plaintext).map_err(|_| CryptoError::EncryptFailed)?;
-        let mut out = Vec::with_capacity(12 + ciphertext.len());
-        out.extend_from_slice(&nonce_bytes);
-        out.extend_from_slice(&ciphertext);
-        Ok(out)
+        self.seal(plaintext, &[], &[])
     }
 
     /// Decrypt an envelope produced by `encrypt_bytes`. Returns the plaintext
     /// as a fresh `Vec<u8>`.
     pub fn decrypt_bytes(&self, envelope: &[u8]) -> Result<Vec<u8>, CryptoError> {
-        if envelope.len() < 12 + 16 {
-            return Err(CryptoError::EnvelopeTooShort);
+        self.open(envelope, &[])
+    }
+
+    /// Encrypt a column value into a v1 envelope
+    /// `header (2B) || nonce (12B) || ciphertext || tag (16B)`, authenticated
+    /// against `aad`.
+    pub fn encrypt_bytes_for(&self, plaintext: &[u8], aad: &EnvelopeAad<'_>) -> Result<Vec<u8>, CryptoError> {
+        self.seal(plaintext, &ENVELOPE_V1_HEADER, &aad.encode())
+    }
+
+    /// Decrypt a column value. Accepts v1 envelopes bound to `aad` and, while
+    /// the owner's envelopes are not yet all upgraded, unbound legacy
+    /// envelopes. A legacy nonce can start with the v1 header by chance, so a
+    /// header match that fails authentication falls back to the legacy layout.
+    pub fn decrypt_bytes_for(&self, envelope: &[u8], aad: &EnvelopeAad<'_>) -> Result<Vec<u8>, CryptoError> {
+        match envelope_version(envelope) {
+            EnvelopeVersion::V1 => match self.open(&envelope[ENVELOPE_V1_HEADER.len()..], &aad.encode()) {
+                Ok(plaintext) => Ok(plaintext),
+                Err(e) if self.legacy_envelopes => self.open(envelope, &[]).map_err(|_| e),
+                Err(e) => Err(e),
+            },
+            EnvelopeVersion::Legacy if self.legacy_envelopes => self.open(envelope, &[]),
+            EnvelopeVersion::Legacy => Err(CryptoError::LegacyEnvelopeRejected),
         }
-        let (nonce_bytes, ciphertext) = envelope.split_at(12);
-        let nonce = Nonce::try_from(nonce_bytes).expect("nonce is always 12 bytes");
-        self.cipher().decrypt(&nonce, ciphertext).map_err(|_| CryptoError::DecryptFailed)
     }
 
-    /// Encrypt an i64 (little-endian).
-    pub fn encrypt_i64(&self, value: i64) -> Result<Vec<u8>, CryptoError> {
-        self.encrypt_bytes(&value.to_le_bytes())
+    /// Encrypt an i64 (little-endian) column value.
+    pub fn encrypt_i64_for(&self, value: i64, aad: &EnvelopeAad<'_>) -> Result<Vec<u8>, CryptoError> {
+        self.encrypt_bytes_for(&value.to_le_bytes(), aad)
     }
 
-    /// Decrypt an i64 envelope. Zeroes the intermediate plaintext buffer
+    /// Decrypt an i64 column value. Zeroes the intermediate plaintext buffer
     /// before returning.
-    pub fn decrypt_i64(&self, envelope: &[u8]) -> Result<i64, CryptoError> {
-        let mut plaintext = self.decrypt_bytes(envelope)?;
+    pub fn decrypt_i64_for(&self, envelope: &[u8], aad: &EnvelopeAad<'_>) -> Result<i64, CryptoError> {
+        let mut plaintext = self.decrypt_bytes_for(envelope, aad)?;
         if plaintext.len() != 8 {
             plaintext.zeroize();
             return Err(CryptoError::PlaintextLengthMismatch);
@@ -105,19 +153,149 @@ impl Dek {
         Ok(i64::from_le_bytes(arr))
     }
 
-    /// Encrypt a UTF-8 string.
-    pub fn encrypt_string(&self, value: &str) -> Result<Vec<u8>, CryptoError> {
-        self.encrypt_bytes(value.as_bytes())
+    /// Encrypt a UTF-8 string column value.
+    pub fn encrypt_string_for(&self, value: &str, aad: &EnvelopeAad<'_>) -> Result<Vec<u8>, CryptoError> {
+        self.encrypt_bytes_for(value.as_bytes(), aad)
     }
 
-    /// Decrypt an envelope that was produced from a UTF-8 string. Zeroes the
-    /// intermediate buffer before returning.
-    pub fn decrypt_string(&self, envelope: &[u8]) -> Result<String, CryptoError> {
-        let mut plaintext = self.decrypt_bytes(envelope)?;
+    /// Decrypt a string column value. Zeroes the intermediate buffer before
+    /// returning.
+    pub fn decrypt_string_for(&self, envelope: &[u8], aad: &EnvelopeAad<'_>) -> Result<String, CryptoError> {
+        let mut plaintext = self.decrypt_bytes_for(envelope, aad)?;
         let result = String::from_utf8(plaintext.clone());
         plaintext.zeroize();
         result.map_err(|_| CryptoError::InvalidUtf8)
     }
+
+    /// Blind index of an entity name, stored in `name_bidx` so Postgres can
+    /// enforce per-user uniqueness and match exact names. HMAC-SHA256 of
+    /// `table || 0x00 || lowercase(name)` under a key derived from the DEK,
+    /// so equal names in different tables get unrelated values and the
+    /// index changes with the DEK on rotation.
+    pub fn name_blind_index(&self, table: &str, name: &str) -> Vec<u8> {
+        let mut key = Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts any key length");
+        key.update(BLIND_INDEX_KEY_LABEL);
+        let mut key_bytes: [u8; 32] = key.finalize().into_bytes().into();
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(&key_bytes).expect("HMAC accepts any key length");
+        key_bytes.zeroize();
+        mac.update(table.as_bytes());
+        mac.update(&[0]);
+        mac.update(name.to_lowercase().as_bytes());
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    fn seal(&self, plaintext: &[u8], header: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let mut nonce_bytes = [0u8; 12];
+        rand::rng().fill_bytes(&mut nonce_bytes);
+        let nonce = Nonce::try_from(nonce_bytes.as_slice()).expect("nonce is always 12 bytes");
+        let ciphertext = self
+            .cipher()
+            .encrypt(&nonce, Payload { msg: plaintext, aad })
+            .map_err(|_| CryptoError::EncryptFailed)?;
+        let mut out = Vec::with_capacity(header.len() + 12 + ciphertext.len());
+        out.extend_from_slice(header);
+        out.extend_from_slice(&nonce_bytes);
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    }
+
+    fn open(&self, body: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if body.len() < 12 + 16 {
+            return Err(CryptoError::EnvelopeTooShort);
+        }
+        let (nonce_bytes, ciphertext) = body.split_at(12);
+        let nonce = Nonce::try_from(nonce_bytes).expect("nonce is always 12 bytes");
+        self.cipher()
+            .decrypt(&nonce, Payload { msg: ciphertext, aad })
+            .map_err(|_| CryptoError::DecryptFailed)
+    }
+}
+
+/// Label for deriving the blind-index key from the DEK, so no index value is
+/// ever computed directly under the encryption key.
+const BLIND_INDEX_KEY_LABEL: &[u8] = b"piggy-pulse name blind index v1";
+
+/// Version byte followed by algorithm byte (1 = AES-256-GCM) that prefixes
+/// every v1 envelope. Legacy envelopes have no header and start directly
+/// with the nonce.
+pub const ENVELOPE_V1_HEADER: [u8; 2] = [0x01, 0x01];
+
+/// Layout of a stored envelope, as far as can be told without the key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvelopeVersion {
+    /// `nonce || ciphertext || tag`, no AAD.
+    Legacy,
+    /// `ENVELOPE_V1_HEADER || nonce || ciphertext || tag`, bound to an
+    /// `EnvelopeAad`.
+    V1,
+}
+
+/// Classify an envelope by its header. A `V1` result on a legacy envelope
+/// is possible (the nonce is random), which `Dek::decrypt_bytes_for`
+/// resolves by falling back; a `Legacy` result is always correct.
+pub fn envelope_version(envelope: &[u8]) -> EnvelopeVersion {
+    if envelope.len() >= ENVELOPE_V1_HEADER.len() + 12 + 16 && envelope.starts_with(&ENVELOPE_V1_HEADER) {
+        EnvelopeVersion::V1
+    } else {
+        EnvelopeVersion::Legacy
+    }
+}
+
+/// The cell an envelope belongs to. Authenticated as AAD so a ciphertext
+/// copied to another column, row or user fails to decrypt.
+///
+/// Encoded as `ENVELOPE_V1_HEADER || u16be len || table || u16be len ||
+/// column || row id (16B) || user id (16B)`. For the ledger the row id is
+/// the logical transaction id, shared by every `seq` of that transaction.
+#[derive(Debug, Clone, Copy)]
+pub struct EnvelopeAad<'a> {
+    pub table: &'a str,
+    pub column: &'a str,
+    pub row_id: Uuid,
+    pub user_id: Uuid,
+}
+
+impl<'a> EnvelopeAad<'a> {
+    pub fn new(table: &'a str, column: &'a str, row_id: Uuid, user_id: Uuid) -> Self {
+        EnvelopeAad {
+            table,
+            column,
+            row_id,
+            user_id,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(2 + 2 + self.table.len() + 2 + self.column.len() + 32);
+        out.extend_from_slice(&ENVELOPE_V1_HEADER);
+        for part in [self.table, self.column] {
+            let len = u16::try_from(part.len()).expect("table and column names fit in u16");
+            out.extend_from_slice(&len.to_be_bytes());
+            out.extend_from_slice(part.as_bytes());
+        }
+        out.extend_from_slice(self.row_id.as_bytes());
+        out.extend_from_slice(self.user_id.as_bytes());
+        out
+    }
+}
+
+/// A row owning several encrypted columns; hands out the per-column AAD.
+#[derive(Debug, Clone, Copy)]
+pub struct RowAad<'a> {
+    table: &'a str,
+    row_id: Uuid,
+    user_id: Uuid,
+}
+
+impl<'a> RowAad<'a> {
+    pub fn new(table: &'a str, row_id: Uuid, user_id: Uuid) -> Self {
+        RowAad { table, row_id, user_id }
+    }
+
+    pub fn column(&self, column: &'a str) -> EnvelopeAad<'a> {
+        EnvelopeAad::new(self.table, column, self.row_id, self.user_id)
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -132,35 +310,43 @@ pub enum CryptoError {
     PlaintextLengthMismatch,
     #[error("decrypted plaintext is not valid UTF-8")]
     InvalidUtf8,
+    #[error("legacy envelope rejected: the owner's envelopes have all been upgraded")]
+    LegacyEnvelopeRejected,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn aad(column: &str) -> EnvelopeAad<'_> {
+        EnvelopeAad::new("account", column, Uuid::from_u128(1), Uuid::from_u128(2))
+    }
+
     #[test]
     fn i64_roundtrip_zero_and_extremes() {
         let dek = Dek::generate();
         for v in [0i64, 1, -1, 1_234_567_890, -987_654_321, i64::MAX, i64::MIN] {
-            let env = dek.encrypt_i64(v).unwrap();
-            assert_eq!(dek.decrypt_i64(&env).unwrap(), v);
+            let env = dek.encrypt_i64_for(v, &aad("current_balance_enc")).unwrap();
+            assert_eq!(dek.decrypt_i64_for(&env, &aad("current_balance_enc")).unwrap(), v);
         }
     }
 
     #[test]
-    fn i64_envelope_is_stable_36_bytes() {
-        // 12 nonce + 8 ct + 16 tag
+    fn i64_envelope_is_stable_38_bytes() {
+        // 2 header + 12 nonce + 8 ct + 16 tag
         let dek = Dek::generate();
-        let env = dek.encrypt_i64(42).unwrap();
-        assert_eq!(env.len(), 36);
+        let env = dek.encrypt_i64_for(42, &aad("current_balance_enc")).unwrap();
+        assert_eq!(env.len(), 38);
+        assert_eq!(env[..2], ENVELOPE_V1_HEADER);
+        assert_eq!(envelope_version(&env), EnvelopeVersion::V1);
     }
 
     #[test]
     fn string_roundtrip_ascii_and_unicode() {
         let dek = Dek::generate();
         for s in ["", "hello", "café au lait", "🍕 pizza", "こんにちは"] {
-            let env = dek.encrypt_string(s).unwrap();
-            assert_eq!(dek.decrypt_string(&env).unwrap(), s);
+            let env = dek.encrypt_string_for(s, &aad("name_enc")).unwrap();
+            assert_eq!(dek.decrypt_string_for(&env, &aad("name_enc")).unwrap(), s);
         }
     }
 
@@ -178,67 +364,169 @@ mod tests {
     #[test]
     fn fresh_nonces_produce_different_envelopes_for_same_plaintext() {
         let dek = Dek::generate();
-        let a = dek.encrypt_i64(42).unwrap();
-        let b = dek.encrypt_i64(42).unwrap();
+        let a = dek.encrypt_i64_for(42, &aad("current_balance_enc")).unwrap();
+        let b = dek.encrypt_i64_for(42, &aad("current_balance_enc")).unwrap();
         assert_ne!(a, b, "fresh-nonce property violated");
-        assert_eq!(dek.decrypt_i64(&a).unwrap(), 42);
-        assert_eq!(dek.decrypt_i64(&b).unwrap(), 42);
+        assert_eq!(dek.decrypt_i64_for(&a, &aad("current_balance_enc")).unwrap(), 42);
+        assert_eq!(dek.decrypt_i64_for(&b, &aad("current_balance_enc")).unwrap(), 42);
     }
 
     #[test]
     fn wrong_dek_rejects_i64() {
         let a = Dek::generate();
         let b = Dek::generate();
-        let env = a.encrypt_i64(100).unwrap();
-        assert!(b.decrypt_i64(&env).is_err());
+        let env = a.encrypt_i64_for(100, &aad("current_balance_enc")).unwrap();
+        assert!(b.decrypt_i64_for(&env, &aad("current_balance_enc")).is_err());
     }
 
     #[test]
     fn wrong_dek_rejects_string() {
         let a = Dek::generate();
         let b = Dek::generate();
-        let env = a.encrypt_string("secret").unwrap();
-        assert!(b.decrypt_string(&env).is_err());
+        let env = a.encrypt_string_for("secret", &aad("name_enc")).unwrap();
+        assert!(b.decrypt_string_for(&env, &aad("name_enc")).is_err());
     }
 
     #[test]
     fn corrupted_ciphertext_rejects() {
         let dek = Dek::generate();
-        let mut env = dek.encrypt_i64(42).unwrap();
+        let mut env = dek.encrypt_i64_for(42, &aad("current_balance_enc")).unwrap();
         env[20] ^= 0xFF; // flip a byte in the ciphertext region
-        assert!(dek.decrypt_i64(&env).is_err());
+        assert!(dek.decrypt_i64_for(&env, &aad("current_balance_enc")).is_err());
     }
 
     #[test]
     fn corrupted_nonce_rejects() {
         let dek = Dek::generate();
-        let mut env = dek.encrypt_string("hello").unwrap();
-        env[0] ^= 0xFF; // flip a byte in the nonce region
-        assert!(dek.decrypt_string(&env).is_err());
+        let mut env = dek.encrypt_string_for("hello", &aad("name_enc")).unwrap();
+        env[2] ^= 0xFF; // flip a byte in the nonce region
+        assert!(dek.decrypt_string_for(&env, &aad("name_enc")).is_err());
     }
 
     #[test]
     fn envelope_too_short_rejects() {
         let dek = Dek::generate();
-        assert!(dek.decrypt_i64(&[0u8; 20]).is_err());
-        assert!(dek.decrypt_i64(&[]).is_err());
+        assert!(dek.decrypt_i64_for(&[0u8; 20], &aad("current_balance_enc")).is_err());
+        assert!(dek.decrypt_i64_for(&[], &aad("current_balance_enc")).is_err());
     }
 
     #[test]
     fn invalid_utf8_returns_utf8_error() {
         let dek = Dek::generate();
         // Encrypt raw bytes that are not valid UTF-8, then try to decrypt as string.
-        let env = dek.encrypt_bytes(&[0xFF, 0xFE, 0xFD]).unwrap();
-        assert!(matches!(dek.decrypt_string(&env), Err(CryptoError::InvalidUtf8)));
+        let env = dek.encrypt_bytes_for(&[0xFF, 0xFE, 0xFD], &aad("name_enc")).unwrap();
+        assert!(matches!(dek.decrypt_string_for(&env, &aad("name_enc")), Err(CryptoError::InvalidUtf8)));
+    }
+
+    #[test]
+    fn envelope_moved_to_another_cell_rejects() {
+        let dek = Dek::generate();
+        let cell = EnvelopeAad::new("account", "spend_limit_enc", Uuid::from_u128(1), Uuid::from_u128(2));
+        let env = dek.encrypt_i64_for(5_000, &cell).unwrap();
+
+        let other_column = EnvelopeAad {
+            column: "current_balance_enc",
+            ..cell
+        };
+        let other_table = EnvelopeAad { table: "category", ..cell };
+        let other_row = EnvelopeAad {
+            row_id: Uuid::from_u128(3),
+            ..cell
+        };
+        let other_user = EnvelopeAad {
+            user_id: Uuid::from_u128(3),
+            ..cell
+        };
+        for moved in [other_column, other_table, other_row, other_user] {
+            assert!(matches!(dek.decrypt_i64_for(&env, &moved), Err(CryptoError::DecryptFailed)));
+        }
+        assert_eq!(dek.decrypt_i64_for(&env, &cell).unwrap(), 5_000);
+    }
+
+    #[test]
+    fn aad_encoding_is_length_prefixed() {
+        // Without length prefixes ("ab", "c") and ("a", "bc") would collide.
+        let row = Uuid::from_u128(1);
+        let user = Uuid::from_u128(2);
+        assert_ne!(EnvelopeAad::new("ab", "c", row, user).encode(), EnvelopeAad::new("a", "bc", row, user).encode());
+        assert_eq!(EnvelopeAad::new("ab", "c", row, user).encode().len(), 2 + 2 + 2 + 2 + 1 + 32);
+    }
+
+    #[test]
+    fn row_aad_hands_out_column_bindings() {
+        let row = RowAad::new("vendor", Uuid::from_u128(5), Uuid::from_u128(6));
+        let name = row.column("name_enc");
+        assert_eq!(
+            name.encode(),
+            EnvelopeAad::new("vendor", "name_enc", Uuid::from_u128(5), Uuid::from_u128(6)).encode()
+        );
+    }
+
+    #[test]
+    fn legacy_envelope_decrypts_until_envelopes_are_upgraded() {
+        let dek = Dek::generate().with_legacy_envelopes(true);
+        let legacy = dek.encrypt_bytes(&42i64.to_le_bytes()).unwrap();
+        assert_eq!(legacy.len(), 36);
+        assert_eq!(dek.decrypt_i64_for(&legacy, &aad("current_balance_enc")).unwrap(), 42);
+        assert!(dek.clone_for_request().accepts_legacy_envelopes());
+
+        let upgraded = dek.with_legacy_envelopes(false);
+        assert!(matches!(
+            upgraded.decrypt_i64_for(&legacy, &aad("current_balance_enc")),
+            Err(CryptoError::LegacyEnvelopeRejected)
+        ));
+    }
+
+    #[test]
+    fn legacy_envelopes_are_rejected_by_default() {
+        let dek = Dek::generate();
+        let legacy = dek.encrypt_bytes(b"groceries").unwrap();
+        assert!(!dek.accepts_legacy_envelopes());
+        assert!(dek.decrypt_string_for(&legacy, &aad("name_enc")).is_err());
+    }
+
+    #[test]
+    fn legacy_envelope_with_header_shaped_nonce_still_decrypts() {
+        let dek = Dek::generate().with_legacy_envelopes(true);
+        let mut nonce = [0u8; 12];
+        rand::rng().fill_bytes(&mut nonce);
+        nonce[..2].copy_from_slice(&ENVELOPE_V1_HEADER);
+        let ciphertext = dek
+            .cipher()
+            .encrypt(&Nonce::try_from(nonce.as_slice()).unwrap(), b"groceries".as_slice())
+            .unwrap();
+        let legacy = [nonce.as_slice(), ciphertext.as_slice()].concat();
+
+        assert_eq!(envelope_version(&legacy), EnvelopeVersion::V1);
+        assert_eq!(dek.decrypt_string_for(&legacy, &aad("name_enc")).unwrap(), "groceries");
+        assert!(dek.with_legacy_envelopes(false).decrypt_string_for(&legacy, &aad("name_enc")).is_err());
+    }
+
+    #[test]
+    fn v1_envelope_is_not_readable_as_legacy() {
+        let dek = Dek::generate();
+        let env = dek.encrypt_string_for("groceries", &aad("name_enc")).unwrap();
+        assert!(dek.decrypt_bytes(&env).is_err());
+    }
+
+    #[test]
+    fn name_blind_index_is_case_insensitive_and_scoped() {
+        let dek = Dek::generate();
+        let index = dek.name_blind_index("vendor", "Corner Shop");
+        assert_eq!(index.len(), 32);
+        assert_eq!(index, dek.name_blind_index("vendor", "CORNER shop"));
+        assert_ne!(index, dek.name_blind_index("vendor", "Corner Shop 2"));
+        assert_ne!(index, dek.name_blind_index("category", "Corner Shop"));
+        assert_ne!(index, Dek::generate().name_blind_index("vendor", "Corner Shop"));
     }
 
     #[test]
     fn clone_for_request_produces_independent_dek_with_same_key() {
         let a = Dek::generate();
         let b = a.clone_for_request();
-        let env = a.encrypt_i64(42).unwrap();
+        let env = a.encrypt_i64_for(42, &aad("current_balance_enc")).unwrap();
         // The clone can decrypt what the original encrypted.
-        assert_eq!(b.decrypt_i64(&env).unwrap(), 42);
+        assert_eq!(b.decrypt_i64_for(&env, &aad("current_balance_enc")).unwrap(), 42);
     }
 
     #[test]
@@ -1,17 +1,25 @@
 pub mod account;
 pub mod api_token;
+pub mod attachment;
 pub mod audit;
 pub mod budget_period;
 pub mod category;
 pub mod category_target;
 pub mod currency;
+pub mod dashboard;
+pub mod dek_rotation;
+pub mod envelope;
+pub mod ledger_verification;
+pub mod name_index;
 pub mod password_reset;
 pub mod pending_2fa_token;
 pub mod postgres_repository;
 pub mod rate_limit;
+pub mod reconciliation;
 pub mod session;
 pub mod settings;
 pub mod subscription;
+pub mod tag;
 pub mod transaction;
 pub mod two_factor;
 pub mod user;
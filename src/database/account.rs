@@ -1,42 +1,71 @@
-use crate::crypto::Dek;
+use crate::crypto::{Dek, EnvelopeAad, RowAad};
+use crate::database::envelope::upgrade_legacy_envelopes;
+use crate::database::name_index::name_conflict;
 use crate::database::postgres_repository::PostgresRepository;
-use crate::dto::accounts::{CreateAccountRequest, UpdateAccountRequest};
+use crate::database::transaction::ledger_aad;
+use crate::dto::accounts::{CreateAccountRequest, TopUpCycle, UpdateAccountRequest};
 use crate::error::app_error::AppError;
 use crate::models::account::{Account, AccountType};
+use crate::models::category::CategoryType;
+use crate::models::transaction::LedgerEntryKind;
+use chrono::{NaiveDate, Utc};
 use uuid::Uuid;
 
+/// Description written on balance adjustment ledger rows.
+const BALANCE_ADJUSTMENT_DESCRIPTION: &str = "Balance adjustment";
+
+/// Latest_Row of an effective logical transaction touching an account,
+/// with what decides its balance effect.
+#[derive(Debug, sqlx::FromRow)]
+pub struct AccountMovementRow {
+    pub id: Uuid,
+    pub occurred_at: NaiveDate,
+    pub from_account_id: Uuid,
+    pub to_account_id: Option<Uuid>,
+    pub category_type: Option<CategoryType>,
+    pub is_adjustment: bool,
+    pub amount_enc: Vec<u8>,
+}
+
 impl PostgresRepository {
     /// Encrypt every plaintext field on the request and insert a new
-    /// `account` row. Name uniqueness is enforced in Rust: the server
-    /// fetches every existing account's `name_enc`, decrypts with the
-    /// caller's DEK, and checks for a case-insensitive match before
-    /// the INSERT. Runs inside a tx that locks the `users` row so two
-    /// concurrent creates from the same session serialize.
+    /// `account` row. Name uniqueness (case-insensitive) is enforced by
+    /// the unique index on `(user_id, name_bidx)`.
     pub async fn create_account(&self, request: &CreateAccountRequest, user_id: &Uuid, dek: &Dek) -> Result<Account, AppError> {
+        self.backfill_name_index("account", user_id, dek).await?;
         let mut tx = self.pool.begin().await?;
 
-        lock_user_row(&mut tx, user_id).await?;
-        check_account_name_unique(&mut tx, dek, user_id, &request.name, None).await?;
-
-        let name_enc = dek.encrypt_string(&request.name)?;
-        let color_enc = dek.encrypt_string(&request.color)?;
-        let current_balance_enc = dek.encrypt_i64(request.initial_balance)?;
-        let spend_limit_enc = request.spend_limit.map(|v| dek.encrypt_i64(v)).transpose()?;
-        let next_transfer_amount_enc = request.next_transfer_amount.map(|v| dek.encrypt_i64(v)).transpose()?;
-        let top_up_amount_enc = request.top_up_amount.map(|v| dek.encrypt_i64(v)).transpose()?;
+        let id = Uuid::new_v4();
+        let aad = RowAad::new("account", id, *user_id);
+        let name_enc = dek.encrypt_string_for(&request.name, &aad.column("name_enc"))?;
+        let color_enc = dek.encrypt_string_for(&request.color, &aad.column("color_enc"))?;
+        let current_balance_enc = dek.encrypt_i64_for(request.initial_balance, &aad.column("current_balance_enc"))?;
+        let opening_balance_enc = dek.encrypt_i64_for(request.initial_balance, &aad.column("opening_balance_enc"))?;
+        let spend_limit_enc = request
+            .spend_limit
+            .map(|v| dek.encrypt_i64_for(v, &aad.column("spend_limit_enc")))
+            .transpose()?;
+        let next_transfer_amount_enc = request
+            .next_transfer_amount
+            .map(|v| dek.encrypt_i64_for(v, &aad.column("next_transfer_amount_enc")))
+            .transpose()?;
+        let top_up_amount_enc = request
+            .top_up_amount
+            .map(|v| dek.encrypt_i64_for(v, &aad.column("top_up_amount_enc")))
+            .transpose()?;
 
         let account: Account = sqlx::query_as(
             r#"
 INSERT INTO account (
     id, user_id, account_type, currency_id, is_archived,
-    name_enc, color_enc, current_balance_enc,
+    name_enc, name_bidx, color_enc, current_balance_enc, opening_balance_enc,
     spend_limit_enc, next_transfer_amount_enc, top_up_amount_enc,
     top_up_cycle, top_up_day, statement_close_day, payment_due_day
 ) VALUES (
-    gen_random_uuid(), $1, $2::text::account_type, $3, false,
-    $4, $5, $6,
-    $7, $8, $9,
-    $10, $11, $12, $13
+    $1, $2, $3::text::account_type, $4, false,
+    $5, $6, $7, $8, $9,
+    $10, $11, $12,
+    $13, $14, $15, $16
 )
 RETURNING
     id, account_type::text AS account_type, currency_id, is_archived,
@@ -45,21 +74,25 @@ RETURNING
     top_up_cycle, top_up_day, statement_close_day, payment_due_day
 "#,
         )
+        .bind(id)
         .bind(user_id)
         .bind(account_type_to_db(request.account_type.into()))
         .bind(request.currency_id)
         .bind(&name_enc)
+        .bind(dek.name_blind_index("account", &request.name))
         .bind(&color_enc)
         .bind(&current_balance_enc)
+        .bind(&opening_balance_enc)
         .bind(spend_limit_enc.as_deref())
         .bind(next_transfer_amount_enc.as_deref())
         .bind(top_up_amount_enc.as_deref())
-        .bind(request.top_up_cycle.as_deref())
+        .bind(request.top_up_cycle.map(TopUpCycle::as_str))
         .bind(request.top_up_day)
         .bind(request.statement_close_day)
         .bind(request.payment_due_day)
         .fetch_one(&mut *tx)
-        .await?;
+        .await
+        .map_err(|e| name_conflict(e, "account", "An account", &request.name))?;
 
         tx.commit().await?;
         Ok(account)
@@ -105,16 +138,26 @@ ORDER BY id
     }
 
     pub async fn update_account(&self, id: &Uuid, request: &UpdateAccountRequest, user_id: &Uuid, dek: &Dek) -> Result<Account, AppError> {
+        self.backfill_name_index("account", user_id, dek).await?;
         let mut tx = self.pool.begin().await?;
 
-        lock_user_row(&mut tx, user_id).await?;
-        check_account_name_unique(&mut tx, dek, user_id, &request.name, Some(id)).await?;
+        let aad = RowAad::new("account", *id, *user_id);
+        let name_enc = dek.encrypt_string_for(&request.name, &aad.column("name_enc"))?;
+        let color_enc = dek.encrypt_string_for(&request.color, &aad.column("color_enc"))?;
+        let spend_limit_enc = request
+            .spend_limit
+            .map(|v| dek.encrypt_i64_for(v, &aad.column("spend_limit_enc")))
+            .transpose()?;
+        let next_transfer_amount_enc = request
+            .next_transfer_amount
+            .map(|v| dek.encrypt_i64_for(v, &aad.column("next_transfer_amount_enc")))
+            .transpose()?;
+        let top_up_amount_enc = request
+            .top_up_amount
+            .map(|v| dek.encrypt_i64_for(v, &aad.column("top_up_amount_enc")))
+            .transpose()?;
 
-        let name_enc = dek.encrypt_string(&request.name)?;
-        let color_enc = dek.encrypt_string(&request.color)?;
-        let spend_limit_enc = request.spend_limit.map(|v| dek.encrypt_i64(v)).transpose()?;
-        let next_transfer_amount_enc = request.next_transfer_amount.map(|v| dek.encrypt_i64(v)).transpose()?;
-        let top_up_amount_enc = request.top_up_amount.map(|v| dek.encrypt_i64(v)).transpose()?;
+        upgrade_legacy_envelopes(&mut tx, "account", id, user_id, dek).await?;
 
         // account_type is intentionally omitted: the Postgres trigger
         // reject_account_type_change (migration 20260327000004) makes
@@ -127,15 +170,16 @@ ORDER BY id
 UPDATE account
 SET currency_id = $1,
     name_enc = $2,
-    color_enc = $3,
-    spend_limit_enc = $4,
-    next_transfer_amount_enc = $5,
-    top_up_amount_enc = $6,
-    top_up_cycle = $7,
-    top_up_day = $8,
-    statement_close_day = $9,
-    payment_due_day = $10
-WHERE id = $11 AND user_id = $12
+    name_bidx = $3,
+    color_enc = $4,
+    spend_limit_enc = $5,
+    next_transfer_amount_enc = $6,
+    top_up_amount_enc = $7,
+    top_up_cycle = $8,
+    top_up_day = $9,
+    statement_close_day = $10,
+    payment_due_day = $11
+WHERE id = $12 AND user_id = $13
 RETURNING
     id, account_type::text AS account_type, currency_id, is_archived,
     name_enc, color_enc, current_balance_enc,
@@ -145,18 +189,20 @@ RETURNING
         )
         .bind(request.currency_id)
         .bind(&name_enc)
+        .bind(dek.name_blind_index("account", &request.name))
         .bind(&color_enc)
         .bind(spend_limit_enc.as_deref())
         .bind(next_transfer_amount_enc.as_deref())
         .bind(top_up_amount_enc.as_deref())
-        .bind(request.top_up_cycle.as_deref())
+        .bind(request.top_up_cycle.map(TopUpCycle::as_str))
         .bind(request.top_up_day)
         .bind(request.statement_close_day)
         .bind(request.payment_due_day)
         .bind(id)
         .bind(user_id)
         .fetch_optional(&mut *tx)
-        .await?
+        .await
+        .map_err(|e| name_conflict(e, "account", "An account", &request.name))?
         .ok_or_else(|| AppError::NotFound("Account not found".to_string()))?;
 
         tx.commit().await?;
@@ -199,8 +245,43 @@ RETURNING
         Ok(())
     }
 
-    /// Set `current_balance_enc` to an absolute value. Uses SELECT FOR
-    /// UPDATE on the row so concurrent transaction inserts that also
+    /// Every effective logical transaction dated on or after `from` that
+    /// moves money out of or into one of `account_ids`. Rows dated after
+    /// any range the caller reports on are included too, since walking
+    /// back from the current balance has to undo them.
+    pub async fn list_account_movements_since(&self, user_id: &Uuid, account_ids: &[Uuid], from: NaiveDate) -> Result<Vec<AccountMovementRow>, AppError> {
+        let rows = sqlx::query_as::<_, AccountMovementRow>(
+            r#"
+SELECT t.id,
+       t.occurred_at,
+       t.from_account_id,
+       t.to_account_id,
+       c.category_type,
+       t.entry_kind = 'adjustment' AS is_adjustment,
+       t.amount_enc
+FROM logical_transaction_state lts
+JOIN transaction t ON t.id = lts.id AND t.seq = lts.latest_seq
+LEFT JOIN category c ON c.id = t.category_id
+WHERE lts.user_id = $1
+  AND lts.is_effective
+  AND t.occurred_at >= $3
+  AND (t.from_account_id = ANY($2) OR t.to_account_id = ANY($2))
+ORDER BY t.occurred_at, t.id
+"#,
+        )
+        .bind(user_id)
+        .bind(account_ids)
+        .bind(from)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Set the balance to an absolute value by appending a balance
+    /// adjustment to the ledger: an uncategorized row whose amount is the
+    /// signed difference from the current balance, already cleared.
+    /// Setting the balance it already has writes nothing. Uses SELECT FOR
+    /// UPDATE on the account so concurrent transaction inserts that also
     /// maintain `current_balance_enc` serialize behind us.
     pub async fn adjust_balance(&self, id: &Uuid, new_balance: i64, user_id: &Uuid, dek: &Dek) -> Result<Account, AppError> {
         let mut tx = self.pool.begin().await?;
@@ -214,7 +295,45 @@ RETURNING
             return Err(AppError::NotFound("Account not found".to_string()));
         }
 
-        let new_enc = dek.encrypt_i64(new_balance)?;
+        upgrade_legacy_envelopes(&mut tx, "account", id, user_id, dek).await?;
+        let balance_aad = EnvelopeAad::new("account", "current_balance_enc", *id, *user_id);
+        let balance_enc: Vec<u8> = sqlx::query_scalar("SELECT current_balance_enc FROM account WHERE id = $1")
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+        let delta = new_balance
+            .checked_sub(dek.decrypt_i64_for(&balance_enc, &balance_aad)?)
+            .ok_or_else(|| AppError::BadRequest("account balance overflow".to_string()))?;
+
+        if delta != 0 {
+            let entry_id = Uuid::new_v4();
+            let aad = ledger_aad(&entry_id, user_id);
+            let amount_enc = dek.encrypt_i64_for(delta, &aad.column("amount_enc"))?;
+            let description_enc = dek.encrypt_string_for(BALANCE_ADJUSTMENT_DESCRIPTION, &aad.column("description_enc"))?;
+
+            let (_, seq, created_at) = self
+                .insert_ledger_row_enc_in_tx(
+                    &mut tx,
+                    &entry_id,
+                    user_id,
+                    &amount_enc,
+                    &description_enc,
+                    Utc::now().date_naive(),
+                    None,
+                    id,
+                    None,
+                    None,
+                    LedgerEntryKind::Adjustment,
+                )
+                .await?;
+            self.upsert_lts_in_tx(&mut tx, dek, &entry_id, user_id, delta, seq, created_at).await?;
+            sqlx::query("UPDATE logical_transaction_state SET cleared_status = 'cleared' WHERE id = $1")
+                .bind(entry_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let new_enc = dek.encrypt_i64_for(new_balance, &balance_aad)?;
 
         let account: Account = sqlx::query_as(
             r#"
@@ -239,42 +358,6 @@ RETURNING
     }
 }
 
-async fn lock_user_row(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, user_id: &Uuid) -> Result<(), AppError> {
-    sqlx::query("SELECT 1 FROM users WHERE id = $1 FOR UPDATE")
-        .bind(user_id)
-        .fetch_one(&mut **tx)
-        .await?;
-    Ok(())
-}
-
-/// Fetch every existing account's `name_enc` for this user, decrypt,
-/// and reject if the new name matches an existing one (optionally
-/// excluding the account being updated). Case-insensitive.
-async fn check_account_name_unique(
-    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
-    dek: &Dek,
-    user_id: &Uuid,
-    candidate: &str,
-    exclude_id: Option<&Uuid>,
-) -> Result<(), AppError> {
-    let rows: Vec<(Uuid, Vec<u8>)> = sqlx::query_as("SELECT id, name_enc FROM account WHERE user_id = $1")
-        .bind(user_id)
-        .fetch_all(&mut **tx)
-        .await?;
-
-    let candidate_lower = candidate.to_lowercase();
-    for (row_id, name_enc) in rows {
-        if exclude_id.is_some_and(|id| *id == row_id) {
-            continue;
-        }
-        let existing = dek.decrypt_string(&name_enc)?;
-        if existing.to_lowercase() == candidate_lower {
-            return Err(AppError::Conflict(format!("An account named '{}' already exists", candidate)));
-        }
-    }
-    Ok(())
-}
-
 pub fn account_type_to_db(account_type: AccountType) -> String {
     match account_type {
         AccountType::Checking => "Checking".to_string(),
@@ -0,0 +1,399 @@
+use std::path::{Path, PathBuf};
+
+use sqlx::{PgPool, Postgres, Transaction};
+use tokio::fs::{self, File};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader, BufWriter};
+use uuid::Uuid;
+use zeroize::Zeroize;
+
+use crate::config::{AttachmentBackend, AttachmentConfig};
+use crate::crypto::{Dek, EnvelopeAad, RowAad};
+use crate::database::postgres_repository::PostgresRepository;
+use crate::error::app_error::AppError;
+use crate::models::attachment::{Attachment, NewAttachment};
+
+/// Plaintext bytes per encrypted chunk.
+pub const ATTACHMENT_CHUNK_SIZE: usize = 256 * 1024;
+
+/// Upper bound on one stored chunk envelope (chunk plus envelope overhead),
+/// so a corrupt length prefix cannot trigger a huge allocation.
+const MAX_CHUNK_ENVELOPE_SIZE: usize = ATTACHMENT_CHUNK_SIZE + 1024;
+
+const ATTACHMENT_COLUMNS: &str = "id, transaction_id, file_name_enc, content_type_enc, size_bytes, created_at";
+
+fn io_error(e: std::io::Error) -> AppError {
+    AppError::internal(format!("attachment storage: {e}"))
+}
+
+/// Number of chunks a file of `size_bytes` is split into.
+fn chunk_count(size_bytes: u64) -> Result<i32, AppError> {
+    i32::try_from(size_bytes.div_ceil(ATTACHMENT_CHUNK_SIZE as u64)).map_err(|_| AppError::BadRequest("Attachment is too large".to_string()))
+}
+
+/// AAD column of chunk `index` out of `count`. Binding the position and the
+/// total keeps chunks from being reordered, moved between attachments or
+/// truncated.
+fn chunk_column(index: i32, count: i32) -> String {
+    format!("chunk:{index}/{count}")
+}
+
+/// Read up to one chunk from `content`, stopping early only at end of input.
+async fn read_chunk<R: AsyncRead + Unpin>(content: &mut R, buf: &mut [u8]) -> Result<usize, AppError> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = content.read(&mut buf[filled..]).await.map_err(io_error)?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+/// Splits an upload, which must be exactly `size_bytes` long, into
+/// encrypted chunks.
+struct ChunkEncryptor<'a, R> {
+    content: R,
+    size_bytes: u64,
+    count: i32,
+    content_key: &'a Dek,
+    attachment_id: Uuid,
+    user_id: Uuid,
+    next: i32,
+    read: u64,
+    buf: Vec<u8>,
+}
+
+impl<'a, R: AsyncRead + Unpin> ChunkEncryptor<'a, R> {
+    fn new(content: R, size_bytes: u64, count: i32, content_key: &'a Dek, attachment_id: Uuid, user_id: Uuid) -> Self {
+        Self {
+            content,
+            size_bytes,
+            count,
+            content_key,
+            attachment_id,
+            user_id,
+            next: 0,
+            read: 0,
+            buf: vec![0u8; ATTACHMENT_CHUNK_SIZE],
+        }
+    }
+
+    /// The next `(index, envelope)`, or `None` once the whole upload was
+    /// read and matched the declared size.
+    async fn next_chunk(&mut self) -> Result<Option<(i32, Vec<u8>)>, AppError> {
+        if self.next >= self.count {
+            if self.read != self.size_bytes || read_chunk(&mut self.content, &mut self.buf[..1]).await? != 0 {
+                return Err(AppError::BadRequest("Attachment size does not match its content".to_string()));
+            }
+            return Ok(None);
+        }
+        let n = read_chunk(&mut self.content, &mut self.buf).await?;
+        self.read += n as u64;
+        let column = chunk_column(self.next, self.count);
+        let envelope = self
+            .content_key
+            .encrypt_bytes_for(&self.buf[..n], &EnvelopeAad::new("attachment_chunk", &column, self.attachment_id, self.user_id));
+        self.buf[..n].zeroize();
+        let index = self.next;
+        self.next += 1;
+        Ok(Some((index, envelope?)))
+    }
+}
+
+fn blob_path(storage: &AttachmentConfig, user_id: &Uuid, attachment_id: &Uuid) -> PathBuf {
+    Path::new(&storage.path).join(user_id.to_string()).join(attachment_id.to_string())
+}
+
+/// Best-effort removal of blob files whose rows are already gone. A file
+/// left behind is unreadable without its sealed content key, so failures
+/// are only logged.
+pub(crate) async fn remove_blob_files(paths: &[String]) {
+    for path in paths {
+        if let Err(e) = fs::remove_file(path).await
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            tracing::warn!(error = %e, path = %path, "failed to remove attachment blob");
+        }
+    }
+}
+
+/// Delete every attachment of `user_id` inside `tx`. Returns the blob files
+/// to pass to `remove_blob_files` once `tx` has committed.
+pub(crate) async fn delete_user_attachments_in_tx(tx: &mut Transaction<'_, Postgres>, user_id: &Uuid) -> Result<Vec<String>, AppError> {
+    let paths: Vec<Option<String>> = sqlx::query_scalar("DELETE FROM attachment WHERE user_id = $1 RETURNING blob_path")
+        .bind(user_id)
+        .fetch_all(&mut **tx)
+        .await?;
+    Ok(paths.into_iter().flatten().collect())
+}
+
+enum ChunkSource {
+    Postgres(PgPool),
+    File(BufReader<File>),
+}
+
+/// Decrypts one attachment chunk by chunk for download.
+pub struct AttachmentReader {
+    pub file_name: String,
+    pub content_type: String,
+    attachment_id: Uuid,
+    user_id: Uuid,
+    content_key: Dek,
+    chunk_count: i32,
+    next: i32,
+    source: ChunkSource,
+}
+
+impl AttachmentReader {
+    /// The next decrypted chunk, or `None` once every chunk was returned.
+    pub async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, AppError> {
+        if self.next >= self.chunk_count {
+            return Ok(None);
+        }
+        let envelope = match &mut self.source {
+            ChunkSource::Postgres(pool) => sqlx::query_scalar::<_, Vec<u8>>("SELECT ciphertext FROM attachment_chunk WHERE attachment_id = $1 AND idx = $2")
+                .bind(self.attachment_id)
+                .bind(self.next)
+                .fetch_optional(&*pool)
+                .await?
+                .ok_or_else(|| AppError::internal(format!("attachment {} is missing chunk {}", self.attachment_id, self.next)))?,
+            ChunkSource::File(file) => {
+                let len = file.read_u32().await.map_err(io_error)? as usize;
+                if len > MAX_CHUNK_ENVELOPE_SIZE {
+                    return Err(AppError::internal(format!(
+                        "attachment {} has a corrupt chunk {}",
+                        self.attachment_id, self.next
+                    )));
+                }
+                let mut envelope = vec![0u8; len];
+                file.read_exact(&mut envelope).await.map_err(io_error)?;
+                envelope
+            }
+        };
+        let column = chunk_column(self.next, self.chunk_count);
+        let chunk = self
+            .content_key
+            .decrypt_bytes_for(&envelope, &EnvelopeAad::new("attachment_chunk", &column, self.attachment_id, self.user_id))?;
+        self.next += 1;
+        Ok(Some(chunk))
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct StoredAttachment {
+    file_name_enc: Vec<u8>,
+    content_type_enc: Vec<u8>,
+    content_key_enc: Vec<u8>,
+    chunk_count: i32,
+    blob_path: Option<String>,
+}
+
+impl PostgresRepository {
+    async fn ensure_transaction_exists(&self, transaction_id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM logical_transaction_state WHERE id = $1 AND user_id = $2)")
+            .bind(transaction_id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        if !exists {
+            return Err(AppError::NotFound("Transaction not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Encrypt `content` under a fresh content key and store it with the
+    /// backend selected in `storage`. The content key is sealed with `dek`.
+    pub async fn create_attachment<R: AsyncRead + Unpin>(
+        &self,
+        transaction_id: &Uuid,
+        user_id: &Uuid,
+        dek: &Dek,
+        upload: &NewAttachment<'_>,
+        mut content: R,
+        storage: &AttachmentConfig,
+    ) -> Result<Attachment, AppError> {
+        self.ensure_transaction_exists(transaction_id, user_id).await?;
+
+        let id = Uuid::new_v4();
+        let aad = RowAad::new("attachment", id, *user_id);
+        let file_name_enc = dek.encrypt_string_for(upload.file_name, &aad.column("file_name_enc"))?;
+        let content_type_enc = dek.encrypt_string_for(upload.content_type, &aad.column("content_type_enc"))?;
+        let content_key = Dek::generate();
+        let content_key_enc = dek.encrypt_bytes_for(content_key.as_bytes(), &aad.column("content_key_enc"))?;
+        let count = chunk_count(upload.size_bytes)?;
+        let size_bytes = i64::try_from(upload.size_bytes).map_err(|_| AppError::BadRequest("Attachment is too large".to_string()))?;
+
+        let insert = format!(
+            r#"
+INSERT INTO attachment (id, user_id, transaction_id, file_name_enc, content_type_enc, content_key_enc, size_bytes, chunk_count, storage, blob_path)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+RETURNING {ATTACHMENT_COLUMNS}
+"#
+        );
+        let insert = sqlx::query_as::<_, Attachment>(&insert)
+            .bind(id)
+            .bind(user_id)
+            .bind(transaction_id)
+            .bind(&file_name_enc)
+            .bind(&content_type_enc)
+            .bind(&content_key_enc)
+            .bind(size_bytes)
+            .bind(count);
+
+        match storage.backend {
+            AttachmentBackend::Postgres => {
+                let mut tx = self.pool.begin().await?;
+                let attachment = insert.bind("postgres").bind(None::<String>).fetch_one(&mut *tx).await?;
+                let mut chunks = ChunkEncryptor::new(&mut content, upload.size_bytes, count, &content_key, id, *user_id);
+                while let Some((index, envelope)) = chunks.next_chunk().await? {
+                    sqlx::query("INSERT INTO attachment_chunk (attachment_id, idx, ciphertext) VALUES ($1, $2, $3)")
+                        .bind(id)
+                        .bind(index)
+                        .bind(envelope)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                tx.commit().await?;
+                Ok(attachment)
+            }
+            AttachmentBackend::Filesystem => {
+                let path = blob_path(storage, user_id, &id);
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir).await.map_err(io_error)?;
+                }
+                // Write under a temporary name so a crash never leaves a
+                // partial file at a path a row points to.
+                let partial = path.with_extension("partial");
+                let written = async {
+                    let mut file = BufWriter::new(File::create(&partial).await.map_err(io_error)?);
+                    let mut chunks = ChunkEncryptor::new(&mut content, upload.size_bytes, count, &content_key, id, *user_id);
+                    while let Some((_, envelope)) = chunks.next_chunk().await? {
+                        file.write_u32(envelope.len() as u32).await.map_err(io_error)?;
+                        file.write_all(&envelope).await.map_err(io_error)?;
+                    }
+                    file.flush().await.map_err(io_error)?;
+                    file.into_inner().sync_all().await.map_err(io_error)?;
+                    fs::rename(&partial, &path).await.map_err(io_error)
+                }
+                .await;
+                if let Err(e) = written {
+                    remove_blob_files(&[partial.to_string_lossy().into_owned()]).await;
+                    return Err(e);
+                }
+
+                let path = path.to_string_lossy().into_owned();
+                match insert.bind("filesystem").bind(&path).fetch_one(&self.pool).await {
+                    Ok(attachment) => Ok(attachment),
+                    Err(e) => {
+                        remove_blob_files(&[path]).await;
+                        Err(e.into())
+                    }
+                }
+            }
+        }
+    }
+
+    /// Attachments of one transaction, oldest first.
+    pub async fn list_attachments(&self, transaction_id: &Uuid, user_id: &Uuid) -> Result<Vec<Attachment>, AppError> {
+        self.ensure_transaction_exists(transaction_id, user_id).await?;
+        let attachments = sqlx::query_as::<_, Attachment>(&format!(
+            "SELECT {ATTACHMENT_COLUMNS} FROM attachment WHERE transaction_id = $1 AND user_id = $2 ORDER BY created_at, id"
+        ))
+        .bind(transaction_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(attachments)
+    }
+
+    /// Unseal an attachment's content key and open its chunks for reading.
+    pub async fn open_attachment(&self, id: &Uuid, transaction_id: &Uuid, user_id: &Uuid, dek: &Dek) -> Result<AttachmentReader, AppError> {
+        let stored: StoredAttachment = sqlx::query_as(
+            r#"
+SELECT file_name_enc, content_type_enc, content_key_enc, chunk_count, blob_path
+FROM attachment
+WHERE id = $1 AND transaction_id = $2 AND user_id = $3
+"#,
+        )
+        .bind(id)
+        .bind(transaction_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Attachment not found".to_string()))?;
+
+        let aad = RowAad::new("attachment", *id, *user_id);
+        let mut key_bytes = dek.decrypt_bytes_for(&stored.content_key_enc, &aad.column("content_key_enc"))?;
+        let content_key = <[u8; 32]>::try_from(key_bytes.as_slice()).map(Dek::from_bytes);
+        key_bytes.zeroize();
+        let content_key = content_key.map_err(|_| AppError::internal(format!("attachment {id} has a malformed content key")))?;
+
+        let source = match stored.blob_path {
+            Some(path) => ChunkSource::File(BufReader::new(File::open(&path).await.map_err(io_error)?)),
+            None => ChunkSource::Postgres(self.pool.clone()),
+        };
+
+        Ok(AttachmentReader {
+            file_name: dek.decrypt_string_for(&stored.file_name_enc, &aad.column("file_name_enc"))?,
+            content_type: dek.decrypt_string_for(&stored.content_type_enc, &aad.column("content_type_enc"))?,
+            attachment_id: *id,
+            user_id: *user_id,
+            content_key,
+            chunk_count: stored.chunk_count,
+            next: 0,
+            source,
+        })
+    }
+
+    pub async fn delete_attachment(&self, id: &Uuid, transaction_id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        let blob_path: Option<String> = sqlx::query_scalar("DELETE FROM attachment WHERE id = $1 AND transaction_id = $2 AND user_id = $3 RETURNING blob_path")
+            .bind(id)
+            .bind(transaction_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Attachment not found".to_string()))?;
+        remove_blob_files(&blob_path.into_iter().collect::<Vec<_>>()).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_count_rounds_up_to_whole_chunks() {
+        assert_eq!(chunk_count(1).unwrap(), 1);
+        assert_eq!(chunk_count(ATTACHMENT_CHUNK_SIZE as u64).unwrap(), 1);
+        assert_eq!(chunk_count(ATTACHMENT_CHUNK_SIZE as u64 + 1).unwrap(), 2);
+    }
+
+    #[rocket::async_test]
+    async fn chunks_only_open_at_their_own_position() {
+        let key = Dek::generate();
+        let (id, user) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        let content = vec![7u8; ATTACHMENT_CHUNK_SIZE + 10];
+        let mut chunks = ChunkEncryptor::new(content.as_slice(), content.len() as u64, 2, &key, id, user);
+        let mut envelopes = Vec::new();
+        while let Some((_, envelope)) = chunks.next_chunk().await.unwrap() {
+            envelopes.push(envelope);
+        }
+        assert_eq!(envelopes.len(), 2);
+
+        let open = |column: &str| key.decrypt_bytes_for(&envelopes[1], &EnvelopeAad::new("attachment_chunk", column, id, user));
+        assert_eq!(open(&chunk_column(1, 2)).unwrap(), vec![7u8; 10]);
+        assert!(open(&chunk_column(0, 2)).is_err(), "chunks cannot be reordered");
+        assert!(open(&chunk_column(1, 3)).is_err(), "the chunk count is bound");
+    }
+
+    #[rocket::async_test]
+    async fn chunk_encryptor_rejects_size_mismatch() {
+        let key = Dek::generate();
+        let content = vec![1u8; 20];
+        let mut chunks = ChunkEncryptor::new(content.as_slice(), 10, 1, &key, Uuid::nil(), Uuid::nil());
+        assert!(chunks.next_chunk().await.unwrap().is_some());
+        assert!(matches!(chunks.next_chunk().await, Err(AppError::BadRequest(_))));
+    }
+}
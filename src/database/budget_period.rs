@@ -77,6 +77,26 @@ impl PostgresRepository {
         Ok(row.id)
     }
 
+    /// Periods starting or ending between `from` and `to`, inclusive.
+    pub async fn list_budget_periods_with_boundary_between(&self, user_id: &Uuid, from: NaiveDate, to: NaiveDate) -> Result<Vec<BudgetPeriod>, AppError> {
+        let periods = sqlx::query_as::<_, BudgetPeriod>(
+            r#"
+            SELECT id, user_id, name, start_date, end_date, is_auto_generated, created_at
+            FROM budget_period
+            WHERE user_id = $1
+              AND (start_date BETWEEN $2 AND $3 OR end_date BETWEEN $2 AND $3)
+            ORDER BY start_date, id
+            "#,
+        )
+        .bind(user_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(periods)
+    }
+
     #[allow(dead_code)]
     pub async fn get_budget_period(&self, budget_period_id: &Uuid, user_id: &Uuid) -> Result<BudgetPeriod, AppError> {
         let budget_period = sqlx::query_as::<_, BudgetPeriod>(
@@ -257,8 +277,8 @@ impl PostgresRepository {
     pub async fn get_budget_period_v2(&self, id: &Uuid, user_id: &Uuid) -> Result<Option<V2PeriodRow>, AppError> {
         // Transaction count, total spent, and total budgeted were all
         // computed from plaintext aggregate tables that no longer
-        // exist under encryption-at-rest. Returned as None so the
-        // client can compute them from its decrypted transaction list.
+        // exist under encryption-at-rest. Returned as None here; the
+        // service fills in the totals when the session is unlocked.
         let row = sqlx::query_as::<_, V2PeriodRow>(
             r#"
             SELECT bp.id, bp.name, bp.start_date, bp.end_date,
@@ -540,7 +560,7 @@ fn subtract_duration(date: NaiveDate, duration_value: i32, duration_unit: &Durat
     }
 }
 
-fn base_month_start_date(year: i32, month: u32, start_day: i32) -> Option<NaiveDate> {
+pub(crate) fn base_month_start_date(year: i32, month: u32, start_day: i32) -> Option<NaiveDate> {
     let day = start_day.clamp(1, 31) as u32;
     for candidate_day in (1..=day).rev() {
         if let Some(date) = NaiveDate::from_ymd_opt(year, month, candidate_day) {
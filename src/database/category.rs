@@ -1,4 +1,5 @@
-use crate::crypto::Dek;
+use crate::crypto::{Dek, EnvelopeAad, RowAad};
+use crate::database::name_index::name_conflict;
 use crate::database::postgres_repository::PostgresRepository;
 use crate::dto::categories::{CreateCategoryRequest, CreateTargetRequest, UpdateCategoryRequest, UpdateTargetRequest};
 use crate::error::app_error::AppError;
@@ -9,40 +10,51 @@ const CATEGORY_COLUMNS: &str = "id, category_type, behavior, parent_id, is_syste
 
 impl PostgresRepository {
     /// Encrypt the request and insert a new category. Name uniqueness
-    /// is enforced in Rust inside a tx that locks the users row.
+    /// is enforced by the unique index on `(user_id, name_bidx)`.
     pub async fn create_category(&self, request: &CreateCategoryRequest, user_id: &Uuid, dek: &Dek) -> Result<Category, AppError> {
+        self.backfill_name_index("category", user_id, dek).await?;
         let mut tx = self.pool.begin().await?;
 
-        lock_user_row(&mut tx, user_id).await?;
-        check_category_name_unique(&mut tx, dek, user_id, &request.name, None).await?;
-
-        let name_enc = dek.encrypt_string(&request.name)?;
-        let color_enc = request.color.as_deref().map(|c| dek.encrypt_string(c)).transpose()?;
-        let icon_enc = dek.encrypt_string(&request.icon)?;
-        let description_enc = request.description.as_deref().map(|d| dek.encrypt_string(d)).transpose()?;
+        let id = Uuid::new_v4();
+        let aad = RowAad::new("category", id, *user_id);
+        let name_enc = dek.encrypt_string_for(&request.name, &aad.column("name_enc"))?;
+        let color_enc = request
+            .color
+            .as_deref()
+            .map(|c| dek.encrypt_string_for(c, &aad.column("color_enc")))
+            .transpose()?;
+        let icon_enc = dek.encrypt_string_for(&request.icon, &aad.column("icon_enc"))?;
+        let description_enc = request
+            .description
+            .as_deref()
+            .map(|d| dek.encrypt_string_for(d, &aad.column("description_enc")))
+            .transpose()?;
 
         let category: Category = sqlx::query_as(&format!(
             r#"
 INSERT INTO category (
     id, user_id, category_type, behavior, parent_id, is_system, is_archived,
-    name_enc, color_enc, icon_enc, description_enc
+    name_enc, name_bidx, color_enc, icon_enc, description_enc
 ) VALUES (
-    gen_random_uuid(), $1, $2, $3, $4, false, false,
-    $5, $6, $7, $8
+    $1, $2, $3, $4, $5, false, false,
+    $6, $7, $8, $9, $10
 )
 RETURNING {CATEGORY_COLUMNS}
 "#,
         ))
+        .bind(id)
         .bind(user_id)
         .bind::<CategoryType>(request.category_type.into())
         .bind(request.behavior.map(CategoryBehavior::from))
         .bind(request.parent_id)
         .bind(&name_enc)
+        .bind(dek.name_blind_index("category", &request.name))
         .bind(color_enc.as_deref())
         .bind(&icon_enc)
         .bind(description_enc.as_deref())
         .fetch_one(&mut *tx)
-        .await?;
+        .await
+        .map_err(|e| name_conflict(e, "category", "A category", &request.name))?;
 
         tx.commit().await?;
         Ok(category)
@@ -67,15 +79,22 @@ RETURNING {CATEGORY_COLUMNS}
     }
 
     pub async fn update_category(&self, id: &Uuid, request: &UpdateCategoryRequest, user_id: &Uuid, dek: &Dek) -> Result<Category, AppError> {
+        self.backfill_name_index("category", user_id, dek).await?;
         let mut tx = self.pool.begin().await?;
 
-        lock_user_row(&mut tx, user_id).await?;
-        check_category_name_unique(&mut tx, dek, user_id, &request.name, Some(id)).await?;
-
-        let name_enc = dek.encrypt_string(&request.name)?;
-        let color_enc = request.color.as_deref().map(|c| dek.encrypt_string(c)).transpose()?;
-        let icon_enc = dek.encrypt_string(&request.icon)?;
-        let description_enc = request.description.as_deref().map(|d| dek.encrypt_string(d)).transpose()?;
+        let aad = RowAad::new("category", *id, *user_id);
+        let name_enc = dek.encrypt_string_for(&request.name, &aad.column("name_enc"))?;
+        let color_enc = request
+            .color
+            .as_deref()
+            .map(|c| dek.encrypt_string_for(c, &aad.column("color_enc")))
+            .transpose()?;
+        let icon_enc = dek.encrypt_string_for(&request.icon, &aad.column("icon_enc"))?;
+        let description_enc = request
+            .description
+            .as_deref()
+            .map(|d| dek.encrypt_string_for(d, &aad.column("description_enc")))
+            .transpose()?;
 
         let category: Category = sqlx::query_as(&format!(
             r#"
@@ -84,10 +103,11 @@ SET category_type = $1,
     behavior = $2,
     parent_id = $3,
     name_enc = $4,
-    color_enc = $5,
-    icon_enc = $6,
-    description_enc = $7
-WHERE id = $8 AND user_id = $9
+    name_bidx = $5,
+    color_enc = $6,
+    icon_enc = $7,
+    description_enc = $8
+WHERE id = $9 AND user_id = $10
 RETURNING {CATEGORY_COLUMNS}
 "#,
         ))
@@ -95,13 +115,15 @@ RETURNING {CATEGORY_COLUMNS}
         .bind(request.behavior.map(CategoryBehavior::from))
         .bind(request.parent_id)
         .bind(&name_enc)
+        .bind(dek.name_blind_index("category", &request.name))
         .bind(color_enc.as_deref())
         .bind(&icon_enc)
         .bind(description_enc.as_deref())
         .bind(id)
         .bind(user_id)
         .fetch_optional(&mut *tx)
-        .await?
+        .await
+        .map_err(|e| name_conflict(e, "category", "A category", &request.name))?
         .ok_or_else(|| AppError::NotFound("Category not found".to_string()))?;
 
         tx.commit().await?;
@@ -109,12 +131,19 @@ RETURNING {CATEGORY_COLUMNS}
     }
 
     pub async fn delete_category(&self, id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
-        // Check if category has any transactions
-        let has_transactions: Option<(i32,)> = sqlx::query_as("SELECT 1 FROM transaction WHERE category_id = $1 AND user_id = $2 LIMIT 1")
-            .bind(id)
-            .bind(user_id)
-            .fetch_optional(&self.pool)
-            .await?;
+        // Check if category has any transactions or split lines
+        let has_transactions: Option<(i32,)> = sqlx::query_as(
+            r#"
+SELECT 1 FROM transaction WHERE category_id = $1 AND user_id = $2
+UNION ALL
+SELECT 1 FROM transaction_split WHERE category_id = $1 AND user_id = $2
+LIMIT 1
+"#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
 
         if has_transactions.is_some() {
             return Err(AppError::Conflict("Cannot delete category with existing transactions".to_string()));
@@ -185,15 +214,17 @@ ORDER BY bc.id
             return Err(AppError::NotFound("Category not found".to_string()));
         }
 
-        let value_enc = dek.encrypt_i64(request.value)?;
+        let id = Uuid::new_v4();
+        let value_enc = dek.encrypt_i64_for(request.value, &EnvelopeAad::new("budget_category", "budgeted_value_enc", id, *user_id))?;
 
         let row: (Uuid, Uuid, bool, Vec<u8>) = sqlx::query_as(
             r#"
 INSERT INTO budget_category (id, user_id, category_id, is_excluded, budgeted_value_enc)
-VALUES (gen_random_uuid(), $1, $2, false, $3)
+VALUES ($1, $2, $3, false, $4)
 RETURNING id, category_id, is_excluded, budgeted_value_enc
 "#,
         )
+        .bind(id)
         .bind(user_id)
         .bind(request.category_id)
         .bind(&value_enc)
@@ -209,7 +240,7 @@ RETURNING id, category_id, is_excluded, budgeted_value_enc
         user_id: &Uuid,
         dek: &Dek,
     ) -> Result<(Uuid, Uuid, bool, Vec<u8>), AppError> {
-        let value_enc = dek.encrypt_i64(request.value)?;
+        let value_enc = dek.encrypt_i64_for(request.value, &EnvelopeAad::new("budget_category", "budgeted_value_enc", *target_id, *user_id))?;
         let row = sqlx::query_as::<_, (Uuid, Uuid, bool, Vec<u8>)>(
             r#"
 UPDATE budget_category
@@ -244,36 +275,3 @@ RETURNING id, category_id, is_excluded, budgeted_value_enc
         Ok(row)
     }
 }
-
-async fn lock_user_row(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, user_id: &Uuid) -> Result<(), AppError> {
-    sqlx::query("SELECT 1 FROM users WHERE id = $1 FOR UPDATE")
-        .bind(user_id)
-        .fetch_one(&mut **tx)
-        .await?;
-    Ok(())
-}
-
-async fn check_category_name_unique(
-    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
-    dek: &Dek,
-    user_id: &Uuid,
-    candidate: &str,
-    exclude_id: Option<&Uuid>,
-) -> Result<(), AppError> {
-    let rows: Vec<(Uuid, Vec<u8>)> = sqlx::query_as("SELECT id, name_enc FROM category WHERE user_id = $1")
-        .bind(user_id)
-        .fetch_all(&mut **tx)
-        .await?;
-
-    let candidate_lower = candidate.to_lowercase();
-    for (row_id, name_enc) in rows {
-        if exclude_id.is_some_and(|id| *id == row_id) {
-            continue;
-        }
-        let existing = dek.decrypt_string(&name_enc)?;
-        if existing.to_lowercase() == candidate_lower {
-            return Err(AppError::Conflict(format!("A category named '{}' already exists", candidate)));
-        }
-    }
-    Ok(())
-}
@@ -0,0 +1,117 @@
+//! Read-side queries behind the dashboard endpoints.
+//!
+//! Every value the dashboard reports is derived from ciphertext, so these
+//! queries only return the structural columns the service needs to
+//! classify a transaction (category type, account types) next to the
+//! envelopes it decrypts in-process. Nothing here aggregates amounts.
+
+use crate::database::postgres_repository::PostgresRepository;
+use crate::error::app_error::AppError;
+use crate::models::account::AccountType;
+use crate::models::budget_period::BudgetPeriod;
+use crate::models::category::CategoryType;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+/// Latest_Row of an effective logical transaction, joined with the types
+/// that decide how it counts towards spending and cash flow.
+#[derive(Debug, sqlx::FromRow)]
+pub struct DashboardTransactionRow {
+    pub id: Uuid,
+    pub occurred_at: NaiveDate,
+    pub category_type: Option<CategoryType>,
+    pub from_account_type: AccountType,
+    pub to_account_type: Option<AccountType>,
+    pub vendor_id: Option<Uuid>,
+    pub amount_enc: Vec<u8>,
+}
+
+impl PostgresRepository {
+    /// Every effective logical transaction whose Latest_Row falls in the
+    /// inclusive date range, with its category and account types.
+    pub async fn list_dashboard_transactions_in_range(&self, user_id: &Uuid, from: NaiveDate, to: NaiveDate) -> Result<Vec<DashboardTransactionRow>, AppError> {
+        let rows = sqlx::query_as::<_, DashboardTransactionRow>(
+            r#"
+SELECT t.id,
+       t.occurred_at,
+       c.category_type,
+       fa.account_type::text AS from_account_type,
+       ta.account_type::text AS to_account_type,
+       t.vendor_id,
+       t.amount_enc
+FROM logical_transaction_state lts
+JOIN transaction t ON t.id = lts.id AND t.seq = lts.latest_seq
+JOIN account fa ON fa.id = t.from_account_id
+LEFT JOIN account ta ON ta.id = t.to_account_id
+LEFT JOIN category c ON c.id = t.category_id
+WHERE lts.user_id = $1
+  AND lts.is_effective
+  AND t.occurred_at BETWEEN $2 AND $3
+ORDER BY t.occurred_at, t.id
+"#,
+        )
+        .bind(user_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// The period covering `day`, if any. Periods cannot overlap, so there
+    /// is at most one.
+    pub async fn find_budget_period_covering(&self, user_id: &Uuid, day: NaiveDate) -> Result<Option<BudgetPeriod>, AppError> {
+        let period = sqlx::query_as::<_, BudgetPeriod>(
+            r#"
+            SELECT id, user_id, name, start_date, end_date, is_auto_generated, created_at
+            FROM budget_period
+            WHERE user_id = $1 AND start_date <= $2 AND end_date >= $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(day)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(period)
+    }
+
+    /// The last `limit` periods that have started on or before `day`,
+    /// oldest first.
+    pub async fn list_recent_budget_periods(&self, user_id: &Uuid, day: NaiveDate, limit: i64) -> Result<Vec<BudgetPeriod>, AppError> {
+        let mut periods = sqlx::query_as::<_, BudgetPeriod>(
+            r#"
+            SELECT id, user_id, name, start_date, end_date, is_auto_generated, created_at
+            FROM budget_period
+            WHERE user_id = $1 AND start_date <= $2
+            ORDER BY start_date DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(day)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        periods.reverse();
+        Ok(periods)
+    }
+
+    /// `(id, budgeted_value_enc)` of every target that counts towards the
+    /// period budget: not excluded, and on a category that is not archived.
+    pub async fn list_active_target_values(&self, user_id: &Uuid) -> Result<Vec<(Uuid, Vec<u8>)>, AppError> {
+        let rows: Vec<(Uuid, Vec<u8>)> = sqlx::query_as(
+            r#"
+SELECT bc.id, bc.budgeted_value_enc
+FROM budget_category bc
+JOIN category c ON c.id = bc.category_id
+WHERE bc.user_id = $1
+  AND NOT bc.is_excluded
+  AND NOT c.is_archived
+"#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+}
@@ -0,0 +1,264 @@
+use serde_json::Value;
+use uuid::Uuid;
+use zeroize::Zeroize;
+
+use crate::crypto::{Dek, EnvelopeAad};
+use crate::database::envelope::{ENCRYPTED_TABLES, rewrite_envelope_batch};
+use crate::database::name_index::clear_name_indexes;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::error::app_error::AppError;
+
+/// Fixed plaintext encrypted under the new DEK when a rotation starts, so a
+/// resumed rotation can prove it is rotating to the same key.
+const ROTATION_CHECK_PLAINTEXT: &[u8] = b"piggy-pulse-dek-rotation";
+
+/// Re-encrypt one envelope from `old` to `new`, always writing a v1
+/// envelope bound to `aad` (rotation doubles as the bulk upgrade of legacy
+/// envelopes, including immutable ledger rows). Returns `None` when the
+/// envelope is already under `new` (a previous, interrupted run got to it).
+fn reencrypt_envelope(old: &Dek, new: &Dek, envelope: &[u8], aad: &EnvelopeAad<'_>) -> Result<Option<Vec<u8>>, AppError> {
+    match old.decrypt_bytes_for(envelope, aad) {
+        Ok(mut plaintext) => {
+            let result = new.encrypt_bytes_for(&plaintext, aad);
+            plaintext.zeroize();
+            Ok(Some(result?))
+        }
+        Err(e) => match new.decrypt_bytes_for(envelope, aad) {
+            Ok(mut plaintext) => {
+                plaintext.zeroize();
+                Ok(None)
+            }
+            Err(_) => Err(e.into()),
+        },
+    }
+}
+
+impl PostgresRepository {
+    /// Return the pending rotation's wrapped DEK and params, if a rotation
+    /// was started and not yet completed.
+    pub async fn get_pending_dek_rotation(&self, user_id: &Uuid) -> Result<Option<(Vec<u8>, Value)>, AppError> {
+        let row: Option<(Vec<u8>, Value)> = sqlx::query_as("SELECT new_wrapped_dek, new_dek_wrap_params FROM dek_rotation WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row)
+    }
+
+    /// Record (or resume) a rotation to `new_dek`, staging the recovery-key
+    /// wrap of the new DEK when the user has a recovery key. Returns
+    /// `Conflict` if a rotation to a different DEK is already in progress.
+    pub async fn begin_dek_rotation(
+        &self,
+        user_id: &Uuid,
+        new_dek: &Dek,
+        new_wrapped_dek: &[u8],
+        new_dek_wrap_params: &Value,
+        new_recovery_wrap: Option<(&[u8], &Value)>,
+    ) -> Result<(), AppError> {
+        let check = new_dek.encrypt_bytes(ROTATION_CHECK_PLAINTEXT)?;
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO dek_rotation (user_id, new_wrapped_dek, new_dek_wrap_params, new_dek_check)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(new_wrapped_dek)
+        .bind(new_dek_wrap_params)
+        .bind(&check)
+        .execute(&mut *tx)
+        .await?;
+
+        let (stored_check,): (Vec<u8>,) = sqlx::query_as("SELECT new_dek_check FROM dek_rotation WHERE user_id = $1 FOR UPDATE")
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        match new_dek.decrypt_bytes(&stored_check) {
+            Ok(plaintext) if plaintext == ROTATION_CHECK_PLAINTEXT => {}
+            _ => return Err(AppError::Conflict("A rotation to a different DEK is already in progress".to_string())),
+        }
+
+        // Keep the latest wraps of the same key (the client may have
+        // re-wrapped it with a fresh nonce on retry).
+        sqlx::query(
+            r#"
+            UPDATE dek_rotation
+            SET new_wrapped_dek = $1,
+                new_dek_wrap_params = $2,
+                new_recovery_wrapped_dek = $3,
+                new_recovery_wrap_params = $4,
+                updated_at = now()
+            WHERE user_id = $5
+            "#,
+        )
+        .bind(new_wrapped_dek)
+        .bind(new_dek_wrap_params)
+        .bind(new_recovery_wrap.map(|(wrapped, _)| wrapped))
+        .bind(new_recovery_wrap.map(|(_, params)| params))
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Resumable phase of a rotation: re-encrypt every ciphertext column the
+    /// user owns from `old` to `new`, committing every `ENVELOPE_BATCH_SIZE`
+    /// rows. Rows already under `new` are skipped, so re-running after an
+    /// interruption picks up where the last run stopped.
+    pub async fn reencrypt_user_data(&self, user_id: &Uuid, old: &Dek, new: &Dek) -> Result<u64, AppError> {
+        let mut rewritten = 0u64;
+
+        for spec in ENCRYPTED_TABLES {
+            let mut cursor = Some((Uuid::nil(), i64::MIN));
+            while let Some(after) = cursor {
+                let mut tx = self.pool.begin().await?;
+                if spec.immutable {
+                    // Re-encryption rewrites ciphertext only; the plaintext
+                    // ledger history is unchanged. Bypass for this batch only.
+                    sqlx::query("SET LOCAL piggy_pulse.allow_ledger_mutations = 'on'").execute(&mut *tx).await?;
+                }
+                let (next, count) = rewrite_envelope_batch(&mut tx, spec, user_id, after, &|envelope, aad| reencrypt_envelope(old, new, envelope, aad)).await?;
+                tx.commit().await?;
+
+                rewritten += count;
+                cursor = next;
+            }
+        }
+
+        Ok(rewritten)
+    }
+
+    /// Final phase of a rotation. Under the user row lock, sweep every table
+    /// once more (catching rows written with the old DEK while the batches
+    /// ran), then swap `users.wrapped_dek` and the staged recovery wrap and
+    /// clear the rotation record in the same transaction. Every envelope is
+    /// now a bound v1 envelope, so legacy envelopes are rejected from here on. A recovery key
+    /// with nothing staged still wraps the old DEK, so it is cleared.
+    /// Returns the number of rows the sweep rewrote and whether a recovery
+    /// key was cleared.
+    pub async fn complete_dek_rotation(
+        &self,
+        user_id: &Uuid,
+        old: &Dek,
+        new: &Dek,
+        new_wrapped_dek: &[u8],
+        new_dek_wrap_params: &Value,
+    ) -> Result<(u64, bool), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let (had_recovery_key,): (bool,) = sqlx::query_as("SELECT recovery_wrapped_dek IS NOT NULL FROM users WHERE id = $1 FOR UPDATE")
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        sqlx::query("SET LOCAL piggy_pulse.allow_ledger_mutations = 'on'").execute(&mut *tx).await?;
+
+        let mut rewritten = 0u64;
+        for spec in ENCRYPTED_TABLES {
+            let mut cursor = Some((Uuid::nil(), i64::MIN));
+            while let Some(after) = cursor {
+                let (next, count) = rewrite_envelope_batch(&mut tx, spec, user_id, after, &|envelope, aad| reencrypt_envelope(old, new, envelope, aad)).await?;
+                rewritten += count;
+                cursor = next;
+            }
+        }
+
+        // Blind indexes are keyed from the DEK; re-derive them lazily under
+        // the new one.
+        clear_name_indexes(&mut tx, user_id).await?;
+
+        let (new_recovery_wrapped_dek, new_recovery_wrap_params): (Option<Vec<u8>>, Option<Value>) =
+            sqlx::query_as("SELECT new_recovery_wrapped_dek, new_recovery_wrap_params FROM dek_rotation WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .unwrap_or((None, None));
+
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET wrapped_dek = $1,
+                dek_wrap_params = $2,
+                recovery_wrapped_dek = $3,
+                recovery_wrap_params = $4,
+                envelopes_upgraded_at = COALESCE(envelopes_upgraded_at, now())
+            WHERE id = $5
+            "#,
+        )
+        .bind(new_wrapped_dek)
+        .bind(new_dek_wrap_params)
+        .bind(&new_recovery_wrapped_dek)
+        .bind(&new_recovery_wrap_params)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM dek_rotation WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok((rewritten, had_recovery_key && new_recovery_wrapped_dek.is_none()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aad() -> EnvelopeAad<'static> {
+        EnvelopeAad::new("category", "name_enc", Uuid::from_u128(1), Uuid::from_u128(2))
+    }
+
+    #[test]
+    fn reencrypt_envelope_moves_old_ciphertext_to_new_key() {
+        let old = Dek::generate();
+        let new = Dek::generate();
+        let envelope = old.encrypt_string_for("groceries", &aad()).unwrap();
+
+        let rotated = reencrypt_envelope(&old, &new, &envelope, &aad()).unwrap().expect("rewritten");
+
+        assert_eq!(new.decrypt_string_for(&rotated, &aad()).unwrap(), "groceries");
+        assert!(old.decrypt_bytes_for(&rotated, &aad()).is_err());
+    }
+
+    #[test]
+    fn reencrypt_envelope_upgrades_legacy_ciphertext() {
+        let old = Dek::generate().with_legacy_envelopes(true);
+        let new = Dek::generate();
+        let legacy = old.encrypt_bytes(b"groceries").unwrap();
+
+        let rotated = reencrypt_envelope(&old, &new, &legacy, &aad()).unwrap().expect("rewritten");
+
+        assert_eq!(crate::crypto::envelope_version(&rotated), crate::crypto::EnvelopeVersion::V1);
+        assert!(new.decrypt_bytes(&rotated).is_err(), "rotated envelope must be bound");
+        assert_eq!(new.decrypt_string_for(&rotated, &aad()).unwrap(), "groceries");
+    }
+
+    #[test]
+    fn reencrypt_envelope_skips_already_rotated_ciphertext() {
+        let old = Dek::generate();
+        let new = Dek::generate();
+        let envelope = new.encrypt_i64_for(4200, &aad()).unwrap();
+
+        assert!(reencrypt_envelope(&old, &new, &envelope, &aad()).unwrap().is_none());
+    }
+
+    #[test]
+    fn reencrypt_envelope_rejects_foreign_ciphertext() {
+        let old = Dek::generate();
+        let new = Dek::generate();
+        let envelope = Dek::generate().encrypt_i64_for(1, &aad()).unwrap();
+
+        assert!(reencrypt_envelope(&old, &new, &envelope, &aad()).is_err());
+    }
+}
@@ -0,0 +1,349 @@
+use sqlx::postgres::PgRow;
+use sqlx::{PgExecutor, Postgres, Row, Transaction};
+use uuid::Uuid;
+use zeroize::Zeroize;
+
+use crate::crypto::{Dek, EnvelopeAad, RowAad};
+use crate::database::postgres_repository::PostgresRepository;
+use crate::error::app_error::AppError;
+
+/// Rows per batch of the bulk sweeps (DEK rotation and the legacy envelope
+/// upgrade commit each one) and of the ledger verifier's envelope check.
+pub(crate) const ENVELOPE_BATCH_SIZE: i64 = 500;
+
+/// A table holding user-owned ciphertext. `has_seq` marks the ledger, whose
+/// primary key is `(id, seq)`; `immutable` marks tables whose rows are
+/// guarded by the ledger immutability trigger.
+pub(crate) struct EncryptedTable {
+    pub table: &'static str,
+    pub has_seq: bool,
+    pub immutable: bool,
+    pub columns: &'static [&'static str],
+}
+
+/// Every `*_enc` column in the schema, grouped by table. Keep in sync with
+/// migrations that add encrypted columns.
+pub(crate) const ENCRYPTED_TABLES: &[EncryptedTable] = &[
+    EncryptedTable {
+        table: "account",
+        has_seq: false,
+        immutable: false,
+        columns: &[
+            "name_enc",
+            "color_enc",
+            "current_balance_enc",
+            "opening_balance_enc",
+            "spend_limit_enc",
+            "next_transfer_amount_enc",
+            "top_up_amount_enc",
+        ],
+    },
+    EncryptedTable {
+        table: "category",
+        has_seq: false,
+        immutable: false,
+        columns: &["name_enc", "color_enc", "icon_enc", "description_enc"],
+    },
+    EncryptedTable {
+        table: "vendor",
+        has_seq: false,
+        immutable: false,
+        columns: &["name_enc", "description_enc"],
+    },
+    EncryptedTable {
+        table: "tag",
+        has_seq: false,
+        immutable: false,
+        columns: &["name_enc", "color_enc"],
+    },
+    EncryptedTable {
+        table: "budget_category",
+        has_seq: false,
+        immutable: false,
+        columns: &["budgeted_value_enc"],
+    },
+    EncryptedTable {
+        table: "subscription",
+        has_seq: false,
+        immutable: false,
+        columns: &["name_enc", "billing_amount_enc"],
+    },
+    EncryptedTable {
+        table: "subscription_billing_event",
+        has_seq: false,
+        immutable: false,
+        columns: &["amount_enc"],
+    },
+    EncryptedTable {
+        table: "logical_transaction_state",
+        has_seq: false,
+        immutable: false,
+        columns: &["current_sum_enc"],
+    },
+    EncryptedTable {
+        table: "transaction",
+        has_seq: true,
+        immutable: true,
+        columns: &["amount_enc", "description_enc"],
+    },
+    EncryptedTable {
+        table: "transaction_split",
+        has_seq: false,
+        immutable: true,
+        columns: &["amount_enc", "memo_enc"],
+    },
+    EncryptedTable {
+        table: "reconciliation",
+        has_seq: false,
+        immutable: false,
+        columns: &["statement_balance_enc"],
+    },
+    EncryptedTable {
+        table: "attachment",
+        has_seq: false,
+        immutable: false,
+        columns: &["file_name_enc", "content_type_enc", "content_key_enc"],
+    },
+];
+
+/// Rewrite any legacy (unbound) envelope still stored on one row as a v1
+/// envelope bound to its cell. Update paths that only touch some of a row's
+/// columns call this so the row is fully upgraded on its first write.
+/// Ledger rows are immutable and are upgraded by the bulk sweep instead.
+/// Does nothing once the owner's envelopes have all been upgraded: a legacy
+/// envelope found then was planted and must not be bound to the cell.
+pub(crate) async fn upgrade_legacy_envelopes(
+    tx: &mut Transaction<'_, Postgres>,
+    table: &'static str,
+    row_id: &Uuid,
+    user_id: &Uuid,
+    dek: &Dek,
+) -> Result<(), AppError> {
+    if !dek.accepts_legacy_envelopes() {
+        return Ok(());
+    }
+
+    let spec = ENCRYPTED_TABLES
+        .iter()
+        .find(|t| t.table == table && !t.immutable)
+        .ok_or_else(|| AppError::internal(format!("{table} is not an upgradable encrypted table")))?;
+
+    let select = format!("SELECT {} FROM {} WHERE id = $1 AND user_id = $2", spec.columns.join(", "), spec.table);
+    let Some(row) = sqlx::query(&select).bind(row_id).bind(user_id).fetch_optional(&mut **tx).await? else {
+        return Ok(());
+    };
+
+    let aad = RowAad::new(spec.table, *row_id, *user_id);
+    for column in spec.columns {
+        let Some(envelope) = row.try_get::<Option<Vec<u8>>, _>(*column)? else {
+            continue;
+        };
+        // Only a legacy envelope opens without AAD.
+        let Ok(mut plaintext) = dek.decrypt_bytes(&envelope) else {
+            continue;
+        };
+        let upgraded = dek.encrypt_bytes_for(&plaintext, &aad.column(column));
+        plaintext.zeroize();
+
+        let update = format!("UPDATE {} SET {} = $1 WHERE id = $2 AND user_id = $3", spec.table, column);
+        sqlx::query(&update).bind(upgraded?).bind(row_id).bind(user_id).execute(&mut **tx).await?;
+    }
+
+    Ok(())
+}
+
+/// Bind a legacy envelope to its cell. Returns `None` when the envelope is
+/// already a v1 envelope bound to `aad`, and an error when it opens as
+/// neither.
+fn upgrade_envelope(dek: &Dek, envelope: &[u8], aad: &EnvelopeAad<'_>) -> Result<Option<Vec<u8>>, AppError> {
+    // Only a legacy envelope opens without AAD.
+    if let Ok(mut plaintext) = dek.decrypt_bytes(envelope) {
+        let upgraded = dek.encrypt_bytes_for(&plaintext, aad);
+        plaintext.zeroize();
+        return Ok(Some(upgraded?));
+    }
+    let mut plaintext = dek.decrypt_bytes_for(envelope, aad)?;
+    plaintext.zeroize();
+    Ok(None)
+}
+
+/// The next `ENVELOPE_BATCH_SIZE` rows of `spec` owned by `user_id` after
+/// the `(id, seq)` cursor, in cursor order, as `id`, `seq` (0 on tables
+/// without one) and the encrypted columns. `lock` takes the rows `FOR
+/// UPDATE`.
+pub(crate) async fn fetch_envelope_batch(
+    executor: impl PgExecutor<'_>,
+    spec: &EncryptedTable,
+    user_id: &Uuid,
+    after: (Uuid, i64),
+    lock: bool,
+) -> Result<Vec<PgRow>, AppError> {
+    let columns = spec.columns.join(", ");
+    let lock = if lock { " FOR UPDATE" } else { "" };
+    let select = if spec.has_seq {
+        format!(
+            "SELECT id, seq, {columns} FROM {table} WHERE user_id = $1 AND (id, seq) > ($2, $3) ORDER BY id, seq LIMIT $4{lock}",
+            table = spec.table
+        )
+    } else {
+        format!(
+            "SELECT id, 0::BIGINT AS seq, {columns} FROM {table} WHERE user_id = $1 AND id > $2 ORDER BY id LIMIT $3{lock}",
+            table = spec.table
+        )
+    };
+
+    let mut query = sqlx::query(&select).bind(user_id).bind(after.0);
+    if spec.has_seq {
+        query = query.bind(after.1);
+    }
+    Ok(query.bind(ENVELOPE_BATCH_SIZE).fetch_all(executor).await?)
+}
+
+/// Pass the envelopes of the next batch of rows of `spec` after the
+/// `(id, seq)` cursor through `rewrite`, which returns the replacement
+/// envelope or `None` to keep it. Returns the cursor of the last row
+/// visited (None when the table is exhausted) and the number of rows
+/// rewritten.
+pub(crate) async fn rewrite_envelope_batch<F>(
+    tx: &mut Transaction<'_, Postgres>,
+    spec: &EncryptedTable,
+    user_id: &Uuid,
+    after: (Uuid, i64),
+    rewrite: &F,
+) -> Result<(Option<(Uuid, i64)>, u64), AppError>
+where
+    F: Fn(&[u8], &EnvelopeAad<'_>) -> Result<Option<Vec<u8>>, AppError> + Sync,
+{
+    let rows = fetch_envelope_batch(&mut **tx, spec, user_id, after, true).await?;
+
+    let assignments = spec
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| format!("{} = ${}", c, i + 1))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let n = spec.columns.len();
+    let update = if spec.has_seq {
+        format!("UPDATE {} SET {} WHERE id = ${} AND seq = ${}", spec.table, assignments, n + 1, n + 2)
+    } else {
+        format!("UPDATE {} SET {} WHERE id = ${}", spec.table, assignments, n + 1)
+    };
+
+    let mut last = None;
+    let mut rewritten = 0u64;
+
+    for row in &rows {
+        let id: Uuid = row.try_get("id")?;
+        let seq: i64 = row.try_get("seq")?;
+        last = Some((id, seq));
+
+        let mut values: Vec<Option<Vec<u8>>> = Vec::with_capacity(n);
+        let mut changed = false;
+        for column in spec.columns {
+            let value: Option<Vec<u8>> = row.try_get(*column)?;
+            let aad = EnvelopeAad::new(spec.table, column, id, *user_id);
+            match value {
+                Some(envelope) => match rewrite(&envelope, &aad)? {
+                    Some(rewritten) => {
+                        changed = true;
+                        values.push(Some(rewritten));
+                    }
+                    None => values.push(Some(envelope)),
+                },
+                None => values.push(None),
+            }
+        }
+
+        if !changed {
+            continue;
+        }
+
+        let mut query = sqlx::query(&update);
+        for value in values {
+            query = query.bind(value);
+        }
+        query = query.bind(id);
+        if spec.has_seq {
+            query = query.bind(seq);
+        }
+        query.execute(&mut **tx).await?;
+        rewritten += 1;
+    }
+
+    if (rows.len() as i64) < ENVELOPE_BATCH_SIZE {
+        last = None;
+    }
+
+    Ok((last, rewritten))
+}
+
+impl PostgresRepository {
+    /// Bulk upgrade: bind every legacy envelope the user owns, ledger rows
+    /// included, committing every `ENVELOPE_BATCH_SIZE` rows, then record in
+    /// `users.envelopes_upgraded_at` that legacy envelopes are no longer
+    /// accepted. Any envelope that does not open under `dek` aborts the
+    /// sweep before the flag is set. Skipped while a DEK rotation is
+    /// pending; completing the rotation sets the flag instead. Returns the
+    /// number of rows rewritten.
+    pub async fn upgrade_all_legacy_envelopes(&self, user_id: &Uuid, dek: &Dek) -> Result<u64, AppError> {
+        if self.get_pending_dek_rotation(user_id).await?.is_some() {
+            return Ok(0);
+        }
+
+        let mut rewritten = 0u64;
+        for spec in ENCRYPTED_TABLES {
+            let mut cursor = Some((Uuid::nil(), i64::MIN));
+            while let Some(after) = cursor {
+                let mut tx = self.pool.begin().await?;
+                if spec.immutable {
+                    // Binding rewrites ciphertext only; the plaintext ledger
+                    // history is unchanged. Bypass for this batch only.
+                    sqlx::query("SET LOCAL piggy_pulse.allow_ledger_mutations = 'on'").execute(&mut *tx).await?;
+                }
+                let (next, count) = rewrite_envelope_batch(&mut tx, spec, user_id, after, &|envelope, aad| upgrade_envelope(dek, envelope, aad)).await?;
+                tx.commit().await?;
+
+                rewritten += count;
+                cursor = next;
+            }
+        }
+
+        sqlx::query("UPDATE users SET envelopes_upgraded_at = now() WHERE id = $1 AND envelopes_upgraded_at IS NULL")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(rewritten)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aad() -> EnvelopeAad<'static> {
+        EnvelopeAad::new("vendor", "name_enc", Uuid::from_u128(1), Uuid::from_u128(2))
+    }
+
+    #[test]
+    fn upgrade_envelope_binds_legacy_ciphertext() {
+        let dek = Dek::generate().with_legacy_envelopes(true);
+        let legacy = dek.encrypt_bytes(b"corner shop").unwrap();
+
+        let upgraded = upgrade_envelope(&dek, &legacy, &aad()).unwrap().expect("rewritten");
+
+        let dek = dek.with_legacy_envelopes(false);
+        assert_eq!(dek.decrypt_string_for(&upgraded, &aad()).unwrap(), "corner shop");
+    }
+
+    #[test]
+    fn upgrade_envelope_keeps_bound_ciphertext_and_rejects_foreign() {
+        let dek = Dek::generate().with_legacy_envelopes(true);
+        let bound = dek.encrypt_string_for("corner shop", &aad()).unwrap();
+        assert!(upgrade_envelope(&dek, &bound, &aad()).unwrap().is_none());
+
+        let foreign = Dek::generate().encrypt_bytes(b"corner shop").unwrap();
+        assert!(upgrade_envelope(&dek, &foreign, &aad()).is_err());
+    }
+}
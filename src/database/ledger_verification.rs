@@ -0,0 +1,399 @@
+//! Ledger verification.
+//!
+//! `account.current_balance_enc` and `logical_transaction_state.current_sum_enc`
+//! are running values kept up to date by read-modify-write on every ledger
+//! insert. Verification replays the ledger itself with the session DEK and
+//! compares the result to those stored values. It writes nothing unless
+//! asked to repair: balance drift is then booked as an adjustment row, so
+//! the ledger accounts for the balance the user sees, and transaction
+//! states are rebuilt from their rows. It also opens every envelope the
+//! user owns so corrupted ciphertext is found before a read path trips
+//! over it.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use sqlx::Row;
+use uuid::Uuid;
+use zeroize::Zeroize;
+
+use crate::crypto::{Dek, EnvelopeAad, RowAad};
+use crate::database::envelope::{ENCRYPTED_TABLES, fetch_envelope_batch};
+use crate::database::postgres_repository::PostgresRepository;
+use crate::database::transaction::{account_balance_delta, ledger_aad, lts_aad};
+use crate::error::app_error::AppError;
+use crate::models::account::AccountType;
+use crate::models::category::CategoryType;
+use crate::models::transaction::LedgerEntryKind;
+
+const LEDGER_REPAIR_DESCRIPTION: &str = "Ledger repair";
+
+/// Stored and replayed balance of one account.
+#[derive(Debug)]
+pub struct AccountBalanceCheck {
+    pub account_id: Uuid,
+    pub stored_balance: i64,
+    pub ledger_balance: i64,
+    /// The account predates recorded opening balances; its opening balance
+    /// was derived from the stored balance on this run, and saved if
+    /// repairing.
+    pub baselined: bool,
+    /// Adjustment row a repair booked the drift as.
+    pub adjustment_id: Option<Uuid>,
+}
+
+/// A logical transaction whose state row disagrees with its ledger rows.
+#[derive(Debug)]
+pub struct TransactionSumCheck {
+    pub transaction_id: Uuid,
+    pub stored_sum: i64,
+    pub ledger_sum: i64,
+}
+
+/// An envelope that does not open under the user's DEK and its cell AAD.
+#[derive(Debug)]
+pub struct EnvelopeFailure {
+    pub table: &'static str,
+    pub column: &'static str,
+    pub row_id: Uuid,
+    pub seq: Option<i64>,
+}
+
+#[derive(Debug)]
+pub struct LedgerVerification {
+    pub accounts: Vec<AccountBalanceCheck>,
+    pub transactions: Vec<TransactionSumCheck>,
+    pub envelope_failures: Vec<EnvelopeFailure>,
+    pub envelopes_checked: u64,
+    pub repaired: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct StateRow {
+    id: Uuid,
+    current_sum_enc: Vec<u8>,
+    is_effective: bool,
+    latest_seq: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct AccountRow {
+    id: Uuid,
+    account_type: AccountType,
+    current_balance_enc: Vec<u8>,
+    opening_balance_enc: Option<Vec<u8>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct LedgerRow {
+    id: Uuid,
+    seq: i64,
+    is_adjustment: bool,
+    category_type: Option<CategoryType>,
+    from_account_id: Uuid,
+    to_account_id: Option<Uuid>,
+    amount_enc: Vec<u8>,
+}
+
+/// Balance changes a ledger row made when it was written. Adjustments carry
+/// the change itself; categorized rows follow the category sign rules, and
+/// only transfers touch their `to_account_id`. Uncategorized rows never
+/// moved a balance.
+fn balance_effects(row: &LedgerRow, amount: i64, account_types: &HashMap<Uuid, AccountType>) -> Vec<(Uuid, i64)> {
+    if row.is_adjustment {
+        return vec![(row.from_account_id, amount)];
+    }
+    let Some(category_type) = row.category_type else {
+        return Vec::new();
+    };
+    let mut effects = Vec::with_capacity(2);
+    if let Some(&account_type) = account_types.get(&row.from_account_id) {
+        effects.push((row.from_account_id, account_balance_delta(category_type, account_type, amount, false)));
+    }
+    if category_type == CategoryType::Transfer
+        && let Some(to) = row.to_account_id
+        && let Some(&account_type) = account_types.get(&to)
+    {
+        effects.push((to, account_balance_delta(category_type, account_type, amount, true)));
+    }
+    effects
+}
+
+impl PostgresRepository {
+    /// Replay the user's ledger and compare it to the stored running
+    /// values. Without `repair` nothing is written. With it, a derived
+    /// opening balance is saved, each drifted balance gets a cleared
+    /// adjustment row for the drift (the stored balance is kept, as the
+    /// ledger is append-only and cannot unwrite what it missed), and
+    /// drifted transaction states are rebuilt from their rows. Anything
+    /// whose inputs do not decrypt is left out of the comparison; it shows
+    /// up in `envelope_failures` instead.
+    pub async fn verify_ledger(&self, user_id: &Uuid, dek: &Dek, repair: bool) -> Result<LedgerVerification, AppError> {
+        let (envelope_failures, envelopes_checked) = self.check_envelopes(user_id, dek).await?;
+        let overflow = || AppError::BadRequest("account balance overflow".to_string());
+
+        let mut tx = self.pool.begin().await?;
+
+        // Lock in the order the write paths do: transaction state first,
+        // then accounts. Every balance change holds its account lock, so
+        // nothing moves between the reads below.
+        let states: Vec<StateRow> =
+            sqlx::query_as("SELECT id, current_sum_enc, is_effective, latest_seq FROM logical_transaction_state WHERE user_id = $1 ORDER BY id FOR UPDATE")
+                .bind(user_id)
+                .fetch_all(&mut *tx)
+                .await?;
+        let accounts: Vec<AccountRow> = sqlx::query_as(
+            r#"
+SELECT id, account_type::text AS account_type, current_balance_enc, opening_balance_enc
+FROM account
+WHERE user_id = $1
+ORDER BY id
+FOR UPDATE
+"#,
+        )
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        let rows: Vec<LedgerRow> = sqlx::query_as(
+            r#"
+SELECT t.id, t.seq, t.entry_kind = 'adjustment' AS is_adjustment, c.category_type,
+    t.from_account_id, t.to_account_id, t.amount_enc
+FROM transaction t
+LEFT JOIN category c ON c.id = t.category_id
+WHERE t.user_id = $1
+ORDER BY t.id, t.seq
+"#,
+        )
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let account_types: HashMap<Uuid, AccountType> = accounts.iter().map(|a| (a.id, a.account_type)).collect();
+        let mut sums: HashMap<Uuid, (i64, i64)> = HashMap::new();
+        let mut effects: HashMap<Uuid, i64> = HashMap::new();
+        let mut unreadable_transactions: HashSet<Uuid> = HashSet::new();
+        let mut unreadable_accounts: HashSet<Uuid> = HashSet::new();
+
+        for row in &rows {
+            let Ok(amount) = dek.decrypt_i64_for(&row.amount_enc, &ledger_aad(&row.id, user_id).column("amount_enc")) else {
+                unreadable_transactions.insert(row.id);
+                if row.is_adjustment || row.category_type.is_some() {
+                    unreadable_accounts.insert(row.from_account_id);
+                    unreadable_accounts.extend(row.to_account_id);
+                }
+                continue;
+            };
+
+            let (sum, latest_seq) = sums.entry(row.id).or_default();
+            *sum = sum
+                .checked_add(amount)
+                .ok_or_else(|| AppError::BadRequest("current_sum overflow".to_string()))?;
+            *latest_seq = (*latest_seq).max(row.seq);
+
+            for (account_id, delta) in balance_effects(row, amount, &account_types) {
+                let effect = effects.entry(account_id).or_default();
+                *effect = effect.checked_add(delta).ok_or_else(overflow)?;
+            }
+        }
+
+        let mut repaired = false;
+
+        let mut account_checks = Vec::with_capacity(accounts.len());
+        for account in &accounts {
+            if unreadable_accounts.contains(&account.id) {
+                continue;
+            }
+            let aad = RowAad::new("account", account.id, *user_id);
+            let Ok(stored_balance) = dek.decrypt_i64_for(&account.current_balance_enc, &aad.column("current_balance_enc")) else {
+                continue;
+            };
+            let effect = effects.get(&account.id).copied().unwrap_or(0);
+
+            let (opening_balance, baselined) = match &account.opening_balance_enc {
+                Some(envelope) => match dek.decrypt_i64_for(envelope, &aad.column("opening_balance_enc")) {
+                    Ok(opening_balance) => (opening_balance, false),
+                    Err(_) => continue,
+                },
+                None => (stored_balance.checked_sub(effect).ok_or_else(overflow)?, true),
+            };
+            if baselined && repair {
+                sqlx::query("UPDATE account SET opening_balance_enc = $1 WHERE id = $2")
+                    .bind(dek.encrypt_i64_for(opening_balance, &aad.column("opening_balance_enc"))?)
+                    .bind(account.id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            let ledger_balance = opening_balance.checked_add(effect).ok_or_else(overflow)?;
+            let mut adjustment_id = None;
+            if repair && ledger_balance != stored_balance {
+                let drift = stored_balance.checked_sub(ledger_balance).ok_or_else(overflow)?;
+                adjustment_id = Some(self.book_drift_in_tx(&mut tx, &account.id, drift, user_id, dek).await?);
+                repaired = true;
+            }
+
+            account_checks.push(AccountBalanceCheck {
+                account_id: account.id,
+                stored_balance,
+                ledger_balance,
+                baselined,
+                adjustment_id,
+            });
+        }
+
+        let mut transaction_checks = Vec::new();
+        for state in &states {
+            if unreadable_transactions.contains(&state.id) {
+                continue;
+            }
+            let Some(&(ledger_sum, latest_seq)) = sums.get(&state.id) else {
+                continue;
+            };
+            let Ok(stored_sum) = dek.decrypt_i64_for(&state.current_sum_enc, &lts_aad(&state.id, user_id)) else {
+                continue;
+            };
+            if stored_sum == ledger_sum && state.is_effective == (ledger_sum != 0) && state.latest_seq == latest_seq {
+                continue;
+            }
+
+            if repair {
+                sqlx::query("UPDATE logical_transaction_state SET current_sum_enc = $1, is_effective = $2, latest_seq = $3 WHERE id = $4")
+                    .bind(dek.encrypt_i64_for(ledger_sum, &lts_aad(&state.id, user_id))?)
+                    .bind(ledger_sum != 0)
+                    .bind(latest_seq)
+                    .bind(state.id)
+                    .execute(&mut *tx)
+                    .await?;
+                repaired = true;
+            }
+
+            transaction_checks.push(TransactionSumCheck {
+                transaction_id: state.id,
+                stored_sum,
+                ledger_sum,
+            });
+        }
+
+        tx.commit().await?;
+
+        Ok(LedgerVerification {
+            accounts: account_checks,
+            transactions: transaction_checks,
+            envelope_failures,
+            envelopes_checked,
+            repaired,
+        })
+    }
+
+    /// Append a cleared adjustment of `drift` to `account_id`, leaving its
+    /// stored balance as is. Returns the adjustment's transaction id.
+    async fn book_drift_in_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        account_id: &Uuid,
+        drift: i64,
+        user_id: &Uuid,
+        dek: &Dek,
+    ) -> Result<Uuid, AppError> {
+        let id = Uuid::new_v4();
+        let aad = ledger_aad(&id, user_id);
+        let amount_enc = dek.encrypt_i64_for(drift, &aad.column("amount_enc"))?;
+        let description_enc = dek.encrypt_string_for(LEDGER_REPAIR_DESCRIPTION, &aad.column("description_enc"))?;
+
+        let (_, seq, created_at) = self
+            .insert_ledger_row_enc_in_tx(
+                tx,
+                &id,
+                user_id,
+                &amount_enc,
+                &description_enc,
+                Utc::now().date_naive(),
+                None,
+                account_id,
+                None,
+                None,
+                LedgerEntryKind::Adjustment,
+            )
+            .await?;
+        self.upsert_lts_in_tx(tx, dek, &id, user_id, drift, seq, created_at).await?;
+        sqlx::query("UPDATE logical_transaction_state SET cleared_status = 'cleared' WHERE id = $1")
+            .bind(id)
+            .execute(&mut **tx)
+            .await?;
+        Ok(id)
+    }
+
+    /// Open every envelope the user owns, in `(id, seq)` batches per table.
+    async fn check_envelopes(&self, user_id: &Uuid, dek: &Dek) -> Result<(Vec<EnvelopeFailure>, u64), AppError> {
+        let mut failures = Vec::new();
+        let mut checked = 0u64;
+        for spec in ENCRYPTED_TABLES {
+            let mut after = (Uuid::nil(), -1i64);
+            loop {
+                let rows = fetch_envelope_batch(&self.pool, spec, user_id, after, false).await?;
+                let Some(last) = rows.last() else { break };
+                after = (last.try_get("id")?, last.try_get("seq")?);
+
+                for row in &rows {
+                    let id: Uuid = row.try_get("id")?;
+                    let seq: i64 = row.try_get("seq")?;
+                    for column in spec.columns {
+                        let Some(envelope) = row.try_get::<Option<Vec<u8>>, _>(*column)? else {
+                            continue;
+                        };
+                        checked += 1;
+                        match dek.decrypt_bytes_for(&envelope, &EnvelopeAad::new(spec.table, column, id, *user_id)) {
+                            Ok(mut plaintext) => plaintext.zeroize(),
+                            Err(_) => failures.push(EnvelopeFailure {
+                                table: spec.table,
+                                column,
+                                row_id: id,
+                                seq: spec.has_seq.then_some(seq),
+                            }),
+                        }
+                    }
+                }
+            }
+        }
+        Ok((failures, checked))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(category_type: Option<CategoryType>, is_adjustment: bool, from: Uuid, to: Option<Uuid>) -> LedgerRow {
+        LedgerRow {
+            id: Uuid::new_v4(),
+            seq: 0,
+            is_adjustment,
+            category_type,
+            from_account_id: from,
+            to_account_id: to,
+            amount_enc: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn balance_effects_follow_the_write_path() {
+        let checking = Uuid::new_v4();
+        let card = Uuid::new_v4();
+        let types = HashMap::from([(checking, AccountType::Checking), (card, AccountType::CreditCard)]);
+
+        let spend = row(Some(CategoryType::Outgoing), false, checking, None);
+        assert_eq!(balance_effects(&spend, 500, &types), vec![(checking, -500)]);
+
+        let payoff = row(Some(CategoryType::Transfer), false, checking, Some(card));
+        assert_eq!(balance_effects(&payoff, 300, &types), vec![(checking, -300), (card, -300)]);
+
+        let reversal = row(Some(CategoryType::Transfer), false, checking, Some(card));
+        assert_eq!(balance_effects(&reversal, -300, &types), vec![(checking, 300), (card, 300)]);
+
+        let adjustment = row(None, true, card, None);
+        assert_eq!(balance_effects(&adjustment, -75, &types), vec![(card, -75)]);
+
+        let uncategorized = row(None, false, checking, None);
+        assert!(balance_effects(&uncategorized, 900, &types).is_empty());
+    }
+}
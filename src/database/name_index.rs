@@ -0,0 +1,79 @@
+use std::collections::HashSet;
+
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::crypto::{Dek, EnvelopeAad};
+use crate::database::postgres_repository::PostgresRepository;
+use crate::error::app_error::AppError;
+
+/// Tables with a `name_bidx` blind index beside `name_enc`, each backed by a
+/// unique `(user_id, name_bidx)` index.
+pub(crate) const NAME_INDEXED_TABLES: &[&str] = &["account", "category", "vendor", "tag"];
+
+impl PostgresRepository {
+    /// Fill in `name_bidx` for the user's rows in `table` that do not have
+    /// one yet (rows written before blind indexes existed, or since the last
+    /// DEK rotation). Runs before every name write or lookup so the unique
+    /// index and equality queries see every row, and commits on its own so
+    /// the work survives a write that then fails on a duplicate. A name that
+    /// collides with another row is left NULL.
+    pub(crate) async fn backfill_name_index(&self, table: &'static str, user_id: &Uuid, dek: &Dek) -> Result<(), AppError> {
+        let missing: Vec<(Uuid, Vec<u8>)> = sqlx::query_as(&format!("SELECT id, name_enc FROM {table} WHERE user_id = $1 AND name_bidx IS NULL"))
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        let existing: Vec<(Vec<u8>,)> = sqlx::query_as(&format!("SELECT name_bidx FROM {table} WHERE user_id = $1 AND name_bidx IS NOT NULL"))
+            .bind(user_id)
+            .fetch_all(&mut *tx)
+            .await?;
+        let mut taken: HashSet<Vec<u8>> = existing.into_iter().map(|(bidx,)| bidx).collect();
+
+        for (id, name_enc) in missing {
+            let name = dek.decrypt_string_for(&name_enc, &EnvelopeAad::new(table, "name_enc", id, *user_id))?;
+            let bidx = dek.name_blind_index(table, &name);
+            if !taken.insert(bidx.clone()) {
+                continue;
+            }
+            // A concurrent backfill may have got here first; it wrote the same value.
+            sqlx::query(&format!("UPDATE {table} SET name_bidx = $1 WHERE id = $2 AND name_bidx IS NULL"))
+                .bind(&bidx)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// Forget every blind index the user owns. The index key is derived from the
+/// DEK, so rotation calls this and the next write re-derives them lazily.
+pub(crate) async fn clear_name_indexes(tx: &mut Transaction<'_, Postgres>, user_id: &Uuid) -> Result<(), AppError> {
+    for table in NAME_INDEXED_TABLES {
+        sqlx::query(&format!("UPDATE {table} SET name_bidx = NULL WHERE user_id = $1"))
+            .bind(user_id)
+            .execute(&mut **tx)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Map a violation of `{table}_user_id_name_bidx_key` to a 409 naming the
+/// duplicate; any other error passes through.
+pub(crate) fn name_conflict(err: sqlx::Error, table: &str, entity: &str, name: &str) -> AppError {
+    if let sqlx::Error::Database(db_err) = &err
+        && db_err.code().is_some_and(|code| code == "23505")
+        && db_err.constraint() == Some(format!("{table}_user_id_name_bidx_key").as_str())
+    {
+        return AppError::Conflict(format!("{entity} named '{name}' already exists"));
+    }
+    err.into()
+}
@@ -150,6 +150,35 @@ impl PostgresRepository {
         Ok(())
     }
 
+    /// Update the password hash and the password-wrapped DEK in a single
+    /// statement, so the stored wrap always matches the password that unlocks it.
+    pub async fn update_user_password_and_wrapped_dek(
+        &self,
+        user_id: &Uuid,
+        password: &str,
+        wrapped_dek: &[u8],
+        dek_wrap_params: &serde_json::Value,
+    ) -> Result<(), AppError> {
+        let (salt, password_hash) = crate::database::user::password_hash(password);
+
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET salt = $1, password_hash = $2, wrapped_dek = $3, dek_wrap_params = $4
+            WHERE id = $5
+            "#,
+        )
+        .bind(&salt)
+        .bind(&password_hash)
+        .bind(wrapped_dek)
+        .bind(dek_wrap_params)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Invalidate all sessions for a user (used after password reset for security)
     pub async fn invalidate_all_user_sessions(&self, user_id: &Uuid) -> Result<u64, AppError> {
         let result = sqlx::query(
@@ -0,0 +1,271 @@
+//! Cleared status and statement reconciliation.
+//!
+//! The cleared balance of an account is its current balance less the
+//! effect of every effective transaction on it that is still uncleared.
+//! It is computed in-process from the envelopes, with the same sign rules
+//! the write path uses to maintain `current_balance_enc`.
+
+use chrono::NaiveDate;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::crypto::{Dek, EnvelopeAad};
+use crate::database::postgres_repository::{PostgresRepository, is_unique_violation};
+use crate::database::transaction::{account_balance_delta, lts_aad};
+use crate::error::app_error::AppError;
+use crate::models::account::AccountType;
+use crate::models::category::CategoryType;
+use crate::models::reconciliation::Reconciliation;
+use crate::models::transaction::ClearedStatus;
+
+const RECONCILIATION_COLUMNS: &str = "id, account_id, statement_date, statement_balance_enc, created_at, finished_at";
+
+pub(crate) fn statement_balance_aad(id: &Uuid, user_id: &Uuid) -> EnvelopeAad<'static> {
+    EnvelopeAad::new("reconciliation", "statement_balance_enc", *id, *user_id)
+}
+
+/// Cleared balance of `account_id`, or `None` if the user has no such
+/// account.
+async fn cleared_balance_on(conn: &mut PgConnection, account_id: &Uuid, user_id: &Uuid, dek: &Dek) -> Result<Option<i64>, AppError> {
+    let account: Option<(AccountType, Vec<u8>)> =
+        sqlx::query_as("SELECT account_type::text AS account_type, current_balance_enc FROM account WHERE id = $1 AND user_id = $2")
+            .bind(account_id)
+            .bind(user_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    let Some((account_type, balance_enc)) = account else {
+        return Ok(None);
+    };
+    let mut balance = dek.decrypt_i64_for(&balance_enc, &EnvelopeAad::new("account", "current_balance_enc", *account_id, *user_id))?;
+
+    // Uncategorized transactions never moved the balance, and only
+    // transfers touch their `to_account_id`.
+    let uncleared: Vec<(Uuid, Vec<u8>, CategoryType, bool)> = sqlx::query_as(
+        r#"
+SELECT lts.id, lts.current_sum_enc, c.category_type, t.from_account_id <> $2 AS to_side
+FROM logical_transaction_state lts
+JOIN transaction t ON t.id = lts.id AND t.seq = lts.latest_seq
+JOIN category c ON c.id = t.category_id
+WHERE lts.user_id = $1
+  AND lts.is_effective
+  AND lts.cleared_status = 'uncleared'
+  AND (t.from_account_id = $2 OR (c.category_type = 'Transfer' AND t.to_account_id = $2))
+"#,
+    )
+    .bind(user_id)
+    .bind(account_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for (id, sum_enc, category_type, to_side) in uncleared {
+        let amount = dek.decrypt_i64_for(&sum_enc, &lts_aad(&id, user_id))?;
+        balance = balance
+            .checked_sub(account_balance_delta(category_type, account_type, amount, to_side))
+            .ok_or_else(|| AppError::BadRequest("account balance overflow".to_string()))?;
+    }
+    Ok(Some(balance))
+}
+
+impl PostgresRepository {
+    pub async fn cleared_balance(&self, account_id: &Uuid, user_id: &Uuid, dek: &Dek) -> Result<i64, AppError> {
+        let mut conn = self.pool.acquire().await?;
+        cleared_balance_on(&mut conn, account_id, user_id, dek)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Account not found".to_string()))
+    }
+
+    /// Move an effective transaction between uncleared and cleared.
+    /// Reconciled transactions must be unlocked first.
+    pub async fn set_cleared_status(&self, id: &Uuid, user_id: &Uuid, status: ClearedStatus) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+        let current = lock_cleared_status(&mut tx, id, user_id).await?;
+        if current == ClearedStatus::Reconciled {
+            return Err(AppError::Conflict("Transaction is reconciled; unlock it first".to_string()));
+        }
+
+        sqlx::query("UPDATE logical_transaction_state SET cleared_status = $1 WHERE id = $2")
+            .bind(status)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Return a reconciled transaction to cleared, so it can be corrected
+    /// or deleted again.
+    pub async fn unlock_transaction(&self, id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+        if lock_cleared_status(&mut tx, id, user_id).await? != ClearedStatus::Reconciled {
+            return Err(AppError::Conflict("Transaction is not reconciled".to_string()));
+        }
+
+        sqlx::query("UPDATE logical_transaction_state SET cleared_status = 'cleared', reconciliation_id = NULL WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn create_reconciliation(
+        &self,
+        account_id: &Uuid,
+        user_id: &Uuid,
+        statement_date: NaiveDate,
+        statement_balance: i64,
+        dek: &Dek,
+    ) -> Result<Reconciliation, AppError> {
+        let account_exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM account WHERE id = $1 AND user_id = $2)")
+            .bind(account_id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        if !account_exists {
+            return Err(AppError::NotFound("Account not found".to_string()));
+        }
+
+        let id = Uuid::new_v4();
+        let statement_balance_enc = dek.encrypt_i64_for(statement_balance, &statement_balance_aad(&id, user_id))?;
+
+        let result = sqlx::query_as::<_, Reconciliation>(&format!(
+            r#"
+INSERT INTO reconciliation (id, user_id, account_id, statement_date, statement_balance_enc)
+VALUES ($1, $2, $3, $4, $5)
+RETURNING {RECONCILIATION_COLUMNS}
+"#
+        ))
+        .bind(id)
+        .bind(user_id)
+        .bind(account_id)
+        .bind(statement_date)
+        .bind(&statement_balance_enc)
+        .fetch_one(&self.pool)
+        .await;
+
+        match result {
+            Ok(reconciliation) => Ok(reconciliation),
+            Err(err) if is_unique_violation(&err) => Err(AppError::Conflict("Account already has an open reconciliation".to_string())),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub async fn list_reconciliations(&self, account_id: &Uuid, user_id: &Uuid) -> Result<Vec<Reconciliation>, AppError> {
+        let rows = sqlx::query_as::<_, Reconciliation>(&format!(
+            "SELECT {RECONCILIATION_COLUMNS} FROM reconciliation WHERE account_id = $1 AND user_id = $2 ORDER BY created_at DESC, id"
+        ))
+        .bind(account_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn get_reconciliation(&self, id: &Uuid, account_id: &Uuid, user_id: &Uuid) -> Result<Reconciliation, AppError> {
+        sqlx::query_as::<_, Reconciliation>(&format!(
+            "SELECT {RECONCILIATION_COLUMNS} FROM reconciliation WHERE id = $1 AND account_id = $2 AND user_id = $3"
+        ))
+        .bind(id)
+        .bind(account_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Reconciliation not found".to_string()))
+    }
+
+    /// Finish an open reconciliation whose cleared balance matches the
+    /// statement: every cleared transaction on the account becomes
+    /// reconciled under it. The account row stays locked throughout so no
+    /// balance change can slip in between the check and the lock.
+    pub async fn finish_reconciliation(&self, id: &Uuid, account_id: &Uuid, user_id: &Uuid, dek: &Dek) -> Result<Reconciliation, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("SELECT id FROM account WHERE id = $1 AND user_id = $2 FOR UPDATE")
+            .bind(account_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let reconciliation = sqlx::query_as::<_, Reconciliation>(&format!(
+            "SELECT {RECONCILIATION_COLUMNS} FROM reconciliation WHERE id = $1 AND account_id = $2 AND user_id = $3 FOR UPDATE"
+        ))
+        .bind(id)
+        .bind(account_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Reconciliation not found".to_string()))?;
+        if reconciliation.finished_at.is_some() {
+            return Err(AppError::Conflict("Reconciliation is already finished".to_string()));
+        }
+
+        let statement_balance = dek.decrypt_i64_for(&reconciliation.statement_balance_enc, &statement_balance_aad(id, user_id))?;
+        let cleared_balance = cleared_balance_on(&mut tx, account_id, user_id, dek)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Account not found".to_string()))?;
+        if cleared_balance != statement_balance {
+            return Err(AppError::Conflict(format!(
+                "Cleared balance differs from the statement by {}",
+                statement_balance - cleared_balance
+            )));
+        }
+
+        sqlx::query(
+            r#"
+UPDATE logical_transaction_state lts
+SET cleared_status = 'reconciled', reconciliation_id = $3
+FROM transaction t
+WHERE t.id = lts.id AND t.seq = lts.latest_seq
+  AND lts.user_id = $1
+  AND lts.is_effective
+  AND lts.cleared_status = 'cleared'
+  AND (t.from_account_id = $2 OR t.to_account_id = $2)
+"#,
+        )
+        .bind(user_id)
+        .bind(account_id)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        let finished = sqlx::query_as::<_, Reconciliation>(&format!(
+            "UPDATE reconciliation SET finished_at = now() WHERE id = $1 RETURNING {RECONCILIATION_COLUMNS}"
+        ))
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(finished)
+    }
+
+    /// Cancel an open reconciliation. Finished ones are history and stay.
+    pub async fn delete_reconciliation(&self, id: &Uuid, account_id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        let reconciliation = self.get_reconciliation(id, account_id, user_id).await?;
+        if reconciliation.finished_at.is_some() {
+            return Err(AppError::Conflict("Finished reconciliations cannot be cancelled".to_string()));
+        }
+
+        sqlx::query("DELETE FROM reconciliation WHERE id = $1 AND finished_at IS NULL")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Lock an effective transaction's state row and return its cleared
+/// status.
+async fn lock_cleared_status(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, id: &Uuid, user_id: &Uuid) -> Result<ClearedStatus, AppError> {
+    let row: Option<(ClearedStatus, bool)> =
+        sqlx::query_as("SELECT cleared_status, is_effective FROM logical_transaction_state WHERE id = $1 AND user_id = $2 FOR UPDATE")
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(&mut **tx)
+            .await?;
+    match row {
+        None => Err(AppError::NotFound("Transaction not found".to_string())),
+        Some((_, false)) => Err(AppError::Conflict("Transaction has already been voided".to_string())),
+        Some((status, true)) => Ok(status),
+    }
+}
@@ -131,4 +131,24 @@ impl PostgresRepository {
 
         Ok(())
     }
+
+    /// Expiry of an auth principal: the session's `expires_at` for cookie
+    /// auth, or the bearer token's `refresh_expires_at` (its maximum
+    /// lifetime across access-token refreshes). `None` if the principal no
+    /// longer exists or the token was revoked.
+    pub async fn get_principal_expires_at(&self, principal_id: &Uuid) -> Result<Option<DateTime<Utc>>, AppError> {
+        let row: Option<(DateTime<Utc>,)> = sqlx::query_as(
+            r#"
+            SELECT expires_at FROM user_session WHERE id = $1
+            UNION ALL
+            SELECT refresh_expires_at FROM api_tokens WHERE id = $1 AND revoked_at IS NULL
+            LIMIT 1
+            "#,
+        )
+        .bind(principal_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|(expires_at,)| expires_at))
+    }
 }
@@ -1,21 +1,51 @@
 #![allow(dead_code)]
-use crate::database::postgres_repository::PostgresRepository;
-use crate::dto::settings::{ColorTheme, DashboardLayout, DateFormat, NumberFormat, Theme};
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rocket::futures::stream::BoxStream;
+
+use crate::crypto::{Dek, EnvelopeAad, RowAad};
+use crate::database::account::account_type_to_db;
+use crate::database::attachment::{delete_user_attachments_in_tx, remove_blob_files};
+use crate::database::name_index::{NAME_INDEXED_TABLES, name_conflict};
+use crate::database::postgres_repository::{PostgresRepository, is_exclusion_violation, is_unique_violation};
+use crate::database::reconciliation::statement_balance_aad;
+use crate::database::subscription::billing_event_aad;
+use crate::database::transaction::{ledger_aad, lts_aad};
+use crate::dto::accounts::TopUpCycle;
+use crate::dto::settings::{BackupDocument, BackupLedgerRow, ColorTheme, DashboardLayout, DateFormat, ImportCounts, NumberFormat, Theme};
+use crate::dto::subscriptions::{BillingCycle, SubscriptionStatus};
 use crate::error::app_error::AppError;
+use crate::models::account::Account;
+use crate::models::budget_period::{BudgetPeriod, PeriodSchedule};
+use crate::models::category::{Category, CategoryBehavior, CategoryType};
+use crate::models::reconciliation::Reconciliation;
 use crate::models::settings::Settings;
+use crate::models::tag::Tag;
+use crate::models::transaction::{ClearedStatus, LedgerEntryKind, MAX_SPLIT_LINES, TransactionSplit};
+use crate::models::vendor::Vendor;
 use uuid::Uuid;
 
+/// Latest row of an effective transaction, as the CSV export reads it.
 #[derive(sqlx::FromRow)]
 pub struct ExportTransactionRow {
-    pub date: String,
-    pub description: String,
-    pub amount: i64,
+    pub id: Uuid,
+    pub occurred_at: NaiveDate,
+    pub category_id: Option<Uuid>,
+    pub from_account_id: Uuid,
+    pub to_account_id: Option<Uuid>,
+    pub vendor_id: Option<Uuid>,
+    pub amount_enc: Vec<u8>,
+    pub description_enc: Vec<u8>,
+}
+
+/// Account label and currency formatting for the CSV export.
+#[derive(sqlx::FromRow)]
+pub struct ExportAccountRow {
+    pub id: Uuid,
+    pub name_enc: Vec<u8>,
     pub currency: String,
-    pub category: String,
-    pub tx_type: String,
-    pub from_account: String,
-    pub to_account: String,
-    pub vendor: String,
+    pub decimal_places: i32,
 }
 
 // ── V2 helper types ──────────────────────────────────────────────────────────
@@ -91,6 +121,101 @@ fn color_theme_str(ct: ColorTheme) -> &'static str {
     }
 }
 
+// ── V2 Backup rows ───────────────────────────────────────────────────────────
+
+/// Ciphertext snapshot of everything a backup covers.
+pub struct BackupRows {
+    pub accounts: Vec<BackupAccountRow>,
+    pub categories: Vec<Category>,
+    pub vendors: Vec<Vendor>,
+    pub tags: Vec<Tag>,
+    /// `(transaction_id, tag_id)` links.
+    pub transaction_tags: Vec<(Uuid, Uuid)>,
+    /// `(id, category_id, is_excluded, budgeted_value_enc)`, as `list_targets`.
+    pub targets: Vec<(Uuid, Uuid, bool, Vec<u8>)>,
+    pub subscriptions: Vec<BackupSubscriptionRow>,
+    pub billing_events: Vec<BackupBillingEventRow>,
+    pub periods: Vec<BudgetPeriod>,
+    pub schedule: Option<PeriodSchedule>,
+    pub reconciliations: Vec<Reconciliation>,
+    /// `(id, cleared_status, reconciliation_id)` of every logical
+    /// transaction that is not uncleared.
+    pub cleared: Vec<(Uuid, ClearedStatus, Option<Uuid>)>,
+    pub ledger: Vec<BackupLedgerRowEnc>,
+    /// Split lines of every ledger row, ordered by row and position.
+    pub splits: Vec<BackupSplitRowEnc>,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct BackupAccountRow {
+    #[sqlx(flatten)]
+    pub account: Account,
+    /// ISO code of `account.currency_id`.
+    pub currency: String,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct BackupSubscriptionRow {
+    pub id: Uuid,
+    pub category_id: Uuid,
+    pub vendor_id: Option<Uuid>,
+    pub billing_cycle: BillingCycle,
+    pub billing_day: i16,
+    pub next_charge_date: NaiveDate,
+    pub status: SubscriptionStatus,
+    pub resume_on: Option<NaiveDate>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+    pub auto_post_account_id: Option<Uuid>,
+    pub last_posted_on: Option<NaiveDate>,
+    pub name_enc: Vec<u8>,
+    pub billing_amount_enc: Vec<u8>,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct BackupBillingEventRow {
+    pub id: Uuid,
+    pub subscription_id: Uuid,
+    pub transaction_id: Option<Uuid>,
+    /// Plaintext amount of an event not yet moved to `amount_enc`.
+    pub amount: Option<i64>,
+    pub amount_enc: Option<Vec<u8>>,
+    pub date: NaiveDate,
+    pub detected: bool,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct BackupLedgerRowEnc {
+    pub id: Uuid,
+    pub seq: i64,
+    pub occurred_at: NaiveDate,
+    pub category_id: Option<Uuid>,
+    pub from_account_id: Uuid,
+    pub to_account_id: Option<Uuid>,
+    pub vendor_id: Option<Uuid>,
+    pub amount_enc: Vec<u8>,
+    pub description_enc: Vec<u8>,
+    pub entry_kind: LedgerEntryKind,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct BackupSplitRowEnc {
+    pub id: Uuid,
+    pub transaction_id: Uuid,
+    pub seq: i64,
+    pub category_id: Uuid,
+    pub amount_enc: Vec<u8>,
+    pub memo_enc: Option<Vec<u8>>,
+}
+
+/// Translate a reference inside the backup document to the id its target
+/// was imported under.
+fn remap(ids: &HashMap<Uuid, Uuid>, id: &Uuid, entity: &str, field: &str) -> Result<Uuid, AppError> {
+    ids.get(id)
+        .copied()
+        .ok_or_else(|| AppError::BadRequest(format!("Backup {entity} {field} {id} does not match any entry in the backup")))
+}
+
 impl PostgresRepository {
     pub async fn get_settings(&self, user_id: &Uuid) -> Result<Settings, AppError> {
         let settings = sqlx::query_as::<_, Settings>(
@@ -273,285 +398,728 @@ impl PostgresRepository {
         self.get_preferences_v2(user_id).await
     }
 
-    // ── V2 Export (retired) ───────────────────────────────────────────────────
-    //
-    // Export and import are retired under encryption-at-rest. The
-    // server no longer has plaintext access to transaction amounts,
-    // descriptions, or entity names, so it cannot produce a CSV or
-    // JSON dump. The client exports from its own decrypted view.
-    pub async fn export_transactions_v2(&self, user_id: &Uuid) -> Result<Vec<ExportTransactionRow>, AppError> {
-        let rows = sqlx::query_as::<_, ExportTransactionRow>(
+    // ── V2 CSV Export ────────────────────────────────────────────────────────
+
+    pub async fn list_export_accounts(&self, user_id: &Uuid) -> Result<Vec<ExportAccountRow>, AppError> {
+        let rows = sqlx::query_as::<_, ExportAccountRow>(
             r#"
-            SELECT t.occurred_at::text AS date,
-                   t.description,
-                   t.amount,
-                   COALESCE(cur.currency, '') AS currency,
-                   COALESCE(cat.name, '') AS category,
-                   CASE
-                       WHEN t.to_account_id IS NOT NULL THEN 'transfer'
-                       WHEN cat.category_type = 'Incoming' THEN 'incoming'
-                       ELSE 'outgoing'
-                   END AS tx_type,
-                   COALESCE(fa.name, '') AS from_account,
-                   COALESCE(ta.name, '') AS to_account,
-                   COALESCE(v.name, '') AS vendor
-            FROM transaction t
-            LEFT JOIN account fa ON fa.id = t.from_account_id
-            LEFT JOIN account ta ON ta.id = t.to_account_id
-            LEFT JOIN currency cur ON cur.id = fa.currency_id
-            LEFT JOIN category cat ON cat.id = t.category_id
-            LEFT JOIN vendor v ON v.id = t.vendor_id
-            WHERE t.user_id = $1
-            ORDER BY t.occurred_at DESC, t.created_at DESC
-            "#,
+SELECT a.id, a.name_enc, cur.currency, cur.decimal_places
+FROM account a
+JOIN currency cur ON cur.id = a.currency_id
+WHERE a.user_id = $1
+"#,
         )
         .bind(user_id)
         .fetch_all(&self.pool)
         .await?;
-
         Ok(rows)
     }
 
-    pub async fn export_all_data_v2(&self, user_id: &Uuid) -> Result<serde_json::Value, AppError> {
-        let accounts = sqlx::query_scalar::<_, serde_json::Value>(
+    /// Stream the latest row of every effective transaction, oldest first,
+    /// optionally limited to an inclusive date range and to transactions
+    /// moving money out of or into one account. Rows are read off a cursor
+    /// so the export never holds the whole ledger in memory.
+    pub fn stream_export_transactions(
+        &self,
+        user_id: Uuid,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        account_id: Option<Uuid>,
+    ) -> BoxStream<'_, Result<ExportTransactionRow, sqlx::Error>> {
+        sqlx::query_as::<_, ExportTransactionRow>(
             r#"
-            SELECT COALESCE(json_agg(row_to_json(a)), '[]'::json) FROM (
-                SELECT id, name, account_type, color, is_archived, currency_id, created_at
-                FROM account WHERE user_id = $1 ORDER BY created_at
-            ) a
-            "#,
+SELECT t.id, t.occurred_at, t.category_id, t.from_account_id, t.to_account_id, t.vendor_id, t.amount_enc, t.description_enc
+FROM logical_transaction_state lts
+JOIN transaction t ON t.id = lts.id AND t.seq = lts.latest_seq
+WHERE lts.user_id = $1
+  AND lts.is_effective
+  AND ($2::date IS NULL OR t.occurred_at >= $2)
+  AND ($3::date IS NULL OR t.occurred_at <= $3)
+  AND ($4::uuid IS NULL OR t.from_account_id = $4 OR t.to_account_id = $4)
+ORDER BY t.occurred_at, t.seq
+"#,
         )
         .bind(user_id)
-        .fetch_one(&self.pool)
-        .await
-        .unwrap_or(serde_json::Value::Array(vec![]));
+        .bind(from)
+        .bind(to)
+        .bind(account_id)
+        .fetch(&self.pool)
+    }
 
-        let categories = sqlx::query_scalar::<_, serde_json::Value>(
+    // ── V2 Backup ────────────────────────────────────────────────────────────
+
+    /// Read everything a backup covers in one repeatable-read snapshot, so
+    /// the ledger, balances and references agree with each other. Returns
+    /// ciphertext; the service decrypts it.
+    pub async fn export_backup_rows(&self, user_id: &Uuid) -> Result<BackupRows, AppError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+
+        let accounts = sqlx::query_as::<_, BackupAccountRow>(
             r#"
-            SELECT COALESCE(json_agg(row_to_json(c)), '[]'::json) FROM (
-                SELECT id, name, category_type, color, icon, is_system, created_at
-                FROM category WHERE user_id = $1 ORDER BY created_at
-            ) c
-            "#,
+SELECT a.id, a.account_type::text AS account_type, a.currency_id, a.is_archived,
+    a.name_enc, a.color_enc, a.current_balance_enc,
+    a.spend_limit_enc, a.next_transfer_amount_enc, a.top_up_amount_enc,
+    a.top_up_cycle, a.top_up_day, a.statement_close_day, a.payment_due_day,
+    cur.currency
+FROM account a
+JOIN currency cur ON cur.id = a.currency_id
+WHERE a.user_id = $1
+ORDER BY a.created_at, a.id
+"#,
         )
         .bind(user_id)
-        .fetch_one(&self.pool)
-        .await
-        .unwrap_or(serde_json::Value::Array(vec![]));
+        .fetch_all(&mut *tx)
+        .await?;
 
-        let transactions = sqlx::query_scalar::<_, serde_json::Value>(
+        let categories = sqlx::query_as::<_, Category>(
             r#"
-            SELECT COALESCE(json_agg(row_to_json(t)), '[]'::json) FROM (
-                SELECT id, description, amount, occurred_at, from_account_id, to_account_id, category_id, vendor_id, created_at
-                FROM transaction WHERE user_id = $1 ORDER BY occurred_at DESC
-            ) t
-            "#,
+SELECT id, category_type, behavior, parent_id, is_system, is_archived, name_enc, color_enc, icon_enc, description_enc
+FROM category
+WHERE user_id = $1
+ORDER BY created_at, id
+"#,
         )
         .bind(user_id)
-        .fetch_one(&self.pool)
-        .await
-        .unwrap_or(serde_json::Value::Array(vec![]));
-
-        Ok(serde_json::json!({
-            "accounts": accounts,
-            "categories": categories,
-            "transactions": transactions,
-        }))
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let vendors = sqlx::query_as::<_, Vendor>("SELECT id, archived, name_enc, description_enc FROM vendor WHERE user_id = $1 ORDER BY created_at, id")
+            .bind(user_id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let tags = sqlx::query_as::<_, Tag>("SELECT id, name_enc, color_enc FROM tag WHERE user_id = $1 ORDER BY created_at, id")
+            .bind(user_id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let transaction_tags: Vec<(Uuid, Uuid)> =
+            sqlx::query_as("SELECT transaction_id, tag_id FROM transaction_tag WHERE user_id = $1 ORDER BY transaction_id, tag_id")
+                .bind(user_id)
+                .fetch_all(&mut *tx)
+                .await?;
+
+        let targets: Vec<(Uuid, Uuid, bool, Vec<u8>)> =
+            sqlx::query_as("SELECT id, category_id, is_excluded, budgeted_value_enc FROM budget_category WHERE user_id = $1 ORDER BY created_at, id")
+                .bind(user_id)
+                .fetch_all(&mut *tx)
+                .await?;
+
+        let subscriptions = sqlx::query_as::<_, BackupSubscriptionRow>(
+            r#"
+SELECT id, category_id, vendor_id, billing_cycle::text AS billing_cycle, billing_day, next_charge_date,
+    status::text AS status, resume_on, cancelled_at, auto_post_account_id, last_posted_on, name_enc, billing_amount_enc
+FROM subscription
+WHERE user_id = $1
+ORDER BY created_at, id
+"#,
+        )
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let billing_events = sqlx::query_as::<_, BackupBillingEventRow>(
+            r#"
+SELECT id, subscription_id, transaction_id, amount, amount_enc, date, detected
+FROM subscription_billing_event
+WHERE user_id = $1
+ORDER BY date, created_at, id
+"#,
+        )
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let periods = sqlx::query_as::<_, BudgetPeriod>(
+            r#"
+SELECT id, user_id, name, start_date, end_date, is_auto_generated, created_at
+FROM budget_period
+WHERE user_id = $1
+ORDER BY start_date
+"#,
+        )
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let schedule = sqlx::query_as::<_, PeriodSchedule>(
+            r#"
+SELECT id, user_id, schedule_type, start_day, duration_value, duration_unit,
+    saturday_adjustment, sunday_adjustment, name_pattern,
+    generate_ahead, recurrence_method, created_at, updated_at
+FROM period_schedule
+WHERE user_id = $1
+"#,
+        )
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let reconciliations = sqlx::query_as::<_, Reconciliation>(
+            r#"
+SELECT id, account_id, statement_date, statement_balance_enc, created_at, finished_at
+FROM reconciliation
+WHERE user_id = $1
+ORDER BY created_at, id
+"#,
+        )
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let cleared: Vec<(Uuid, ClearedStatus, Option<Uuid>)> =
+            sqlx::query_as("SELECT id, cleared_status, reconciliation_id FROM logical_transaction_state WHERE user_id = $1 AND cleared_status <> 'uncleared'")
+                .bind(user_id)
+                .fetch_all(&mut *tx)
+                .await?;
+
+        let ledger = sqlx::query_as::<_, BackupLedgerRowEnc>(
+            r#"
+SELECT id, seq, occurred_at, category_id, from_account_id, to_account_id, vendor_id, amount_enc, description_enc, entry_kind, created_at
+FROM transaction
+WHERE user_id = $1
+ORDER BY seq
+"#,
+        )
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let splits = sqlx::query_as::<_, BackupSplitRowEnc>(
+            r#"
+SELECT id, transaction_id, seq, category_id, amount_enc, memo_enc
+FROM transaction_split
+WHERE user_id = $1
+ORDER BY transaction_id, seq, position
+"#,
+        )
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(BackupRows {
+            accounts,
+            categories,
+            vendors,
+            tags,
+            transaction_tags,
+            targets,
+            subscriptions,
+            billing_events,
+            periods,
+            schedule,
+            reconciliations,
+            cleared,
+            ledger,
+            splits,
+        })
     }
 
-    /// Import data from a JSON backup (reverse of `export_all_data_v2`).
+    /// Restore a decrypted backup into the user's data, all or nothing.
     ///
-    /// Inserts accounts, categories, and transactions from the provided JSON blob.
-    /// Fresh UUIDs are generated for every imported row so that a backup from
-    /// one account can be safely imported into a different account without ID
-    /// collisions. Old→new ID mappings are maintained in memory so that
-    /// transaction foreign-key references are correctly remapped.
+    /// Every row gets a fresh id and is encrypted under `dek` bound to that
+    /// id; references inside the document are remapped through the old→new
+    /// id maps. System categories map onto the user's existing system
+    /// category of the same type. Ledger rows are appended in `seq` order,
+    /// so each logical transaction keeps its full history, and
+    /// `logical_transaction_state` is rebuilt from their sums along with
+    /// the transaction's tag links and cleared status. Account
+    /// balances are restored as exported rather than replayed.
     ///
-    /// Returns the counts of imported rows: `(accounts, categories, transactions)`.
-    pub async fn import_data_v2(&self, user_id: &Uuid, data: &serde_json::Value) -> Result<(usize, usize, usize), AppError> {
-        use std::collections::HashMap;
-
-        let empty_vec = serde_json::Value::Array(vec![]);
-
-        let accounts_json = data.get("accounts").unwrap_or(&empty_vec);
-        let categories_json = data.get("categories").unwrap_or(&empty_vec);
-        let transactions_json = data.get("transactions").unwrap_or(&empty_vec);
-
-        let accounts_arr = accounts_json
-            .as_array()
-            .ok_or_else(|| AppError::BadRequest("'accounts' must be an array".to_string()))?;
-        let categories_arr = categories_json
-            .as_array()
-            .ok_or_else(|| AppError::BadRequest("'categories' must be an array".to_string()))?;
-        let transactions_arr = transactions_json
-            .as_array()
-            .ok_or_else(|| AppError::BadRequest("'transactions' must be an array".to_string()))?;
+    /// Anything that clashes with the user's existing data (a name, an
+    /// overlapping period, an existing schedule) rolls the whole import
+    /// back with a 409.
+    pub async fn import_backup(&self, user_id: &Uuid, backup: &BackupDocument, dek: &Dek) -> Result<ImportCounts, AppError> {
+        for table in NAME_INDEXED_TABLES {
+            self.backfill_name_index(table, user_id, dek).await?;
+        }
 
         let mut tx = self.pool.begin().await?;
+        let mut counts = ImportCounts::default();
 
         // ── Accounts ─────────────────────────────────────────────────────────
-        let mut account_id_map: HashMap<String, Uuid> = HashMap::new();
-
-        for acct in accounts_arr {
-            let old_id = acct
-                .get("id")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| AppError::BadRequest("account missing 'id'".to_string()))?;
-            let name = acct
-                .get("name")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| AppError::BadRequest("account missing 'name'".to_string()))?;
-            let account_type = acct
-                .get("account_type")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| AppError::BadRequest("account missing 'account_type'".to_string()))?;
-            let color = acct.get("color").and_then(|v| v.as_str()).unwrap_or("#868E96");
-            let is_archived = acct.get("is_archived").and_then(|v| v.as_bool()).unwrap_or(false);
-            let currency_id = acct.get("currency_id").and_then(|v| v.as_str()).and_then(|s| Uuid::parse_str(s).ok());
-
-            let new_id = Uuid::new_v4();
-            account_id_map.insert(old_id.to_string(), new_id);
+        let mut currency_ids: HashMap<&str, Uuid> = HashMap::new();
+        let mut account_ids: HashMap<Uuid, Uuid> = HashMap::new();
+
+        for account in &backup.accounts {
+            let currency_id = match currency_ids.get(account.currency.as_str()) {
+                Some(id) => *id,
+                None => {
+                    let id: Uuid = sqlx::query_scalar("SELECT id FROM currency WHERE currency = $1")
+                        .bind(&account.currency)
+                        .fetch_optional(&mut *tx)
+                        .await?
+                        .ok_or_else(|| AppError::CurrencyDoesNotExist(account.currency.clone()))?;
+                    currency_ids.insert(&account.currency, id);
+                    id
+                }
+            };
+
+            let id = Uuid::new_v4();
+            let aad = RowAad::new("account", id, *user_id);
+            let encrypt_opt = |value: Option<i64>, column| value.map(|v| dek.encrypt_i64_for(v, &aad.column(column))).transpose();
 
             sqlx::query(
                 r#"
-                INSERT INTO account (id, user_id, name, account_type, color, icon, balance, is_archived, currency_id)
-                VALUES ($1, $2, $3, $4::text::account_type, $5, '', 0, $6, $7)
-                "#,
+INSERT INTO account (
+    id, user_id, account_type, currency_id, is_archived,
+    name_enc, name_bidx, color_enc, current_balance_enc,
+    spend_limit_enc, next_transfer_amount_enc, top_up_amount_enc,
+    top_up_cycle, top_up_day, statement_close_day, payment_due_day
+) VALUES ($1, $2, $3::text::account_type, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+"#,
             )
-            .bind(new_id)
+            .bind(id)
             .bind(user_id)
-            .bind(name)
-            .bind(account_type)
-            .bind(color)
-            .bind(is_archived)
+            .bind(account_type_to_db(account.account_type.into()))
             .bind(currency_id)
+            .bind(account.is_archived)
+            .bind(dek.encrypt_string_for(&account.name, &aad.column("name_enc"))?)
+            .bind(dek.name_blind_index("account", &account.name))
+            .bind(dek.encrypt_string_for(&account.color, &aad.column("color_enc"))?)
+            .bind(dek.encrypt_i64_for(account.balance, &aad.column("current_balance_enc"))?)
+            .bind(encrypt_opt(account.spend_limit, "spend_limit_enc")?)
+            .bind(encrypt_opt(account.next_transfer_amount, "next_transfer_amount_enc")?)
+            .bind(encrypt_opt(account.top_up_amount, "top_up_amount_enc")?)
+            .bind(account.top_up_cycle.map(TopUpCycle::as_str))
+            .bind(account.top_up_day)
+            .bind(account.statement_close_day)
+            .bind(account.payment_due_day)
             .execute(&mut *tx)
-            .await?;
+            .await
+            .map_err(|e| name_conflict(e, "account", "An account", &account.name))?;
+
+            account_ids.insert(account.id, id);
+            counts.accounts += 1;
         }
 
         // ── Categories ───────────────────────────────────────────────────────
-        let mut category_id_map: HashMap<String, Uuid> = HashMap::new();
-
-        for cat in categories_arr {
-            let old_id = cat
-                .get("id")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| AppError::BadRequest("category missing 'id'".to_string()))?;
-            let name = cat
-                .get("name")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| AppError::BadRequest("category missing 'name'".to_string()))?;
-            let category_type = cat
-                .get("category_type")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| AppError::BadRequest("category missing 'category_type'".to_string()))?;
-            let color = cat.get("color").and_then(|v| v.as_str()).unwrap_or("#868E96");
-            let icon = cat.get("icon").and_then(|v| v.as_str()).unwrap_or("?");
-            let is_system = cat.get("is_system").and_then(|v| v.as_bool()).unwrap_or(false);
-
-            // System categories (e.g. Transfer) may already exist after reset-structure.
-            // Map the old ID to the existing system category instead of inserting a duplicate.
-            if is_system {
-                let existing: Option<(Uuid,)> =
-                    sqlx::query_as("SELECT id FROM category WHERE user_id = $1 AND name = $2 AND category_type = $3::text::category_type AND is_system = true")
-                        .bind(user_id)
-                        .bind(name)
-                        .bind(category_type)
-                        .fetch_optional(&mut *tx)
-                        .await?;
-                if let Some((existing_id,)) = existing {
-                    category_id_map.insert(old_id.to_string(), existing_id);
+        // Parents are linked in a second pass so the document order does
+        // not matter.
+        let mut category_ids: HashMap<Uuid, Uuid> = HashMap::new();
+
+        for category in &backup.categories {
+            let category_type: CategoryType = category.category_type.into();
+
+            if category.is_system {
+                let existing: Option<Uuid> = sqlx::query_scalar("SELECT id FROM category WHERE user_id = $1 AND is_system AND category_type = $2")
+                    .bind(user_id)
+                    .bind(category_type)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+                if let Some(existing) = existing {
+                    category_ids.insert(category.id, existing);
                     continue;
                 }
             }
 
-            let new_id = Uuid::new_v4();
-            category_id_map.insert(old_id.to_string(), new_id);
+            let id = Uuid::new_v4();
+            let aad = RowAad::new("category", id, *user_id);
+            let encrypt_opt = |value: Option<&str>, column| value.map(|v| dek.encrypt_string_for(v, &aad.column(column))).transpose();
 
             sqlx::query(
                 r#"
-                INSERT INTO category (id, user_id, name, category_type, color, icon, is_system)
-                VALUES ($1, $2, $3, $4::text::category_type, $5, $6, $7)
-                "#,
+INSERT INTO category (
+    id, user_id, category_type, behavior, is_system, is_archived,
+    name_enc, name_bidx, color_enc, icon_enc, description_enc
+) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+"#,
             )
-            .bind(new_id)
+            .bind(id)
             .bind(user_id)
-            .bind(name)
             .bind(category_type)
-            .bind(color)
-            .bind(icon)
-            .bind(is_system)
+            .bind(category.behavior.map(CategoryBehavior::from))
+            .bind(category.is_system)
+            .bind(category.is_archived)
+            .bind(dek.encrypt_string_for(&category.name, &aad.column("name_enc"))?)
+            .bind(dek.name_blind_index("category", &category.name))
+            .bind(encrypt_opt(category.color.as_deref(), "color_enc")?)
+            .bind(encrypt_opt(category.icon.as_deref(), "icon_enc")?)
+            .bind(encrypt_opt(category.description.as_deref(), "description_enc")?)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| name_conflict(e, "category", "A category", &category.name))?;
+
+            category_ids.insert(category.id, id);
+            counts.categories += 1;
+        }
+
+        for category in &backup.categories {
+            let Some(parent_id) = category.parent_id else { continue };
+            sqlx::query("UPDATE category SET parent_id = $1 WHERE id = $2")
+                .bind(remap(&category_ids, &parent_id, "category", "parentId")?)
+                .bind(category_ids[&category.id])
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // ── Vendors ──────────────────────────────────────────────────────────
+        let mut vendor_ids: HashMap<Uuid, Uuid> = HashMap::new();
+
+        for vendor in &backup.vendors {
+            let id = Uuid::new_v4();
+            let aad = RowAad::new("vendor", id, *user_id);
+            let description_enc = vendor
+                .description
+                .as_deref()
+                .map(|d| dek.encrypt_string_for(d, &aad.column("description_enc")))
+                .transpose()?;
+
+            sqlx::query("INSERT INTO vendor (id, user_id, archived, name_enc, name_bidx, description_enc) VALUES ($1, $2, $3, $4, $5, $6)")
+                .bind(id)
+                .bind(user_id)
+                .bind(vendor.archived)
+                .bind(dek.encrypt_string_for(&vendor.name, &aad.column("name_enc"))?)
+                .bind(dek.name_blind_index("vendor", &vendor.name))
+                .bind(description_enc)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| name_conflict(e, "vendor", "A vendor", &vendor.name))?;
+
+            vendor_ids.insert(vendor.id, id);
+            counts.vendors += 1;
+        }
+
+        // ── Tags ─────────────────────────────────────────────────────────────
+        let mut tag_ids: HashMap<Uuid, Uuid> = HashMap::new();
+
+        for tag in &backup.tags {
+            let id = Uuid::new_v4();
+            let aad = RowAad::new("tag", id, *user_id);
+
+            sqlx::query("INSERT INTO tag (id, user_id, name_enc, name_bidx, color_enc) VALUES ($1, $2, $3, $4, $5)")
+                .bind(id)
+                .bind(user_id)
+                .bind(dek.encrypt_string_for(&tag.name, &aad.column("name_enc"))?)
+                .bind(dek.name_blind_index("tag", &tag.name))
+                .bind(dek.encrypt_string_for(&tag.color, &aad.column("color_enc"))?)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| name_conflict(e, "tag", "A tag", &tag.name))?;
+
+            tag_ids.insert(tag.id, id);
+            counts.tags += 1;
+        }
+
+        // ── Targets ──────────────────────────────────────────────────────────
+        for target in &backup.targets {
+            let id = Uuid::new_v4();
+            let value_enc = dek.encrypt_i64_for(target.value, &EnvelopeAad::new("budget_category", "budgeted_value_enc", id, *user_id))?;
+
+            sqlx::query("INSERT INTO budget_category (id, user_id, category_id, is_excluded, budgeted_value_enc) VALUES ($1, $2, $3, $4, $5)")
+                .bind(id)
+                .bind(user_id)
+                .bind(remap(&category_ids, &target.category_id, "target", "categoryId")?)
+                .bind(target.is_excluded)
+                .bind(&value_enc)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| match e {
+                    e if is_unique_violation(&e) => AppError::Conflict("A target already exists for an imported category".to_string()),
+                    e => e.into(),
+                })?;
+
+            counts.targets += 1;
+        }
+
+        // ── Periods and schedule ─────────────────────────────────────────────
+        for period in &backup.periods {
+            sqlx::query("INSERT INTO budget_period (user_id, name, start_date, end_date, is_auto_generated) VALUES ($1, $2, $3, $4, $5)")
+                .bind(user_id)
+                .bind(&period.name)
+                .bind(period.start_date.0)
+                .bind(period.end_date.0)
+                .bind(period.is_auto_generated)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| match e {
+                    e if is_unique_violation(&e) => AppError::Conflict(format!("A period named '{}' already exists", period.name)),
+                    e if is_exclusion_violation(&e) => AppError::Conflict(format!("Period '{}' overlaps an existing period", period.name)),
+                    e => e.into(),
+                })?;
+
+            counts.periods += 1;
+        }
+
+        if let Some(schedule) = &backup.schedule {
+            sqlx::query(
+                r#"
+INSERT INTO period_schedule (
+    user_id, schedule_type, start_day, duration_value, duration_unit,
+    saturday_adjustment, sunday_adjustment, name_pattern, generate_ahead,
+    recurrence_method
+) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, COALESCE($10, 'dayOfMonth'))
+"#,
+            )
+            .bind(user_id)
+            .bind(&schedule.schedule_type)
+            .bind(schedule.start_day)
+            .bind(schedule.duration_value)
+            .bind(schedule.duration_unit)
+            .bind(schedule.saturday_adjustment)
+            .bind(schedule.sunday_adjustment)
+            .bind(schedule.name_pattern.as_deref())
+            .bind(schedule.generate_ahead)
+            .bind(schedule.recurrence_method.as_deref())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| match e {
+                e if is_unique_violation(&e) => AppError::Conflict("Schedule already exists".to_string()),
+                e => e.into(),
+            })?;
+
+            counts.schedule = true;
+        }
+
+        // ── Reconciliations ──────────────────────────────────────────────────
+        let mut reconciliation_ids: HashMap<Uuid, Uuid> = HashMap::new();
+
+        for reconciliation in &backup.reconciliations {
+            let id = Uuid::new_v4();
+
+            sqlx::query(
+                r#"
+INSERT INTO reconciliation (id, user_id, account_id, statement_date, statement_balance_enc, created_at, finished_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
+"#,
+            )
+            .bind(id)
+            .bind(user_id)
+            .bind(remap(&account_ids, &reconciliation.account_id, "reconciliation", "accountId")?)
+            .bind(reconciliation.statement_date.0)
+            .bind(dek.encrypt_i64_for(reconciliation.statement_balance, &statement_balance_aad(&id, user_id))?)
+            .bind(reconciliation.created_at)
+            .bind(reconciliation.finished_at)
             .execute(&mut *tx)
+            .await
+            .map_err(|e| match e {
+                e if is_unique_violation(&e) => AppError::BadRequest("Backup has more than one open reconciliation for an account".to_string()),
+                e => e.into(),
+            })?;
+
+            reconciliation_ids.insert(reconciliation.id, id);
+            counts.reconciliations += 1;
+        }
+
+        // ── Ledger ───────────────────────────────────────────────────────────
+        // Appending in `seq` order gives every logical transaction the same
+        // row order under the new sequence values.
+        struct LogicalState {
+            id: Uuid,
+            sum: i64,
+            latest_seq: i64,
+            first_created_at: DateTime<Utc>,
+            tag_ids: Vec<Uuid>,
+            cleared_status: ClearedStatus,
+            reconciliation_id: Option<Uuid>,
+        }
+
+        let mut rows: Vec<&BackupLedgerRow> = backup.transactions.iter().collect();
+        rows.sort_by_key(|row| row.seq);
+
+        // Old logical id → index into `states`.
+        let mut ledger_ids: HashMap<Uuid, usize> = HashMap::new();
+        let mut states: Vec<LogicalState> = Vec::new();
+
+        for row in rows {
+            let index = *ledger_ids.entry(row.id).or_insert_with(|| {
+                states.push(LogicalState {
+                    id: Uuid::new_v4(),
+                    sum: 0,
+                    latest_seq: 0,
+                    first_created_at: row.created_at,
+                    tag_ids: Vec::new(),
+                    cleared_status: ClearedStatus::Uncleared,
+                    reconciliation_id: None,
+                });
+                states.len() - 1
+            });
+            let id = states[index].id;
+            let aad = ledger_aad(&id, user_id);
+            // Transactions keep pointing at vendors that were deleted later
+            // (there is no foreign key), so an unknown vendor is dropped.
+            let vendor_id = row.vendor_id.and_then(|v| vendor_ids.get(&v).copied());
+
+            let seq: i64 = sqlx::query_scalar(
+                r#"
+INSERT INTO transaction (
+    id, user_id, amount_enc, description_enc, occurred_at,
+    category_id, from_account_id, to_account_id, vendor_id, entry_kind, created_at
+) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+RETURNING seq
+"#,
+            )
+            .bind(id)
+            .bind(user_id)
+            .bind(dek.encrypt_i64_for(row.amount, &aad.column("amount_enc"))?)
+            .bind(dek.encrypt_string_for(&row.description, &aad.column("description_enc"))?)
+            .bind(row.occurred_at.0)
+            .bind(row.category_id.map(|c| remap(&category_ids, &c, "transaction", "categoryId")).transpose()?)
+            .bind(remap(&account_ids, &row.from_account_id, "transaction", "fromAccountId")?)
+            .bind(row.to_account_id.map(|a| remap(&account_ids, &a, "transaction", "toAccountId")).transpose()?)
+            .bind(vendor_id)
+            .bind(row.kind)
+            .bind(row.created_at)
+            .fetch_one(&mut *tx)
             .await?;
+
+            if row.splits.len() > MAX_SPLIT_LINES {
+                return Err(AppError::BadRequest(format!(
+                    "Backup transaction {} has more than {MAX_SPLIT_LINES} split lines",
+                    row.id
+                )));
+            }
+            if !row.splits.is_empty() && row.splits.iter().try_fold(0i64, |sum, line| sum.checked_add(line.amount)) != Some(row.amount) {
+                return Err(AppError::BadRequest(format!(
+                    "Backup transaction {} split lines do not sum to its amount",
+                    row.id
+                )));
+            }
+            let splits = row
+                .splits
+                .iter()
+                .map(|line| {
+                    Ok(TransactionSplit {
+                        category_id: remap(&category_ids, &line.category_id, "split line", "categoryId")?,
+                        amount: line.amount,
+                        memo: line.memo.clone(),
+                    })
+                })
+                .collect::<Result<Vec<_>, AppError>>()?;
+            self.insert_splits_in_tx(&mut tx, dek, &id, seq, user_id, &splits).await?;
+
+            let state = &mut states[index];
+            state.sum = state
+                .sum
+                .checked_add(row.amount)
+                .ok_or_else(|| AppError::BadRequest("transaction amount overflow".to_string()))?;
+            state.latest_seq = seq;
+            for tag_id in &row.tag_ids {
+                let tag_id = remap(&tag_ids, tag_id, "transaction", "tagIds")?;
+                if !state.tag_ids.contains(&tag_id) {
+                    state.tag_ids.push(tag_id);
+                }
+            }
+            if let Some(status) = row.cleared_status {
+                state.cleared_status = status;
+            }
+            if let Some(reconciliation_id) = &row.reconciliation_id {
+                state.reconciliation_id = Some(remap(&reconciliation_ids, reconciliation_id, "transaction", "reconciliationId")?);
+            }
+            counts.ledger_rows += 1;
         }
 
-        // ── Transactions ─────────────────────────────────────────────────────
-        let mut imported_transactions: usize = 0;
-
-        for txn in transactions_arr {
-            let description = txn.get("description").and_then(|v| v.as_str()).unwrap_or("");
-            let amount = txn
-                .get("amount")
-                .and_then(|v| v.as_i64())
-                .ok_or_else(|| AppError::BadRequest("transaction missing 'amount'".to_string()))?;
-            let occurred_at = txn
-                .get("occurred_at")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| AppError::BadRequest("transaction missing 'occurred_at'".to_string()))?;
-
-            let from_account_old_id = txn
-                .get("from_account_id")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| AppError::BadRequest("transaction missing 'from_account_id'".to_string()))?;
-            let from_account_id = account_id_map
-                .get(from_account_old_id)
-                .copied()
-                .ok_or_else(|| AppError::BadRequest(format!("transaction references unknown from_account_id '{}'", from_account_old_id)))?;
-
-            let to_account_id = txn
-                .get("to_account_id")
-                .and_then(|v| if v.is_null() { None } else { v.as_str() })
-                .and_then(|old| account_id_map.get(old).copied());
-
-            let category_old_id = txn
-                .get("category_id")
-                .and_then(|v| if v.is_null() { None } else { v.as_str() })
-                .ok_or_else(|| AppError::BadRequest("transaction missing 'category_id'".to_string()))?;
-            let category_id = category_id_map
-                .get(category_old_id)
-                .copied()
-                .ok_or_else(|| AppError::BadRequest(format!("transaction references unknown category_id '{}'", category_old_id)))?;
-
-            let new_id = Uuid::new_v4();
+        for state in &states {
+            let sum_enc = dek.encrypt_i64_for(state.sum, &lts_aad(&state.id, user_id))?;
+            sqlx::query(
+                r#"
+INSERT INTO logical_transaction_state (
+    id, user_id, current_sum_enc, is_effective, latest_seq, first_created_at, cleared_status, reconciliation_id
+) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+"#,
+            )
+            .bind(state.id)
+            .bind(user_id)
+            .bind(&sum_enc)
+            .bind(state.sum != 0)
+            .bind(state.latest_seq)
+            .bind(state.first_created_at)
+            .bind(state.cleared_status)
+            .bind(state.reconciliation_id)
+            .execute(&mut *tx)
+            .await?;
+
+            for tag_id in &state.tag_ids {
+                sqlx::query("INSERT INTO transaction_tag (transaction_id, tag_id, user_id) VALUES ($1, $2, $3)")
+                    .bind(state.id)
+                    .bind(tag_id)
+                    .bind(user_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+        counts.transactions = states.len();
+
+        // ── Subscriptions ────────────────────────────────────────────────────
+        for subscription in &backup.subscriptions {
+            let id = Uuid::new_v4();
+            let aad = RowAad::new("subscription", id, *user_id);
 
             sqlx::query(
                 r#"
-                INSERT INTO transaction (id, user_id, description, amount, occurred_at, from_account_id, to_account_id, category_id)
-                VALUES ($1, $2, $3, $4, $5::date, $6, $7, $8)
-                "#,
+INSERT INTO subscription (
+    id, user_id, category_id, vendor_id, billing_cycle, billing_day,
+    next_charge_date, status, resume_on, cancelled_at, auto_post_account_id, last_posted_on,
+    name_enc, billing_amount_enc
+) VALUES (
+    $1, $2, $3, $4, $5::text::subscription_billing_cycle, $6,
+    $7, $8::text::subscription_status, $9, $10, $11, $12, $13, $14
+)
+"#,
             )
-            .bind(new_id)
+            .bind(id)
             .bind(user_id)
-            .bind(description)
-            .bind(amount)
-            .bind(occurred_at)
-            .bind(from_account_id)
-            .bind(to_account_id)
-            .bind(category_id)
+            .bind(remap(&category_ids, &subscription.category_id, "subscription", "categoryId")?)
+            .bind(subscription.vendor_id.map(|v| remap(&vendor_ids, &v, "subscription", "vendorId")).transpose()?)
+            .bind(subscription.billing_cycle)
+            .bind(subscription.billing_day)
+            .bind(subscription.next_charge_date.0)
+            .bind(subscription.status)
+            .bind(
+                subscription
+                    .resume_on
+                    .as_ref()
+                    .filter(|_| subscription.status == SubscriptionStatus::Paused)
+                    .map(|d| d.0),
+            )
+            .bind(subscription.cancelled_at)
+            .bind(
+                subscription
+                    .auto_post_account_id
+                    .map(|a| remap(&account_ids, &a, "subscription", "autoPostAccountId"))
+                    .transpose()?,
+            )
+            .bind(subscription.last_posted_on.as_ref().map(|d| d.0))
+            .bind(dek.encrypt_string_for(&subscription.name, &aad.column("name_enc"))?)
+            .bind(dek.encrypt_i64_for(subscription.billing_amount, &aad.column("billing_amount_enc"))?)
             .execute(&mut *tx)
             .await?;
 
-            imported_transactions += 1;
+            for event in &subscription.billing_events {
+                let event_id = Uuid::new_v4();
+                sqlx::query(
+                    r#"
+INSERT INTO subscription_billing_event (id, subscription_id, transaction_id, user_id, amount_enc, date, detected)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
+"#,
+                )
+                .bind(event_id)
+                .bind(id)
+                .bind(event.transaction_id.and_then(|t| ledger_ids.get(&t).map(|&i| states[i].id)))
+                .bind(user_id)
+                .bind(dek.encrypt_i64_for(event.amount, &billing_event_aad(&event_id, user_id))?)
+                .bind(event.date.0)
+                .bind(event.detected)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            counts.subscriptions += 1;
         }
 
         tx.commit().await?;
 
-        Ok((accounts_arr.len(), categories_arr.len(), imported_transactions))
+        Ok(counts)
     }
 
     // ── V2 Reset Structure ───────────────────────────────────────────────────
 
-    /// V2 reset: also deletes vendors (unlike V1)
+    /// V2 reset: also deletes vendors and tags (unlike V1)
     pub async fn reset_structure_v2(&self, user_id: &Uuid, dek: &crate::crypto::Dek) -> Result<(), AppError> {
         let mut tx = self.pool.begin().await?;
 
@@ -560,6 +1128,8 @@ impl PostgresRepository {
         // this transaction only; it is cleared automatically on commit.
         sqlx::query("SET LOCAL piggy_pulse.allow_ledger_mutations = 'on'").execute(&mut *tx).await?;
 
+        let blob_paths = delete_user_attachments_in_tx(&mut tx, user_id).await?;
+
         sqlx::query("DELETE FROM period_schedule WHERE user_id = $1")
             .bind(user_id)
             .execute(&mut *tx)
@@ -576,25 +1146,32 @@ impl PostgresRepository {
 
         sqlx::query("DELETE FROM vendor WHERE user_id = $1").bind(user_id).execute(&mut *tx).await?;
 
+        sqlx::query("DELETE FROM tag WHERE user_id = $1").bind(user_id).execute(&mut *tx).await?;
+
         // Encrypt the system category fields before storing.
-        let name_enc = dek.encrypt_string("Transfer").map_err(AppError::from)?;
-        let color_enc = dek.encrypt_string("#868E96").map_err(AppError::from)?;
-        let icon_enc = dek.encrypt_string("↔").map_err(AppError::from)?;
+        let transfer_id = Uuid::new_v4();
+        let aad = RowAad::new("category", transfer_id, *user_id);
+        let name_enc = dek.encrypt_string_for("Transfer", &aad.column("name_enc")).map_err(AppError::from)?;
+        let color_enc = dek.encrypt_string_for("#868E96", &aad.column("color_enc")).map_err(AppError::from)?;
+        let icon_enc = dek.encrypt_string_for("↔", &aad.column("icon_enc")).map_err(AppError::from)?;
 
         sqlx::query(
             r#"
-            INSERT INTO category (user_id, name_enc, color_enc, icon_enc, category_type, is_system)
-            VALUES ($1, $2, $3, $4, 'Transfer'::category_type, TRUE)
+            INSERT INTO category (id, user_id, name_enc, name_bidx, color_enc, icon_enc, category_type, is_system)
+            VALUES ($1, $2, $3, $4, $5, $6, 'Transfer'::category_type, TRUE)
             "#,
         )
+        .bind(transfer_id)
         .bind(user_id)
         .bind(&name_enc)
+        .bind(dek.name_blind_index("category", "Transfer"))
         .bind(&color_enc)
         .bind(&icon_enc)
         .execute(&mut *tx)
         .await?;
 
         tx.commit().await?;
+        remove_blob_files(&blob_paths).await;
 
         Ok(())
     }
@@ -608,6 +1185,8 @@ impl PostgresRepository {
         // transactions; bypass the ledger immutability trigger for this tx only.
         sqlx::query("SET LOCAL piggy_pulse.allow_ledger_mutations = 'on'").execute(&mut *tx).await?;
 
+        let blob_paths = delete_user_attachments_in_tx(&mut tx, user_id).await?;
+
         sqlx::query("DELETE FROM period_schedule WHERE user_id = $1")
             .bind(user_id)
             .execute(&mut *tx)
@@ -619,8 +1198,16 @@ impl PostgresRepository {
         sqlx::query("DELETE FROM account WHERE user_id = $1").bind(user_id).execute(&mut *tx).await?;
         sqlx::query("DELETE FROM category WHERE user_id = $1").bind(user_id).execute(&mut *tx).await?;
         sqlx::query("DELETE FROM vendor WHERE user_id = $1").bind(user_id).execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM tag WHERE user_id = $1").bind(user_id).execute(&mut *tx).await?;
+        // Ledger rows went with their accounts; the per-transaction state has
+        // no FK to them and would keep the user row referenced.
+        sqlx::query("DELETE FROM logical_transaction_state WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
 
         tx.commit().await?;
+        remove_blob_files(&blob_paths).await;
 
         Ok(())
     }
@@ -1,12 +1,19 @@
-use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashSet;
+
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use sqlx::PgConnection;
 use uuid::Uuid;
 
-use crate::crypto::Dek;
-use crate::database::postgres_repository::PostgresRepository;
+use crate::crypto::{Dek, EnvelopeAad, RowAad};
+use crate::database::budget_period::base_month_start_date;
+use crate::database::postgres_repository::{PostgresRepository, is_unique_violation};
+use crate::database::transaction::lts_aad;
 use crate::dto::subscriptions::{
-    BillingCycle, CreateSubscriptionRequest, EncryptedSubscriptionResponse, SubscriptionStatus, UpdateSubscriptionRequest, to_response,
+    BillingCycle, BillingEventResponse, CreateSubscriptionRequest, EncryptedSubscriptionResponse, SubscriptionAlertResponse, SubscriptionDetailResponse,
+    SubscriptionStatus, UpdateSubscriptionRequest, to_billing_event_response, to_response,
 };
 use crate::error::app_error::AppError;
+use crate::models::transaction::TransactionRequest;
 
 #[derive(sqlx::FromRow)]
 struct SubscriptionRow {
@@ -17,7 +24,10 @@ struct SubscriptionRow {
     billing_day: i16,
     next_charge_date: NaiveDate,
     status: SubscriptionStatus,
+    resume_on: Option<NaiveDate>,
     cancelled_at: Option<DateTime<Utc>>,
+    auto_post_account_id: Option<Uuid>,
+    last_posted_on: Option<NaiveDate>,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
     name_enc: Vec<u8>,
@@ -34,7 +44,10 @@ impl From<SubscriptionRow> for EncryptedSubscriptionResponse {
             row.billing_day,
             row.next_charge_date,
             row.status,
+            row.resume_on,
             row.cancelled_at,
+            row.auto_post_account_id,
+            row.last_posted_on,
             row.created_at,
             row.updated_at,
             &row.name_enc,
@@ -43,7 +56,53 @@ impl From<SubscriptionRow> for EncryptedSubscriptionResponse {
     }
 }
 
-const COLS: &str = "id, category_id, vendor_id, billing_cycle::text as billing_cycle, billing_day, next_charge_date, status::text as status, cancelled_at, created_at, updated_at, name_enc, billing_amount_enc";
+const COLS: &str = "id, category_id, vendor_id, billing_cycle::text as billing_cycle, billing_day, next_charge_date, status::text as status, resume_on, cancelled_at, auto_post_account_id, last_posted_on, created_at, updated_at, name_enc, billing_amount_enc";
+
+#[derive(sqlx::FromRow)]
+struct BillingEventRow {
+    id: Uuid,
+    subscription_id: Uuid,
+    transaction_id: Option<Uuid>,
+    date: NaiveDate,
+    amount_enc: Vec<u8>,
+    detected: bool,
+    created_at: DateTime<Utc>,
+}
+
+impl From<BillingEventRow> for BillingEventResponse {
+    fn from(row: BillingEventRow) -> Self {
+        to_billing_event_response(
+            row.id,
+            row.subscription_id,
+            row.transaction_id,
+            row.date,
+            &row.amount_enc,
+            row.detected,
+            row.created_at,
+        )
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct AlertRow {
+    #[sqlx(flatten)]
+    event: BillingEventRow,
+    cancelled_at: DateTime<Utc>,
+}
+
+const EVENT_COLS: &str = "id, subscription_id, transaction_id, date, amount_enc, detected, created_at";
+
+/// Billing events `e` of subscription `s` dated after the subscription was
+/// cancelled.
+const AFTER_CANCELLATION: &str = "s.status = 'cancelled' AND e.date > s.cancelled_at::date";
+
+/// Per cent of a subscription's billing amount a charge may differ by and
+/// still be matched to it.
+const MATCH_TOLERANCE_PERCENT: i64 = 10;
+
+pub(crate) fn billing_event_aad(id: &Uuid, user_id: &Uuid) -> EnvelopeAad<'static> {
+    EnvelopeAad::new("subscription_billing_event", "amount_enc", *id, *user_id)
+}
 
 impl PostgresRepository {
     pub async fn list_subscriptions(&self, user_id: &Uuid) -> Result<Vec<EncryptedSubscriptionResponse>, AppError> {
@@ -54,26 +113,165 @@ impl PostgresRepository {
         Ok(rows.into_iter().map(Into::into).collect())
     }
 
+    /// One subscription with its billing history.
+    pub async fn get_subscription(&self, id: &Uuid, user_id: &Uuid, dek: &Dek) -> Result<SubscriptionDetailResponse, AppError> {
+        self.backfill_billing_event_amounts(user_id, dek).await?;
+
+        let row: SubscriptionRow = sqlx::query_as(&format!("SELECT {COLS} FROM subscription WHERE id = $1 AND user_id = $2"))
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Subscription not found".to_string()))?;
+
+        let events: Vec<BillingEventRow> = sqlx::query_as(&format!(
+            "SELECT {EVENT_COLS} FROM subscription_billing_event WHERE subscription_id = $1 AND user_id = $2 ORDER BY date DESC, created_at DESC, id"
+        ))
+        .bind(id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(SubscriptionDetailResponse {
+            subscription: row.into(),
+            billing_history: events.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    /// Bill an existing, effective transaction to a subscription. The event
+    /// records the transaction's current amount and date.
+    pub async fn link_billing_transaction(
+        &self,
+        subscription_id: &Uuid,
+        transaction_id: &Uuid,
+        user_id: &Uuid,
+        dek: &Dek,
+    ) -> Result<BillingEventResponse, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        // The state row is locked so a concurrent correction cannot leave
+        // the event with a stale amount.
+        let state: Option<(Vec<u8>, bool, NaiveDate)> = sqlx::query_as(
+            r#"
+SELECT lts.current_sum_enc, lts.is_effective, t.occurred_at
+FROM logical_transaction_state lts
+JOIN transaction t ON t.id = lts.id AND t.seq = lts.latest_seq
+WHERE lts.id = $1 AND lts.user_id = $2
+FOR UPDATE OF lts
+"#,
+        )
+        .bind(transaction_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let (sum_enc, occurred_at) = match state {
+            None => return Err(AppError::NotFound("Transaction not found".to_string())),
+            Some((_, false, _)) => return Err(AppError::Conflict("Transaction has already been voided".to_string())),
+            Some((sum_enc, true, occurred_at)) => (sum_enc, occurred_at),
+        };
+        lock_subscription_status(&mut tx, subscription_id, user_id).await?;
+
+        let amount = dek.decrypt_i64_for(&sum_enc, &lts_aad(transaction_id, user_id))?;
+        let event = insert_billing_event(&mut tx, dek, subscription_id, Some(transaction_id), user_id, amount, occurred_at, false).await?;
+
+        tx.commit().await?;
+        Ok(event)
+    }
+
+    pub async fn unlink_billing_transaction(&self, subscription_id: &Uuid, transaction_id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        let result = sqlx::query("DELETE FROM subscription_billing_event WHERE subscription_id = $1 AND transaction_id = $2 AND user_id = $3")
+            .bind(subscription_id)
+            .bind(transaction_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Billing event not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Encrypt the plaintext amounts of the user's billing events recorded
+    /// before amounts were encrypted. Runs before they are read and commits
+    /// on its own.
+    pub(crate) async fn backfill_billing_event_amounts(&self, user_id: &Uuid, dek: &Dek) -> Result<(), AppError> {
+        let legacy: Vec<(Uuid, i64)> = sqlx::query_as("SELECT id, amount FROM subscription_billing_event WHERE user_id = $1 AND amount_enc IS NULL")
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        if legacy.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for (id, amount) in legacy {
+            let amount_enc = dek.encrypt_i64_for(amount, &billing_event_aad(&id, user_id))?;
+            // A concurrent backfill may have got here first.
+            sqlx::query("UPDATE subscription_billing_event SET amount_enc = $1, amount = NULL WHERE id = $2 AND amount_enc IS NULL")
+                .bind(&amount_enc)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Charges billed to cancelled subscriptions after their cancellation,
+    /// newest first. Charges paid by a since-deleted transaction drop out.
+    pub async fn list_subscription_alerts(&self, user_id: &Uuid, dek: &Dek) -> Result<Vec<SubscriptionAlertResponse>, AppError> {
+        self.backfill_billing_event_amounts(user_id, dek).await?;
+
+        let rows: Vec<AlertRow> = sqlx::query_as(&format!(
+            r#"
+SELECT e.id, e.subscription_id, e.transaction_id, e.date, e.amount_enc, e.detected, e.created_at, s.cancelled_at
+FROM subscription_billing_event e
+JOIN subscription s ON s.id = e.subscription_id
+LEFT JOIN logical_transaction_state lts ON lts.id = e.transaction_id
+WHERE e.user_id = $1
+  AND {AFTER_CANCELLATION}
+  AND (lts.id IS NULL OR lts.is_effective)
+ORDER BY e.date DESC, e.created_at DESC, e.id
+"#
+        ))
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| SubscriptionAlertResponse {
+                event: row.event.into(),
+                cancelled_at: row.cancelled_at,
+            })
+            .collect())
+    }
+
     pub async fn create_subscription(&self, req: &CreateSubscriptionRequest, user_id: &Uuid, dek: &Dek) -> Result<EncryptedSubscriptionResponse, AppError> {
-        let name_enc = dek.encrypt_string(&req.name)?;
-        let amount_enc = dek.encrypt_i64(req.billing_amount)?;
+        self.check_auto_post_account(req.auto_post_account_id.as_ref(), user_id).await?;
+        let id = Uuid::new_v4();
+        let aad = RowAad::new("subscription", id, *user_id);
+        let name_enc = dek.encrypt_string_for(&req.name, &aad.column("name_enc"))?;
+        let amount_enc = dek.encrypt_i64_for(req.billing_amount, &aad.column("billing_amount_enc"))?;
         let row: SubscriptionRow = sqlx::query_as(&format!(
             r#"
 INSERT INTO subscription (
     id, user_id, category_id, vendor_id, billing_cycle, billing_day,
-    next_charge_date, status, created_at, updated_at, name_enc, billing_amount_enc
+    next_charge_date, status, auto_post_account_id, created_at, updated_at, name_enc, billing_amount_enc
 ) VALUES (
-    gen_random_uuid(), $1, $2, $3, $4::text::subscription_billing_cycle, $5, $6, 'active'::subscription_status, now(), now(), $7, $8
+    $1, $2, $3, $4, $5::text::subscription_billing_cycle, $6, $7, 'active'::subscription_status, $8, now(), now(), $9, $10
 )
 RETURNING {COLS}
 "#,
         ))
+        .bind(id)
         .bind(user_id)
         .bind(req.category_id)
         .bind(req.vendor_id)
         .bind(billing_cycle_str(req.billing_cycle))
         .bind(req.billing_day)
         .bind(req.next_charge_date.0)
+        .bind(req.auto_post_account_id)
         .bind(&name_enc)
         .bind(&amount_enc)
         .fetch_one(&self.pool)
@@ -88,8 +286,10 @@ RETURNING {COLS}
         user_id: &Uuid,
         dek: &Dek,
     ) -> Result<EncryptedSubscriptionResponse, AppError> {
-        let name_enc = dek.encrypt_string(&req.name)?;
-        let amount_enc = dek.encrypt_i64(req.billing_amount)?;
+        self.check_auto_post_account(req.auto_post_account_id.as_ref(), user_id).await?;
+        let aad = RowAad::new("subscription", *id, *user_id);
+        let name_enc = dek.encrypt_string_for(&req.name, &aad.column("name_enc"))?;
+        let amount_enc = dek.encrypt_i64_for(req.billing_amount, &aad.column("billing_amount_enc"))?;
         let row: Option<SubscriptionRow> = sqlx::query_as(&format!(
             r#"
 UPDATE subscription
@@ -98,10 +298,11 @@ SET category_id = $1,
     billing_cycle = $3::text::subscription_billing_cycle,
     billing_day = $4,
     next_charge_date = $5,
-    name_enc = $6,
-    billing_amount_enc = $7,
+    auto_post_account_id = $6,
+    name_enc = $7,
+    billing_amount_enc = $8,
     updated_at = now()
-WHERE id = $8 AND user_id = $9
+WHERE id = $9 AND user_id = $10
 RETURNING {COLS}
 "#,
         ))
@@ -110,6 +311,7 @@ RETURNING {COLS}
         .bind(billing_cycle_str(req.billing_cycle))
         .bind(req.billing_day)
         .bind(req.next_charge_date.0)
+        .bind(req.auto_post_account_id)
         .bind(&name_enc)
         .bind(&amount_enc)
         .bind(id)
@@ -119,6 +321,21 @@ RETURNING {COLS}
         row.map(Into::into).ok_or_else(|| AppError::NotFound("Subscription not found".to_string()))
     }
 
+    async fn check_auto_post_account(&self, account_id: Option<&Uuid>, user_id: &Uuid) -> Result<(), AppError> {
+        let Some(account_id) = account_id else {
+            return Ok(());
+        };
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM account WHERE id = $1 AND user_id = $2)")
+            .bind(account_id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        if !exists {
+            return Err(AppError::BadRequest("Referenced auto-post account not found".to_string()));
+        }
+        Ok(())
+    }
+
     pub async fn delete_subscription(&self, id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
         let result = sqlx::query("DELETE FROM subscription WHERE id = $1 AND user_id = $2")
             .bind(id)
@@ -141,6 +358,7 @@ RETURNING {COLS}
             r#"
 UPDATE subscription
 SET status = 'cancelled'::subscription_status,
+    resume_on = NULL,
     cancelled_at = COALESCE($1::date::timestamptz, now()),
     updated_at = now()
 WHERE id = $2 AND user_id = $3
@@ -154,6 +372,490 @@ RETURNING {COLS}
         .await?;
         row.map(Into::into).ok_or_else(|| AppError::NotFound("Subscription not found".to_string()))
     }
+
+    /// Pause an active subscription, optionally until `resume_on`. Pausing
+    /// a paused subscription again replaces its resume date.
+    pub async fn pause_subscription(&self, id: &Uuid, user_id: &Uuid, resume_on: Option<NaiveDate>) -> Result<EncryptedSubscriptionResponse, AppError> {
+        let mut tx = self.pool.begin().await?;
+        let status = lock_subscription_status(&mut tx, id, user_id).await?;
+        if status == SubscriptionStatus::Cancelled {
+            return Err(AppError::Conflict("Cancelled subscriptions cannot be paused".to_string()));
+        }
+
+        let row: SubscriptionRow = sqlx::query_as(&format!(
+            r#"
+UPDATE subscription
+SET status = 'paused'::subscription_status,
+    resume_on = $1,
+    updated_at = now()
+WHERE id = $2
+RETURNING {COLS}
+"#,
+        ))
+        .bind(resume_on)
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(row.into())
+    }
+
+    /// Reactivate a paused subscription. Its next charge date is rolled
+    /// forward past the charges that fell due while it was paused.
+    pub async fn resume_subscription(&self, id: &Uuid, user_id: &Uuid, today: NaiveDate) -> Result<EncryptedSubscriptionResponse, AppError> {
+        let mut tx = self.pool.begin().await?;
+        match lock_subscription_status(&mut tx, id, user_id).await? {
+            SubscriptionStatus::Paused => {}
+            SubscriptionStatus::Active => {
+                let row: SubscriptionRow = sqlx::query_as(&format!("SELECT {COLS} FROM subscription WHERE id = $1"))
+                    .bind(id)
+                    .fetch_one(&mut *tx)
+                    .await?;
+                return Ok(row.into());
+            }
+            SubscriptionStatus::Cancelled => {
+                return Err(AppError::Conflict("Cancelled subscriptions cannot be resumed".to_string()));
+            }
+        }
+
+        let schedule: ChargeScheduleRow = sqlx::query_as(&format!("SELECT {SCHEDULE_COLS} FROM subscription WHERE id = $1"))
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+        let next_charge_date = schedule.next_charge_on_or_after(today)?;
+
+        let row: SubscriptionRow = sqlx::query_as(&format!(
+            r#"
+UPDATE subscription
+SET status = 'active'::subscription_status,
+    resume_on = NULL,
+    next_charge_date = $1,
+    updated_at = now()
+WHERE id = $2
+RETURNING {COLS}
+"#,
+        ))
+        .bind(next_charge_date)
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(row.into())
+    }
+
+    /// Cron entry point: reactivate paused subscriptions whose resume date
+    /// has come, then roll every active subscription's past next charge
+    /// date forward to the first charge on or after `today`. Only
+    /// plaintext scheduling columns are touched, so no DEK is needed.
+    ///
+    /// Auto-post subscriptions keep their next charge date on the first
+    /// charge not yet posted, unless they were just resumed: charges that
+    /// fell due while paused are skipped as for every other subscription.
+    pub async fn advance_subscriptions(&self, today: NaiveDate) -> Result<SubscriptionAdvanceResult, AppError> {
+        let resumed_ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+UPDATE subscription
+SET status = 'active'::subscription_status,
+    resume_on = NULL,
+    updated_at = now()
+WHERE status = 'paused' AND resume_on <= $1
+RETURNING id
+"#,
+        )
+        .bind(today)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let due: Vec<ChargeScheduleRow> = sqlx::query_as(&format!(
+            r#"
+SELECT {SCHEDULE_COLS}
+FROM subscription
+WHERE status = 'active'
+  AND next_charge_date < $1
+  AND (auto_post_account_id IS NULL OR id = ANY($2))
+ORDER BY id
+"#
+        ))
+        .bind(today)
+        .bind(&resumed_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut advanced = 0;
+        for schedule in &due {
+            let next_charge_date = schedule.next_charge_on_or_after(today)?;
+            // A client edit since the read wins; the next run picks the
+            // subscription up again if it is still behind.
+            advanced +=
+                sqlx::query("UPDATE subscription SET next_charge_date = $1, updated_at = now() WHERE id = $2 AND next_charge_date = $3 AND status = 'active'")
+                    .bind(next_charge_date)
+                    .bind(schedule.id)
+                    .bind(schedule.next_charge_date)
+                    .execute(&self.pool)
+                    .await?
+                    .rows_affected();
+        }
+
+        Ok(SubscriptionAdvanceResult {
+            resumed: resumed_ids.len() as u64,
+            advanced,
+        })
+    }
+
+    /// Charges the user's subscriptions are projected to make from `from`
+    /// through `to`, ordered by subscription then date. Active
+    /// subscriptions charge on their schedule; a paused one charges again
+    /// from its resume date, the way resuming skips missed charges.
+    pub async fn list_projected_charges(&self, user_id: &Uuid, from: NaiveDate, to: NaiveDate) -> Result<Vec<ProjectedCharge>, AppError> {
+        let rows: Vec<ProjectionRow> = sqlx::query_as(&format!(
+            r#"
+SELECT {SCHEDULE_COLS}, resume_on, name_enc, billing_amount_enc
+FROM subscription
+WHERE user_id = $1
+  AND (status = 'active' OR (status = 'paused' AND resume_on <= $2))
+ORDER BY id
+"#
+        ))
+        .bind(user_id)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut charges = Vec::new();
+        for row in rows {
+            let schedule = &row.schedule;
+            let mut date = schedule.next_charge_on_or_after(row.resume_on.map_or(from, |resume_on| resume_on.max(from)))?;
+            while date <= to {
+                charges.push(ProjectedCharge {
+                    subscription_id: schedule.id,
+                    date,
+                    name_enc: row.name_enc.clone(),
+                    billing_amount_enc: row.billing_amount_enc.clone(),
+                });
+                date = next_charge_date(date, schedule.billing_cycle, schedule.billing_day)
+                    .ok_or_else(|| AppError::BadRequest("Date overflow while advancing subscription".to_string()))?;
+            }
+        }
+        Ok(charges)
+    }
+
+    /// Unlock hook: post every charge of the user's active auto-post
+    /// subscriptions from the first one not yet posted through `today`
+    /// to the subscription's account, via `batch_create_transactions` so
+    /// each posted transaction is billed to its subscription. Returns the
+    /// billing events of the posted charges, oldest first.
+    ///
+    /// The due subscriptions are locked, advanced past the posted charges
+    /// and posted in one database transaction. A concurrent unlock on
+    /// another device waits on the lock and then finds nothing due, and a
+    /// failed post leaves every subscription as it was for the next unlock.
+    pub async fn post_due_subscription_charges(&self, user_id: &Uuid, dek: &Dek, today: NaiveDate) -> Result<Vec<BillingEventResponse>, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let due: Vec<AutoPostRow> = sqlx::query_as(&format!(
+            r#"
+SELECT {SCHEDULE_COLS}, category_id, vendor_id, auto_post_account_id, name_enc, billing_amount_enc
+FROM subscription
+WHERE user_id = $1
+  AND status = 'active'
+  AND auto_post_account_id IS NOT NULL
+  AND next_charge_date <= $2
+ORDER BY id
+FOR UPDATE
+"#
+        ))
+        .bind(user_id)
+        .bind(today)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut requests = Vec::new();
+        for row in due {
+            let (dates, next_charge_date) = row.schedule.charges_through(today)?;
+            let Some(&last_posted_on) = dates.last() else {
+                continue;
+            };
+            let aad = RowAad::new("subscription", row.schedule.id, *user_id);
+            let name = dek.decrypt_string_for(&row.name_enc, &aad.column("name_enc"))?;
+            let amount = dek.decrypt_i64_for(&row.billing_amount_enc, &aad.column("billing_amount_enc"))?;
+
+            sqlx::query("UPDATE subscription SET next_charge_date = $1, last_posted_on = $2, updated_at = now() WHERE id = $3")
+                .bind(next_charge_date)
+                .bind(last_posted_on)
+                .bind(row.schedule.id)
+                .execute(&mut *tx)
+                .await?;
+
+            requests.extend(dates.into_iter().map(|date| TransactionRequest {
+                amount,
+                description: name.clone(),
+                occurred_at: date,
+                category_id: row.category_id,
+                from_account_id: row.auto_post_account_id,
+                to_account_id: None,
+                vendor_id: row.vendor_id,
+                splits: Vec::new(),
+                tag_ids: None,
+                subscription_id: Some(row.schedule.id),
+            }));
+        }
+        if requests.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let posted = self.batch_create_transactions_in_tx(&mut tx, &requests, user_id, dek).await?;
+
+        let transaction_ids: Vec<Uuid> = posted.iter().map(|p| p.id).collect();
+        let events: Vec<BillingEventRow> = sqlx::query_as(&format!(
+            "SELECT {EVENT_COLS} FROM subscription_billing_event WHERE transaction_id = ANY($1) AND user_id = $2 ORDER BY date, subscription_id, id"
+        ))
+        .bind(&transaction_ids)
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(events.into_iter().map(Into::into).collect())
+    }
+}
+
+/// A charge a subscription is projected to make.
+pub struct ProjectedCharge {
+    pub subscription_id: Uuid,
+    pub date: NaiveDate,
+    pub name_enc: Vec<u8>,
+    pub billing_amount_enc: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SubscriptionAdvanceResult {
+    pub resumed: u64,
+    pub advanced: u64,
+}
+
+const SCHEDULE_COLS: &str = "id, billing_cycle::text as billing_cycle, billing_day, next_charge_date";
+
+#[derive(sqlx::FromRow)]
+struct ChargeScheduleRow {
+    id: Uuid,
+    billing_cycle: BillingCycle,
+    billing_day: i16,
+    next_charge_date: NaiveDate,
+}
+
+#[derive(sqlx::FromRow)]
+struct ProjectionRow {
+    #[sqlx(flatten)]
+    schedule: ChargeScheduleRow,
+    resume_on: Option<NaiveDate>,
+    name_enc: Vec<u8>,
+    billing_amount_enc: Vec<u8>,
+}
+
+/// An active auto-post subscription with charges due.
+#[derive(sqlx::FromRow)]
+struct AutoPostRow {
+    #[sqlx(flatten)]
+    schedule: ChargeScheduleRow,
+    category_id: Uuid,
+    vendor_id: Option<Uuid>,
+    auto_post_account_id: Uuid,
+    name_enc: Vec<u8>,
+    billing_amount_enc: Vec<u8>,
+}
+
+/// Most charges posted for one subscription per unlock; a subscription
+/// further behind catches up over later unlocks.
+const MAX_AUTO_POST_CHARGES: usize = 36;
+
+impl ChargeScheduleRow {
+    /// Charges from the next charge date through `today`, at most
+    /// `MAX_AUTO_POST_CHARGES` of them, and the charge date after the last.
+    fn charges_through(&self, today: NaiveDate) -> Result<(Vec<NaiveDate>, NaiveDate), AppError> {
+        let mut dates = Vec::new();
+        let mut date = self.next_charge_date;
+        while date <= today && dates.len() < MAX_AUTO_POST_CHARGES {
+            dates.push(date);
+            date = next_charge_date(date, self.billing_cycle, self.billing_day)
+                .ok_or_else(|| AppError::BadRequest("Date overflow while advancing subscription".to_string()))?;
+        }
+        Ok((dates, date))
+    }
+
+    fn next_charge_on_or_after(&self, today: NaiveDate) -> Result<NaiveDate, AppError> {
+        let mut date = self.next_charge_date;
+        while date < today {
+            date = next_charge_date(date, self.billing_cycle, self.billing_day)
+                .ok_or_else(|| AppError::BadRequest("Date overflow while advancing subscription".to_string()))?;
+        }
+        Ok(date)
+    }
+}
+
+/// The charge after one on `date`: `billing_day` of the month one billing
+/// cycle later, clamped to that month's last day the way automatic period
+/// generation clamps its start day.
+fn next_charge_date(date: NaiveDate, cycle: BillingCycle, billing_day: i16) -> Option<NaiveDate> {
+    let months = match cycle {
+        BillingCycle::Monthly => 1,
+        BillingCycle::Quarterly => 3,
+        BillingCycle::Yearly => 12,
+    };
+    let month = date.with_day(1)?.checked_add_months(Months::new(months))?;
+    base_month_start_date(month.year(), month.month(), i32::from(billing_day))
+}
+
+/// Record a charge of `amount` on `date` against a subscription the caller
+/// has already checked belongs to `user_id`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn insert_billing_event(
+    conn: &mut PgConnection,
+    dek: &Dek,
+    subscription_id: &Uuid,
+    transaction_id: Option<&Uuid>,
+    user_id: &Uuid,
+    amount: i64,
+    date: NaiveDate,
+    detected: bool,
+) -> Result<BillingEventResponse, AppError> {
+    let id = Uuid::new_v4();
+    let amount_enc = dek.encrypt_i64_for(amount, &billing_event_aad(&id, user_id))?;
+    let result = sqlx::query_as::<_, BillingEventRow>(&format!(
+        r#"
+INSERT INTO subscription_billing_event (id, subscription_id, transaction_id, user_id, amount_enc, date, detected)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
+RETURNING {EVENT_COLS}
+"#
+    ))
+    .bind(id)
+    .bind(subscription_id)
+    .bind(transaction_id)
+    .bind(user_id)
+    .bind(&amount_enc)
+    .bind(date)
+    .bind(detected)
+    .fetch_one(&mut *conn)
+    .await;
+
+    match result {
+        Ok(row) => Ok(row.into()),
+        Err(err) if is_unique_violation(&err) => Err(AppError::Conflict("Transaction is already billed to this subscription".to_string())),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Carry a correction of transaction `transaction_id` over to the billing
+/// events it paid for, and bill it to `subscription_id` as well if given
+/// and not already billed there.
+pub(crate) async fn sync_billing_events(
+    conn: &mut PgConnection,
+    dek: &Dek,
+    transaction_id: &Uuid,
+    user_id: &Uuid,
+    subscription_id: Option<&Uuid>,
+    amount: i64,
+    date: NaiveDate,
+) -> Result<(), AppError> {
+    let events: Vec<(Uuid, Uuid)> = sqlx::query_as("SELECT id, subscription_id FROM subscription_billing_event WHERE transaction_id = $1 AND user_id = $2")
+        .bind(transaction_id)
+        .bind(user_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    for (id, _) in &events {
+        let amount_enc = dek.encrypt_i64_for(amount, &billing_event_aad(id, user_id))?;
+        sqlx::query("UPDATE subscription_billing_event SET amount_enc = $1, amount = NULL, date = $2 WHERE id = $3")
+            .bind(&amount_enc)
+            .bind(date)
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    if let Some(subscription_id) = subscription_id
+        && !events.iter().any(|(_, billed_to)| billed_to == subscription_id)
+    {
+        insert_billing_event(conn, dek, subscription_id, Some(transaction_id), user_id, amount, date, false).await?;
+    }
+    Ok(())
+}
+
+/// Match a transaction that is not billed to any subscription yet against
+/// the user's subscriptions: same category, same vendor when the
+/// subscription names one, and an amount within `MATCH_TOLERANCE_PERCENT`
+/// of the billing amount. The closest match gets a detected billing event.
+pub(crate) async fn detect_billing_event(
+    conn: &mut PgConnection,
+    dek: &Dek,
+    transaction_id: &Uuid,
+    user_id: &Uuid,
+    transaction: &TransactionRequest,
+) -> Result<(), AppError> {
+    let billed: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM subscription_billing_event WHERE transaction_id = $1)")
+        .bind(transaction_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    if billed {
+        return Ok(());
+    }
+
+    let candidates: Vec<(Uuid, Vec<u8>)> =
+        sqlx::query_as("SELECT id, billing_amount_enc FROM subscription WHERE user_id = $1 AND category_id = $2 AND (vendor_id IS NULL OR vendor_id = $3)")
+            .bind(user_id)
+            .bind(transaction.category_id)
+            .bind(transaction.vendor_id)
+            .fetch_all(&mut *conn)
+            .await?;
+
+    let mut best: Option<(i128, Uuid)> = None;
+    for (id, billing_amount_enc) in candidates {
+        let billing_amount = dek.decrypt_i64_for(&billing_amount_enc, &RowAad::new("subscription", id, *user_id).column("billing_amount_enc"))?;
+        let diff = (i128::from(transaction.amount) - i128::from(billing_amount)).abs();
+        if diff * 100 <= i128::from(billing_amount) * i128::from(MATCH_TOLERANCE_PERCENT) && best.is_none_or(|b| (diff, id) < b) {
+            best = Some((diff, id));
+        }
+    }
+
+    if let Some((_, subscription_id)) = best {
+        insert_billing_event(
+            conn,
+            dek,
+            &subscription_id,
+            Some(transaction_id),
+            user_id,
+            transaction.amount,
+            transaction.occurred_at,
+            true,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Logical transactions among `ids` billed to a cancelled subscription
+/// after its cancellation.
+pub(crate) async fn charged_after_cancellation(conn: &mut PgConnection, ids: &[Uuid]) -> Result<HashSet<Uuid>, AppError> {
+    let flagged: Vec<Uuid> = sqlx::query_scalar(&format!(
+        r#"
+SELECT DISTINCT e.transaction_id
+FROM subscription_billing_event e
+JOIN subscription s ON s.id = e.subscription_id
+WHERE e.transaction_id = ANY($1) AND {AFTER_CANCELLATION}
+"#
+    ))
+    .bind(ids)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(flagged.into_iter().collect())
+}
+
+async fn lock_subscription_status(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, id: &Uuid, user_id: &Uuid) -> Result<SubscriptionStatus, AppError> {
+    sqlx::query_scalar("SELECT status::text FROM subscription WHERE id = $1 AND user_id = $2 FOR UPDATE")
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Subscription not found".to_string()))
 }
 
 fn billing_cycle_str(c: BillingCycle) -> &'static str {
@@ -163,3 +865,56 @@ fn billing_cycle_str(c: BillingCycle) -> &'static str {
         BillingCycle::Yearly => "yearly",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{BillingCycle, ChargeScheduleRow, next_charge_date};
+    use chrono::NaiveDate;
+    use uuid::Uuid;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).expect("valid date")
+    }
+
+    #[test]
+    fn next_charge_clamps_to_month_end_and_recovers() {
+        assert_eq!(next_charge_date(date(2026, 1, 31), BillingCycle::Monthly, 31), Some(date(2026, 2, 28)));
+        assert_eq!(next_charge_date(date(2026, 2, 28), BillingCycle::Monthly, 31), Some(date(2026, 3, 31)));
+        assert_eq!(next_charge_date(date(2027, 11, 30), BillingCycle::Quarterly, 30), Some(date(2028, 2, 29)));
+        assert_eq!(next_charge_date(date(2026, 3, 15), BillingCycle::Yearly, 15), Some(date(2027, 3, 15)));
+    }
+
+    #[test]
+    fn next_charge_on_or_after_skips_missed_charges() {
+        let schedule = ChargeScheduleRow {
+            id: Uuid::nil(),
+            billing_cycle: BillingCycle::Monthly,
+            billing_day: 5,
+            next_charge_date: date(2026, 1, 5),
+        };
+        assert_eq!(schedule.next_charge_on_or_after(date(2026, 4, 5)).unwrap(), date(2026, 4, 5));
+        assert_eq!(schedule.next_charge_on_or_after(date(2026, 4, 6)).unwrap(), date(2026, 5, 5));
+        assert_eq!(schedule.next_charge_on_or_after(date(2025, 12, 1)).unwrap(), date(2026, 1, 5));
+    }
+
+    #[test]
+    fn charges_through_includes_today_and_caps_catch_up() {
+        let schedule = ChargeScheduleRow {
+            id: Uuid::nil(),
+            billing_cycle: BillingCycle::Monthly,
+            billing_day: 31,
+            next_charge_date: date(2026, 1, 31),
+        };
+        let (dates, next) = schedule.charges_through(date(2026, 3, 31)).unwrap();
+        assert_eq!(dates, [date(2026, 1, 31), date(2026, 2, 28), date(2026, 3, 31)]);
+        assert_eq!(next, date(2026, 4, 30));
+
+        let (dates, next) = schedule.charges_through(date(2026, 1, 30)).unwrap();
+        assert!(dates.is_empty());
+        assert_eq!(next, date(2026, 1, 31));
+
+        let (dates, next) = schedule.charges_through(date(2036, 1, 1)).unwrap();
+        assert_eq!(dates.len(), super::MAX_AUTO_POST_CHARGES);
+        assert_eq!(next, date(2029, 1, 31));
+    }
+}
@@ -0,0 +1,87 @@
+use crate::crypto::{Dek, RowAad};
+use crate::database::name_index::name_conflict;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::tags::{CreateTagRequest, UpdateTagRequest};
+use crate::error::app_error::AppError;
+use crate::models::tag::Tag;
+use uuid::Uuid;
+
+const TAG_COLUMNS: &str = "id, name_enc, color_enc";
+
+impl PostgresRepository {
+    pub async fn create_tag(&self, request: &CreateTagRequest, user_id: &Uuid, dek: &Dek) -> Result<Tag, AppError> {
+        self.backfill_name_index("tag", user_id, dek).await?;
+
+        let id = Uuid::new_v4();
+        let aad = RowAad::new("tag", id, *user_id);
+        let name_enc = dek.encrypt_string_for(&request.name, &aad.column("name_enc"))?;
+        let color_enc = dek.encrypt_string_for(&request.color, &aad.column("color_enc"))?;
+
+        let tag: Tag = sqlx::query_as(&format!(
+            r#"
+INSERT INTO tag (id, user_id, name_enc, name_bidx, color_enc)
+VALUES ($1, $2, $3, $4, $5)
+RETURNING {TAG_COLUMNS}
+"#,
+        ))
+        .bind(id)
+        .bind(user_id)
+        .bind(&name_enc)
+        .bind(dek.name_blind_index("tag", &request.name))
+        .bind(&color_enc)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| name_conflict(e, "tag", "A tag", &request.name))?;
+
+        Ok(tag)
+    }
+
+    pub async fn list_tags(&self, user_id: &Uuid) -> Result<Vec<Tag>, AppError> {
+        let tags = sqlx::query_as::<_, Tag>(&format!("SELECT {TAG_COLUMNS} FROM tag WHERE user_id = $1 ORDER BY created_at, id"))
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(tags)
+    }
+
+    pub async fn update_tag(&self, id: &Uuid, request: &UpdateTagRequest, user_id: &Uuid, dek: &Dek) -> Result<Tag, AppError> {
+        self.backfill_name_index("tag", user_id, dek).await?;
+
+        let aad = RowAad::new("tag", *id, *user_id);
+        let name_enc = dek.encrypt_string_for(&request.name, &aad.column("name_enc"))?;
+        let color_enc = dek.encrypt_string_for(&request.color, &aad.column("color_enc"))?;
+
+        let tag: Tag = sqlx::query_as(&format!(
+            r#"
+UPDATE tag
+SET name_enc = $1, name_bidx = $2, color_enc = $3
+WHERE id = $4 AND user_id = $5
+RETURNING {TAG_COLUMNS}
+"#,
+        ))
+        .bind(&name_enc)
+        .bind(dek.name_blind_index("tag", &request.name))
+        .bind(&color_enc)
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| name_conflict(e, "tag", "A tag", &request.name))?
+        .ok_or_else(|| AppError::NotFound("Tag not found".to_string()))?;
+
+        Ok(tag)
+    }
+
+    /// Deleting a tag removes it from every transaction it was on.
+    pub async fn delete_tag(&self, id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        let result = sqlx::query("DELETE FROM tag WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Tag not found".to_string()));
+        }
+        Ok(())
+    }
+}
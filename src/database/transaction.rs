@@ -18,12 +18,14 @@
 //! Until Phase 3 lands, read-side queries return `unimplemented!()` or
 //! are deleted outright at the call sites.
 
-use crate::crypto::Dek;
+use crate::crypto::{Dek, EnvelopeAad, RowAad};
 use crate::database::postgres_repository::PostgresRepository;
+use crate::database::subscription::{charged_after_cancellation, detect_billing_event, insert_billing_event, sync_billing_events};
 use crate::error::app_error::AppError;
 use crate::models::account::AccountType;
 use crate::models::category::CategoryType;
-use crate::models::transaction::TransactionRequest;
+use crate::models::pagination::{CursorParams, TransactionDirection, TransactionFilters};
+use crate::models::transaction::{ClearedStatus, LedgerEntryKind, TransactionRequest, TransactionSplit};
 use chrono::NaiveDate;
 use uuid::Uuid;
 
@@ -45,6 +47,7 @@ pub struct LogicalTransactionState {
     pub is_effective: bool,
     pub latest_seq: i64,
     pub first_created_at: chrono::DateTime<chrono::Utc>,
+    pub cleared_status: ClearedStatus,
 }
 
 /// Snapshot of the Latest_Row's metadata columns. `description_enc` and
@@ -61,6 +64,7 @@ pub struct LatestRowSnapshot {
     pub from_account_id: Uuid,
     pub to_account_id: Option<Uuid>,
     pub vendor_id: Option<Uuid>,
+    pub entry_kind: LedgerEntryKind,
 }
 
 /// Minimal return type for a write operation. The client already has all
@@ -78,6 +82,132 @@ pub struct LedgerInsertResult {
     pub vendor_id: Option<Uuid>,
     pub amount_enc: Vec<u8>,
     pub description_enc: Vec<u8>,
+    pub splits: Vec<LedgerSplit>,
+    pub tag_ids: Vec<Uuid>,
+    pub cleared_status: ClearedStatus,
+    pub is_adjustment: bool,
+    /// Billed to a subscription with a date after it was cancelled.
+    pub charged_after_cancellation: bool,
+}
+
+/// Latest_Row of an effective logical transaction, as the list queries
+/// select it.
+#[derive(sqlx::FromRow)]
+struct EffectiveRow {
+    id: Uuid,
+    seq: i64,
+    created_at: chrono::DateTime<chrono::Utc>,
+    occurred_at: NaiveDate,
+    from_account_id: Uuid,
+    to_account_id: Option<Uuid>,
+    category_id: Option<Uuid>,
+    vendor_id: Option<Uuid>,
+    amount_enc: Vec<u8>,
+    description_enc: Vec<u8>,
+    cleared_status: ClearedStatus,
+    is_adjustment: bool,
+}
+
+impl From<EffectiveRow> for LedgerInsertResult {
+    fn from(r: EffectiveRow) -> Self {
+        Self {
+            id: r.id,
+            seq: r.seq,
+            first_created_at: r.created_at,
+            occurred_at: r.occurred_at,
+            from_account_id: r.from_account_id,
+            to_account_id: r.to_account_id,
+            category_id: r.category_id,
+            vendor_id: r.vendor_id,
+            amount_enc: r.amount_enc,
+            description_enc: r.description_enc,
+            splits: Vec::new(),
+            tag_ids: Vec::new(),
+            cleared_status: r.cleared_status,
+            is_adjustment: r.is_adjustment,
+            charged_after_cancellation: false,
+        }
+    }
+}
+
+/// Filters shared by the paginated listing's page and count queries.
+/// `$1` user, `$2`/`$3` date bounds, `$4` account ids (either side),
+/// `$5` category ids (row or split line), `$6` vendor ids, `$7` category
+/// type, `$8` uncategorized only (balance adjustments excluded), `$9` tag
+/// ids (any of). Empty arrays and NULLs disable a filter.
+const FILTERED_EFFECTIVE_ROWS: &str = r#"
+FROM logical_transaction_state lts
+JOIN transaction t ON t.id = lts.id AND t.seq = lts.latest_seq
+LEFT JOIN category c ON c.id = t.category_id
+WHERE lts.user_id = $1
+  AND lts.is_effective
+  AND ($2::date IS NULL OR t.occurred_at >= $2)
+  AND ($3::date IS NULL OR t.occurred_at <= $3)
+  AND (cardinality($4::uuid[]) = 0 OR t.from_account_id = ANY($4) OR t.to_account_id = ANY($4))
+  AND (cardinality($5::uuid[]) = 0 OR t.category_id = ANY($5) OR EXISTS (
+        SELECT 1 FROM transaction_split s
+        WHERE s.transaction_id = t.id AND s.seq = t.seq AND s.category_id = ANY($5)))
+  AND (cardinality($6::uuid[]) = 0 OR t.vendor_id = ANY($6))
+  AND ($7::text IS NULL OR c.category_type::text = $7)
+  AND (NOT $8 OR (t.category_id IS NULL AND t.entry_kind <> 'adjustment'))
+  AND (cardinality($9::uuid[]) = 0 OR EXISTS (
+        SELECT 1 FROM transaction_tag tt
+        WHERE tt.transaction_id = t.id AND tt.tag_id = ANY($9)))
+"#;
+
+/// One stored split line of a ledger row.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct LedgerSplit {
+    pub id: Uuid,
+    pub category_id: Uuid,
+    pub amount_enc: Vec<u8>,
+    pub memo_enc: Option<Vec<u8>>,
+}
+
+/// Signed change a ledger amount makes to one account's balance, per the
+/// rules on `apply_category_balance_effect`. `to_side` picks the receiving
+/// account of a transfer.
+pub(crate) fn account_balance_delta(cat_type: CategoryType, account_type: AccountType, amount: i64, to_side: bool) -> i64 {
+    let credit = match cat_type {
+        CategoryType::Incoming => true,
+        CategoryType::Outgoing => false,
+        CategoryType::Transfer => to_side,
+    };
+    let delta = if credit { amount } else { -amount };
+    // Credit card balances are debt owed, so money in lowers them.
+    if account_type == AccountType::CreditCard { -delta } else { delta }
+}
+
+/// Ledger envelopes are bound to the logical id rather than `(id, seq)`,
+/// so compensating rows can carry the Latest_Row's ciphertext verbatim.
+pub(crate) fn ledger_aad(id: &Uuid, user_id: &Uuid) -> RowAad<'static> {
+    RowAad::new("transaction", *id, *user_id)
+}
+
+pub(crate) fn lts_aad(id: &Uuid, user_id: &Uuid) -> EnvelopeAad<'static> {
+    EnvelopeAad::new("logical_transaction_state", "current_sum_enc", *id, *user_id)
+}
+
+/// Split envelopes are bound to the line's own id. Compensating lines are
+/// re-encrypted rather than copied, so nothing needs the logical id here.
+pub(crate) fn split_aad(id: &Uuid, user_id: &Uuid) -> RowAad<'static> {
+    RowAad::new("transaction_split", *id, *user_id)
+}
+
+/// One row of a logical transaction's ledger history.
+#[derive(Debug, sqlx::FromRow)]
+pub struct LedgerHistoryRow {
+    pub id: Uuid,
+    pub seq: i64,
+    pub entry_kind: LedgerEntryKind,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub occurred_at: NaiveDate,
+    pub from_account_id: Uuid,
+    pub to_account_id: Option<Uuid>,
+    pub category_id: Option<Uuid>,
+    pub vendor_id: Option<Uuid>,
+    pub amount_enc: Vec<u8>,
+    pub description_enc: Vec<u8>,
 }
 
 impl PostgresRepository {
@@ -126,6 +256,55 @@ impl PostgresRepository {
             }
         }
 
+        if let Some(subscription_id) = transaction.subscription_id {
+            let subscription_exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM subscription WHERE id = $1 AND user_id = $2)")
+                .bind(subscription_id)
+                .bind(user_id)
+                .fetch_one(&self.pool)
+                .await?;
+            if !subscription_exists {
+                return Err(AppError::BadRequest("Invalid subscription_id for current user".to_string()));
+            }
+        }
+
+        if !transaction.splits.is_empty() {
+            // Balance effects follow the transaction's category type, so
+            // every line must be of that same type.
+            let mut split_categories: Vec<Uuid> = transaction.splits.iter().map(|line| line.category_id).collect();
+            split_categories.sort_unstable();
+            split_categories.dedup();
+            let matching: i64 = sqlx::query_scalar(
+                r#"
+SELECT COUNT(*)
+FROM category c
+JOIN category m ON m.id = $3 AND m.user_id = $2
+WHERE c.id = ANY($1) AND c.user_id = $2 AND c.category_type = m.category_type
+"#,
+            )
+            .bind(&split_categories)
+            .bind(user_id)
+            .bind(transaction.category_id)
+            .fetch_one(&self.pool)
+            .await?;
+            if matching != split_categories.len() as i64 {
+                return Err(AppError::BadRequest("Invalid split category_id for current user or category type".to_string()));
+            }
+        }
+
+        if let Some(tag_ids) = transaction.tag_ids.as_deref().filter(|ids| !ids.is_empty()) {
+            let mut tag_ids = tag_ids.to_vec();
+            tag_ids.sort_unstable();
+            tag_ids.dedup();
+            let owned: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tag WHERE id = ANY($1) AND user_id = $2")
+                .bind(&tag_ids)
+                .bind(user_id)
+                .fetch_one(&self.pool)
+                .await?;
+            if owned != tag_ids.len() as i64 {
+                return Err(AppError::BadRequest("Invalid tag_id for current user".to_string()));
+            }
+        }
+
         Ok(())
     }
 
@@ -145,7 +324,7 @@ impl PostgresRepository {
     ) -> Result<Option<LogicalTransactionState>, AppError> {
         let row = sqlx::query_as::<_, LogicalTransactionState>(
             r#"
-            SELECT id, user_id, current_sum_enc, is_effective, latest_seq, first_created_at
+            SELECT id, user_id, current_sum_enc, is_effective, latest_seq, first_created_at, cleared_status
               FROM logical_transaction_state
              WHERE id = $1 AND user_id = $2
              FOR UPDATE
@@ -170,7 +349,7 @@ impl PostgresRepository {
         let row = sqlx::query_as::<_, LatestRowSnapshot>(
             r#"
             SELECT amount_enc, description_enc, occurred_at, category_id,
-                   from_account_id, to_account_id, vendor_id
+                   from_account_id, to_account_id, vendor_id, entry_kind
               FROM transaction
              WHERE id = $1 AND seq = $2
             "#,
@@ -186,14 +365,14 @@ impl PostgresRepository {
     /// `description_enc`. Returns the inserted row's `seq` so callers can
     /// update `latest_seq` in `logical_transaction_state`.
     ///
-    /// `id = None` generates a fresh UUID (for brand-new logical
-    /// transactions); `id = Some(_)` reuses an existing logical id (for
-    /// void and correct compensating rows).
+    /// `id` is a fresh UUID for brand-new logical transactions, or an
+    /// existing logical id for void and correct compensating rows. It is
+    /// chosen by the caller because the envelopes are bound to it.
     #[allow(clippy::too_many_arguments)]
-    async fn insert_ledger_row_enc_in_tx(
+    pub(super) async fn insert_ledger_row_enc_in_tx(
         &self,
         tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
-        id: Option<&Uuid>,
+        id: &Uuid,
         user_id: &Uuid,
         amount_enc: &[u8],
         description_enc: &[u8],
@@ -202,6 +381,7 @@ impl PostgresRepository {
         from_account_id: &Uuid,
         to_account_id: Option<&Uuid>,
         vendor_id: Option<&Uuid>,
+        entry_kind: LedgerEntryKind,
     ) -> Result<(Uuid, i64, chrono::DateTime<chrono::Utc>), AppError> {
         #[derive(sqlx::FromRow)]
         struct InsertedRow {
@@ -214,9 +394,9 @@ impl PostgresRepository {
             r#"
             INSERT INTO transaction (
                 id, user_id, amount_enc, description_enc, occurred_at,
-                category_id, from_account_id, to_account_id, vendor_id
+                category_id, from_account_id, to_account_id, vendor_id, entry_kind
             )
-            VALUES (COALESCE($1, gen_random_uuid()), $2, $3, $4, $5, $6, $7, $8, $9)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
             RETURNING id, seq, created_at
             "#,
         )
@@ -229,12 +409,133 @@ impl PostgresRepository {
         .bind(from_account_id)
         .bind(to_account_id)
         .bind(vendor_id)
+        .bind(entry_kind)
         .fetch_one(&mut **tx)
         .await?;
 
         Ok((row.id, row.seq, row.created_at))
     }
 
+    /// Encrypt and insert the split lines of ledger row `(id, seq)`, in
+    /// order. Returns the stored lines; empty when `lines` is.
+    pub(super) async fn insert_splits_in_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        dek: &Dek,
+        id: &Uuid,
+        seq: i64,
+        user_id: &Uuid,
+        lines: &[TransactionSplit],
+    ) -> Result<Vec<LedgerSplit>, AppError> {
+        let mut stored = Vec::with_capacity(lines.len());
+        for (position, line) in lines.iter().enumerate() {
+            let position = i16::try_from(position).map_err(|_| AppError::BadRequest("too many split lines".to_string()))?;
+            let split_id = Uuid::new_v4();
+            let aad = split_aad(&split_id, user_id);
+            let amount_enc = dek.encrypt_i64_for(line.amount, &aad.column("amount_enc"))?;
+            let memo_enc = line.memo.as_deref().map(|m| dek.encrypt_string_for(m, &aad.column("memo_enc"))).transpose()?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO transaction_split (id, user_id, transaction_id, seq, position, category_id, amount_enc, memo_enc)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+            )
+            .bind(split_id)
+            .bind(user_id)
+            .bind(id)
+            .bind(seq)
+            .bind(position)
+            .bind(line.category_id)
+            .bind(&amount_enc)
+            .bind(memo_enc.as_deref())
+            .execute(&mut **tx)
+            .await?;
+
+            stored.push(LedgerSplit {
+                id: split_id,
+                category_id: line.category_id,
+                amount_enc,
+                memo_enc,
+            });
+        }
+        Ok(stored)
+    }
+
+    /// Write the negation of row `(id, from_seq)`'s split lines onto the
+    /// compensating row `(id, to_seq)`, so each row's lines keep summing
+    /// to its own amount. A no-op for unsplit rows.
+    async fn compensate_splits_in_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        dek: &Dek,
+        id: &Uuid,
+        from_seq: i64,
+        to_seq: i64,
+        user_id: &Uuid,
+    ) -> Result<Vec<LedgerSplit>, AppError> {
+        let rows: Vec<LedgerSplit> =
+            sqlx::query_as("SELECT id, category_id, amount_enc, memo_enc FROM transaction_split WHERE transaction_id = $1 AND seq = $2 ORDER BY position")
+                .bind(id)
+                .bind(from_seq)
+                .fetch_all(&mut **tx)
+                .await?;
+
+        let mut lines = Vec::with_capacity(rows.len());
+        for row in rows {
+            let aad = split_aad(&row.id, user_id);
+            let amount = dek.decrypt_i64_for(&row.amount_enc, &aad.column("amount_enc"))?;
+            let memo = row
+                .memo_enc
+                .as_deref()
+                .map(|m| dek.decrypt_string_for(m, &aad.column("memo_enc")))
+                .transpose()?;
+            lines.push(TransactionSplit {
+                category_id: row.category_id,
+                amount: -amount,
+                memo,
+            });
+        }
+
+        self.insert_splits_in_tx(tx, dek, id, to_seq, user_id, &lines).await
+    }
+
+    /// Replace the tags of logical transaction `id` with `tag_ids`, or keep
+    /// them when `None`, and return the resulting set. Tags hang off the
+    /// logical id, so no ledger row is written for them.
+    async fn sync_tags_in_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        id: &Uuid,
+        user_id: &Uuid,
+        tag_ids: Option<&[Uuid]>,
+    ) -> Result<Vec<Uuid>, AppError> {
+        if let Some(tag_ids) = tag_ids {
+            sqlx::query("DELETE FROM transaction_tag WHERE transaction_id = $1")
+                .bind(id)
+                .execute(&mut **tx)
+                .await?;
+            sqlx::query(
+                r#"
+                INSERT INTO transaction_tag (transaction_id, tag_id, user_id)
+                SELECT $1, tag_id, $3 FROM UNNEST($2::uuid[]) AS tag_id
+                ON CONFLICT DO NOTHING
+                "#,
+            )
+            .bind(id)
+            .bind(tag_ids)
+            .bind(user_id)
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        let tags = sqlx::query_scalar("SELECT tag_id FROM transaction_tag WHERE transaction_id = $1 ORDER BY tag_id")
+            .bind(id)
+            .fetch_all(&mut **tx)
+            .await?;
+        Ok(tags)
+    }
+
     /// Upsert `logical_transaction_state` for a given logical id. On first
     /// insert, seeds the row with `current_sum = amount`, `is_effective =
     /// (amount != 0)`, `latest_seq = seq`, `first_created_at = created_at`.
@@ -244,7 +545,7 @@ impl PostgresRepository {
     /// Returns the new `current_sum` after the delta is applied, for
     /// callers that need it (e.g. void needs to write encrypt(0)).
     #[allow(clippy::too_many_arguments)]
-    async fn upsert_lts_in_tx(
+    pub(super) async fn upsert_lts_in_tx(
         &self,
         tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
         dek: &Dek,
@@ -266,11 +567,11 @@ impl PostgresRepository {
 
         let new_sum: i64 = match existing {
             Some(row) => {
-                let prev = dek.decrypt_i64(&row.current_sum_enc)?;
+                let prev = dek.decrypt_i64_for(&row.current_sum_enc, &lts_aad(id, user_id))?;
                 let new = prev
                     .checked_add(delta)
                     .ok_or_else(|| AppError::BadRequest("current_sum overflow".to_string()))?;
-                let new_enc = dek.encrypt_i64(new)?;
+                let new_enc = dek.encrypt_i64_for(new, &lts_aad(id, user_id))?;
                 sqlx::query(
                     "UPDATE logical_transaction_state
                         SET current_sum_enc = $1,
@@ -287,7 +588,7 @@ impl PostgresRepository {
                 new
             }
             None => {
-                let new_enc = dek.encrypt_i64(delta)?;
+                let new_enc = dek.encrypt_i64_for(delta, &lts_aad(id, user_id))?;
                 sqlx::query(
                     "INSERT INTO logical_transaction_state
                         (id, user_id, current_sum_enc, is_effective, latest_seq, first_created_at)
@@ -323,16 +624,17 @@ impl PostgresRepository {
             return Ok(());
         }
 
-        let row: (Vec<u8>,) = sqlx::query_as("SELECT current_balance_enc FROM account WHERE id = $1 FOR UPDATE")
+        let row: (Uuid, Vec<u8>) = sqlx::query_as("SELECT user_id, current_balance_enc FROM account WHERE id = $1 FOR UPDATE")
             .bind(account_id)
             .fetch_one(&mut **tx)
             .await?;
 
-        let prev: i64 = dek.decrypt_i64(&row.0)?;
+        let aad = EnvelopeAad::new("account", "current_balance_enc", *account_id, row.0);
+        let prev: i64 = dek.decrypt_i64_for(&row.1, &aad)?;
         let new = prev
             .checked_add(delta)
             .ok_or_else(|| AppError::BadRequest("account balance overflow".to_string()))?;
-        let new_enc = dek.encrypt_i64(new)?;
+        let new_enc = dek.encrypt_i64_for(new, &aad)?;
 
         sqlx::query("UPDATE account SET current_balance_enc = $1 WHERE id = $2")
             .bind(&new_enc)
@@ -392,30 +694,18 @@ impl PostgresRepository {
             None
         };
 
-        match cat_type {
-            Some(CategoryType::Incoming) => {
-                // Payment TO the from_account: decreases debt for credit cards
-                let delta = if from_type == AccountType::CreditCard { -amount } else { amount };
-                self.apply_account_balance_delta(tx, from_account_id, delta, dek).await?;
-            }
-            Some(CategoryType::Outgoing) => {
-                // Purchase FROM the from_account: increases debt for credit cards
-                let delta = if from_type == AccountType::CreditCard { amount } else { -amount };
-                self.apply_account_balance_delta(tx, from_account_id, delta, dek).await?;
-            }
-            Some(CategoryType::Transfer) => {
-                // Debit from_account, credit to_account
-                // For credit cards, invert the sign
-                let from_delta = if from_type == AccountType::CreditCard { amount } else { -amount };
-                self.apply_account_balance_delta(tx, from_account_id, from_delta, dek).await?;
-                if let Some(to) = to_account_id {
-                    let to_delta = if to_type == Some(AccountType::CreditCard) { -amount } else { amount };
-                    self.apply_account_balance_delta(tx, to, to_delta, dek).await?;
-                }
-            }
-            None => {
-                // Uncategorized — no balance effect, matching legacy behavior.
-            }
+        let Some(cat_type) = cat_type else {
+            // Uncategorized — no balance effect, matching legacy behavior.
+            return Ok(());
+        };
+
+        let from_delta = account_balance_delta(cat_type, from_type, amount, false);
+        self.apply_account_balance_delta(tx, from_account_id, from_delta, dek).await?;
+        if cat_type == CategoryType::Transfer
+            && let (Some(to), Some(to_type)) = (to_account_id, to_type)
+        {
+            let to_delta = account_balance_delta(cat_type, to_type, amount, true);
+            self.apply_account_balance_delta(tx, to, to_delta, dek).await?;
         }
         Ok(())
     }
@@ -446,17 +736,19 @@ impl PostgresRepository {
     pub async fn create_transaction(&self, transaction: &TransactionRequest, user_id: &Uuid, dek: &Dek) -> Result<LedgerInsertResult, AppError> {
         self.validate_transaction_ownership(transaction, user_id).await?;
 
-        let amount_enc = dek.encrypt_i64(transaction.amount)?;
-        let description_enc = dek.encrypt_string(&transaction.description)?;
+        let id = Uuid::new_v4();
+        let aad = ledger_aad(&id, user_id);
+        let amount_enc = dek.encrypt_i64_for(transaction.amount, &aad.column("amount_enc"))?;
+        let description_enc = dek.encrypt_string_for(&transaction.description, &aad.column("description_enc"))?;
 
         let mut tx = self.pool.begin().await?;
 
         let cat_type = self.resolve_category_type(&mut tx, Some(&transaction.category_id)).await?;
 
-        let (id, seq, created_at) = self
+        let (_, seq, created_at) = self
             .insert_ledger_row_enc_in_tx(
                 &mut tx,
-                None,
+                &id,
                 user_id,
                 &amount_enc,
                 &description_enc,
@@ -465,10 +757,28 @@ impl PostgresRepository {
                 &transaction.from_account_id,
                 transaction.to_account_id.as_ref(),
                 transaction.vendor_id.as_ref(),
+                LedgerEntryKind::Original,
             )
             .await?;
 
+        let splits = self.insert_splits_in_tx(&mut tx, dek, &id, seq, user_id, &transaction.splits).await?;
         self.upsert_lts_in_tx(&mut tx, dek, &id, user_id, transaction.amount, seq, created_at).await?;
+        let tag_ids = self.sync_tags_in_tx(&mut tx, &id, user_id, transaction.tag_ids.as_deref()).await?;
+        if let Some(subscription_id) = &transaction.subscription_id {
+            insert_billing_event(
+                &mut tx,
+                dek,
+                subscription_id,
+                Some(&id),
+                user_id,
+                transaction.amount,
+                transaction.occurred_at,
+                false,
+            )
+            .await?;
+        }
+        detect_billing_event(&mut tx, dek, &id, user_id, transaction).await?;
+        let charged_after_cancellation = charged_after_cancellation(&mut tx, &[id]).await?.contains(&id);
 
         self.apply_category_balance_effect(
             &mut tx,
@@ -493,6 +803,11 @@ impl PostgresRepository {
             vendor_id: transaction.vendor_id,
             amount_enc,
             description_enc,
+            splits,
+            tag_ids,
+            cleared_status: ClearedStatus::Uncleared,
+            is_adjustment: false,
+            charged_after_cancellation,
         })
     }
 
@@ -511,21 +826,31 @@ impl PostgresRepository {
         if !state.is_effective {
             return Err(AppError::Conflict("Transaction has already been voided".to_string()));
         }
+        if state.cleared_status == ClearedStatus::Reconciled {
+            return Err(AppError::Conflict("Transaction is reconciled; unlock it first".to_string()));
+        }
 
-        let prev_sum = dek.decrypt_i64(&state.current_sum_enc)?;
+        let prev_sum = dek.decrypt_i64_for(&state.current_sum_enc, &lts_aad(id, user_id))?;
         let latest = self.fetch_latest_row(&mut tx, id, state.latest_seq).await?;
+        if latest.entry_kind == LedgerEntryKind::Adjustment {
+            return Err(AppError::Conflict(
+                "Balance adjustments cannot be changed; adjust the balance again".to_string(),
+            ));
+        }
         let cat_type = self.resolve_category_type(&mut tx, latest.category_id.as_ref()).await?;
 
         // Encrypt the compensating amount. Description bytes pass through
         // verbatim — we're preserving the Latest_Row's description on the
         // void compensating row (matches the ledger refactor's semantics).
+        // The envelope stays valid: ledger AAD binds the logical id, which
+        // every seq shares.
         let compensating_amount = -prev_sum;
-        let amount_enc = dek.encrypt_i64(compensating_amount)?;
+        let amount_enc = dek.encrypt_i64_for(compensating_amount, &ledger_aad(id, user_id).column("amount_enc"))?;
 
         let (_, seq, _) = self
             .insert_ledger_row_enc_in_tx(
                 &mut tx,
-                Some(id),
+                id,
                 user_id,
                 &amount_enc,
                 &latest.description_enc,
@@ -534,9 +859,11 @@ impl PostgresRepository {
                 &latest.from_account_id,
                 latest.to_account_id.as_ref(),
                 latest.vendor_id.as_ref(),
+                LedgerEntryKind::Void,
             )
             .await?;
 
+        self.compensate_splits_in_tx(&mut tx, dek, id, state.latest_seq, seq, user_id).await?;
         self.upsert_lts_in_tx(&mut tx, dek, id, user_id, compensating_amount, seq, chrono::Utc::now())
             .await?;
 
@@ -554,6 +881,79 @@ impl PostgresRepository {
         Ok(())
     }
 
+    /// Restore (un-void) a deleted logical transaction. The void row
+    /// carries `-prev_sum` and the Latest_Row's metadata, so negating its
+    /// amount and copying everything else re-applies the last effective
+    /// row as a new seq, with the matching balance side effect.
+    pub async fn restore_transaction(&self, id: &Uuid, user_id: &Uuid, dek: &Dek) -> Result<LedgerInsertResult, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let state = self
+            .lock_logical_transaction_state(&mut tx, id, user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Transaction not found".to_string()))?;
+
+        if state.is_effective {
+            return Err(AppError::Conflict("Transaction has not been deleted".to_string()));
+        }
+
+        let latest = self.fetch_latest_row(&mut tx, id, state.latest_seq).await?;
+        if latest.entry_kind != LedgerEntryKind::Void {
+            return Err(AppError::Conflict("Only deleted transactions can be restored".to_string()));
+        }
+
+        let aad = ledger_aad(id, user_id);
+        let voided_amount = dek.decrypt_i64_for(&latest.amount_enc, &aad.column("amount_enc"))?;
+        let restored_amount = -voided_amount;
+        let amount_enc = dek.encrypt_i64_for(restored_amount, &aad.column("amount_enc"))?;
+        let cat_type = self.resolve_category_type(&mut tx, latest.category_id.as_ref()).await?;
+
+        let (_, seq, _) = self
+            .insert_ledger_row_enc_in_tx(
+                &mut tx,
+                id,
+                user_id,
+                &amount_enc,
+                &latest.description_enc,
+                latest.occurred_at,
+                latest.category_id.as_ref(),
+                &latest.from_account_id,
+                latest.to_account_id.as_ref(),
+                latest.vendor_id.as_ref(),
+                LedgerEntryKind::Restore,
+            )
+            .await?;
+
+        let splits = self.compensate_splits_in_tx(&mut tx, dek, id, state.latest_seq, seq, user_id).await?;
+        self.upsert_lts_in_tx(&mut tx, dek, id, user_id, restored_amount, seq, chrono::Utc::now())
+            .await?;
+        let tag_ids = self.sync_tags_in_tx(&mut tx, id, user_id, None).await?;
+        let charged_after_cancellation = charged_after_cancellation(&mut tx, &[*id]).await?.contains(id);
+
+        self.apply_category_balance_effect(&mut tx, cat_type, restored_amount, &latest.from_account_id, latest.to_account_id.as_ref(), dek)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(LedgerInsertResult {
+            id: *id,
+            seq,
+            first_created_at: state.first_created_at,
+            occurred_at: latest.occurred_at,
+            from_account_id: latest.from_account_id,
+            to_account_id: latest.to_account_id,
+            category_id: latest.category_id,
+            vendor_id: latest.vendor_id,
+            amount_enc,
+            description_enc: latest.description_enc,
+            splits,
+            tag_ids,
+            cleared_status: state.cleared_status,
+            is_adjustment: false,
+            charged_after_cancellation,
+        })
+    }
+
     /// Correct (update) a logical transaction by inserting a reversal
     /// row (bringing the running sum to 0) followed by a correction row
     /// (applying the desired new metadata and amount). Both inserts
@@ -561,8 +961,9 @@ impl PostgresRepository {
     pub async fn update_transaction(&self, id: &Uuid, transaction: &TransactionRequest, user_id: &Uuid, dek: &Dek) -> Result<LedgerInsertResult, AppError> {
         self.validate_transaction_ownership(transaction, user_id).await?;
 
-        let amount_enc = dek.encrypt_i64(transaction.amount)?;
-        let description_enc = dek.encrypt_string(&transaction.description)?;
+        let aad = ledger_aad(id, user_id);
+        let amount_enc = dek.encrypt_i64_for(transaction.amount, &aad.column("amount_enc"))?;
+        let description_enc = dek.encrypt_string_for(&transaction.description, &aad.column("description_enc"))?;
 
         let mut tx = self.pool.begin().await?;
 
@@ -574,20 +975,28 @@ impl PostgresRepository {
         if !state.is_effective {
             return Err(AppError::Conflict("Transaction has already been voided".to_string()));
         }
+        if state.cleared_status == ClearedStatus::Reconciled {
+            return Err(AppError::Conflict("Transaction is reconciled; unlock it first".to_string()));
+        }
 
-        let prev_sum = dek.decrypt_i64(&state.current_sum_enc)?;
+        let prev_sum = dek.decrypt_i64_for(&state.current_sum_enc, &lts_aad(id, user_id))?;
         let latest = self.fetch_latest_row(&mut tx, id, state.latest_seq).await?;
+        if latest.entry_kind == LedgerEntryKind::Adjustment {
+            return Err(AppError::Conflict(
+                "Balance adjustments cannot be changed; adjust the balance again".to_string(),
+            ));
+        }
         let old_cat_type = self.resolve_category_type(&mut tx, latest.category_id.as_ref()).await?;
         let new_cat_type = self.resolve_category_type(&mut tx, Some(&transaction.category_id)).await?;
 
         // Full_Reversal_Row: brings the running sum to zero. Copies all
         // metadata (including description_enc) from the Latest_Row.
         let reversal_amount = -prev_sum;
-        let reversal_amount_enc = dek.encrypt_i64(reversal_amount)?;
+        let reversal_amount_enc = dek.encrypt_i64_for(reversal_amount, &aad.column("amount_enc"))?;
         let (_, reversal_seq, _) = self
             .insert_ledger_row_enc_in_tx(
                 &mut tx,
-                Some(id),
+                id,
                 user_id,
                 &reversal_amount_enc,
                 &latest.description_enc,
@@ -596,9 +1005,11 @@ impl PostgresRepository {
                 &latest.from_account_id,
                 latest.to_account_id.as_ref(),
                 latest.vendor_id.as_ref(),
+                LedgerEntryKind::Reversal,
             )
             .await?;
 
+        self.compensate_splits_in_tx(&mut tx, dek, id, state.latest_seq, reversal_seq, user_id).await?;
         self.upsert_lts_in_tx(&mut tx, dek, id, user_id, reversal_amount, reversal_seq, chrono::Utc::now())
             .await?;
 
@@ -616,7 +1027,7 @@ impl PostgresRepository {
         let (_, correction_seq, correction_created_at) = self
             .insert_ledger_row_enc_in_tx(
                 &mut tx,
-                Some(id),
+                id,
                 user_id,
                 &amount_enc,
                 &description_enc,
@@ -625,11 +1036,26 @@ impl PostgresRepository {
                 &transaction.from_account_id,
                 transaction.to_account_id.as_ref(),
                 transaction.vendor_id.as_ref(),
+                LedgerEntryKind::Correction,
             )
             .await?;
 
+        let splits = self.insert_splits_in_tx(&mut tx, dek, id, correction_seq, user_id, &transaction.splits).await?;
         self.upsert_lts_in_tx(&mut tx, dek, id, user_id, transaction.amount, correction_seq, correction_created_at)
             .await?;
+        let tag_ids = self.sync_tags_in_tx(&mut tx, id, user_id, transaction.tag_ids.as_deref()).await?;
+        sync_billing_events(
+            &mut tx,
+            dek,
+            id,
+            user_id,
+            transaction.subscription_id.as_ref(),
+            transaction.amount,
+            transaction.occurred_at,
+        )
+        .await?;
+        detect_billing_event(&mut tx, dek, id, user_id, transaction).await?;
+        let charged_after_cancellation = charged_after_cancellation(&mut tx, &[*id]).await?.contains(id);
 
         self.apply_category_balance_effect(
             &mut tx,
@@ -654,28 +1080,49 @@ impl PostgresRepository {
             vendor_id: transaction.vendor_id,
             amount_enc,
             description_enc,
+            splits,
+            tag_ids,
+            cleared_status: state.cleared_status,
+            is_adjustment: false,
+            charged_after_cancellation,
         })
     }
 
     /// Create N logical transactions inside a single database transaction.
     /// All-or-nothing: any failure rolls the whole batch back.
     pub async fn batch_create_transactions(&self, transactions: &[TransactionRequest], user_id: &Uuid, dek: &Dek) -> Result<Vec<LedgerInsertResult>, AppError> {
+        let mut tx = self.pool.begin().await?;
+        let results = self.batch_create_transactions_in_tx(&mut tx, transactions, user_id, dek).await?;
+        tx.commit().await?;
+        Ok(results)
+    }
+
+    /// [`Self::batch_create_transactions`] inside the caller's transaction,
+    /// so the batch commits or rolls back with the caller's other writes.
+    pub(super) async fn batch_create_transactions_in_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        transactions: &[TransactionRequest],
+        user_id: &Uuid,
+        dek: &Dek,
+    ) -> Result<Vec<LedgerInsertResult>, AppError> {
         for req in transactions {
             self.validate_transaction_ownership(req, user_id).await?;
         }
 
-        let mut tx = self.pool.begin().await?;
         let mut results = Vec::with_capacity(transactions.len());
 
         for req in transactions {
-            let amount_enc = dek.encrypt_i64(req.amount)?;
-            let description_enc = dek.encrypt_string(&req.description)?;
-            let cat_type = self.resolve_category_type(&mut tx, Some(&req.category_id)).await?;
+            let id = Uuid::new_v4();
+            let aad = ledger_aad(&id, user_id);
+            let amount_enc = dek.encrypt_i64_for(req.amount, &aad.column("amount_enc"))?;
+            let description_enc = dek.encrypt_string_for(&req.description, &aad.column("description_enc"))?;
+            let cat_type = self.resolve_category_type(tx, Some(&req.category_id)).await?;
 
-            let (id, seq, created_at) = self
+            let (_, seq, created_at) = self
                 .insert_ledger_row_enc_in_tx(
-                    &mut tx,
-                    None,
+                    tx,
+                    &id,
                     user_id,
                     &amount_enc,
                     &description_enc,
@@ -684,11 +1131,19 @@ impl PostgresRepository {
                     &req.from_account_id,
                     req.to_account_id.as_ref(),
                     req.vendor_id.as_ref(),
+                    LedgerEntryKind::Original,
                 )
                 .await?;
 
-            self.upsert_lts_in_tx(&mut tx, dek, &id, user_id, req.amount, seq, created_at).await?;
-            self.apply_category_balance_effect(&mut tx, cat_type, req.amount, &req.from_account_id, req.to_account_id.as_ref(), dek)
+            let splits = self.insert_splits_in_tx(tx, dek, &id, seq, user_id, &req.splits).await?;
+            self.upsert_lts_in_tx(tx, dek, &id, user_id, req.amount, seq, created_at).await?;
+            let tag_ids = self.sync_tags_in_tx(tx, &id, user_id, req.tag_ids.as_deref()).await?;
+            if let Some(subscription_id) = &req.subscription_id {
+                insert_billing_event(tx, dek, subscription_id, Some(&id), user_id, req.amount, req.occurred_at, false).await?;
+            }
+            detect_billing_event(tx, dek, &id, user_id, req).await?;
+            let charged_after_cancellation = charged_after_cancellation(tx, &[id]).await?.contains(&id);
+            self.apply_category_balance_effect(tx, cat_type, req.amount, &req.from_account_id, req.to_account_id.as_ref(), dek)
                 .await?;
 
             results.push(LedgerInsertResult {
@@ -702,10 +1157,14 @@ impl PostgresRepository {
                 vendor_id: req.vendor_id,
                 amount_enc,
                 description_enc,
+                splits,
+                tag_ids,
+                cleared_status: ClearedStatus::Uncleared,
+                is_adjustment: false,
+                charged_after_cancellation,
             });
         }
 
-        tx.commit().await?;
         Ok(results)
     }
 
@@ -713,23 +1172,10 @@ impl PostgresRepository {
     /// whose Latest_Row's `occurred_at` falls in the inclusive date
     /// range. Returns ciphertext; the client decrypts with its DEK and
     /// computes every card/chart locally. No pagination, no filters —
-    /// the whole range comes back on one call.
+    /// the whole range comes back on one call. Filtered screens use
+    /// `list_transactions_page` instead.
     pub async fn list_effective_transactions_in_range(&self, user_id: &Uuid, from: NaiveDate, to: NaiveDate) -> Result<Vec<LedgerInsertResult>, AppError> {
-        #[derive(sqlx::FromRow)]
-        struct Row {
-            id: Uuid,
-            seq: i64,
-            created_at: chrono::DateTime<chrono::Utc>,
-            occurred_at: NaiveDate,
-            from_account_id: Uuid,
-            to_account_id: Option<Uuid>,
-            category_id: Option<Uuid>,
-            vendor_id: Option<Uuid>,
-            amount_enc: Vec<u8>,
-            description_enc: Vec<u8>,
-        }
-
-        let rows: Vec<Row> = sqlx::query_as(
+        let rows: Vec<EffectiveRow> = sqlx::query_as(
             r#"
 SELECT t.id,
        t.seq,
@@ -740,7 +1186,9 @@ SELECT t.id,
        t.category_id,
        t.vendor_id,
        t.amount_enc,
-       t.description_enc
+       t.description_enc,
+       lts.cleared_status,
+       t.entry_kind = 'adjustment' AS is_adjustment
 FROM logical_transaction_state lts
 JOIN transaction t ON t.id = lts.id AND t.seq = lts.latest_seq
 WHERE lts.user_id = $1
@@ -755,20 +1203,205 @@ ORDER BY t.occurred_at DESC, t.seq DESC
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(rows
-            .into_iter()
-            .map(|r| LedgerInsertResult {
-                id: r.id,
-                seq: r.seq,
-                first_created_at: r.created_at,
-                occurred_at: r.occurred_at,
-                from_account_id: r.from_account_id,
-                to_account_id: r.to_account_id,
-                category_id: r.category_id,
-                vendor_id: r.vendor_id,
-                amount_enc: r.amount_enc,
-                description_enc: r.description_enc,
-            })
-            .collect())
+        let mut results: Vec<LedgerInsertResult> = rows.into_iter().map(LedgerInsertResult::from).collect();
+        self.attach_splits(&mut results).await?;
+        self.attach_tags(&mut results).await?;
+        self.attach_cancellation_flags(&mut results).await?;
+        Ok(results)
+    }
+
+    /// One page of effective logical transactions matching `filters`,
+    /// newest first by `(occurred_at, id)`. `params.cursor` is the id of
+    /// the last transaction of the previous page; its current Latest_Row
+    /// date anchors the next page, so edits elsewhere cannot shift it.
+    /// Fetches one row past the limit so the caller can tell whether
+    /// another page exists. Also returns the filtered total.
+    pub async fn list_transactions_page(
+        &self,
+        user_id: &Uuid,
+        filters: &TransactionFilters,
+        params: &CursorParams,
+    ) -> Result<(Vec<LedgerInsertResult>, i64), AppError> {
+        let direction = filters.direction.as_ref().map(TransactionDirection::as_str);
+
+        let total_count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) {FILTERED_EFFECTIVE_ROWS}"))
+            .bind(user_id)
+            .bind(filters.date_from)
+            .bind(filters.date_to)
+            .bind(&filters.account_ids)
+            .bind(&filters.category_ids)
+            .bind(&filters.vendor_ids)
+            .bind(direction)
+            .bind(filters.uncategorized)
+            .bind(&filters.tag_ids)
+            .fetch_one(&self.pool)
+            .await?;
+
+        let rows: Vec<EffectiveRow> = sqlx::query_as(&format!(
+            r#"
+SELECT t.id,
+       t.seq,
+       lts.first_created_at AS created_at,
+       t.occurred_at,
+       t.from_account_id,
+       t.to_account_id,
+       t.category_id,
+       t.vendor_id,
+       t.amount_enc,
+       t.description_enc,
+       lts.cleared_status,
+       t.entry_kind = 'adjustment' AS is_adjustment
+{FILTERED_EFFECTIVE_ROWS}
+  AND ($10::uuid IS NULL OR (t.occurred_at, t.id) < (
+        SELECT ct.occurred_at, ct.id
+        FROM logical_transaction_state cl
+        JOIN transaction ct ON ct.id = cl.id AND ct.seq = cl.latest_seq
+        WHERE cl.id = $10 AND cl.user_id = $1))
+ORDER BY t.occurred_at DESC, t.id DESC
+LIMIT $11
+"#
+        ))
+        .bind(user_id)
+        .bind(filters.date_from)
+        .bind(filters.date_to)
+        .bind(&filters.account_ids)
+        .bind(&filters.category_ids)
+        .bind(&filters.vendor_ids)
+        .bind(direction)
+        .bind(filters.uncategorized)
+        .bind(&filters.tag_ids)
+        .bind(params.cursor)
+        .bind(params.fetch_limit())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut results: Vec<LedgerInsertResult> = rows.into_iter().map(LedgerInsertResult::from).collect();
+        self.attach_splits(&mut results).await?;
+        self.attach_tags(&mut results).await?;
+        self.attach_cancellation_flags(&mut results).await?;
+        Ok((results, total_count))
+    }
+
+    /// Fill in the split lines of each result's `(id, seq)` row with one
+    /// query for the whole list.
+    pub(crate) async fn attach_splits(&self, results: &mut [LedgerInsertResult]) -> Result<(), AppError> {
+        #[derive(sqlx::FromRow)]
+        struct Row {
+            transaction_id: Uuid,
+            seq: i64,
+            #[sqlx(flatten)]
+            split: LedgerSplit,
+        }
+
+        if results.is_empty() {
+            return Ok(());
+        }
+        let ids: Vec<Uuid> = results.iter().map(|r| r.id).collect();
+        let seqs: Vec<i64> = results.iter().map(|r| r.seq).collect();
+
+        let rows: Vec<Row> = sqlx::query_as(
+            r#"
+SELECT s.transaction_id, s.seq, s.id, s.category_id, s.amount_enc, s.memo_enc
+FROM transaction_split s
+JOIN UNNEST($1::uuid[], $2::bigint[]) AS r(id, seq) ON s.transaction_id = r.id AND s.seq = r.seq
+ORDER BY s.transaction_id, s.seq, s.position
+"#,
+        )
+        .bind(&ids)
+        .bind(&seqs)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut by_row: std::collections::HashMap<(Uuid, i64), Vec<LedgerSplit>> = std::collections::HashMap::new();
+        for row in rows {
+            by_row.entry((row.transaction_id, row.seq)).or_default().push(row.split);
+        }
+        for result in results.iter_mut() {
+            if let Some(splits) = by_row.remove(&(result.id, result.seq)) {
+                result.splits = splits;
+            }
+        }
+        Ok(())
+    }
+
+    /// Fill in each result's tags with one query for the whole list.
+    pub(crate) async fn attach_tags(&self, results: &mut [LedgerInsertResult]) -> Result<(), AppError> {
+        if results.is_empty() {
+            return Ok(());
+        }
+        let ids: Vec<Uuid> = results.iter().map(|r| r.id).collect();
+
+        let rows: Vec<(Uuid, Uuid)> = sqlx::query_as("SELECT transaction_id, tag_id FROM transaction_tag WHERE transaction_id = ANY($1) ORDER BY tag_id")
+            .bind(&ids)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut by_id: std::collections::HashMap<Uuid, Vec<Uuid>> = std::collections::HashMap::new();
+        for (transaction_id, tag_id) in rows {
+            by_id.entry(transaction_id).or_default().push(tag_id);
+        }
+        for result in results.iter_mut() {
+            if let Some(tag_ids) = by_id.remove(&result.id) {
+                result.tag_ids = tag_ids;
+            }
+        }
+        Ok(())
+    }
+
+    /// Flag each result charged to a subscription after its cancellation,
+    /// with one query for the whole list.
+    pub(crate) async fn attach_cancellation_flags(&self, results: &mut [LedgerInsertResult]) -> Result<(), AppError> {
+        if results.is_empty() {
+            return Ok(());
+        }
+        let ids: Vec<Uuid> = results.iter().map(|r| r.id).collect();
+
+        let mut conn = self.pool.acquire().await?;
+        let flagged = charged_after_cancellation(&mut conn, &ids).await?;
+        for result in results.iter_mut() {
+            result.charged_after_cancellation = flagged.contains(&result.id);
+        }
+        Ok(())
+    }
+
+    /// Every ledger row of one logical transaction, oldest first,
+    /// including reversal and void rows. Returns ciphertext; an empty
+    /// result means the id does not exist for this user.
+    pub async fn list_transaction_history(&self, id: &Uuid, user_id: &Uuid) -> Result<Vec<LedgerHistoryRow>, AppError> {
+        let rows = sqlx::query_as::<_, LedgerHistoryRow>(
+            r#"
+SELECT id, seq, entry_kind, created_at, occurred_at, from_account_id, to_account_id,
+       category_id, vendor_id, amount_enc, description_enc
+FROM transaction
+WHERE id = $1 AND user_id = $2
+ORDER BY seq
+"#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_balance_delta_regular_account() {
+        assert_eq!(account_balance_delta(CategoryType::Incoming, AccountType::Checking, 500, false), 500);
+        assert_eq!(account_balance_delta(CategoryType::Outgoing, AccountType::Checking, 500, false), -500);
+        assert_eq!(account_balance_delta(CategoryType::Transfer, AccountType::Savings, 500, false), -500);
+        assert_eq!(account_balance_delta(CategoryType::Transfer, AccountType::Savings, 500, true), 500);
+    }
+
+    #[test]
+    fn test_account_balance_delta_credit_card() {
+        assert_eq!(account_balance_delta(CategoryType::Incoming, AccountType::CreditCard, 500, false), -500);
+        assert_eq!(account_balance_delta(CategoryType::Outgoing, AccountType::CreditCard, 500, false), 500);
+        assert_eq!(account_balance_delta(CategoryType::Transfer, AccountType::CreditCard, 500, false), 500);
+        assert_eq!(account_balance_delta(CategoryType::Transfer, AccountType::CreditCard, 500, true), -500);
     }
 }
@@ -26,13 +26,14 @@ impl PostgresRepository {
         password: &str,
         wrapped_dek: Option<&[u8]>,
         dek_wrap_params: Option<&serde_json::Value>,
+        recovery_wrap: Option<(&[u8], &serde_json::Value)>,
     ) -> Result<User, AppError> {
         let (salt, password_hash) = password_hash(password);
 
         let user = sqlx::query_as::<_, User>(
             r#"
-            INSERT INTO users (name, email, salt, password_hash, wrapped_dek, dek_wrap_params)
-            VALUES($1, $2, $3, $4, $5, $6)
+            INSERT INTO users (name, email, salt, password_hash, wrapped_dek, dek_wrap_params, recovery_wrapped_dek, recovery_wrap_params)
+            VALUES($1, $2, $3, $4, $5, $6, $7, $8)
             RETURNING id, name, email, password_hash
             "#,
         )
@@ -42,6 +43,8 @@ impl PostgresRepository {
         .bind(&password_hash)
         .bind(wrapped_dek)
         .bind(dek_wrap_params)
+        .bind(recovery_wrap.map(|(wrapped, _)| wrapped))
+        .bind(recovery_wrap.map(|(_, params)| params))
         .fetch_one(&self.pool)
         .await?;
 
@@ -66,6 +69,42 @@ impl PostgresRepository {
         Ok(())
     }
 
+    pub async fn get_recovery_wrapped_dek(&self, user_id: &Uuid) -> Result<(Option<Vec<u8>>, Option<serde_json::Value>), AppError> {
+        let row: (Option<Vec<u8>>, Option<serde_json::Value>) = sqlx::query_as("SELECT recovery_wrapped_dek, recovery_wrap_params FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(row)
+    }
+
+    pub async fn update_recovery_wrapped_dek(&self, user_id: &Uuid, wrapped_dek: &[u8], wrap_params: &serde_json::Value) -> Result<(), AppError> {
+        sqlx::query("UPDATE users SET recovery_wrapped_dek = $1, recovery_wrap_params = $2 WHERE id = $3")
+            .bind(wrapped_dek)
+            .bind(wrap_params)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn clear_recovery_wrapped_dek(&self, user_id: &Uuid) -> Result<(), AppError> {
+        sqlx::query("UPDATE users SET recovery_wrapped_dek = NULL, recovery_wrap_params = NULL WHERE id = $1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Whether the user may still have legacy (unbound) envelopes on disk,
+    /// i.e. their envelopes have not all been upgraded yet.
+    pub async fn has_legacy_envelopes(&self, user_id: &Uuid) -> Result<bool, AppError> {
+        let (pending,): (bool,) = sqlx::query_as("SELECT envelopes_upgraded_at IS NULL FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(pending)
+    }
+
     pub async fn get_user_by_email(&self, email: &str) -> Result<Option<User>, AppError> {
         let user = sqlx::query_as::<_, User>(
             r#"
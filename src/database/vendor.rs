@@ -1,4 +1,5 @@
-use crate::crypto::Dek;
+use crate::crypto::{Dek, RowAad};
+use crate::database::name_index::name_conflict;
 use crate::database::postgres_repository::PostgresRepository;
 use crate::dto::vendors::{CreateVendorRequest, UpdateVendorRequest};
 use crate::error::app_error::AppError;
@@ -9,26 +10,33 @@ const VENDOR_COLUMNS: &str = "id, archived, name_enc, description_enc";
 
 impl PostgresRepository {
     pub async fn create_vendor(&self, request: &CreateVendorRequest, user_id: &Uuid, dek: &Dek) -> Result<Vendor, AppError> {
+        self.backfill_name_index("vendor", user_id, dek).await?;
         let mut tx = self.pool.begin().await?;
 
-        lock_user_row(&mut tx, user_id).await?;
-        check_vendor_name_unique(&mut tx, dek, user_id, &request.name, None).await?;
-
-        let name_enc = dek.encrypt_string(&request.name)?;
-        let description_enc = request.description.as_deref().map(|d| dek.encrypt_string(d)).transpose()?;
+        let id = Uuid::new_v4();
+        let aad = RowAad::new("vendor", id, *user_id);
+        let name_enc = dek.encrypt_string_for(&request.name, &aad.column("name_enc"))?;
+        let description_enc = request
+            .description
+            .as_deref()
+            .map(|d| dek.encrypt_string_for(d, &aad.column("description_enc")))
+            .transpose()?;
 
         let vendor: Vendor = sqlx::query_as(&format!(
             r#"
-INSERT INTO vendor (id, user_id, archived, name_enc, description_enc)
-VALUES (gen_random_uuid(), $1, false, $2, $3)
+INSERT INTO vendor (id, user_id, archived, name_enc, name_bidx, description_enc)
+VALUES ($1, $2, false, $3, $4, $5)
 RETURNING {VENDOR_COLUMNS}
 "#,
         ))
+        .bind(id)
         .bind(user_id)
         .bind(&name_enc)
+        .bind(dek.name_blind_index("vendor", &request.name))
         .bind(description_enc.as_deref())
         .fetch_one(&mut *tx)
-        .await?;
+        .await
+        .map_err(|e| name_conflict(e, "vendor", "A vendor", &request.name))?;
 
         tx.commit().await?;
         Ok(vendor)
@@ -53,34 +61,54 @@ RETURNING {VENDOR_COLUMNS}
     }
 
     pub async fn update_vendor(&self, id: &Uuid, request: &UpdateVendorRequest, user_id: &Uuid, dek: &Dek) -> Result<Vendor, AppError> {
+        self.backfill_name_index("vendor", user_id, dek).await?;
         let mut tx = self.pool.begin().await?;
 
-        lock_user_row(&mut tx, user_id).await?;
-        check_vendor_name_unique(&mut tx, dek, user_id, &request.name, Some(id)).await?;
-
-        let name_enc = dek.encrypt_string(&request.name)?;
-        let description_enc = request.description.as_deref().map(|d| dek.encrypt_string(d)).transpose()?;
+        let aad = RowAad::new("vendor", *id, *user_id);
+        let name_enc = dek.encrypt_string_for(&request.name, &aad.column("name_enc"))?;
+        let description_enc = request
+            .description
+            .as_deref()
+            .map(|d| dek.encrypt_string_for(d, &aad.column("description_enc")))
+            .transpose()?;
 
         let vendor: Vendor = sqlx::query_as(&format!(
             r#"
 UPDATE vendor
-SET name_enc = $1, description_enc = $2
-WHERE id = $3 AND user_id = $4
+SET name_enc = $1, name_bidx = $2, description_enc = $3
+WHERE id = $4 AND user_id = $5
 RETURNING {VENDOR_COLUMNS}
 "#,
         ))
         .bind(&name_enc)
+        .bind(dek.name_blind_index("vendor", &request.name))
         .bind(description_enc.as_deref())
         .bind(id)
         .bind(user_id)
         .fetch_optional(&mut *tx)
-        .await?
+        .await
+        .map_err(|e| name_conflict(e, "vendor", "A vendor", &request.name))?
         .ok_or_else(|| AppError::NotFound("Vendor not found".to_string()))?;
 
         tx.commit().await?;
         Ok(vendor)
     }
 
+    /// Exact, case-insensitive name lookup through the blind index, e.g. to
+    /// match imported vendors against existing ones without decrypting the
+    /// user's whole vendor list.
+    #[allow(dead_code)]
+    pub async fn find_vendor_by_name(&self, name: &str, user_id: &Uuid, dek: &Dek) -> Result<Option<Vendor>, AppError> {
+        self.backfill_name_index("vendor", user_id, dek).await?;
+
+        let vendor = sqlx::query_as::<_, Vendor>(&format!("SELECT {VENDOR_COLUMNS} FROM vendor WHERE user_id = $1 AND name_bidx = $2"))
+            .bind(user_id)
+            .bind(dek.name_blind_index("vendor", name))
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(vendor)
+    }
+
     pub async fn delete_vendor(&self, id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
         let result = sqlx::query("DELETE FROM vendor WHERE id = $1 AND user_id = $2")
             .bind(id)
@@ -117,36 +145,3 @@ RETURNING {VENDOR_COLUMNS}
         Ok(())
     }
 }
-
-async fn lock_user_row(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, user_id: &Uuid) -> Result<(), AppError> {
-    sqlx::query("SELECT 1 FROM users WHERE id = $1 FOR UPDATE")
-        .bind(user_id)
-        .fetch_one(&mut **tx)
-        .await?;
-    Ok(())
-}
-
-async fn check_vendor_name_unique(
-    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
-    dek: &Dek,
-    user_id: &Uuid,
-    candidate: &str,
-    exclude_id: Option<&Uuid>,
-) -> Result<(), AppError> {
-    let rows: Vec<(Uuid, Vec<u8>)> = sqlx::query_as("SELECT id, name_enc FROM vendor WHERE user_id = $1")
-        .bind(user_id)
-        .fetch_all(&mut **tx)
-        .await?;
-
-    let candidate_lower = candidate.to_lowercase();
-    for (row_id, name_enc) in rows {
-        if exclude_id.is_some_and(|id| *id == row_id) {
-            continue;
-        }
-        let existing = dek.decrypt_string(&name_enc)?;
-        if existing.to_lowercase() == candidate_lower {
-            return Err(AppError::Conflict(format!("A vendor named '{}' already exists", candidate)));
-        }
-    }
-    Ok(())
-}
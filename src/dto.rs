@@ -1,11 +1,15 @@
 pub mod accounts;
 pub mod auth;
+pub mod calendar;
 pub mod categories;
 pub mod common;
+pub mod dashboard;
 pub mod health;
 pub mod misc;
 pub mod period;
+pub mod reconciliations;
 pub mod settings;
 pub mod subscriptions;
+pub mod tags;
 pub mod transactions;
 pub mod vendors;
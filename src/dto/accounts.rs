@@ -1,9 +1,10 @@
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD as B64;
+use chrono::Weekday;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::dto::common::PaginatedResponse;
+use crate::dto::common::{Date, PaginatedResponse};
 
 // ===== Account type / status =====
 
@@ -26,6 +27,55 @@ pub enum AccountStatus {
     Inactive,
 }
 
+/// How often an allowance account is topped up. With `Weekly` and
+/// `BiWeekly` the account's `top_up_day` is the ISO weekday, 1 (Monday) to
+/// 7 (Sunday); with `Monthly` it is the day of the month, 1 to 31, clamped
+/// to the month's last day.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TopUpCycle {
+    Weekly,
+    /// Every other week. The week it starts in is not stored, so the
+    /// upcoming calendar does not project these top-ups.
+    BiWeekly,
+    Monthly,
+}
+
+impl TopUpCycle {
+    pub const ALL: [TopUpCycle; 3] = [TopUpCycle::Weekly, TopUpCycle::BiWeekly, TopUpCycle::Monthly];
+
+    /// Value stored in `account.top_up_cycle`, the same as on the wire.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TopUpCycle::Weekly => "weekly",
+            TopUpCycle::BiWeekly => "bi-weekly",
+            TopUpCycle::Monthly => "monthly",
+        }
+    }
+
+    /// Read a stored value. The column predates validation, so an unknown
+    /// value is `None` rather than an error.
+    pub fn from_db(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == value)
+    }
+
+    /// Whether `day` is a valid `top_up_day` for this cycle.
+    pub fn accepts_day(self, day: i32) -> bool {
+        match self {
+            TopUpCycle::Weekly | TopUpCycle::BiWeekly => Self::weekday(day).is_some(),
+            TopUpCycle::Monthly => (1..=31).contains(&day),
+        }
+    }
+
+    /// The weekday of a weekly `top_up_day`, `None` outside 1 to 7.
+    pub fn weekday(day: i32) -> Option<Weekday> {
+        match day {
+            1..=7 => Weekday::try_from(day as u8 - 1).ok(),
+            _ => None,
+        }
+    }
+}
+
 // ===== Encrypted response =====
 //
 // Monetary and label fields are returned as base64-encoded AES-GCM
@@ -50,7 +100,7 @@ pub struct EncryptedAccountResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_up_amount_enc: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub top_up_cycle: Option<String>,
+    pub top_up_cycle: Option<TopUpCycle>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_up_day: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -94,7 +144,7 @@ pub struct CreateAccountRequest {
     pub spend_limit: Option<i64>,
     pub next_transfer_amount: Option<i64>,
     pub top_up_amount: Option<i64>,
-    pub top_up_cycle: Option<String>,
+    pub top_up_cycle: Option<TopUpCycle>,
     pub top_up_day: Option<i32>,
     pub statement_close_day: Option<i32>,
     pub payment_due_day: Option<i32>,
@@ -102,12 +152,101 @@ pub struct CreateAccountRequest {
 
 pub type UpdateAccountRequest = CreateAccountRequest;
 
+// ===== Balance history =====
+//
+// Closing balances are derived per request from the ledger with the
+// session DEK, so they come back as plaintext integer cents.
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountBalanceHistoryPoint {
+    pub date: Date,
+    /// Balance at the end of the day.
+    pub balance: i64,
+    /// Effective transactions on the account dated that day.
+    pub transaction_count: i64,
+}
+
+pub type AccountBalanceHistoryResponse = Vec<AccountBalanceHistoryPoint>;
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountBalanceHistoryItem {
+    pub account_id: Uuid,
+    pub history: Vec<AccountBalanceHistoryPoint>,
+}
+
+pub type AccountsBalanceHistoryResponse = Vec<AccountBalanceHistoryItem>;
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct AdjustBalanceRequest {
     pub new_balance: i64,
 }
 
+// ===== Ledger verification =====
+//
+// Figures are replayed from the ledger with the session DEK and come back
+// as plaintext integer cents.
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyLedgerRequest {
+    /// Book balance drift as adjustment rows, rebuild drifted transaction
+    /// sums and save derived opening balances. Without it nothing is
+    /// written.
+    #[serde(default)]
+    pub repair: bool,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountLedgerCheckResponse {
+    pub account_id: Uuid,
+    pub stored_balance: i64,
+    pub ledger_balance: i64,
+    /// `storedBalance - ledgerBalance`.
+    pub drift: i64,
+    /// The opening balance was derived from the stored balance on this run,
+    /// because the account predates recorded opening balances. It is saved
+    /// only when repairing.
+    pub baselined: bool,
+    /// Adjustment transaction the repair booked the drift as.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adjustment_id: Option<Uuid>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionLedgerCheckResponse {
+    pub transaction_id: Uuid,
+    pub stored_sum: i64,
+    pub ledger_sum: i64,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvelopeFailureResponse {
+    pub table: String,
+    pub column: String,
+    pub row_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seq: Option<i64>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerVerificationResponse {
+    /// No drift anywhere and every envelope decrypts.
+    pub consistent: bool,
+    pub repaired: bool,
+    pub accounts: Vec<AccountLedgerCheckResponse>,
+    /// Transactions whose stored state disagrees with their ledger rows.
+    pub transactions: Vec<TransactionLedgerCheckResponse>,
+    pub envelopes_checked: u64,
+    pub envelope_failures: Vec<EnvelopeFailureResponse>,
+}
+
 pub fn b64(bytes: &[u8]) -> String {
     B64.encode(bytes)
 }
@@ -117,6 +117,9 @@ pub struct RegisterRequest {
     pub name: String,
     pub wrapped_dek: Option<String>,
     pub dek_wrap_params: Option<serde_json::Value>,
+    /// Optional second wrapping of the DEK under the user's recovery key.
+    pub recovery_wrapped_dek: Option<String>,
+    pub recovery_wrap_params: Option<serde_json::Value>,
 }
 
 // ===== Wrapped DEK =====
@@ -126,6 +129,12 @@ pub struct RegisterRequest {
 pub struct WrappedDekResponse {
     pub wrapped_dek: Option<String>,
     pub dek_wrap_params: Option<serde_json::Value>,
+    /// Wrap of the new DEK while a rotation is in progress. The client
+    /// unwraps it and repeats `POST /v2/auth/rotate-dek` to finish.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_wrapped_dek: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_dek_wrap_params: Option<serde_json::Value>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -135,6 +144,40 @@ pub struct UpdateWrappedDekRequest {
     pub dek_wrap_params: serde_json::Value,
 }
 
+// ===== DEK Rotation =====
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RotateDekResponse {
+    pub rows_reencrypted: u64,
+    /// A recovery key enrolled while the rotation ran still wrapped the old
+    /// DEK and was removed; the client should set up a new one.
+    pub recovery_key_cleared: bool,
+}
+
+// ===== Recovery Key =====
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryKeyResponse {
+    pub recovery_wrapped_dek: Option<String>,
+    pub recovery_wrap_params: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRecoveryKeyRequest {
+    pub recovery_wrapped_dek: String,
+    pub recovery_wrap_params: serde_json::Value,
+}
+
+#[derive(Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryKeyForResetRequest {
+    #[validate(length(min = 1))]
+    pub token: String,
+}
+
 // ===== 2FA Complete (after challenge) =====
 
 #[derive(Deserialize, Debug, Validate)]
@@ -170,6 +213,11 @@ pub struct ResetPasswordRequest {
     #[validate(length(min = 8))]
     #[validate(custom(function = "crate::models::user::validate_password_strength"))]
     pub password: String,
+    /// DEK recovered with the recovery key, re-wrapped client-side under the
+    /// new password. When omitted the old wrap is kept and encrypted data
+    /// stays unreadable until the old password is remembered.
+    pub wrapped_dek: Option<String>,
+    pub dek_wrap_params: Option<serde_json::Value>,
 }
 
 // ===== 2FA Management =====
@@ -237,4 +285,8 @@ pub struct ChangePasswordRequest {
     #[validate(length(min = 8))]
     #[validate(custom(function = "crate::models::user::validate_password_strength"))]
     pub new_password: String,
+    /// The DEK re-wrapped under the new password. Required: it is stored in
+    /// the same statement as the new password hash.
+    pub wrapped_dek: Option<String>,
+    pub dek_wrap_params: Option<serde_json::Value>,
 }
@@ -0,0 +1,46 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::dto::common::Date;
+
+// Names and amounts of subscriptions and accounts are encrypted at rest.
+// They come back as base64 ciphertext (`*Enc`) when the session has no
+// DEK, and as plaintext when it has one; never both.
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum UpcomingEventKind {
+    PeriodEnd,
+    PeriodStart,
+    StatementClose,
+    PaymentDue,
+    AllowanceTopUp,
+    SubscriptionCharge,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpcomingEvent {
+    pub date: Date,
+    pub kind: UpcomingEventKind,
+    /// Subscription, account or budget period the event belongs to.
+    pub source_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_enc: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Subscription charge or allowance top-up amount in cents.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_enc: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<i64>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpcomingCalendarResponse {
+    pub from: Date,
+    pub to: Date,
+    /// Chronological; events on the same day are ordered by kind.
+    pub events: Vec<UpcomingEvent>,
+}
@@ -0,0 +1,89 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::dto::accounts::AccountType;
+use crate::dto::common::Date;
+
+// Dashboard responses carry plaintext amounts in minor units. They are
+// computed per request from the session DEK and never stored.
+
+// ===== Current period =====
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentPeriodResponse {
+    pub period_id: Uuid,
+    pub period_name: String,
+    pub start_date: Date,
+    pub end_date: Date,
+    pub spent: i64,
+    pub target: i64,
+    pub days_in_period: i64,
+    pub days_remaining: i64,
+    pub projected_spend: i64,
+    /// Spending per day, one entry per day of the period, oldest first.
+    pub daily_spend: Vec<i64>,
+}
+
+// ===== Net position =====
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountBalanceResponse {
+    pub account_id: Uuid,
+    pub account_type: AccountType,
+    pub balance: i64,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NetPositionResponse {
+    pub total: i64,
+    pub liquid: i64,
+    pub protected: i64,
+    pub debt: i64,
+    /// Change in net position over the current period; null when no
+    /// period covers today.
+    pub difference_this_period: Option<i64>,
+    pub accounts: Vec<AccountBalanceResponse>,
+}
+
+// ===== Cash flow =====
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CashFlowResponse {
+    pub period_id: Uuid,
+    pub inflows: i64,
+    pub outflows: i64,
+    pub net: i64,
+}
+
+// ===== Spending trend =====
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendingTrendItem {
+    pub period_id: Uuid,
+    pub period_name: String,
+    pub start_date: Date,
+    pub end_date: Date,
+    pub total_spend: i64,
+}
+
+pub type SpendingTrendResponse = Vec<SpendingTrendItem>;
+
+// ===== Top vendors =====
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TopVendorItem {
+    pub vendor_id: Uuid,
+    pub vendor_name: String,
+    pub total_spend: i64,
+    pub transaction_count: i64,
+    /// Share of all vendor spend in the period, rounded to a whole percent.
+    pub percentage: i64,
+}
+
+pub type TopVendorsResponse = Vec<TopVendorItem>;
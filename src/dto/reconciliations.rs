@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::dto::common::Date;
+use crate::models::reconciliation::Reconciliation;
+
+#[derive(Serialize, Debug, Copy, Clone, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReconciliationStatus {
+    Open,
+    Finished,
+}
+
+/// A reconciliation with its figures decrypted. Amounts are integer
+/// cents; `difference` is the statement balance minus the cleared
+/// balance, so zero means the account reconciles.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconciliationResponse {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub status: ReconciliationStatus,
+    pub statement_date: Date,
+    pub statement_balance: i64,
+    pub cleared_balance: i64,
+    pub difference: i64,
+    pub created_at: String,
+    pub finished_at: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateReconciliationRequest {
+    pub statement_date: Date,
+    pub statement_balance: i64,
+}
+
+pub fn to_response(reconciliation: &Reconciliation, statement_balance: i64, cleared_balance: i64) -> ReconciliationResponse {
+    ReconciliationResponse {
+        id: reconciliation.id,
+        account_id: reconciliation.account_id,
+        status: if reconciliation.finished_at.is_some() {
+            ReconciliationStatus::Finished
+        } else {
+            ReconciliationStatus::Open
+        },
+        statement_date: Date(reconciliation.statement_date),
+        statement_balance,
+        cleared_balance,
+        difference: statement_balance - cleared_balance,
+        created_at: reconciliation.created_at.to_rfc3339(),
+        finished_at: reconciliation.finished_at.map(|t| t.to_rfc3339()),
+    }
+}
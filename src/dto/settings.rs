@@ -1,9 +1,14 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::dto::common::{BCP_47_REGEX, ISO_4217_REGEX};
+use crate::dto::accounts::{AccountType, TopUpCycle};
+use crate::dto::categories::{CategoryBehavior, CategoryType};
+use crate::dto::common::{BCP_47_REGEX, Date, ISO_4217_REGEX};
+use crate::dto::subscriptions::{BillingCycle, SubscriptionStatus};
+use crate::models::budget_period::{DurationUnit, WeekendAdjustment};
+use crate::models::transaction::{ClearedStatus, LedgerEntryKind};
 
 // ===== Profile =====
 
@@ -141,3 +146,325 @@ pub struct DeleteAccountRequest {
 }
 
 pub type ResetStructureRequest = DeleteAccountRequest;
+
+// ===== Backup =====
+//
+// A backup is the user's data decrypted with the session DEK. Ids are the
+// exporting instance's and only tie the document together: import assigns
+// fresh ids and re-encrypts under the importing user's DEK. Accounts carry
+// the currency code rather than its id, which differs between instances.
+//
+// Version history:
+// 1. initial format
+// 2. tags and transaction tag links
+// 3. cleared status and reconciliations
+
+pub const BACKUP_FORMAT: &str = "piggy-pulse-backup";
+pub const BACKUP_VERSION: u32 = 3;
+/// Oldest version import still accepts. Sections added since default to
+/// empty when absent.
+pub const BACKUP_MIN_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupDocument {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub accounts: Vec<BackupAccount>,
+    pub categories: Vec<BackupCategory>,
+    pub vendors: Vec<BackupVendor>,
+    /// Since version 2.
+    #[serde(default)]
+    pub tags: Vec<BackupTag>,
+    pub targets: Vec<BackupTarget>,
+    pub subscriptions: Vec<BackupSubscription>,
+    pub periods: Vec<BackupPeriod>,
+    pub schedule: Option<BackupSchedule>,
+    /// Since version 3.
+    #[serde(default)]
+    pub reconciliations: Vec<BackupReconciliation>,
+    /// Every ledger row, in `seq` order.
+    pub transactions: Vec<BackupLedgerRow>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupAccount {
+    pub id: Uuid,
+    pub account_type: AccountType,
+    pub currency: String,
+    pub name: String,
+    pub color: String,
+    /// Current balance. The ledger is restored as history and is not
+    /// replayed into it.
+    pub balance: i64,
+    pub is_archived: bool,
+    pub spend_limit: Option<i64>,
+    pub next_transfer_amount: Option<i64>,
+    pub top_up_amount: Option<i64>,
+    pub top_up_cycle: Option<TopUpCycle>,
+    pub top_up_day: Option<i32>,
+    pub statement_close_day: Option<i32>,
+    pub payment_due_day: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupCategory {
+    pub id: Uuid,
+    #[serde(rename = "type")]
+    pub category_type: CategoryType,
+    pub behavior: Option<CategoryBehavior>,
+    pub parent_id: Option<Uuid>,
+    pub is_system: bool,
+    pub is_archived: bool,
+    pub name: String,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupVendor {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub archived: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupTag {
+    pub id: Uuid,
+    pub name: String,
+    pub color: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupTarget {
+    pub id: Uuid,
+    pub category_id: Uuid,
+    pub is_excluded: bool,
+    pub value: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSubscription {
+    pub id: Uuid,
+    pub category_id: Uuid,
+    pub vendor_id: Option<Uuid>,
+    pub name: String,
+    pub billing_amount: i64,
+    pub billing_cycle: BillingCycle,
+    pub billing_day: i16,
+    pub next_charge_date: Date,
+    pub status: SubscriptionStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resume_on: Option<Date>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_post_account_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_posted_on: Option<Date>,
+    pub billing_events: Vec<BackupBillingEvent>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupBillingEvent {
+    pub transaction_id: Option<Uuid>,
+    pub amount: i64,
+    pub date: Date,
+    pub detected: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupPeriod {
+    pub id: Uuid,
+    pub name: String,
+    pub start_date: Date,
+    pub end_date: Date,
+    pub is_auto_generated: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSchedule {
+    pub schedule_type: String,
+    pub start_day: Option<i32>,
+    pub duration_value: Option<i32>,
+    pub duration_unit: Option<DurationUnit>,
+    pub saturday_adjustment: Option<WeekendAdjustment>,
+    pub sunday_adjustment: Option<WeekendAdjustment>,
+    pub name_pattern: Option<String>,
+    pub generate_ahead: Option<i32>,
+    pub recurrence_method: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupReconciliation {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub statement_date: Date,
+    pub statement_balance: i64,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// One ledger row. Rows sharing an `id` are one logical transaction; the
+/// amounts are signed deltas (reversals and voids are negative) whose sum
+/// is the transaction's current amount.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupLedgerRow {
+    pub id: Uuid,
+    pub seq: i64,
+    pub occurred_at: Date,
+    pub category_id: Option<Uuid>,
+    pub from_account_id: Uuid,
+    pub to_account_id: Option<Uuid>,
+    pub vendor_id: Option<Uuid>,
+    pub amount: i64,
+    pub description: String,
+    pub kind: LedgerEntryKind,
+    pub created_at: DateTime<Utc>,
+    /// Split lines of this row, in order; absent for an unsplit row.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub splits: Vec<BackupSplitLine>,
+    /// Tags of the logical transaction. Links belong to the transaction,
+    /// not to a row, so they are carried on its first row only; absent
+    /// when untagged.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag_ids: Vec<Uuid>,
+    /// Cleared status of the logical transaction, on its first row like
+    /// `tag_ids`; absent while uncleared.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cleared_status: Option<ClearedStatus>,
+    /// Reconciliation that reconciled the transaction, on its first row.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reconciliation_id: Option<Uuid>,
+}
+
+/// One split line of a ledger row. Lines carry signed amounts like their
+/// row and sum to the row's `amount`.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSplitLine {
+    pub category_id: Uuid,
+    pub amount: i64,
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportCounts {
+    pub accounts: usize,
+    pub categories: usize,
+    pub vendors: usize,
+    pub tags: usize,
+    pub targets: usize,
+    pub subscriptions: usize,
+    pub periods: usize,
+    pub schedule: bool,
+    pub reconciliations: usize,
+    /// Logical transactions.
+    pub transactions: usize,
+    pub ledger_rows: usize,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportDataResponse {
+    pub imported: ImportCounts,
+}
+
+// ===== CSV Export =====
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvColumn {
+    Date,
+    Description,
+    Amount,
+    Currency,
+    Category,
+    Type,
+    FromAccount,
+    ToAccount,
+    Vendor,
+}
+
+impl CsvColumn {
+    /// Every column, in the default order.
+    pub const ALL: [CsvColumn; 9] = [
+        CsvColumn::Date,
+        CsvColumn::Description,
+        CsvColumn::Amount,
+        CsvColumn::Currency,
+        CsvColumn::Category,
+        CsvColumn::Type,
+        CsvColumn::FromAccount,
+        CsvColumn::ToAccount,
+        CsvColumn::Vendor,
+    ];
+
+    /// Name used in the `columns` parameter and the header row.
+    pub fn name(self) -> &'static str {
+        match self {
+            CsvColumn::Date => "date",
+            CsvColumn::Description => "description",
+            CsvColumn::Amount => "amount",
+            CsvColumn::Currency => "currency",
+            CsvColumn::Category => "category",
+            CsvColumn::Type => "type",
+            CsvColumn::FromAccount => "from_account",
+            CsvColumn::ToAccount => "to_account",
+            CsvColumn::Vendor => "vendor",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CsvDelimiter {
+    #[default]
+    Comma,
+    Semicolon,
+    Tab,
+}
+
+impl CsvDelimiter {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "comma" => Some(CsvDelimiter::Comma),
+            "semicolon" => Some(CsvDelimiter::Semicolon),
+            "tab" => Some(CsvDelimiter::Tab),
+            _ => None,
+        }
+    }
+
+    pub fn as_char(self) -> char {
+        match self {
+            CsvDelimiter::Comma => ',',
+            CsvDelimiter::Semicolon => ';',
+            CsvDelimiter::Tab => '\t',
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CsvExportOptions {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub account_id: Option<Uuid>,
+    pub columns: Vec<CsvColumn>,
+    pub delimiter: CsvDelimiter,
+}
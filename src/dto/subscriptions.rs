@@ -39,7 +39,14 @@ pub struct EncryptedSubscriptionResponse {
     pub billing_day: i16,
     pub next_charge_date: Date,
     pub status: SubscriptionStatus,
+    /// Date a paused subscription becomes active again, if one was set.
+    pub resume_on: Option<Date>,
     pub cancelled_at: Option<DateTime<Utc>>,
+    /// Account due charges are posted to on unlock; null when auto-post
+    /// is off.
+    pub auto_post_account_id: Option<Uuid>,
+    /// Date of the most recent charge posted automatically.
+    pub last_posted_on: Option<Date>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub name_enc: String,
@@ -48,6 +55,32 @@ pub struct EncryptedSubscriptionResponse {
 
 pub type SubscriptionListResponse = Vec<EncryptedSubscriptionResponse>;
 
+/// One charge billed to a subscription.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BillingEventResponse {
+    pub id: Uuid,
+    pub subscription_id: Uuid,
+    /// Logical transaction the charge was paid with; cleared if that
+    /// transaction is purged.
+    pub transaction_id: Option<Uuid>,
+    pub billing_date: Date,
+    pub amount_enc: String,
+    /// Whether the charge was matched automatically rather than linked
+    /// by the user.
+    pub detected: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionDetailResponse {
+    #[serde(flatten)]
+    pub subscription: EncryptedSubscriptionResponse,
+    /// Newest first.
+    pub billing_history: Vec<BillingEventResponse>,
+}
+
 #[derive(Deserialize, Debug, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateSubscriptionRequest {
@@ -61,6 +94,9 @@ pub struct CreateSubscriptionRequest {
     #[validate(range(min = 1, max = 31))]
     pub billing_day: i16,
     pub next_charge_date: Date,
+    /// Opt in to auto-posting: each charge is posted to this account as a
+    /// transaction when the user next unlocks a session.
+    pub auto_post_account_id: Option<Uuid>,
 }
 
 pub type UpdateSubscriptionRequest = CreateSubscriptionRequest;
@@ -71,6 +107,37 @@ pub struct CancelSubscriptionRequest {
     pub cancellation_date: Option<Date>,
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PauseSubscriptionRequest {
+    /// Resume automatically on this date; without it the subscription
+    /// stays paused until resumed by hand.
+    pub resume_on: Option<Date>,
+}
+
+/// Body of `POST /v2/auth/unlock`: the charges posted for auto-post
+/// subscriptions that fell due since they were last posted.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoPostResponse {
+    pub posted_charges: Vec<BillingEventResponse>,
+}
+
+/// A charge billed to a subscription after it was cancelled.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionAlertResponse {
+    #[serde(flatten)]
+    pub event: BillingEventResponse,
+    pub cancelled_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkBillingTransactionRequest {
+    pub transaction_id: Uuid,
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn to_response(
     id: Uuid,
@@ -80,7 +147,10 @@ pub fn to_response(
     billing_day: i16,
     next_charge_date: chrono::NaiveDate,
     status: SubscriptionStatus,
+    resume_on: Option<chrono::NaiveDate>,
     cancelled_at: Option<DateTime<Utc>>,
+    auto_post_account_id: Option<Uuid>,
+    last_posted_on: Option<chrono::NaiveDate>,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
     name_enc: &[u8],
@@ -94,10 +164,33 @@ pub fn to_response(
         billing_day,
         next_charge_date: Date(next_charge_date),
         status,
+        resume_on: resume_on.map(Date),
         cancelled_at,
+        auto_post_account_id,
+        last_posted_on: last_posted_on.map(Date),
         created_at,
         updated_at,
         name_enc: b64(name_enc),
         billing_amount_enc: b64(billing_amount_enc),
     }
 }
+
+pub fn to_billing_event_response(
+    id: Uuid,
+    subscription_id: Uuid,
+    transaction_id: Option<Uuid>,
+    date: chrono::NaiveDate,
+    amount_enc: &[u8],
+    detected: bool,
+    created_at: DateTime<Utc>,
+) -> BillingEventResponse {
+    BillingEventResponse {
+        id,
+        subscription_id,
+        transaction_id,
+        billing_date: Date(date),
+        amount_enc: b64(amount_enc),
+        detected,
+        created_at,
+    }
+}
@@ -0,0 +1,41 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as B64;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::dto::common::PaginatedResponse;
+use crate::models::tag::Tag;
+
+fn b64(bytes: &[u8]) -> String {
+    B64.encode(bytes)
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedTagResponse {
+    pub id: Uuid,
+    pub name_enc: String,
+    pub color_enc: String,
+}
+
+pub type TagListResponse = PaginatedResponse<EncryptedTagResponse>;
+
+#[derive(Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTagRequest {
+    #[validate(length(min = 1))]
+    pub name: String,
+    #[validate(length(min = 1))]
+    pub color: String,
+}
+
+pub type UpdateTagRequest = CreateTagRequest;
+
+pub fn to_encrypted_response(tag: &Tag) -> EncryptedTagResponse {
+    EncryptedTagResponse {
+        id: tag.id,
+        name_enc: b64(&tag.name_enc),
+        color_enc: b64(&tag.color_enc),
+    }
+}
@@ -5,7 +5,8 @@ use base64::engine::general_purpose::STANDARD as BASE64;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::dto::common::Date;
+use crate::dto::common::{Date, PaginatedResponse};
+use crate::models::transaction::{ClearedStatus, LedgerEntryKind};
 
 // ─────────────────────────────────────────────────────────────────────
 // Encrypted transaction response
@@ -37,6 +38,45 @@ pub struct EncryptedTransactionResponse {
     pub amount_enc: String,
     /// Base64-encoded AES-256-GCM envelope.
     pub description_enc: String,
+    /// Split lines of this row, in order. Empty for an unsplit
+    /// transaction.
+    pub splits: Vec<EncryptedSplitResponse>,
+    /// Tags on the logical transaction. They belong to the id, not the
+    /// row, so corrections keep them.
+    pub tag_ids: Vec<Uuid>,
+    /// Statement status of the logical transaction.
+    pub cleared_status: ClearedStatus,
+    /// Whether this is a balance adjustment. Its amount is the signed
+    /// change to the account's balance rather than a category amount.
+    pub is_adjustment: bool,
+    /// Whether the transaction is billed to a subscription with a date
+    /// after that subscription was cancelled.
+    pub charged_after_cancellation: bool,
+}
+
+pub type TransactionListResponse = PaginatedResponse<EncryptedTransactionResponse>;
+
+/// One split line. The envelopes are bound to the line's own `id`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedSplitResponse {
+    pub id: Uuid,
+    pub category_id: Uuid,
+    /// Base64-encoded AES-256-GCM envelope.
+    pub amount_enc: String,
+    /// Base64-encoded AES-256-GCM envelope, absent when the line has no memo.
+    pub memo_enc: Option<String>,
+}
+
+impl From<crate::database::transaction::LedgerSplit> for EncryptedSplitResponse {
+    fn from(s: crate::database::transaction::LedgerSplit) -> Self {
+        Self {
+            id: s.id,
+            category_id: s.category_id,
+            amount_enc: BASE64.encode(&s.amount_enc),
+            memo_enc: s.memo_enc.as_deref().map(|m| BASE64.encode(m)),
+        }
+    }
 }
 
 impl From<crate::database::transaction::LedgerInsertResult> for EncryptedTransactionResponse {
@@ -52,6 +92,80 @@ impl From<crate::database::transaction::LedgerInsertResult> for EncryptedTransac
             vendor_id: r.vendor_id,
             amount_enc: BASE64.encode(&r.amount_enc),
             description_enc: BASE64.encode(&r.description_enc),
+            splits: r.splits.into_iter().map(EncryptedSplitResponse::from).collect(),
+            tag_ids: r.tag_ids,
+            cleared_status: r.cleared_status,
+            is_adjustment: r.is_adjustment,
+            charged_after_cancellation: r.charged_after_cancellation,
+        }
+    }
+}
+
+/// One ledger row in a transaction's revision history. Rows come oldest
+/// first; `kind` says what each did to the running sum, which the client
+/// reads by decrypting `amountEnc`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionHistoryEntry {
+    pub id: Uuid,
+    pub seq: i64,
+    pub kind: LedgerEntryKind,
+    /// ISO-8601 timestamp of the write that added this row.
+    pub created_at: String,
+    pub date: Date,
+    pub from_account_id: Uuid,
+    pub to_account_id: Option<Uuid>,
+    pub category_id: Option<Uuid>,
+    pub vendor_id: Option<Uuid>,
+    /// Base64-encoded AES-256-GCM envelope of the signed delta.
+    pub amount_enc: String,
+    /// Base64-encoded AES-256-GCM envelope.
+    pub description_enc: String,
+}
+
+impl From<crate::database::transaction::LedgerHistoryRow> for TransactionHistoryEntry {
+    fn from(r: crate::database::transaction::LedgerHistoryRow) -> Self {
+        Self {
+            id: r.id,
+            seq: r.seq,
+            kind: r.entry_kind,
+            created_at: r.created_at.to_rfc3339(),
+            date: Date(r.occurred_at),
+            from_account_id: r.from_account_id,
+            to_account_id: r.to_account_id,
+            category_id: r.category_id,
+            vendor_id: r.vendor_id,
+            amount_enc: BASE64.encode(&r.amount_enc),
+            description_enc: BASE64.encode(&r.description_enc),
+        }
+    }
+}
+
+/// A file attached to a transaction. The content itself is fetched from
+/// the download endpoint, decrypted server-side with the session DEK.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedAttachmentResponse {
+    pub id: Uuid,
+    pub transaction_id: Uuid,
+    /// Base64-encoded AES-256-GCM envelope.
+    pub file_name_enc: String,
+    /// Base64-encoded AES-256-GCM envelope.
+    pub content_type_enc: String,
+    pub size_bytes: i64,
+    /// ISO-8601 timestamp of the upload.
+    pub created_at: String,
+}
+
+impl From<crate::models::attachment::Attachment> for EncryptedAttachmentResponse {
+    fn from(a: crate::models::attachment::Attachment) -> Self {
+        Self {
+            id: a.id,
+            transaction_id: a.transaction_id,
+            file_name_enc: BASE64.encode(&a.file_name_enc),
+            content_type_enc: BASE64.encode(&a.content_type_enc),
+            size_bytes: a.size_bytes,
+            created_at: a.created_at.to_rfc3339(),
         }
     }
 }
@@ -77,6 +191,12 @@ pub enum CreateTransactionRequest {
         category_id: Uuid,
         #[serde(rename = "vendorId")]
         vendor_id: Option<Uuid>,
+        #[serde(default)]
+        splits: Vec<SplitLineRequest>,
+        #[serde(rename = "tagIds", default)]
+        tag_ids: Option<Vec<Uuid>>,
+        #[serde(rename = "subscriptionId", default)]
+        subscription_id: Option<Uuid>,
     },
     Transfer {
         date: Date,
@@ -90,7 +210,21 @@ pub enum CreateTransactionRequest {
         vendor_id: Option<Uuid>,
         #[serde(rename = "toAccountId")]
         to_account_id: Uuid,
+        #[serde(rename = "tagIds", default)]
+        tag_ids: Option<Vec<Uuid>>,
+        #[serde(rename = "subscriptionId", default)]
+        subscription_id: Option<Uuid>,
     },
 }
 
+/// One split line of a regular transaction. Every line's category must
+/// share the type of the transaction's `categoryId`.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitLineRequest {
+    pub category_id: Uuid,
+    pub amount: i64,
+    pub memo: Option<String>,
+}
+
 pub type UpdateTransactionRequest = CreateTransactionRequest;
@@ -12,8 +12,8 @@ mod routes;
 mod service;
 pub mod session_dek;
 
-pub use config::Config;
-pub use cron_tasks::{GeneratePeriodsResult, cleanup_expired_tokens, generate_periods};
+pub use config::{AttachmentBackend, Config, DekStoreBackend};
+pub use cron_tasks::{AdvanceSubscriptionsResult, GeneratePeriodsResult, advance_subscriptions, cleanup_expired_tokens, generate_periods};
 
 use crate::db::stage_db;
 use crate::middleware::RequestLogger;
@@ -143,6 +143,25 @@ fn ensure_two_factor_encryption_key(two_factor_config: &config::TwoFactorConfig)
     }
 }
 
+fn ensure_dek_store_sealing_key(dek_store_config: &config::DekStoreConfig) {
+    // The in-process store never serializes DEKs, so only the networked backend needs a real key.
+    let profile = std::env::var("ROCKET_PROFILE").unwrap_or_else(|_| "debug".to_string());
+    if profile == "debug" || dek_store_config.backend != config::DekStoreBackend::Redis {
+        return;
+    }
+
+    if dek_store_config.sealing_key_is_default() {
+        panic!(
+            "PIGGY_PULSE_DEK_STORE__SEALING_KEY must be set for profile '{}' and cannot use the insecure default. Generate one with: openssl rand -hex 32",
+            profile
+        );
+    }
+
+    if let Err(err) = dek_store_config.parse_sealing_key() {
+        panic!("Invalid PIGGY_PULSE_DEK_STORE__SEALING_KEY for profile '{}': {}", profile, err);
+    }
+}
+
 fn ensure_cookie_secure(session_config: &config::SessionConfig) {
     let profile = std::env::var("ROCKET_PROFILE").unwrap_or_else(|_| "debug".to_string());
     if profile == "debug" {
@@ -259,6 +278,7 @@ fn mount_v2_routes(mut rocket: Rocket<Build>, base_path: &str) -> Rocket<Build>
     rocket = rocket.mount(join_base_path(base_path, "accounts"), app_routes::v2::accounts::routes());
     rocket = rocket.mount(join_base_path(base_path, "categories"), app_routes::v2::categories::routes());
     rocket = rocket.mount(join_base_path(base_path, "vendors"), app_routes::v2::vendors::routes());
+    rocket = rocket.mount(join_base_path(base_path, "tags"), app_routes::v2::tags::routes());
     rocket = rocket.mount(join_base_path(base_path, "periods"), app_routes::v2::periods::routes());
     rocket = rocket.mount(join_base_path(base_path, "targets"), app_routes::v2::targets::routes());
     rocket = rocket.mount(join_base_path(base_path, "transactions"), app_routes::v2::transactions::routes());
@@ -266,6 +286,8 @@ fn mount_v2_routes(mut rocket: Rocket<Build>, base_path: &str) -> Rocket<Build>
     rocket = rocket.mount(join_base_path(base_path, "settings"), app_routes::v2::settings::routes());
     rocket = rocket.mount(join_base_path(base_path, "settings/sessions"), app_routes::v2::settings::session_routes());
     // Dashboard, reference data, system
+    rocket = rocket.mount(join_base_path(base_path, "dashboard"), app_routes::v2::dashboard::routes());
+    rocket = rocket.mount(join_base_path(base_path, "calendar"), app_routes::v2::calendar::routes());
     rocket = rocket.mount(join_base_path(base_path, "subscriptions"), app_routes::v2::subscriptions::routes());
     rocket = rocket.mount(join_base_path(base_path, "currencies"), app_routes::v2::currencies::routes());
     rocket = rocket.mount(join_base_path(base_path, "onboarding"), app_routes::v2::onboarding::routes());
@@ -281,17 +303,27 @@ pub fn build_rocket(config: Config) -> Rocket<Build> {
     init_tracing(&config.logging.level, config.logging.json_format);
     ensure_rocket_secret_key();
     ensure_two_factor_encryption_key(&config.two_factor);
+    ensure_dek_store_sealing_key(&config.dek_store);
     ensure_cookie_secure(&config.session);
 
     let cors = build_cors(&config.cors).to_cors().expect("Failed to create CORS fairing");
+    let dek_store = session_dek::build_dek_store(&config.dek_store).expect("Failed to create session DEK store");
 
     let base_path = normalize_base_path(&config.api.base_path);
 
-    let mut rocket = rocket::build()
+    // Uploads are streamed to a temp file by the form parser, which caps
+    // them at the `file` limit.
+    let attachment_limit = config.attachments.max_size_bytes;
+    let figment = rocket::Config::figment()
+        .merge(("limits.file", attachment_limit))
+        .merge(("limits.data-form", attachment_limit + 64 * 1024));
+
+    let mut rocket = rocket::custom(figment)
         .manage(config.clone())
-        .manage(session_dek::SessionDekStore::new())
+        .manage(dek_store)
         .attach(cors)
         .attach(RequestLogger)
+        .attach(session_dek::stage_dek_store_eviction())
         .attach(stage_db(config.database, config.logging.slow_query_ms));
 
     rocket = mount_v2_routes(rocket, &base_path);
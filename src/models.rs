@@ -1,5 +1,6 @@
 pub mod account;
 pub mod api_token;
+pub mod attachment;
 pub mod audit;
 pub mod budget_period;
 pub mod category;
@@ -7,8 +8,10 @@ pub mod currency;
 pub mod pagination;
 pub mod password_reset;
 pub mod rate_limit;
+pub mod reconciliation;
 pub mod session;
 pub mod settings;
+pub mod tag;
 pub mod transaction;
 pub mod two_factor;
 pub mod user;
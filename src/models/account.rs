@@ -29,7 +29,7 @@ pub struct Account {
     pub next_transfer_amount_enc: Option<Vec<u8>>,
     pub top_up_amount_enc: Option<Vec<u8>>,
     pub top_up_cycle: Option<String>,
-    pub top_up_day: Option<i32>,
-    pub statement_close_day: Option<i32>,
-    pub payment_due_day: Option<i32>,
+    pub top_up_day: Option<i16>,
+    pub statement_close_day: Option<i16>,
+    pub payment_due_day: Option<i16>,
 }
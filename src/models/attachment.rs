@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Raw attachment row. Size and timestamps stay plaintext; the file name,
+/// content type and the sealed content key are encrypted with the DEK.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Attachment {
+    pub id: Uuid,
+    pub transaction_id: Uuid,
+    pub file_name_enc: Vec<u8>,
+    pub content_type_enc: Vec<u8>,
+    pub size_bytes: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Plaintext metadata of an upload, validated by the service layer.
+#[derive(Debug)]
+pub struct NewAttachment<'a> {
+    pub file_name: &'a str,
+    pub content_type: &'a str,
+    pub size_bytes: u64,
+}
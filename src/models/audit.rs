@@ -14,6 +14,8 @@ pub mod audit_events {
 
     // Account events
     pub const PASSWORD_CHANGED: &str = "password_changed";
+    pub const DEK_ROTATED: &str = "dek_rotated";
+    pub const LEDGER_REPAIRED: &str = "ledger_repaired";
 
     // Password reset events
     pub const PASSWORD_RESET_REQUESTED: &str = "password_reset_requested";
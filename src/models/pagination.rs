@@ -56,10 +56,14 @@ pub struct TransactionFilters {
     pub category_ids: Vec<Uuid>,
     pub direction: Option<TransactionDirection>,
     pub vendor_ids: Vec<Uuid>,
+    /// Transactions carrying any of these tags.
+    pub tag_ids: Vec<Uuid>,
     pub date_from: Option<NaiveDate>,
     pub date_to: Option<NaiveDate>,
     /// Free-text search: matches against description (ILIKE) or amount (LIKE).
     pub search: Option<String>,
+    /// Only transactions without a category.
+    pub uncategorized: bool,
 }
 
 impl TransactionFilters {
@@ -69,9 +73,11 @@ impl TransactionFilters {
             && self.category_ids.is_empty()
             && self.direction.is_none()
             && self.vendor_ids.is_empty()
+            && self.tag_ids.is_empty()
             && self.date_from.is_none()
             && self.date_to.is_none()
             && self.search.is_none()
+            && !self.uncategorized
     }
 }
 
@@ -117,4 +123,22 @@ mod tests {
         };
         assert!(!f.is_empty());
     }
+
+    #[test]
+    fn test_transaction_filters_is_empty_with_tag() {
+        let f = TransactionFilters {
+            tag_ids: vec![Uuid::new_v4()],
+            ..Default::default()
+        };
+        assert!(!f.is_empty());
+    }
+
+    #[test]
+    fn test_transaction_filters_is_empty_with_uncategorized() {
+        let f = TransactionFilters {
+            uncategorized: true,
+            ..Default::default()
+        };
+        assert!(!f.is_empty());
+    }
 }
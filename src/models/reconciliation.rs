@@ -0,0 +1,14 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+/// Raw reconciliation row. The statement balance is encrypted; an open
+/// reconciliation has no `finished_at`.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Reconciliation {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub statement_date: NaiveDate,
+    pub statement_balance_enc: Vec<u8>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,9 @@
+use uuid::Uuid;
+
+/// Raw tag row. Both label fields (name, color) are encrypted.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Tag {
+    pub id: Uuid,
+    pub name_enc: Vec<u8>,
+    pub color_enc: Vec<u8>,
+}
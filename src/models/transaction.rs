@@ -1,5 +1,5 @@
 use chrono::NaiveDate;
-use rocket::serde::Deserialize;
+use rocket::serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
@@ -17,4 +17,62 @@ pub struct TransactionRequest {
     pub from_account_id: Uuid,
     pub to_account_id: Option<Uuid>,
     pub vendor_id: Option<Uuid>,
+    /// Allocation of `amount` across categories. Empty for an unsplit
+    /// transaction; otherwise the line amounts sum to `amount`.
+    #[serde(default)]
+    pub splits: Vec<TransactionSplit>,
+    /// Tags to set on the logical transaction. `None` leaves the current
+    /// tags untouched; `Some` replaces them.
+    #[serde(default)]
+    pub tag_ids: Option<Vec<Uuid>>,
+    /// Subscription this transaction pays; a billing event is recorded
+    /// for it.
+    #[serde(default)]
+    pub subscription_id: Option<Uuid>,
+}
+
+/// Most split lines a transaction may have.
+pub const MAX_SPLIT_LINES: usize = 100;
+
+/// One plaintext split line. On compensating rows `amount` is the
+/// negation of the line being cancelled.
+#[derive(Deserialize, Debug, Clone)]
+pub struct TransactionSplit {
+    pub category_id: Uuid,
+    pub amount: i64,
+    pub memo: Option<String>,
+}
+
+/// What a ledger row does to its logical transaction. Recorded on insert
+/// because the server cannot read the sign of an encrypted amount.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum LedgerEntryKind {
+    /// First row of a logical transaction.
+    Original,
+    /// Brings the running sum to zero ahead of a correction.
+    Reversal,
+    /// Carries the edited metadata and amount.
+    Correction,
+    /// Compensating row written by a delete.
+    Void,
+    /// Re-applies the amount a void cancelled.
+    Restore,
+    /// Uncategorized row that moves its account's balance by the signed
+    /// amount, written when the balance is set by hand.
+    Adjustment,
+}
+
+/// Where a logical transaction stands against the bank statement.
+/// `Reconciled` is only reached by finishing a reconciliation, and locks
+/// the transaction against correction and deletion until it is unlocked.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ClearedStatus {
+    #[default]
+    Uncleared,
+    Cleared,
+    Reconciled,
 }
@@ -0,0 +1,42 @@
+use rocket::State;
+use rocket::get;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::CurrentUser;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::accounts::{AccountBalanceHistoryResponse, AccountsBalanceHistoryResponse};
+use crate::error::app_error::AppError;
+use crate::service::account::{AccountService, parse_history_range};
+
+#[get("/<id>/balance-history?<from>&<to>")]
+pub async fn get_balance_history(
+    pool: &State<PgPool>,
+    user: CurrentUser,
+    dek: Dek,
+    id: &str,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Json<AccountBalanceHistoryResponse>, AppError> {
+    let uuid = Uuid::parse_str(id).map_err(|e| AppError::uuid("Invalid account id", e))?;
+    let (from, to) = parse_history_range(from, to)?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = AccountService::new(&repo);
+    Ok(Json(service.balance_history(&uuid, &user.id, from, to, &dek).await?))
+}
+
+#[get("/balance-history?<from>&<to>")]
+pub async fn list_balance_histories(
+    pool: &State<PgPool>,
+    user: CurrentUser,
+    dek: Dek,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Json<AccountsBalanceHistoryResponse>, AppError> {
+    let (from, to) = parse_history_range(from, to)?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = AccountService::new(&repo);
+    Ok(Json(service.balance_histories(&user.id, from, to, &dek).await?))
+}
@@ -1,12 +1,15 @@
 mod adjust_balance;
 mod archive;
+mod balance_history;
 mod create;
 mod delete;
 mod get;
 mod list;
 mod options;
+mod reconciliations;
 mod unarchive;
 mod update;
+mod verify;
 
 pub fn routes() -> Vec<rocket::Route> {
     rocket::routes![
@@ -19,5 +22,13 @@ pub fn routes() -> Vec<rocket::Route> {
         archive::archive_account,
         unarchive::unarchive_account,
         adjust_balance::adjust_balance,
+        balance_history::get_balance_history,
+        balance_history::list_balance_histories,
+        reconciliations::start_reconciliation,
+        reconciliations::list_reconciliations,
+        reconciliations::get_reconciliation,
+        reconciliations::finish_reconciliation,
+        reconciliations::cancel_reconciliation,
+        verify::verify_ledger,
     ]
 }
@@ -0,0 +1,78 @@
+use rocket::State;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{delete, get, post};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::CurrentUser;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::reconciliations::{CreateReconciliationRequest, ReconciliationResponse};
+use crate::error::app_error::AppError;
+use crate::service::reconciliation::ReconciliationService;
+
+fn parse_ids(id: &str, reconciliation_id: &str) -> Result<(Uuid, Uuid), AppError> {
+    let account_id = Uuid::parse_str(id).map_err(|e| AppError::uuid("Invalid account id", e))?;
+    let reconciliation_id = Uuid::parse_str(reconciliation_id).map_err(|e| AppError::uuid("Invalid reconciliation id", e))?;
+    Ok((account_id, reconciliation_id))
+}
+
+#[post("/<id>/reconciliations", data = "<payload>")]
+pub async fn start_reconciliation(
+    pool: &State<PgPool>,
+    user: CurrentUser,
+    dek: Dek,
+    id: &str,
+    payload: Json<CreateReconciliationRequest>,
+) -> Result<(Status, Json<ReconciliationResponse>), AppError> {
+    let account_id = Uuid::parse_str(id).map_err(|e| AppError::uuid("Invalid account id", e))?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = ReconciliationService::new(&repo);
+    Ok((Status::Created, Json(service.start(&account_id, &payload, &user.id, &dek).await?)))
+}
+
+#[get("/<id>/reconciliations")]
+pub async fn list_reconciliations(pool: &State<PgPool>, user: CurrentUser, dek: Dek, id: &str) -> Result<Json<Vec<ReconciliationResponse>>, AppError> {
+    let account_id = Uuid::parse_str(id).map_err(|e| AppError::uuid("Invalid account id", e))?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = ReconciliationService::new(&repo);
+    Ok(Json(service.list(&account_id, &user.id, &dek).await?))
+}
+
+#[get("/<id>/reconciliations/<reconciliation_id>")]
+pub async fn get_reconciliation(
+    pool: &State<PgPool>,
+    user: CurrentUser,
+    dek: Dek,
+    id: &str,
+    reconciliation_id: &str,
+) -> Result<Json<ReconciliationResponse>, AppError> {
+    let (account_id, reconciliation_id) = parse_ids(id, reconciliation_id)?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = ReconciliationService::new(&repo);
+    Ok(Json(service.get(&reconciliation_id, &account_id, &user.id, &dek).await?))
+}
+
+#[post("/<id>/reconciliations/<reconciliation_id>/finish")]
+pub async fn finish_reconciliation(
+    pool: &State<PgPool>,
+    user: CurrentUser,
+    dek: Dek,
+    id: &str,
+    reconciliation_id: &str,
+) -> Result<Json<ReconciliationResponse>, AppError> {
+    let (account_id, reconciliation_id) = parse_ids(id, reconciliation_id)?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = ReconciliationService::new(&repo);
+    Ok(Json(service.finish(&reconciliation_id, &account_id, &user.id, &dek).await?))
+}
+
+#[delete("/<id>/reconciliations/<reconciliation_id>")]
+pub async fn cancel_reconciliation(pool: &State<PgPool>, user: CurrentUser, id: &str, reconciliation_id: &str) -> Result<Status, AppError> {
+    let (account_id, reconciliation_id) = parse_ids(id, reconciliation_id)?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = ReconciliationService::new(&repo);
+    service.cancel(&reconciliation_id, &account_id, &user.id).await?;
+    Ok(Status::NoContent)
+}
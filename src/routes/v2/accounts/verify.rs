@@ -0,0 +1,26 @@
+use rocket::State;
+use rocket::post;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+
+use crate::auth::CurrentUser;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::accounts::{LedgerVerificationResponse, VerifyLedgerRequest};
+use crate::error::app_error::AppError;
+use crate::middleware::{ClientIp, UserAgent};
+use crate::service::account::AccountService;
+
+#[post("/verify", data = "<payload>")]
+pub async fn verify_ledger(
+    pool: &State<PgPool>,
+    user: CurrentUser,
+    dek: Dek,
+    user_agent: UserAgent,
+    client_ip: ClientIp,
+    payload: Json<VerifyLedgerRequest>,
+) -> Result<Json<LedgerVerificationResponse>, AppError> {
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = AccountService::new(&repo);
+    Ok(Json(service.verify_ledger(&payload, &user.id, &dek, client_ip.0, user_agent.0).await?))
+}
@@ -9,7 +9,7 @@ use crate::database::postgres_repository::PostgresRepository;
 use crate::error::app_error::AppError;
 use crate::middleware::{ClientIp, UserAgent};
 use crate::service::auth::AuthService;
-use crate::session_dek::SessionDekStore;
+use crate::session_dek::DekStore;
 
 #[post("/logout")]
 pub async fn logout(
@@ -18,7 +18,7 @@ pub async fn logout(
     cookies: &rocket::http::CookieJar<'_>,
     user_agent: UserAgent,
     client_ip: ClientIp,
-    dek_store: &State<SessionDekStore>,
+    dek_store: &State<DekStore>,
     user: CurrentUser,
 ) -> Result<Status, AppError> {
     let repo = PostgresRepository { pool: pool.inner().clone() };
@@ -34,7 +34,11 @@ pub async fn logout(
     auth.logout(&user.id, user.session_id, client_ip.0.clone(), user_agent.0.clone()).await?;
 
     if let Some(principal_id) = user.principal_id() {
-        dek_store.remove(&principal_id).await;
+        // The principal is already gone, so a leftover entry is unreachable
+        // and expires with its TTL; don't fail the logout over it.
+        if let Err(e) = dek_store.remove(&principal_id).await {
+            tracing::warn!(error = %e, "Failed to drop session DEK on logout");
+        }
     }
 
     cookies.remove_private(Cookie::build("user").build());
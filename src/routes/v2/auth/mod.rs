@@ -3,9 +3,11 @@ pub(crate) mod login;
 mod logout;
 mod me;
 mod password;
+mod recovery_key;
 mod refresh;
 mod register;
 mod reset_password;
+mod rotate_dek;
 mod two_factor;
 mod unlock;
 mod wrapped_dek;
@@ -21,8 +23,13 @@ pub fn routes() -> Vec<rocket::Route> {
         forgot_password::forgot_password,
         reset_password::reset_password,
         unlock::unlock,
+        rotate_dek::rotate_dek,
         wrapped_dek::get_wrapped_dek,
         wrapped_dek::update_wrapped_dek,
+        recovery_key::get_recovery_key,
+        recovery_key::update_recovery_key,
+        recovery_key::delete_recovery_key,
+        recovery_key::get_recovery_key_for_reset,
     ]
 }
 
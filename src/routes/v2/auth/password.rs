@@ -11,7 +11,9 @@ use crate::database::postgres_repository::PostgresRepository;
 use crate::dto::auth::ChangePasswordRequest;
 use crate::error::app_error::AppError;
 use crate::middleware::{ClientIp, UserAgent};
+use crate::routes::v2::auth::recovery_key::decode_optional_wrap;
 use crate::service::auth::AuthService;
+use crate::session_dek::DekStore;
 
 #[put("/password", data = "<payload>")]
 pub async fn change_password(
@@ -19,19 +21,31 @@ pub async fn change_password(
     config: &State<Config>,
     user_agent: UserAgent,
     client_ip: ClientIp,
+    dek_store: &State<DekStore>,
     user: CurrentUser,
     payload: Json<ChangePasswordRequest>,
 ) -> Result<Status, AppError> {
     payload.validate()?;
 
+    // The stored wrap must always match the password, so a change without
+    // the re-wrapped DEK is rejected outright.
+    let (wrapped_dek, dek_wrap_params) = decode_optional_wrap(payload.wrapped_dek.as_deref(), payload.dek_wrap_params.as_ref(), "wrappedDek")?
+        .ok_or_else(|| AppError::BadRequest("wrappedDek and dekWrapParams are required".to_string()))?;
+    if wrapped_dek.is_empty() {
+        return Err(AppError::BadRequest("wrappedDek must not be empty".to_string()));
+    }
+
     let repo = PostgresRepository { pool: pool.inner().clone() };
     let auth = AuthService::new(&repo, config);
 
     auth.change_password(
+        dek_store.inner().as_ref(),
         &user.id,
         user.session_id,
         &payload.current_password,
         &payload.new_password,
+        &wrapped_dek,
+        &dek_wrap_params,
         client_ip.0.clone(),
         user_agent.0.clone(),
     )
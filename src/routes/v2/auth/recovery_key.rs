@@ -0,0 +1,79 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as B64;
+use rocket::State;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{delete, get, post, put};
+use sqlx::PgPool;
+use validator::Validate;
+
+use crate::auth::CurrentUser;
+use crate::config::Config;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::auth::{RecoveryKeyForResetRequest, RecoveryKeyResponse, UpdateRecoveryKeyRequest};
+use crate::error::app_error::AppError;
+use crate::service::auth::AuthService;
+
+/// Decode an optional `(wrapped, params)` pair from a request body. Both
+/// halves must be present or both absent; a lone half is a client error.
+pub(crate) fn decode_optional_wrap(
+    wrapped: Option<&str>,
+    params: Option<&serde_json::Value>,
+    field: &str,
+) -> Result<Option<(Vec<u8>, serde_json::Value)>, AppError> {
+    match (wrapped, params) {
+        (Some(wrapped), Some(params)) => {
+            let bytes = B64.decode(wrapped).map_err(|_| AppError::BadRequest(format!("Invalid base64 in {}", field)))?;
+            Ok(Some((bytes, params.clone())))
+        }
+        (None, None) => Ok(None),
+        _ => Err(AppError::BadRequest(format!("{} and its wrap params must be provided together", field))),
+    }
+}
+
+#[get("/recovery-key")]
+pub async fn get_recovery_key(pool: &State<PgPool>, user: CurrentUser) -> Result<Json<RecoveryKeyResponse>, AppError> {
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let (recovery_wrapped_dek, recovery_wrap_params) = repo.get_recovery_wrapped_dek(&user.id).await?;
+    Ok(Json(RecoveryKeyResponse {
+        recovery_wrapped_dek: recovery_wrapped_dek.map(|b| B64.encode(&b)),
+        recovery_wrap_params,
+    }))
+}
+
+#[put("/recovery-key", data = "<payload>")]
+pub async fn update_recovery_key(pool: &State<PgPool>, user: CurrentUser, payload: Json<UpdateRecoveryKeyRequest>) -> Result<(), AppError> {
+    let bytes = B64
+        .decode(&payload.recovery_wrapped_dek)
+        .map_err(|_| AppError::BadRequest("Invalid base64 in recoveryWrappedDek".to_string()))?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    repo.update_recovery_wrapped_dek(&user.id, &bytes, &payload.recovery_wrap_params).await
+}
+
+#[delete("/recovery-key")]
+pub async fn delete_recovery_key(pool: &State<PgPool>, user: CurrentUser) -> Result<Status, AppError> {
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    repo.clear_recovery_wrapped_dek(&user.id).await?;
+    Ok(Status::NoContent)
+}
+
+/// `POST /v2/auth/reset-password/recovery-key` — exchange a valid reset token
+/// for the recovery-key wrapping of the DEK. Unauthenticated: the reset token
+/// is the credential. Does not consume the token.
+#[post("/reset-password/recovery-key", data = "<payload>")]
+pub async fn get_recovery_key_for_reset(
+    pool: &State<PgPool>,
+    config: &State<Config>,
+    payload: Json<RecoveryKeyForResetRequest>,
+) -> Result<Json<RecoveryKeyResponse>, AppError> {
+    payload.validate()?;
+
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let auth = AuthService::new(&repo, config);
+
+    let (wrapped, params) = auth.get_recovery_key_for_reset(&payload.token).await?;
+    Ok(Json(RecoveryKeyResponse {
+        recovery_wrapped_dek: Some(B64.encode(&wrapped)),
+        recovery_wrap_params: Some(params),
+    }))
+}
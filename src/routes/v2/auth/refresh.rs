@@ -12,6 +12,7 @@ use crate::error::app_error::AppError;
 use crate::middleware::{ClientIp, UserAgent};
 use crate::routes::v2::auth::login::set_session_cookie;
 use crate::service::auth::AuthService;
+use crate::session_dek::DekStore;
 
 #[post("/refresh")]
 pub async fn refresh(
@@ -20,6 +21,7 @@ pub async fn refresh(
     cookies: &CookieJar<'_>,
     user_agent: UserAgent,
     client_ip: ClientIp,
+    dek_store: &State<DekStore>,
     user: RefreshableUser,
 ) -> Result<Json<RefreshResponse>, AppError> {
     let repo = PostgresRepository { pool: pool.inner().clone() };
@@ -38,6 +40,15 @@ pub async fn refresh(
                 .refresh_session(&user.id, user.session_id, user_agent.0.as_deref(), client_ip.0.as_deref())
                 .await?;
 
+            // Carry an unlocked DEK over to the replacement session so the
+            // client does not have to unlock again after every refresh.
+            if let Some(old_session_id) = user.session_id
+                && let Some(expires_at) = repo.get_principal_expires_at(&new_session_id).await?
+                && let Err(e) = dek_store.transfer(&old_session_id, new_session_id, user.id, expires_at).await
+            {
+                tracing::warn!(error = %e, "Failed to move session DEK to refreshed session");
+            }
+
             set_session_cookie(cookies, config, new_session_id, user.id);
             Ok(Json(RefreshResponse {
                 token: new_session_id.to_string(),
@@ -13,6 +13,7 @@ use crate::dto::auth::{AuthenticatedResponse, RegisterRequest};
 use crate::error::app_error::AppError;
 use crate::middleware::{ClientIp, UserAgent};
 use crate::routes::v2::auth::login::set_session_cookie;
+use crate::routes::v2::auth::recovery_key::decode_optional_wrap;
 use crate::service::auth::AuthService;
 
 #[post("/register", data = "<payload>")]
@@ -35,6 +36,11 @@ pub async fn register(
         .map(|s| BASE64.decode(s))
         .transpose()
         .map_err(|_| AppError::BadRequest("Invalid base64 in wrappedDek".to_string()))?;
+    let recovery_wrap = decode_optional_wrap(
+        payload.recovery_wrapped_dek.as_deref(),
+        payload.recovery_wrap_params.as_ref(),
+        "recoveryWrappedDek",
+    )?;
 
     let (user, session_id) = auth
         .register(
@@ -45,6 +51,7 @@ pub async fn register(
             client_ip.0.as_deref(),
             wrapped_dek_bytes.as_deref(),
             payload.dek_wrap_params.as_ref(),
+            recovery_wrap.as_ref().map(|(wrapped, params)| (wrapped.as_slice(), params)),
         )
         .await?;
 
@@ -9,16 +9,31 @@ use crate::config::Config;
 use crate::database::postgres_repository::PostgresRepository;
 use crate::dto::auth::ResetPasswordRequest;
 use crate::error::app_error::AppError;
+use crate::routes::v2::auth::recovery_key::decode_optional_wrap;
 use crate::service::auth::AuthService;
+use crate::session_dek::DekStore;
 
 #[post("/reset-password", data = "<payload>")]
-pub async fn reset_password(pool: &State<PgPool>, config: &State<Config>, payload: Json<ResetPasswordRequest>) -> Result<Status, AppError> {
+pub async fn reset_password(
+    pool: &State<PgPool>,
+    config: &State<Config>,
+    dek_store: &State<DekStore>,
+    payload: Json<ResetPasswordRequest>,
+) -> Result<Status, AppError> {
     payload.validate()?;
 
     let repo = PostgresRepository { pool: pool.inner().clone() };
     let auth = AuthService::new(&repo, config);
 
-    auth.reset_password(&payload.token, &payload.password).await?;
+    let new_wrap = decode_optional_wrap(payload.wrapped_dek.as_deref(), payload.dek_wrap_params.as_ref(), "wrappedDek")?;
+
+    auth.reset_password(
+        dek_store.inner().as_ref(),
+        &payload.token,
+        &payload.password,
+        new_wrap.as_ref().map(|(w, p)| (w.as_slice(), p)),
+    )
+    .await?;
 
     Ok(Status::Ok)
 }
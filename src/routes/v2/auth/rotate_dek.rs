@@ -0,0 +1,102 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as B64;
+use rocket::State;
+use rocket::post;
+use rocket::serde::json::Json;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::auth::CurrentUser;
+use crate::config::Config;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::auth::RotateDekResponse;
+use crate::error::app_error::AppError;
+use crate::middleware::{ClientIp, UserAgent};
+use crate::routes::v2::auth::recovery_key::decode_optional_wrap;
+use crate::routes::v2::auth::unlock::decode_dek;
+use crate::service::auth::AuthService;
+use crate::session_dek::DekStore;
+
+/// Request body for `POST /v2/auth/rotate-dek`. Carries the new plaintext
+/// DEK (needed to re-encrypt), its wrap under the user's KEK and, when the
+/// user has a recovery key, its wrap under the recovery key (both stored
+/// once the rotation completes).
+///
+/// Intentionally does NOT derive `Debug`, for the same reason as
+/// `UnlockRequest`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RotateDekRequest {
+    pub new_dek: String,
+    pub wrapped_dek: String,
+    pub dek_wrap_params: serde_json::Value,
+    pub recovery_wrapped_dek: Option<String>,
+    pub recovery_wrap_params: Option<serde_json::Value>,
+}
+
+/// `POST /v2/auth/rotate-dek` — replace the user's DEK.
+///
+/// Client flow:
+///   1. Generate a new 32-byte DEK and wrap it with the KEK derived from
+///      the current password.
+///   2. POST the new DEK and its wrap here from an unlocked session. The
+///      session's DEK is the old key.
+///   3. Every ciphertext column the user owns is re-encrypted in committed
+///      batches, then `users.wrapped_dek` (and the recovery wrap) is swapped
+///      and every unlocked device of this user has its cached DEK replaced.
+///
+/// A user with a recovery key must send the new DEK wrapped under it too,
+/// or the request is rejected with 400. If a recovery key was enrolled
+/// while the rotation ran, it is cleared and `recoveryKeyCleared` tells the
+/// client to enroll again.
+///
+/// If the request is interrupted, repeat it with the same new DEK; rows
+/// already rotated are skipped. The pending wrap is exposed by
+/// `GET /v2/auth/wrapped-dek` so a client that lost the new DEK can
+/// recover it. A retry with a different new DEK is rejected with 409.
+#[post("/rotate-dek", data = "<payload>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn rotate_dek(
+    pool: &State<PgPool>,
+    config: &State<Config>,
+    store: &State<DekStore>,
+    user_agent: UserAgent,
+    client_ip: ClientIp,
+    user: CurrentUser,
+    dek: Dek,
+    payload: Json<RotateDekRequest>,
+) -> Result<Json<RotateDekResponse>, AppError> {
+    let new_dek = decode_dek(&payload.new_dek, "newDek")?;
+    let wrapped_dek = B64
+        .decode(&payload.wrapped_dek)
+        .map_err(|_| AppError::BadRequest("Invalid base64 in wrappedDek".to_string()))?;
+    let recovery_wrap = decode_optional_wrap(
+        payload.recovery_wrapped_dek.as_deref(),
+        payload.recovery_wrap_params.as_ref(),
+        "recoveryWrappedDek",
+    )?;
+
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let auth = AuthService::new(&repo, config);
+
+    let (rows_reencrypted, recovery_key_cleared) = auth
+        .rotate_dek(
+            &user.id,
+            &dek,
+            &new_dek,
+            &wrapped_dek,
+            &payload.dek_wrap_params,
+            recovery_wrap.as_ref().map(|(w, p)| (w.as_slice(), p)),
+            client_ip.0.clone(),
+            user_agent.0.clone(),
+        )
+        .await?;
+
+    store.replace_for_user(&user.id, &new_dek).await?;
+
+    Ok(Json(RotateDekResponse {
+        rows_reencrypted,
+        recovery_key_cleared,
+    }))
+}
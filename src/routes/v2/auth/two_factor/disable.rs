@@ -12,6 +12,7 @@ use crate::dto::auth::TwoFactorDisableRequest;
 use crate::error::app_error::AppError;
 use crate::middleware::{ClientIp, UserAgent};
 use crate::service::two_factor::TwoFactorService;
+use crate::session_dek::DekStore;
 
 #[post("/disable", data = "<payload>")]
 pub async fn disable_two_factor(
@@ -19,6 +20,7 @@ pub async fn disable_two_factor(
     config: &State<Config>,
     user_agent: UserAgent,
     client_ip: ClientIp,
+    dek_store: &State<DekStore>,
     user: CurrentUser,
     payload: Json<TwoFactorDisableRequest>,
 ) -> Result<Status, AppError> {
@@ -27,7 +29,8 @@ pub async fn disable_two_factor(
     let repo = PostgresRepository { pool: pool.inner().clone() };
     let tfa = TwoFactorService::new(&repo, config);
 
-    tfa.disable(&user.id, &payload.code, client_ip.0.clone(), user_agent.0.clone()).await?;
+    tfa.disable(dek_store.inner().as_ref(), &user.id, &payload.code, client_ip.0.clone(), user_agent.0.clone())
+        .await?;
 
     Ok(Status::Ok)
 }
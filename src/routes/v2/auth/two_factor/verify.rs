@@ -15,15 +15,18 @@ use crate::middleware::{ClientIp, UserAgent};
 use crate::routes::v2::auth::login::set_session_cookie;
 use crate::service::auth::AuthService;
 use crate::service::two_factor::TwoFactorService;
+use crate::session_dek::DekStore;
 
 /// Confirm 2FA setup or complete a 2FA login challenge.
 #[post("/verify", data = "<payload>")]
+#[allow(clippy::too_many_arguments)]
 pub async fn verify_two_factor(
     pool: &State<PgPool>,
     config: &State<Config>,
     cookies: &CookieJar<'_>,
     user_agent: UserAgent,
     client_ip: ClientIp,
+    dek_store: &State<DekStore>,
     user: Option<CurrentUser>,
     payload: Json<TwoFactorCompleteRequest>,
 ) -> Result<Json<AuthenticatedResponse>, AppError> {
@@ -59,7 +62,9 @@ pub async fn verify_two_factor(
 
     let user = user.ok_or(AppError::Unauthorized)?;
 
-    let backup_codes = tfa.verify_setup(&user.id, &payload.code, client_ip.0.clone(), user_agent.0.clone()).await?;
+    let backup_codes = tfa
+        .verify_setup(dek_store.inner().as_ref(), &user.id, &payload.code, client_ip.0.clone(), user_agent.0.clone())
+        .await?;
 
     let auth = AuthService::new(&repo, config);
     let user_response = auth.get_user_response(&user.id).await?;
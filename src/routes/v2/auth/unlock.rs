@@ -1,15 +1,20 @@
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD as BASE64;
 use rocket::State;
-use rocket::http::Status;
 use rocket::post;
 use rocket::serde::json::Json;
 use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+use zeroize::Zeroize;
 
 use crate::auth::CurrentUser;
 use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::subscriptions::AutoPostResponse;
 use crate::error::app_error::AppError;
-use crate::session_dek::SessionDekStore;
+use crate::service::subscription::SubscriptionService;
+use crate::session_dek::DekStore;
 
 /// Request body for `POST /v2/auth/unlock`. Carries the plaintext DEK
 /// that the client just unwrapped locally using the user's password.
@@ -41,32 +46,89 @@ pub struct UnlockRequest {
 /// The plaintext DEK only exists in:
 ///   * client memory between derive and upload
 ///   * the Rocket request body buffer briefly
-///   * the in-process `SessionDekStore` for the session/token lifetime
+///   * the configured `DekStore` for the session/token lifetime (sealed
+///     with the server-side key when the store is networked)
 ///
-/// Returns 204 on success, 400 on malformed input, 401 if the caller is
+/// The stored entry records whether the user's envelopes have all been
+/// upgraded. If not, a background task binds every remaining legacy
+/// envelope to its cell and then stops the user's entries from accepting
+/// legacy envelopes; the response does not wait for it. Then charges of
+/// auto-post subscriptions that fell due since they were last posted are
+/// posted to the ledger; the response lists their billing events. A
+/// failure in either step is logged and does not fail the unlock — it is
+/// retried on the next one.
+///
+/// Returns 200 on success, 400 on malformed input, 401 if the caller is
 /// not authenticated. Unlock is idempotent — calling it with a different
 /// DEK simply overwrites the previous entry for the same principal.
 #[post("/unlock", data = "<payload>")]
-pub async fn unlock(user: CurrentUser, store: &State<SessionDekStore>, payload: Json<UnlockRequest>) -> Result<Status, AppError> {
+pub async fn unlock(
+    pool: &State<PgPool>,
+    user: CurrentUser,
+    store: &State<DekStore>,
+    payload: Json<UnlockRequest>,
+) -> Result<Json<AutoPostResponse>, AppError> {
     let principal_id = user.principal_id().ok_or(AppError::Unauthorized)?;
 
-    let raw = BASE64
-        .decode(payload.dek.as_bytes())
-        .map_err(|_| AppError::BadRequest("dek is not valid base64".to_string()))?;
+    let dek = decode_dek(&payload.dek, "dek")?;
+
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let expires_at = repo.get_principal_expires_at(&principal_id).await?.ok_or(AppError::Unauthorized)?;
+
+    let legacy = repo.has_legacy_envelopes(&user.id).await?;
+    let dek = dek.with_legacy_envelopes(legacy);
+    store.put(principal_id, user.id, dek.clone_for_request(), expires_at).await?;
+
+    if legacy {
+        spawn_legacy_envelope_upgrade(repo.clone(), store.inner().clone(), user.id, dek.clone_for_request());
+    }
+
+    let response = match SubscriptionService::new(&repo).post_due_charges(&user.id, &dek).await {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::warn!(error = ?e, "Failed to post due subscription charges");
+            AutoPostResponse { posted_charges: Vec::new() }
+        }
+    };
+    Ok(Json(response))
+}
+
+/// Run the legacy envelope sweep off the request. Unlocked devices stop
+/// accepting legacy envelopes only once the sweep has set
+/// `users.envelopes_upgraded_at`; a sweep skipped for a pending rotation
+/// leaves them as they are.
+fn spawn_legacy_envelope_upgrade(repo: PostgresRepository, store: DekStore, user_id: Uuid, dek: Dek) {
+    tokio::spawn(async move {
+        let result = async {
+            repo.upgrade_all_legacy_envelopes(&user_id, &dek).await?;
+            if !repo.has_legacy_envelopes(&user_id).await? {
+                store.mark_envelopes_upgraded(&user_id).await?;
+            }
+            Ok::<_, AppError>(())
+        };
+        if let Err(e) = result.await {
+            tracing::warn!(error = ?e, "Failed to upgrade legacy envelopes");
+        }
+    });
+}
+
+/// Decode a base64 plaintext DEK from a request body. `field` names the
+/// JSON field in error messages.
+pub(crate) fn decode_dek(encoded: &str, field: &str) -> Result<Dek, AppError> {
+    let mut raw = BASE64
+        .decode(encoded.as_bytes())
+        .map_err(|_| AppError::BadRequest(format!("{} is not valid base64", field)))?;
 
     if raw.len() != 32 {
-        return Err(AppError::BadRequest("dek must decode to exactly 32 bytes".to_string()));
+        raw.zeroize();
+        return Err(AppError::BadRequest(format!("{} must decode to exactly 32 bytes", field)));
     }
 
     let mut bytes = [0u8; 32];
     bytes.copy_from_slice(&raw);
-    let dek = Dek::from_bytes(bytes);
-
-    store.put(principal_id, dek).await;
 
-    // Drop the raw vec (was never sensitive beyond the copy we already
-    // moved into the Dek), but be explicit anyway for memory hygiene.
-    drop(raw);
+    // The bytes now live in the Dek (zeroed on drop); wipe the decode buffer.
+    raw.zeroize();
 
-    Ok(Status::NoContent)
+    Ok(Dek::from_bytes(bytes))
 }
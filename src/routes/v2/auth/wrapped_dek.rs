@@ -15,9 +15,16 @@ use crate::error::app_error::AppError;
 pub async fn get_wrapped_dek(pool: &State<PgPool>, user: CurrentUser) -> Result<Json<WrappedDekResponse>, AppError> {
     let repo = PostgresRepository { pool: pool.inner().clone() };
     let (wrapped_dek, dek_wrap_params) = repo.get_wrapped_dek(&user.id).await?;
+    let pending = repo.get_pending_dek_rotation(&user.id).await?;
+    let (pending_wrapped_dek, pending_dek_wrap_params) = match pending {
+        Some((wrapped, params)) => (Some(B64.encode(&wrapped)), Some(params)),
+        None => (None, None),
+    };
     Ok(Json(WrappedDekResponse {
         wrapped_dek: wrapped_dek.map(|b| B64.encode(&b)),
         dek_wrap_params,
+        pending_wrapped_dek,
+        pending_dek_wrap_params,
     }))
 }
 
@@ -0,0 +1,5 @@
+mod upcoming;
+
+pub fn routes() -> Vec<rocket::Route> {
+    rocket::routes![upcoming::get_upcoming]
+}
@@ -0,0 +1,18 @@
+use rocket::State;
+use rocket::get;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+
+use crate::auth::CurrentUser;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::calendar::UpcomingCalendarResponse;
+use crate::error::app_error::AppError;
+use crate::service::calendar::CalendarService;
+
+#[get("/upcoming?<days>")]
+pub async fn get_upcoming(pool: &State<PgPool>, user: CurrentUser, dek: Option<Dek>, days: Option<u32>) -> Result<Json<UpcomingCalendarResponse>, AppError> {
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = CalendarService::new(&repo);
+    Ok(Json(service.upcoming(days.unwrap_or(30), &user.id, dek.as_ref()).await?))
+}
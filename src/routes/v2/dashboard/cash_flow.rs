@@ -0,0 +1,20 @@
+use rocket::State;
+use rocket::get;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+
+use crate::auth::CurrentUser;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::dashboard::CashFlowResponse;
+use crate::error::app_error::AppError;
+use crate::service::dashboard::DashboardService;
+
+#[get("/cash-flow?<periodId>")]
+#[allow(non_snake_case)]
+pub async fn get_cash_flow(pool: &State<PgPool>, user: CurrentUser, dek: Dek, periodId: Option<String>) -> Result<Json<CashFlowResponse>, AppError> {
+    let period_id = super::parse_period_id(periodId)?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = DashboardService::new(&repo);
+    Ok(Json(service.cash_flow(period_id, &user.id, &dek).await?))
+}
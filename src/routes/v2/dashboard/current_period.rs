@@ -0,0 +1,20 @@
+use rocket::State;
+use rocket::get;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+
+use crate::auth::CurrentUser;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::dashboard::CurrentPeriodResponse;
+use crate::error::app_error::AppError;
+use crate::service::dashboard::DashboardService;
+
+#[get("/current-period?<periodId>")]
+#[allow(non_snake_case)]
+pub async fn get_current_period(pool: &State<PgPool>, user: CurrentUser, dek: Dek, periodId: Option<String>) -> Result<Json<CurrentPeriodResponse>, AppError> {
+    let period_id = super::parse_period_id(periodId)?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = DashboardService::new(&repo);
+    Ok(Json(service.current_period(period_id, &user.id, &dek).await?))
+}
@@ -0,0 +1,28 @@
+use uuid::Uuid;
+
+use crate::error::app_error::AppError;
+
+mod cash_flow;
+mod current_period;
+mod net_position;
+mod spending_trend;
+mod top_vendors;
+
+pub fn routes() -> Vec<rocket::Route> {
+    rocket::routes![
+        current_period::get_current_period,
+        net_position::get_net_position,
+        cash_flow::get_cash_flow,
+        spending_trend::get_spending_trend,
+        top_vendors::get_top_vendors,
+    ]
+}
+
+/// Parse the optional `periodId` query parameter shared by the
+/// period-scoped cards. Absent means the period covering today.
+fn parse_period_id(period_id: Option<String>) -> Result<Option<Uuid>, AppError> {
+    match period_id {
+        Some(ref s) if !s.is_empty() && s != "null" => Ok(Some(Uuid::parse_str(s).map_err(|e| AppError::uuid("Invalid periodId", e))?)),
+        _ => Ok(None),
+    }
+}
@@ -0,0 +1,18 @@
+use rocket::State;
+use rocket::get;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+
+use crate::auth::CurrentUser;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::dashboard::NetPositionResponse;
+use crate::error::app_error::AppError;
+use crate::service::dashboard::DashboardService;
+
+#[get("/net-position")]
+pub async fn get_net_position(pool: &State<PgPool>, user: CurrentUser, dek: Dek) -> Result<Json<NetPositionResponse>, AppError> {
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = DashboardService::new(&repo);
+    Ok(Json(service.net_position(&user.id, &dek).await?))
+}
@@ -0,0 +1,19 @@
+use rocket::State;
+use rocket::get;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+
+use crate::auth::CurrentUser;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::dashboard::SpendingTrendResponse;
+use crate::error::app_error::AppError;
+use crate::service::dashboard::DashboardService;
+
+#[get("/spending-trend?<periods>")]
+pub async fn get_spending_trend(pool: &State<PgPool>, user: CurrentUser, dek: Dek, periods: Option<u32>) -> Result<Json<SpendingTrendResponse>, AppError> {
+    let effective_periods = periods.unwrap_or(6).clamp(1, 24) as i64;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = DashboardService::new(&repo);
+    Ok(Json(service.spending_trend(effective_periods, &user.id, &dek).await?))
+}
@@ -0,0 +1,27 @@
+use rocket::State;
+use rocket::get;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+
+use crate::auth::CurrentUser;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::dashboard::TopVendorsResponse;
+use crate::error::app_error::AppError;
+use crate::service::dashboard::DashboardService;
+
+#[get("/top-vendors?<periodId>&<limit>")]
+#[allow(non_snake_case)]
+pub async fn get_top_vendors(
+    pool: &State<PgPool>,
+    user: CurrentUser,
+    dek: Dek,
+    periodId: Option<String>,
+    limit: Option<u32>,
+) -> Result<Json<TopVendorsResponse>, AppError> {
+    let period_id = super::parse_period_id(periodId)?;
+    let effective_limit = limit.unwrap_or(5).clamp(1, 50) as usize;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = DashboardService::new(&repo);
+    Ok(Json(service.top_vendors(period_id, effective_limit, &user.id, &dek).await?))
+}
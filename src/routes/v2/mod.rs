@@ -3,13 +3,16 @@
 
 pub mod accounts;
 pub mod auth;
+pub mod calendar;
 pub mod categories;
 pub mod currencies;
+pub mod dashboard;
 pub mod health;
 pub mod onboarding;
 pub mod periods;
 pub mod settings;
 pub mod subscriptions;
+pub mod tags;
 pub mod targets;
 pub mod transactions;
 pub mod unlock;
@@ -5,15 +5,21 @@ use rocket::serde::json::Json;
 use sqlx::PgPool;
 
 use crate::auth::CurrentUser;
+use crate::crypto::Dek;
 use crate::database::postgres_repository::PostgresRepository;
 use crate::dto::period::{CreatePeriodRequest, PeriodResponse};
 use crate::error::app_error::AppError;
 use crate::service::period::PeriodService;
 
 #[post("/", data = "<payload>")]
-pub async fn create_period(pool: &State<PgPool>, user: CurrentUser, payload: Json<CreatePeriodRequest>) -> Result<(Status, Json<PeriodResponse>), AppError> {
+pub async fn create_period(
+    pool: &State<PgPool>,
+    user: CurrentUser,
+    dek: Option<Dek>,
+    payload: Json<CreatePeriodRequest>,
+) -> Result<(Status, Json<PeriodResponse>), AppError> {
     let repo = PostgresRepository { pool: pool.inner().clone() };
     let service = PeriodService::new(&repo);
-    let response = service.create_period(&payload, &user.id).await?;
+    let response = service.create_period(&payload, &user.id, dek.as_ref()).await?;
     Ok((Status::Created, Json(response)))
 }
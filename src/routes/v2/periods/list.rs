@@ -5,13 +5,20 @@ use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::auth::CurrentUser;
+use crate::crypto::Dek;
 use crate::database::postgres_repository::PostgresRepository;
 use crate::dto::period::PeriodListResponse;
 use crate::error::app_error::AppError;
 use crate::service::period::PeriodService;
 
 #[get("/?<cursor>&<limit>")]
-pub async fn list_periods(pool: &State<PgPool>, user: CurrentUser, cursor: Option<String>, limit: Option<u32>) -> Result<Json<PeriodListResponse>, AppError> {
+pub async fn list_periods(
+    pool: &State<PgPool>,
+    user: CurrentUser,
+    dek: Option<Dek>,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Json<PeriodListResponse>, AppError> {
     let cursor_uuid = match cursor {
         Some(ref s) if !s.is_empty() && s != "null" => Some(Uuid::parse_str(s).map_err(|e| AppError::uuid("Invalid cursor", e))?),
         _ => None,
@@ -20,6 +27,6 @@ pub async fn list_periods(pool: &State<PgPool>, user: CurrentUser, cursor: Optio
 
     let repo = PostgresRepository { pool: pool.inner().clone() };
     let service = PeriodService::new(&repo);
-    let response = service.list_periods(cursor_uuid, effective_limit, &user.id).await?;
+    let response = service.list_periods(cursor_uuid, effective_limit, &user.id, dek.as_ref()).await?;
     Ok(Json(response))
 }
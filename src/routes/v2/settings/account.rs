@@ -10,18 +10,20 @@ use crate::database::postgres_repository::PostgresRepository;
 use crate::dto::settings::DeleteAccountRequest;
 use crate::error::app_error::AppError;
 use crate::service::settings::SettingsService;
+use crate::session_dek::DekStore;
 
 #[delete("/account", data = "<payload>")]
 pub async fn delete_account(
     pool: &State<PgPool>,
     user: CurrentUser,
     cookies: &rocket::http::CookieJar<'_>,
+    dek_store: &State<DekStore>,
     payload: Json<DeleteAccountRequest>,
 ) -> Result<Status, AppError> {
     payload.validate()?;
     let repo = PostgresRepository { pool: pool.inner().clone() };
     let service = SettingsService::new(&repo);
-    service.delete_account(&user.id, &payload.password).await?;
+    service.delete_account(dek_store.inner().as_ref(), &user.id, &payload.password).await?;
     cookies.remove_private(Cookie::build("user").build());
     Ok(Status::NoContent)
 }
@@ -0,0 +1,18 @@
+use rocket::State;
+use rocket::get;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+
+use crate::auth::CurrentUser;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::settings::BackupDocument;
+use crate::error::app_error::AppError;
+use crate::service::settings::SettingsService;
+
+#[get("/export/data")]
+pub async fn export_data(pool: &State<PgPool>, user: CurrentUser, dek: Dek) -> Result<Json<BackupDocument>, AppError> {
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = SettingsService::new(&repo);
+    Ok(Json(service.export_data(&user.id, &dek).await?))
+}
@@ -0,0 +1,116 @@
+use chrono::NaiveDate;
+use rocket::State;
+use rocket::futures::TryStreamExt;
+use rocket::get;
+use rocket::http::Header;
+use rocket::response::stream::TextStream;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::CurrentUser;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::settings::{CsvColumn, CsvDelimiter, CsvExportOptions};
+use crate::error::app_error::AppError;
+use crate::service::settings::SettingsService;
+
+/// Lines are batched into chunks of about this size before being written.
+const CHUNK_SIZE: usize = 16 * 1024;
+
+#[derive(rocket::Responder)]
+#[response(content_type = "text/csv")]
+pub struct CsvDownload<T> {
+    body: T,
+    disposition: Header<'static>,
+}
+
+#[get("/export/transactions.csv?<from>&<to>&<accountId>&<columns>&<delimiter>")]
+#[allow(non_snake_case, clippy::too_many_arguments)]
+pub async fn export_transactions_csv(
+    pool: &State<PgPool>,
+    user: CurrentUser,
+    dek: Dek,
+    from: Option<String>,
+    to: Option<String>,
+    accountId: Option<String>,
+    columns: Option<String>,
+    delimiter: Option<String>,
+) -> Result<CsvDownload<TextStream![String]>, AppError> {
+    let options = CsvExportOptions {
+        from: parse_date("from", from)?,
+        to: parse_date("to", to)?,
+        account_id: accountId
+            .map(|id| Uuid::parse_str(&id).map_err(|_| AppError::BadRequest(format!("Invalid accountId: {}", id))))
+            .transpose()?,
+        columns: parse_columns(columns)?,
+        delimiter: match delimiter {
+            Some(name) => CsvDelimiter::from_name(&name).ok_or_else(|| AppError::BadRequest(format!("Invalid delimiter: {}", name)))?,
+            None => CsvDelimiter::default(),
+        },
+    };
+    if let (Some(from), Some(to)) = (options.from, options.to)
+        && from > to
+    {
+        return Err(AppError::BadRequest("'from' must be <= 'to'".to_string()));
+    }
+    let (from, to, account_id) = (options.from, options.to, options.account_id);
+
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let writer = SettingsService::new(&repo).transactions_csv_writer(&user.id, dek, options).await?;
+    let user_id = user.id;
+
+    // The status line is already sent once rows stream, so a failure past
+    // this point can only end the body early.
+    let body = TextStream! {
+        let mut chunk = writer.header();
+        let mut rows = repo.stream_export_transactions(user_id, from, to, account_id);
+        loop {
+            let line = match rows.try_next().await {
+                Ok(Some(row)) => writer.row(&row),
+                Ok(None) => break,
+                Err(e) => Err(e.into()),
+            };
+            match line {
+                Ok(line) => chunk.push_str(&line),
+                Err(e) => {
+                    tracing::error!(error = %e, "CSV export aborted");
+                    break;
+                }
+            }
+            if chunk.len() >= CHUNK_SIZE {
+                yield std::mem::take(&mut chunk);
+            }
+        }
+        if !chunk.is_empty() {
+            yield chunk;
+        }
+    };
+
+    Ok(CsvDownload {
+        body,
+        disposition: Header::new("Content-Disposition", "attachment; filename=\"transactions.csv\""),
+    })
+}
+
+fn parse_date(name: &str, value: Option<String>) -> Result<Option<NaiveDate>, AppError> {
+    value
+        .map(|s| NaiveDate::parse_from_str(&s, "%Y-%m-%d").map_err(|_| AppError::BadRequest(format!("Invalid '{}' date: {}", name, s))))
+        .transpose()
+}
+
+/// Comma-separated column names; every column in the default order when
+/// absent.
+fn parse_columns(value: Option<String>) -> Result<Vec<CsvColumn>, AppError> {
+    let Some(value) = value else {
+        return Ok(CsvColumn::ALL.to_vec());
+    };
+    let mut columns = Vec::new();
+    for name in value.split(',').map(str::trim) {
+        let column = CsvColumn::from_name(name).ok_or_else(|| AppError::BadRequest(format!("Unknown column: {}", name)))?;
+        if columns.contains(&column) {
+            return Err(AppError::BadRequest(format!("Duplicate column: {}", name)));
+        }
+        columns.push(column);
+    }
+    Ok(columns)
+}
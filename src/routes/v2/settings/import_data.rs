@@ -0,0 +1,33 @@
+use rocket::State;
+use rocket::data::{Data, ToByteUnit};
+use rocket::post;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+
+use crate::auth::CurrentUser;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::settings::{BackupDocument, ImportDataResponse};
+use crate::error::app_error::AppError;
+use crate::service::settings::SettingsService;
+
+/// Backups carry the whole ledger, so they are read outside Rocket's 1 MiB
+/// JSON limit with a cap of their own.
+const MAX_BACKUP_SIZE_MIB: u64 = 32;
+
+#[post("/import/data", data = "<body>")]
+pub async fn import_data(pool: &State<PgPool>, user: CurrentUser, dek: Dek, body: Data<'_>) -> Result<Json<ImportDataResponse>, AppError> {
+    let body = body
+        .open(MAX_BACKUP_SIZE_MIB.mebibytes())
+        .into_string()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Failed to read backup: {e}")))?;
+    if !body.is_complete() {
+        return Err(AppError::BadRequest(format!("Backup exceeds {MAX_BACKUP_SIZE_MIB} MiB")));
+    }
+    let backup: BackupDocument = serde_json::from_str(&body).map_err(|e| AppError::BadRequest(format!("Invalid backup: {e}")))?;
+
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = SettingsService::new(&repo);
+    Ok(Json(service.import_data(&user.id, &backup, &dek).await?))
+}
@@ -1,4 +1,7 @@
 mod account;
+mod export_data;
+mod export_transactions;
+mod import_data;
 mod preferences;
 mod profile;
 mod reset_structure;
@@ -13,6 +16,9 @@ pub fn routes() -> Vec<rocket::Route> {
         preferences::get_preferences,
         preferences::update_preferences,
         reset_structure::reset_structure,
+        export_data::export_data,
+        export_transactions::export_transactions_csv,
+        import_data::import_data,
     ]
 }
 
@@ -10,12 +10,12 @@ use crate::database::postgres_repository::PostgresRepository;
 use crate::dto::settings::ResetStructureRequest;
 use crate::error::app_error::AppError;
 use crate::service::settings::SettingsService;
-use crate::session_dek::SessionDekStore;
+use crate::session_dek::DekStore;
 
 #[post("/reset-structure", data = "<payload>")]
 pub async fn reset_structure(
     pool: &State<PgPool>,
-    store: &State<SessionDekStore>,
+    store: &State<DekStore>,
     user: CurrentUser,
     payload: Json<ResetStructureRequest>,
 ) -> Result<Status, AppError> {
@@ -23,7 +23,7 @@ pub async fn reset_structure(
 
     // Look up the session DEK for the authenticated principal.
     let principal_id = user.principal_id().ok_or(AppError::Unauthorized)?;
-    let dek = store.get_cloned(&principal_id).await;
+    let dek = store.get_cloned(&principal_id).await?;
     let dek = dek.ok_or(AppError::Unauthorized)?;
 
     let repo = PostgresRepository { pool: pool.inner().clone() };
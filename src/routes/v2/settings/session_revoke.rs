@@ -8,14 +8,14 @@ use crate::auth::CurrentUser;
 use crate::database::postgres_repository::PostgresRepository;
 use crate::error::app_error::AppError;
 use crate::service::settings::SettingsService;
-use crate::session_dek::SessionDekStore;
+use crate::session_dek::DekStore;
 
 #[delete("/<id>")]
 pub async fn revoke_session(
     pool: &State<PgPool>,
     user: CurrentUser,
     cookies: &rocket::http::CookieJar<'_>,
-    dek_store: &State<SessionDekStore>,
+    dek_store: &State<DekStore>,
     id: &str,
 ) -> Result<Status, AppError> {
     let session_id = Uuid::parse_str(id).map_err(|e| AppError::uuid("Invalid session id", e))?;
@@ -23,7 +23,9 @@ pub async fn revoke_session(
     let service = SettingsService::new(&repo);
     service.revoke_session(&session_id, &user.id).await?;
 
-    dek_store.remove(&session_id).await;
+    if let Err(e) = dek_store.remove(&session_id).await {
+        tracing::warn!(error = %e, "Failed to drop session DEK on session revoke");
+    }
 
     // Clear the cookie if the revoked session is the current one
     if user.session_id == Some(session_id) {
@@ -0,0 +1,18 @@
+use rocket::State;
+use rocket::get;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+
+use crate::auth::CurrentUser;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::subscriptions::SubscriptionAlertResponse;
+use crate::error::app_error::AppError;
+use crate::service::subscription::SubscriptionService;
+
+#[get("/alerts")]
+pub async fn list_subscription_alerts(pool: &State<PgPool>, user: CurrentUser, dek: Dek) -> Result<Json<Vec<SubscriptionAlertResponse>>, AppError> {
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = SubscriptionService::new(&repo);
+    Ok(Json(service.alerts(&user.id, &dek).await?))
+}
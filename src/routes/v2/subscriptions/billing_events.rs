@@ -0,0 +1,38 @@
+use rocket::State;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{delete, post};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::CurrentUser;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::subscriptions::{BillingEventResponse, LinkBillingTransactionRequest};
+use crate::error::app_error::AppError;
+use crate::service::subscription::SubscriptionService;
+
+#[post("/<id>/transactions", data = "<payload>")]
+pub async fn link_transaction(
+    pool: &State<PgPool>,
+    user: CurrentUser,
+    dek: Dek,
+    id: &str,
+    payload: Json<LinkBillingTransactionRequest>,
+) -> Result<(Status, Json<BillingEventResponse>), AppError> {
+    let uuid = Uuid::parse_str(id).map_err(|e| AppError::uuid("Invalid subscription id", e))?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = SubscriptionService::new(&repo);
+    let event = service.link_transaction(&uuid, &payload.transaction_id, &user.id, &dek).await?;
+    Ok((Status::Created, Json(event)))
+}
+
+#[delete("/<id>/transactions/<transaction_id>")]
+pub async fn unlink_transaction(pool: &State<PgPool>, user: CurrentUser, id: &str, transaction_id: &str) -> Result<Status, AppError> {
+    let uuid = Uuid::parse_str(id).map_err(|e| AppError::uuid("Invalid subscription id", e))?;
+    let transaction_id = Uuid::parse_str(transaction_id).map_err(|e| AppError::uuid("Invalid transaction id", e))?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = SubscriptionService::new(&repo);
+    service.unlink_transaction(&uuid, &transaction_id, &user.id).await?;
+    Ok(Status::NoContent)
+}
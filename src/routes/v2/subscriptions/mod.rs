@@ -1,15 +1,26 @@
+mod alerts;
+mod billing_events;
 mod cancel;
 mod create;
 mod delete;
+mod get;
 mod list;
+mod pause;
+mod resume;
 mod update;
 
 pub fn routes() -> Vec<rocket::Route> {
     rocket::routes![
         list::list_subscriptions,
+        alerts::list_subscription_alerts,
+        get::get_subscription,
         create::create_subscription,
         update::update_subscription,
         delete::delete_subscription,
         cancel::cancel_subscription,
+        pause::pause_subscription,
+        resume::resume_subscription,
+        billing_events::link_transaction,
+        billing_events::unlink_transaction,
     ]
 }
@@ -0,0 +1,25 @@
+use rocket::State;
+use rocket::post;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::CurrentUser;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::subscriptions::{EncryptedSubscriptionResponse, PauseSubscriptionRequest};
+use crate::error::app_error::AppError;
+use crate::service::subscription::SubscriptionService;
+
+#[post("/<id>/pause", data = "<payload>")]
+pub async fn pause_subscription(
+    pool: &State<PgPool>,
+    user: CurrentUser,
+    id: &str,
+    payload: Json<PauseSubscriptionRequest>,
+) -> Result<Json<EncryptedSubscriptionResponse>, AppError> {
+    let uuid = Uuid::parse_str(id).map_err(|e| AppError::uuid("Invalid subscription id", e))?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = SubscriptionService::new(&repo);
+    let resume_on = payload.resume_on.as_ref().map(|d| d.0);
+    Ok(Json(service.pause(&uuid, &user.id, resume_on).await?))
+}
@@ -0,0 +1,19 @@
+use rocket::State;
+use rocket::post;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::CurrentUser;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::subscriptions::EncryptedSubscriptionResponse;
+use crate::error::app_error::AppError;
+use crate::service::subscription::SubscriptionService;
+
+#[post("/<id>/resume")]
+pub async fn resume_subscription(pool: &State<PgPool>, user: CurrentUser, id: &str) -> Result<Json<EncryptedSubscriptionResponse>, AppError> {
+    let uuid = Uuid::parse_str(id).map_err(|e| AppError::uuid("Invalid subscription id", e))?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = SubscriptionService::new(&repo);
+    Ok(Json(service.resume(&uuid, &user.id).await?))
+}
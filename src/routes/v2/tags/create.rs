@@ -0,0 +1,26 @@
+use rocket::State;
+use rocket::http::Status;
+use rocket::post;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+use validator::Validate;
+
+use crate::auth::CurrentUser;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::tags::{CreateTagRequest, EncryptedTagResponse};
+use crate::error::app_error::AppError;
+use crate::service::tag::TagService;
+
+#[post("/", data = "<payload>")]
+pub async fn create_tag(
+    pool: &State<PgPool>,
+    user: CurrentUser,
+    dek: Dek,
+    payload: Json<CreateTagRequest>,
+) -> Result<(Status, Json<EncryptedTagResponse>), AppError> {
+    payload.validate()?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = TagService::new(&repo);
+    Ok((Status::Created, Json(service.create_tag(&payload, &user.id, &dek).await?)))
+}
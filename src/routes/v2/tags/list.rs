@@ -0,0 +1,17 @@
+use rocket::State;
+use rocket::get;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+
+use crate::auth::CurrentUser;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::tags::TagListResponse;
+use crate::error::app_error::AppError;
+use crate::service::tag::TagService;
+
+#[get("/")]
+pub async fn list_tags(pool: &State<PgPool>, user: CurrentUser) -> Result<Json<TagListResponse>, AppError> {
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = TagService::new(&repo);
+    Ok(Json(service.list_tags(&user.id).await?))
+}
@@ -0,0 +1,8 @@
+mod create;
+mod delete;
+mod list;
+mod update;
+
+pub fn routes() -> Vec<rocket::Route> {
+    rocket::routes![list::list_tags, create::create_tag, update::update_tag, delete::delete_tag,]
+}
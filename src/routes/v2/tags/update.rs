@@ -0,0 +1,28 @@
+use rocket::State;
+use rocket::put;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::auth::CurrentUser;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::tags::{EncryptedTagResponse, UpdateTagRequest};
+use crate::error::app_error::AppError;
+use crate::service::tag::TagService;
+
+#[put("/<id>", data = "<payload>")]
+pub async fn update_tag(
+    pool: &State<PgPool>,
+    user: CurrentUser,
+    dek: Dek,
+    id: &str,
+    payload: Json<UpdateTagRequest>,
+) -> Result<Json<EncryptedTagResponse>, AppError> {
+    payload.validate()?;
+    let uuid = Uuid::parse_str(id).map_err(|e| AppError::uuid("Invalid tag id", e))?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = TagService::new(&repo);
+    Ok(Json(service.update_tag(&uuid, &payload, &user.id, &dek).await?))
+}
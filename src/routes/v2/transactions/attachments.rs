@@ -0,0 +1,134 @@
+use rocket::State;
+use rocket::form::{Form, FromForm};
+use rocket::fs::TempFile;
+use rocket::http::{ContentType, Header, Status};
+use rocket::response::stream::ByteStream;
+use rocket::serde::json::Json;
+use rocket::{delete, get, post};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::CurrentUser;
+use crate::config::Config;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::transactions::EncryptedAttachmentResponse;
+use crate::error::app_error::AppError;
+use crate::service::attachment::AttachmentService;
+
+/// `multipart/form-data` body of an upload; the file goes in the `file` part.
+#[derive(FromForm)]
+pub struct AttachmentUpload<'r> {
+    file: TempFile<'r>,
+}
+
+#[derive(rocket::Responder)]
+pub struct AttachmentDownload<T> {
+    body: T,
+    content_type: ContentType,
+    disposition: Header<'static>,
+    nosniff: Header<'static>,
+}
+
+fn parse_ids(id: &str, attachment_id: &str) -> Result<(Uuid, Uuid), AppError> {
+    let tx_id = Uuid::parse_str(id).map_err(|e| AppError::uuid("Invalid transaction id", e))?;
+    let attachment_id = Uuid::parse_str(attachment_id).map_err(|e| AppError::uuid("Invalid attachment id", e))?;
+    Ok((tx_id, attachment_id))
+}
+
+/// `Content-Disposition` value carrying the original file name: an ASCII
+/// fallback plus the exact name in RFC 5987 form.
+fn content_disposition(file_name: &str) -> String {
+    let fallback: String = file_name
+        .chars()
+        .map(|c| if c.is_ascii_graphic() && c != '"' && c != '\\' || c == ' ' { c } else { '_' })
+        .collect();
+    format!("attachment; filename=\"{}\"; filename*=UTF-8''{}", fallback, urlencoding::encode(file_name))
+}
+
+#[post("/<id>/attachments", data = "<upload>")]
+pub async fn upload_attachment(
+    pool: &State<PgPool>,
+    config: &State<Config>,
+    user: CurrentUser,
+    dek: Dek,
+    id: &str,
+    upload: Form<AttachmentUpload<'_>>,
+) -> Result<(Status, Json<EncryptedAttachmentResponse>), AppError> {
+    let tx_id = Uuid::parse_str(id).map_err(|e| AppError::uuid("Invalid transaction id", e))?;
+    let file = &upload.file;
+    // The name is only ever stored encrypted, never used as a path.
+    let file_name = file.raw_name().map(|n| n.dangerous_unsafe_unsanitized_raw().as_str());
+    let content_type = file.content_type().map(ToString::to_string);
+    let content = file.open().await.map_err(|e| AppError::internal(format!("attachment upload: {e}")))?;
+
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = AttachmentService::new(&repo, &config.attachments);
+    let response = service
+        .upload_attachment(&tx_id, &user.id, &dek, file_name, content_type.as_deref(), file.len(), content)
+        .await?;
+    Ok((Status::Created, Json(response)))
+}
+
+#[get("/<id>/attachments")]
+pub async fn list_attachments(
+    pool: &State<PgPool>,
+    config: &State<Config>,
+    user: CurrentUser,
+    id: &str,
+) -> Result<Json<Vec<EncryptedAttachmentResponse>>, AppError> {
+    let tx_id = Uuid::parse_str(id).map_err(|e| AppError::uuid("Invalid transaction id", e))?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = AttachmentService::new(&repo, &config.attachments);
+    let response = service.list_attachments(&tx_id, &user.id).await?;
+    Ok(Json(response))
+}
+
+#[get("/<id>/attachments/<attachment_id>")]
+pub async fn download_attachment(
+    pool: &State<PgPool>,
+    config: &State<Config>,
+    user: CurrentUser,
+    dek: Dek,
+    id: &str,
+    attachment_id: &str,
+) -> Result<AttachmentDownload<ByteStream![Vec<u8>]>, AppError> {
+    let (tx_id, attachment_id) = parse_ids(id, attachment_id)?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = AttachmentService::new(&repo, &config.attachments);
+    let mut reader = service.open_attachment(&attachment_id, &tx_id, &user.id, &dek).await?;
+
+    let content_type = ContentType::parse_flexible(&reader.content_type).unwrap_or(ContentType::Binary);
+    let disposition = Header::new("Content-Disposition", content_disposition(&reader.file_name));
+
+    // The status line is already sent once chunks stream, so a failure past
+    // this point can only end the body early.
+    let body = ByteStream! {
+        loop {
+            match reader.next_chunk().await {
+                Ok(Some(chunk)) => yield chunk,
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::error!(error = %e, "attachment download aborted");
+                    break;
+                }
+            }
+        }
+    };
+
+    Ok(AttachmentDownload {
+        body,
+        content_type,
+        disposition,
+        nosniff: Header::new("X-Content-Type-Options", "nosniff"),
+    })
+}
+
+#[delete("/<id>/attachments/<attachment_id>")]
+pub async fn delete_attachment(pool: &State<PgPool>, config: &State<Config>, user: CurrentUser, id: &str, attachment_id: &str) -> Result<Status, AppError> {
+    let (tx_id, attachment_id) = parse_ids(id, attachment_id)?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = AttachmentService::new(&repo, &config.attachments);
+    service.delete_attachment(&attachment_id, &tx_id, &user.id).await?;
+    Ok(Status::NoContent)
+}
@@ -0,0 +1,19 @@
+use rocket::State;
+use rocket::get;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::CurrentUser;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::transactions::TransactionHistoryEntry;
+use crate::error::app_error::AppError;
+use crate::service::transaction::TransactionService;
+
+#[get("/<id>/history")]
+pub async fn get_transaction_history(pool: &State<PgPool>, user: CurrentUser, id: &str) -> Result<Json<Vec<TransactionHistoryEntry>>, AppError> {
+    let tx_id = Uuid::parse_str(id).map_err(|e| AppError::uuid("Invalid transaction id", e))?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = TransactionService::new(&repo);
+    Ok(Json(service.history(&tx_id, &user.id).await?))
+}
@@ -1,17 +1,34 @@
+mod attachments;
 mod batch;
+mod clear;
 mod create;
 mod delete;
+mod history;
 mod list;
+mod page;
 mod range;
+mod restore;
+mod unclear;
+mod unlock;
 mod update;
 
 pub fn routes() -> Vec<rocket::Route> {
     rocket::routes![
         range::list_transactions_range,
         list::list_transactions,
+        page::list_transactions_page,
         create::create_transaction,
         batch::batch_create_transactions,
         update::update_transaction,
         delete::delete_transaction,
+        restore::restore_transaction,
+        history::get_transaction_history,
+        clear::clear_transaction,
+        unclear::unclear_transaction,
+        unlock::unlock_transaction,
+        attachments::upload_attachment,
+        attachments::list_attachments,
+        attachments::download_attachment,
+        attachments::delete_attachment,
     ]
 }
@@ -0,0 +1,82 @@
+use rocket::State;
+use rocket::get;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::CurrentUser;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::transactions::TransactionListResponse;
+use crate::error::app_error::AppError;
+use crate::models::pagination::{CursorParams, TransactionFilters};
+use crate::service::transaction::{TransactionService, parse_date, parse_direction};
+
+fn parse_uuid(value: Option<String>, label: &str) -> Result<Option<Uuid>, AppError> {
+    match value {
+        Some(ref s) if !s.is_empty() && s != "null" => Ok(Some(Uuid::parse_str(s).map_err(|e| AppError::uuid(label, e))?)),
+        _ => Ok(None),
+    }
+}
+
+#[get("/page?<periodId>&<from>&<to>&<accountId>&<categoryId>&<vendorId>&<tagId>&<direction>&<uncategorized>&<cursor>&<limit>")]
+#[allow(non_snake_case, clippy::too_many_arguments)]
+pub async fn list_transactions_page(
+    pool: &State<PgPool>,
+    user: CurrentUser,
+    periodId: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    accountId: Option<String>,
+    categoryId: Option<String>,
+    vendorId: Option<String>,
+    tagId: Option<String>,
+    direction: Option<String>,
+    uncategorized: Option<String>,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Json<TransactionListResponse>, AppError> {
+    let period_id = parse_uuid(periodId, "Invalid periodId")?;
+    let date_from = from.as_deref().map(parse_date).transpose()?;
+    let date_to = to.as_deref().map(parse_date).transpose()?;
+    if period_id.is_some() && (date_from.is_some() || date_to.is_some()) {
+        return Err(AppError::BadRequest("periodId cannot be combined with from/to".to_string()));
+    }
+    if let (Some(from), Some(to)) = (date_from, date_to)
+        && from > to
+    {
+        return Err(AppError::BadRequest("'from' must be <= 'to'".to_string()));
+    }
+
+    let uncategorized = match uncategorized.as_deref() {
+        None | Some("false") => false,
+        Some("true") => true,
+        Some(other) => return Err(AppError::BadRequest(format!("Invalid uncategorized '{}'. Must be true or false", other))),
+    };
+    let category_id = parse_uuid(categoryId, "Invalid categoryId")?;
+    if uncategorized && category_id.is_some() {
+        return Err(AppError::BadRequest("categoryId cannot be combined with uncategorized".to_string()));
+    }
+
+    let filters = TransactionFilters {
+        account_ids: parse_uuid(accountId, "Invalid accountId")?.into_iter().collect(),
+        category_ids: category_id.into_iter().collect(),
+        direction: direction.as_deref().map(parse_direction).transpose()?,
+        vendor_ids: parse_uuid(vendorId, "Invalid vendorId")?.into_iter().collect(),
+        tag_ids: parse_uuid(tagId, "Invalid tagId")?.into_iter().collect(),
+        date_from,
+        date_to,
+        search: None,
+        uncategorized,
+    };
+    if limit == Some(0) {
+        return Err(AppError::BadRequest("limit must be >= 1".to_string()));
+    }
+    let params = CursorParams {
+        cursor: parse_uuid(cursor, "Invalid cursor")?,
+        limit: limit.map(i64::from),
+    };
+
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = TransactionService::new(&repo);
+    Ok(Json(service.list_page(filters, period_id.as_ref(), &params, &user.id).await?))
+}
@@ -0,0 +1,21 @@
+use rocket::State;
+use rocket::post;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::CurrentUser;
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::transactions::EncryptedTransactionResponse;
+use crate::error::app_error::AppError;
+use crate::service::transaction::TransactionService;
+
+#[post("/<id>/restore")]
+pub async fn restore_transaction(pool: &State<PgPool>, user: CurrentUser, dek: Dek, id: &str) -> Result<Json<EncryptedTransactionResponse>, AppError> {
+    let tx_id = Uuid::parse_str(id).map_err(|e| AppError::uuid("Invalid transaction id", e))?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = TransactionService::new(&repo);
+    let response = service.restore_transaction(&tx_id, &user.id, &dek).await?;
+    Ok(Json(response))
+}
@@ -0,0 +1,19 @@
+use rocket::State;
+use rocket::http::Status;
+use rocket::post;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::CurrentUser;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::error::app_error::AppError;
+use crate::service::reconciliation::ReconciliationService;
+
+#[post("/<id>/unlock")]
+pub async fn unlock_transaction(pool: &State<PgPool>, user: CurrentUser, id: &str) -> Result<Status, AppError> {
+    let uuid = Uuid::parse_str(id).map_err(|e| AppError::uuid("Invalid transaction id", e))?;
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let service = ReconciliationService::new(&repo);
+    service.unlock_transaction(&uuid, &user.id).await?;
+    Ok(Status::NoContent)
+}
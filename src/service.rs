@@ -1,12 +1,17 @@
 pub mod account;
+pub mod attachment;
 pub mod auth;
+pub mod calendar;
 pub mod category;
 pub mod currency;
+pub mod dashboard;
 pub mod email;
 pub mod onboarding;
 pub mod period;
+pub mod reconciliation;
 pub mod settings;
 pub mod subscription;
+pub mod tag;
 pub mod transaction;
 pub mod two_factor;
 pub mod unlock;
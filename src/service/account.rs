@@ -1,14 +1,27 @@
-use crate::crypto::Dek;
+use std::collections::HashMap;
+
+use crate::crypto::{Dek, EnvelopeAad};
+use crate::database::account::AccountMovementRow;
 use crate::database::postgres_repository::{PostgresRepository, is_foreign_key_violation};
+use crate::database::transaction::account_balance_delta;
 use crate::dto::accounts::{
-    AccountListResponse, AccountOptionListResponse, AccountOptionResponse, AccountStatus, AdjustBalanceRequest, CreateAccountRequest, EncryptedAccountResponse,
-    UpdateAccountRequest, b64,
+    AccountBalanceHistoryItem, AccountBalanceHistoryPoint, AccountBalanceHistoryResponse, AccountLedgerCheckResponse, AccountListResponse,
+    AccountOptionListResponse, AccountOptionResponse, AccountStatus, AccountsBalanceHistoryResponse, AdjustBalanceRequest, CreateAccountRequest,
+    EncryptedAccountResponse, EnvelopeFailureResponse, LedgerVerificationResponse, TopUpCycle, TransactionLedgerCheckResponse, UpdateAccountRequest,
+    VerifyLedgerRequest, b64,
 };
-use crate::dto::common::PaginatedResponse;
+use crate::dto::common::{Date, PaginatedResponse};
 use crate::error::app_error::AppError;
 use crate::models::account::Account;
+use crate::models::audit::audit_events;
+use crate::models::category::CategoryType;
+use crate::service::transaction::parse_date;
+use chrono::NaiveDate;
 use uuid::Uuid;
 
+/// Longest range a balance history covers, in days.
+pub const MAX_BALANCE_HISTORY_DAYS: i64 = 366;
+
 pub struct AccountService<'a> {
     repository: &'a PostgresRepository,
 }
@@ -54,11 +67,13 @@ impl<'a> AccountService<'a> {
     }
 
     pub async fn create_account(&self, request: &CreateAccountRequest, user_id: &Uuid, dek: &Dek) -> Result<EncryptedAccountResponse, AppError> {
+        validate_top_up_day(request)?;
         let account = self.repository.create_account(request, user_id, dek).await.map_err(map_fk_violation)?;
         Ok(to_encrypted_response(&account))
     }
 
     pub async fn update_account(&self, id: &Uuid, request: &UpdateAccountRequest, user_id: &Uuid, dek: &Dek) -> Result<EncryptedAccountResponse, AppError> {
+        validate_top_up_day(request)?;
         let account = self.repository.update_account(id, request, user_id, dek).await.map_err(map_fk_violation)?;
         Ok(to_encrypted_response(&account))
     }
@@ -79,6 +94,237 @@ impl<'a> AccountService<'a> {
         let account = self.repository.adjust_balance(id, request.new_balance, user_id, dek).await?;
         Ok(to_encrypted_response(&account))
     }
+
+    /// Closing balance of each day in `from..=to`, walked back from the
+    /// current balance through the effective transactions dated after it.
+    pub async fn balance_history(
+        &self,
+        id: &Uuid,
+        user_id: &Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+        dek: &Dek,
+    ) -> Result<AccountBalanceHistoryResponse, AppError> {
+        let account = self
+            .repository
+            .get_account_by_id(id, user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Account not found".to_string()))?;
+        let movements = self.decrypted_movements(user_id, &[account.id], from, dek).await?;
+        daily_balances(&account, &movements, from, to, user_id, dek)
+    }
+
+    /// `balance_history` for every account of the user, from one ledger
+    /// read.
+    pub async fn balance_histories(&self, user_id: &Uuid, from: NaiveDate, to: NaiveDate, dek: &Dek) -> Result<AccountsBalanceHistoryResponse, AppError> {
+        let accounts = self.repository.list_accounts(user_id).await?;
+        let ids: Vec<Uuid> = accounts.iter().map(|a| a.id).collect();
+        let movements = self.decrypted_movements(user_id, &ids, from, dek).await?;
+        accounts
+            .iter()
+            .map(|account| {
+                Ok(AccountBalanceHistoryItem {
+                    account_id: account.id,
+                    history: daily_balances(account, &movements, from, to, user_id, dek)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Replay the ledger against the stored balances and transaction sums,
+    /// repairing drift when asked. A repair that changed anything is
+    /// recorded in the security audit log with the adjustment rows it
+    /// booked.
+    pub async fn verify_ledger(
+        &self,
+        request: &VerifyLedgerRequest,
+        user_id: &Uuid,
+        dek: &Dek,
+        client_ip: Option<String>,
+        user_agent: Option<String>,
+    ) -> Result<LedgerVerificationResponse, AppError> {
+        let verification = self.repository.verify_ledger(user_id, dek, request.repair).await?;
+
+        if verification.repaired {
+            let account_ids: Vec<Uuid> = verification
+                .accounts
+                .iter()
+                .filter(|a| a.stored_balance != a.ledger_balance)
+                .map(|a| a.account_id)
+                .collect();
+            let adjustment_ids: Vec<Uuid> = verification.accounts.iter().filter_map(|a| a.adjustment_id).collect();
+            let transaction_ids: Vec<Uuid> = verification.transactions.iter().map(|t| t.transaction_id).collect();
+            let _ = self
+                .repository
+                .create_security_audit_log(
+                    Some(user_id),
+                    audit_events::LEDGER_REPAIRED,
+                    true,
+                    client_ip,
+                    user_agent,
+                    Some(serde_json::json!({
+                        "account_ids": account_ids,
+                        "adjustment_ids": adjustment_ids,
+                        "transaction_ids": transaction_ids,
+                    })),
+                )
+                .await;
+        }
+
+        let consistent = verification.envelope_failures.is_empty()
+            && verification.transactions.is_empty()
+            && verification.accounts.iter().all(|a| a.stored_balance == a.ledger_balance);
+        Ok(LedgerVerificationResponse {
+            consistent,
+            repaired: verification.repaired,
+            accounts: verification
+                .accounts
+                .into_iter()
+                .map(|a| AccountLedgerCheckResponse {
+                    account_id: a.account_id,
+                    stored_balance: a.stored_balance,
+                    ledger_balance: a.ledger_balance,
+                    drift: a.stored_balance - a.ledger_balance,
+                    baselined: a.baselined,
+                    adjustment_id: a.adjustment_id,
+                })
+                .collect(),
+            transactions: verification
+                .transactions
+                .into_iter()
+                .map(|t| TransactionLedgerCheckResponse {
+                    transaction_id: t.transaction_id,
+                    stored_sum: t.stored_sum,
+                    ledger_sum: t.ledger_sum,
+                })
+                .collect(),
+            envelopes_checked: verification.envelopes_checked,
+            envelope_failures: verification
+                .envelope_failures
+                .into_iter()
+                .map(|f| EnvelopeFailureResponse {
+                    table: f.table.to_string(),
+                    column: f.column.to_string(),
+                    row_id: f.row_id,
+                    seq: f.seq,
+                })
+                .collect(),
+        })
+    }
+
+    async fn decrypted_movements(&self, user_id: &Uuid, account_ids: &[Uuid], from: NaiveDate, dek: &Dek) -> Result<Vec<(AccountMovementRow, i64)>, AppError> {
+        self.repository
+            .list_account_movements_since(user_id, account_ids, from)
+            .await?
+            .into_iter()
+            .map(|row| {
+                let amount = dek.decrypt_i64_for(&row.amount_enc, &EnvelopeAad::new("transaction", "amount_enc", row.id, *user_id))?;
+                Ok((row, amount))
+            })
+            .collect()
+    }
+}
+
+/// Parse and check the required `from`/`to` query dates of a balance
+/// history request.
+pub fn parse_history_range(from: Option<String>, to: Option<String>) -> Result<(NaiveDate, NaiveDate), AppError> {
+    let from = from.ok_or_else(|| AppError::BadRequest("from is required (YYYY-MM-DD)".to_string()))?;
+    let to = to.ok_or_else(|| AppError::BadRequest("to is required (YYYY-MM-DD)".to_string()))?;
+    let (from, to) = (parse_date(&from)?, parse_date(&to)?);
+    if from > to {
+        return Err(AppError::BadRequest("'from' must be <= 'to'".to_string()));
+    }
+    if (to - from).num_days() >= MAX_BALANCE_HISTORY_DAYS {
+        return Err(AppError::BadRequest(format!(
+            "Balance history covers at most {} days",
+            MAX_BALANCE_HISTORY_DAYS
+        )));
+    }
+    Ok((from, to))
+}
+
+/// Change a movement makes to `account`'s balance, or `None` when it does
+/// not touch the account. Adjustments carry the balance change itself;
+/// other uncategorized transactions never moved a balance.
+fn movement_delta(account: &Account, row: &AccountMovementRow, amount: i64) -> Option<i64> {
+    let from_side = row.from_account_id == account.id;
+    let to_side = row.to_account_id == Some(account.id);
+    if !from_side && !to_side {
+        return None;
+    }
+    if row.is_adjustment {
+        return Some(if from_side { amount } else { 0 });
+    }
+    let Some(category_type) = row.category_type else {
+        return Some(0);
+    };
+    let mut delta = 0;
+    if from_side {
+        delta += account_balance_delta(category_type, account.account_type, amount, false);
+    }
+    if to_side && category_type == CategoryType::Transfer {
+        delta += account_balance_delta(category_type, account.account_type, amount, true);
+    }
+    Some(delta)
+}
+
+fn daily_balances(
+    account: &Account,
+    movements: &[(AccountMovementRow, i64)],
+    from: NaiveDate,
+    to: NaiveDate,
+    user_id: &Uuid,
+    dek: &Dek,
+) -> Result<Vec<AccountBalanceHistoryPoint>, AppError> {
+    let overflow = || AppError::BadRequest("account balance overflow".to_string());
+    let aad = EnvelopeAad::new("account", "current_balance_enc", account.id, *user_id);
+    let mut closing = dek.decrypt_i64_for(&account.current_balance_enc, &aad)?;
+
+    // Per-day (delta, count) inside the range; later rows are undone up
+    // front to reach the closing balance of `to`.
+    let mut days: HashMap<NaiveDate, (i64, i64)> = HashMap::new();
+    for (row, amount) in movements {
+        let Some(delta) = movement_delta(account, row, *amount) else {
+            continue;
+        };
+        if row.occurred_at > to {
+            closing = closing.checked_sub(delta).ok_or_else(overflow)?;
+        } else {
+            let day = days.entry(row.occurred_at).or_default();
+            day.0 = day.0.checked_add(delta).ok_or_else(overflow)?;
+            day.1 += 1;
+        }
+    }
+
+    let mut points = Vec::new();
+    let mut day = to;
+    while day >= from {
+        let (delta, count) = days.get(&day).copied().unwrap_or_default();
+        points.push(AccountBalanceHistoryPoint {
+            date: Date(day),
+            balance: closing,
+            transaction_count: count,
+        });
+        closing = closing.checked_sub(delta).ok_or_else(overflow)?;
+        let Some(previous) = day.pred_opt() else { break };
+        day = previous;
+    }
+    points.reverse();
+    Ok(points)
+}
+
+/// `top_up_day` is read by `top_up_cycle`; see [`TopUpCycle`].
+fn validate_top_up_day(request: &CreateAccountRequest) -> Result<(), AppError> {
+    let (Some(cycle), Some(day)) = (request.top_up_cycle, request.top_up_day) else {
+        return Ok(());
+    };
+    if cycle.accepts_day(day) {
+        return Ok(());
+    }
+    Err(AppError::BadRequest(match cycle {
+        TopUpCycle::Weekly | TopUpCycle::BiWeekly => format!("topUpDay must be 1 (Monday) to 7 (Sunday) for a {} cycle", cycle.as_str()),
+        TopUpCycle::Monthly => "topUpDay must be 1 to 31 for a monthly cycle".to_string(),
+    }))
 }
 
 fn map_fk_violation(err: AppError) -> AppError {
@@ -106,9 +352,70 @@ fn to_encrypted_response(account: &Account) -> EncryptedAccountResponse {
         spend_limit_enc: account.spend_limit_enc.as_deref().map(b64),
         next_transfer_amount_enc: account.next_transfer_amount_enc.as_deref().map(b64),
         top_up_amount_enc: account.top_up_amount_enc.as_deref().map(b64),
-        top_up_cycle: account.top_up_cycle.clone(),
-        top_up_day: account.top_up_day,
-        statement_close_day: account.statement_close_day,
-        payment_due_day: account.payment_due_day,
+        top_up_cycle: account.top_up_cycle.as_deref().and_then(TopUpCycle::from_db),
+        top_up_day: account.top_up_day.map(i32::from),
+        statement_close_day: account.statement_close_day.map(i32::from),
+        payment_due_day: account.payment_due_day.map(i32::from),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::account::AccountType;
+
+    fn account(account_type: AccountType) -> Account {
+        Account {
+            id: Uuid::new_v4(),
+            account_type,
+            currency_id: Uuid::nil(),
+            is_archived: false,
+            name_enc: Vec::new(),
+            color_enc: Vec::new(),
+            current_balance_enc: Vec::new(),
+            spend_limit_enc: None,
+            next_transfer_amount_enc: None,
+            top_up_amount_enc: None,
+            top_up_cycle: None,
+            top_up_day: None,
+            statement_close_day: None,
+            payment_due_day: None,
+        }
+    }
+
+    fn movement(from: Uuid, to: Option<Uuid>, category_type: Option<CategoryType>, is_adjustment: bool) -> AccountMovementRow {
+        AccountMovementRow {
+            id: Uuid::new_v4(),
+            occurred_at: NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(),
+            from_account_id: from,
+            to_account_id: to,
+            category_type,
+            is_adjustment,
+            amount_enc: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn movement_delta_follows_balance_sign_rules() {
+        let checking = account(AccountType::Checking);
+        let card = account(AccountType::CreditCard);
+        let other = Uuid::new_v4();
+
+        let purchase = movement(checking.id, None, Some(CategoryType::Outgoing), false);
+        assert_eq!(movement_delta(&checking, &purchase, 500), Some(-500));
+        assert_eq!(movement_delta(&card, &purchase, 500), None);
+
+        let card_purchase = movement(card.id, None, Some(CategoryType::Outgoing), false);
+        assert_eq!(movement_delta(&card, &card_purchase, 500), Some(500));
+
+        let payoff = movement(checking.id, Some(card.id), Some(CategoryType::Transfer), false);
+        assert_eq!(movement_delta(&checking, &payoff, 700), Some(-700));
+        assert_eq!(movement_delta(&card, &payoff, 700), Some(-700));
+
+        let uncategorized = movement(checking.id, Some(other), None, false);
+        assert_eq!(movement_delta(&checking, &uncategorized, 300), Some(0));
+
+        let adjustment = movement(card.id, None, None, true);
+        assert_eq!(movement_delta(&card, &adjustment, -250), Some(-250));
     }
 }
@@ -0,0 +1,76 @@
+use tokio::io::AsyncRead;
+use uuid::Uuid;
+
+use crate::config::AttachmentConfig;
+use crate::crypto::Dek;
+use crate::database::attachment::AttachmentReader;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::transactions::EncryptedAttachmentResponse;
+use crate::error::app_error::AppError;
+use crate::models::attachment::NewAttachment;
+
+/// Longest accepted file name, in characters.
+const MAX_FILE_NAME_LENGTH: usize = 255;
+
+const DEFAULT_CONTENT_TYPE: &str = "application/octet-stream";
+
+pub struct AttachmentService<'a> {
+    repository: &'a PostgresRepository,
+    config: &'a AttachmentConfig,
+}
+
+impl<'a> AttachmentService<'a> {
+    pub fn new(repository: &'a PostgresRepository, config: &'a AttachmentConfig) -> Self {
+        AttachmentService { repository, config }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn upload_attachment<R: AsyncRead + Unpin>(
+        &self,
+        transaction_id: &Uuid,
+        user_id: &Uuid,
+        dek: &Dek,
+        file_name: Option<&str>,
+        content_type: Option<&str>,
+        size_bytes: u64,
+        content: R,
+    ) -> Result<EncryptedAttachmentResponse, AppError> {
+        let file_name = file_name.map(str::trim).filter(|n| !n.is_empty()).unwrap_or("attachment");
+        if file_name.chars().count() > MAX_FILE_NAME_LENGTH {
+            return Err(AppError::BadRequest(format!("File name must be at most {} characters", MAX_FILE_NAME_LENGTH)));
+        }
+        if size_bytes == 0 {
+            return Err(AppError::BadRequest("Attachment is empty".to_string()));
+        }
+        if size_bytes > self.config.max_size_bytes {
+            return Err(AppError::BadRequest(format!(
+                "Attachment exceeds the {} byte limit",
+                self.config.max_size_bytes
+            )));
+        }
+
+        let upload = NewAttachment {
+            file_name,
+            content_type: content_type.unwrap_or(DEFAULT_CONTENT_TYPE),
+            size_bytes,
+        };
+        let attachment = self
+            .repository
+            .create_attachment(transaction_id, user_id, dek, &upload, content, self.config)
+            .await?;
+        Ok(attachment.into())
+    }
+
+    pub async fn list_attachments(&self, transaction_id: &Uuid, user_id: &Uuid) -> Result<Vec<EncryptedAttachmentResponse>, AppError> {
+        let attachments = self.repository.list_attachments(transaction_id, user_id).await?;
+        Ok(attachments.into_iter().map(EncryptedAttachmentResponse::from).collect())
+    }
+
+    pub async fn open_attachment(&self, id: &Uuid, transaction_id: &Uuid, user_id: &Uuid, dek: &Dek) -> Result<AttachmentReader, AppError> {
+        self.repository.open_attachment(id, transaction_id, user_id, dek).await
+    }
+
+    pub async fn delete_attachment(&self, id: &Uuid, transaction_id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        self.repository.delete_attachment(id, transaction_id, user_id).await
+    }
+}
@@ -1,11 +1,14 @@
 // src/service/auth.rs
 
 use crate::Config;
+use crate::crypto::Dek;
 use crate::database::postgres_repository::{PostgresRepository, is_unique_violation};
 use crate::error::app_error::AppError;
 use crate::models::audit::audit_events;
+use crate::models::password_reset::PasswordReset;
 use crate::models::rate_limit::RateLimitStatus;
 use crate::models::user::User;
+use crate::session_dek::SessionDekStore;
 use chrono::Utc;
 use sha2::{Digest, Sha256};
 use uuid::Uuid;
@@ -182,6 +185,8 @@ impl<'a> AuthService<'a> {
     // ─── V2 methods ─────────────────────────────────────────────────────────
 
     /// Register a new user, create default resources, and start a session.
+    /// The recovery wrap, when given, is stored with the user row so a
+    /// failed signup never leaves an account without it.
     #[allow(clippy::too_many_arguments)]
     pub async fn register(
         &self,
@@ -192,15 +197,20 @@ impl<'a> AuthService<'a> {
         client_ip: Option<&str>,
         wrapped_dek: Option<&[u8]>,
         dek_wrap_params: Option<&serde_json::Value>,
+        recovery_wrap: Option<(&[u8], &serde_json::Value)>,
     ) -> Result<(User, Uuid), AppError> {
-        let user = self.repo.create_user(name, email, password, wrapped_dek, dek_wrap_params).await.map_err(|e| {
-            if let AppError::Db { ref source, .. } = e
-                && is_unique_violation(source)
-            {
-                return AppError::UserAlreadyExists(email.to_string());
-            }
-            e
-        })?;
+        let user = self
+            .repo
+            .create_user(name, email, password, wrapped_dek, dek_wrap_params, recovery_wrap)
+            .await
+            .map_err(|e| {
+                if let AppError::Db { ref source, .. } = e
+                    && is_unique_violation(source)
+                {
+                    return AppError::UserAlreadyExists(email.to_string());
+                }
+                e
+            })?;
 
         // Best-effort: create default settings (no currency chosen yet — done in onboarding)
         if let Err(e) = self.repo.create_default_settings(&user.id).await {
@@ -352,13 +362,19 @@ impl<'a> AuthService<'a> {
     }
 
     /// Change password for V2 (maps wrong-current-password to 401).
-    /// Invalidates all other sessions after successful change.
+    /// The new password hash and the DEK re-wrapped under it are stored
+    /// together. Invalidates all other sessions and every bearer token after
+    /// a successful change, and locks their cached DEKs.
+    #[allow(clippy::too_many_arguments)]
     pub async fn change_password(
         &self,
+        dek_store: &dyn SessionDekStore,
         user_id: &Uuid,
         current_session_id: Option<Uuid>,
         current_password: &str,
         new_password: &str,
+        wrapped_dek: &[u8],
+        dek_wrap_params: &serde_json::Value,
         client_ip: Option<String>,
         user_agent: Option<String>,
     ) -> Result<(), AppError> {
@@ -369,7 +385,9 @@ impl<'a> AuthService<'a> {
             .await
             .map_err(|_| AppError::InvalidCredentials)?;
 
-        self.repo.update_user_password(user_id, new_password).await?;
+        self.repo
+            .update_user_password_and_wrapped_dek(user_id, new_password, wrapped_dek, dek_wrap_params)
+            .await?;
 
         // Invalidate all other sessions (keep the current one)
         if let Some(session_id) = current_session_id {
@@ -381,6 +399,9 @@ impl<'a> AuthService<'a> {
         // Revoke all bearer tokens — force re-authentication
         let _ = self.repo.revoke_all_for_user(user_id).await;
 
+        // Other devices must unlock again with the new password
+        forget_user_deks(dek_store, user_id, current_session_id.as_ref()).await;
+
         let _ = self
             .repo
             .create_security_audit_log(Some(user_id), audit_events::PASSWORD_CHANGED, true, client_ip, user_agent, None)
@@ -396,6 +417,63 @@ impl<'a> AuthService<'a> {
         Ok(())
     }
 
+    /// Rotate the user's DEK from `old_dek` (the session's key) to `new_dek`.
+    /// Re-encrypts every ciphertext column the user owns, then swaps the
+    /// stored wrapped DEK. Safe to re-run with the same `new_dek` after an
+    /// interruption. Returns the number of rows re-encrypted and whether a
+    /// recovery key without a wrap of the new DEK was cleared.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn rotate_dek(
+        &self,
+        user_id: &Uuid,
+        old_dek: &Dek,
+        new_dek: &Dek,
+        new_wrapped_dek: &[u8],
+        new_dek_wrap_params: &serde_json::Value,
+        new_recovery_wrap: Option<(&[u8], &serde_json::Value)>,
+        client_ip: Option<String>,
+        user_agent: Option<String>,
+    ) -> Result<(u64, bool), AppError> {
+        if old_dek.as_bytes() == new_dek.as_bytes() {
+            return Err(AppError::BadRequest("New DEK must differ from the current DEK".to_string()));
+        }
+
+        // The recovery key wraps the DEK itself; without a wrap of the new
+        // DEK it would hand out the retired key on the next reset.
+        if new_recovery_wrap.is_none() && self.repo.get_recovery_wrapped_dek(user_id).await?.0.is_some() {
+            return Err(AppError::BadRequest(
+                "recoveryWrappedDek and recoveryWrapParams are required while a recovery key is configured".to_string(),
+            ));
+        }
+
+        self.repo
+            .begin_dek_rotation(user_id, new_dek, new_wrapped_dek, new_dek_wrap_params, new_recovery_wrap)
+            .await?;
+
+        let batched = self.repo.reencrypt_user_data(user_id, old_dek, new_dek).await?;
+        let (swept, recovery_key_cleared) = self
+            .repo
+            .complete_dek_rotation(user_id, old_dek, new_dek, new_wrapped_dek, new_dek_wrap_params)
+            .await?;
+
+        let _ = self
+            .repo
+            .create_security_audit_log(
+                Some(user_id),
+                audit_events::DEK_ROTATED,
+                true,
+                client_ip,
+                user_agent,
+                Some(serde_json::json!({
+                    "rows_reencrypted": batched + swept,
+                    "recovery_key_cleared": recovery_key_cleared,
+                })),
+            )
+            .await;
+
+        Ok((batched + swept, recovery_key_cleared))
+    }
+
     /// Request a password reset email. Always returns Ok for anti-enumeration.
     pub async fn forgot_password(&self, email: &str) -> Result<(), AppError> {
         let user = match self.repo.get_user_by_email(email).await? {
@@ -442,8 +520,8 @@ impl<'a> AuthService<'a> {
         Ok(())
     }
 
-    /// Reset a password using a token. Returns Unauthorized on invalid/expired token.
-    pub async fn reset_password(&self, token: &str, new_password: &str) -> Result<(), AppError> {
+    /// Look up a reset token and check it is unused and unexpired.
+    async fn valid_password_reset(&self, token: &str) -> Result<PasswordReset, AppError> {
         let token_hash = hex::encode(Sha256::digest(token.as_bytes()));
 
         let reset = self.repo.get_password_reset_by_token(&token_hash).await?.ok_or(AppError::Unauthorized)?;
@@ -452,8 +530,45 @@ impl<'a> AuthService<'a> {
             return Err(AppError::Unauthorized);
         }
 
-        // Update password
-        self.repo.update_user_password(&reset.user_id, new_password).await?;
+        Ok(reset)
+    }
+
+    /// Return the recovery-key wrapping of the DEK for the user a reset token
+    /// belongs to, without consuming the token. The client unwraps it with the
+    /// recovery key and re-wraps the DEK under the new password before calling
+    /// `reset_password`.
+    pub async fn get_recovery_key_for_reset(&self, token: &str) -> Result<(Vec<u8>, serde_json::Value), AppError> {
+        let reset = self.valid_password_reset(token).await?;
+
+        match self.repo.get_recovery_wrapped_dek(&reset.user_id).await? {
+            (Some(wrapped), Some(params)) => Ok((wrapped, params)),
+            _ => Err(AppError::NotFound("No recovery key configured".to_string())),
+        }
+    }
+
+    /// Reset a password using a token. Returns Unauthorized on invalid/expired token.
+    ///
+    /// When `new_wrap` is provided (the DEK recovered via the recovery key and
+    /// re-wrapped under the new password), it replaces the stored wrapped DEK
+    /// in the same statement as the password hash.
+    pub async fn reset_password(
+        &self,
+        dek_store: &dyn SessionDekStore,
+        token: &str,
+        new_password: &str,
+        new_wrap: Option<(&[u8], &serde_json::Value)>,
+    ) -> Result<(), AppError> {
+        let reset = self.valid_password_reset(token).await?;
+
+        // Update password (and the wrapped DEK, if the client recovered it)
+        match new_wrap {
+            Some((wrapped_dek, dek_wrap_params)) => {
+                self.repo
+                    .update_user_password_and_wrapped_dek(&reset.user_id, new_password, wrapped_dek, dek_wrap_params)
+                    .await?
+            }
+            None => self.repo.update_user_password(&reset.user_id, new_password).await?,
+        }
 
         // Mark token as used
         self.repo.mark_password_reset_used(&reset.id).await?;
@@ -461,6 +576,7 @@ impl<'a> AuthService<'a> {
         // Invalidate all sessions and revoke all bearer tokens
         let sessions_invalidated = self.repo.invalidate_all_user_sessions(&reset.user_id).await?;
         let _ = self.repo.revoke_all_for_user(&reset.user_id).await;
+        forget_user_deks(dek_store, &reset.user_id, None).await;
 
         // Clean up remaining reset tokens
         self.repo.delete_password_resets_for_user(&reset.user_id).await?;
@@ -473,7 +589,10 @@ impl<'a> AuthService<'a> {
                 true,
                 None,
                 None,
-                Some(serde_json::json!({"sessions_invalidated": sessions_invalidated})),
+                Some(serde_json::json!({
+                    "sessions_invalidated": sessions_invalidated,
+                    "dek_rewrapped": new_wrap.is_some(),
+                })),
             )
             .await;
 
@@ -571,3 +690,12 @@ impl<'a> AuthService<'a> {
         Ok(())
     }
 }
+
+/// Lock every device of a user except `keep` by dropping their cached DEKs.
+/// Best-effort: callers run this after the sessions/tokens are already gone,
+/// so a leftover entry is unreachable and ages out with its TTL.
+pub(crate) async fn forget_user_deks(dek_store: &dyn SessionDekStore, user_id: &Uuid, keep: Option<&Uuid>) {
+    if let Err(e) = dek_store.remove_all_for_user(user_id, keep).await {
+        tracing::warn!(error = %e, "Failed to drop session DEKs for user {}", user_id);
+    }
+}
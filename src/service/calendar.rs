@@ -0,0 +1,262 @@
+//! Upcoming calendar — every dated event of the coming days in one list.
+//!
+//! Nothing here is stored: subscription charges are projected from their
+//! billing schedule, credit-card and allowance dates from the plaintext
+//! schedule columns of the account, and period boundaries are read from
+//! the user's budget periods.
+//!
+//! Schedule days outside their range, and stored top-up cycles and days
+//! that predate their validation, produce no events rather than an error:
+//! the credit-card day columns are not validated on write. Bi-weekly
+//! top-ups are not projected, since the week they start in is not stored.
+
+use chrono::{Datelike, Days, NaiveDate, Utc, Weekday};
+use uuid::Uuid;
+
+use crate::crypto::{Dek, EnvelopeAad, RowAad};
+use crate::database::budget_period::base_month_start_date;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::accounts::{TopUpCycle, b64};
+use crate::dto::calendar::{UpcomingCalendarResponse, UpcomingEvent, UpcomingEventKind};
+use crate::dto::common::Date;
+use crate::error::app_error::AppError;
+use crate::models::account::AccountType;
+
+/// Longest window the calendar covers, in days.
+pub const MAX_UPCOMING_DAYS: u32 = 366;
+
+pub struct CalendarService<'a> {
+    repository: &'a PostgresRepository,
+}
+
+impl<'a> CalendarService<'a> {
+    pub fn new(repository: &'a PostgresRepository) -> Self {
+        CalendarService { repository }
+    }
+
+    /// Events from today through the following `days - 1` days. Names and
+    /// amounts are decrypted when `dek` is given.
+    pub async fn upcoming(&self, days: u32, user_id: &Uuid, dek: Option<&Dek>) -> Result<UpcomingCalendarResponse, AppError> {
+        let from = Utc::now().date_naive();
+        let to = from + Days::new(u64::from(days.clamp(1, MAX_UPCOMING_DAYS)) - 1);
+        let mut events: Vec<(NaiveDate, UpcomingEvent)> = Vec::new();
+
+        for charge in self.repository.list_projected_charges(user_id, from, to).await? {
+            let aad = RowAad::new("subscription", charge.subscription_id, *user_id);
+            let (name_enc, name) = reveal_string(dek, &charge.name_enc, &aad.column("name_enc"))?;
+            let (amount_enc, amount) = reveal_i64(dek, &charge.billing_amount_enc, &aad.column("billing_amount_enc"))?;
+            events.push((
+                charge.date,
+                UpcomingEvent {
+                    date: Date(charge.date),
+                    kind: UpcomingEventKind::SubscriptionCharge,
+                    source_id: charge.subscription_id,
+                    name_enc,
+                    name,
+                    amount_enc,
+                    amount,
+                },
+            ));
+        }
+
+        for account in self.repository.list_accounts(user_id).await? {
+            if account.is_archived {
+                continue;
+            }
+            let mut dated: Vec<(NaiveDate, UpcomingEventKind)> = Vec::new();
+            match account.account_type {
+                AccountType::CreditCard => {
+                    if let Some(day) = account.statement_close_day {
+                        dated.extend(monthly_dates(day.into(), from, to).into_iter().map(|d| (d, UpcomingEventKind::StatementClose)));
+                    }
+                    if let Some(day) = account.payment_due_day {
+                        dated.extend(monthly_dates(day.into(), from, to).into_iter().map(|d| (d, UpcomingEventKind::PaymentDue)));
+                    }
+                }
+                AccountType::Allowance => {
+                    if let (Some(cycle), Some(day)) = (account.top_up_cycle.as_deref().and_then(TopUpCycle::from_db), account.top_up_day) {
+                        dated.extend(
+                            top_up_dates(cycle, day.into(), from, to)
+                                .into_iter()
+                                .map(|d| (d, UpcomingEventKind::AllowanceTopUp)),
+                        );
+                    }
+                }
+                _ => {}
+            }
+            if dated.is_empty() {
+                continue;
+            }
+
+            let aad = RowAad::new("account", account.id, *user_id);
+            let (name_enc, name) = reveal_string(dek, &account.name_enc, &aad.column("name_enc"))?;
+            let (amount_enc, amount) = match &account.top_up_amount_enc {
+                Some(envelope) => reveal_i64(dek, envelope, &aad.column("top_up_amount_enc"))?,
+                None => (None, None),
+            };
+            for (date, kind) in dated {
+                let top_up = kind == UpcomingEventKind::AllowanceTopUp;
+                events.push((
+                    date,
+                    UpcomingEvent {
+                        date: Date(date),
+                        kind,
+                        source_id: account.id,
+                        name_enc: name_enc.clone(),
+                        name: name.clone(),
+                        amount_enc: amount_enc.clone().filter(|_| top_up),
+                        amount: amount.filter(|_| top_up),
+                    },
+                ));
+            }
+        }
+
+        for period in self.repository.list_budget_periods_with_boundary_between(user_id, from, to).await? {
+            let boundaries = [
+                (period.start_date, UpcomingEventKind::PeriodStart),
+                (period.end_date, UpcomingEventKind::PeriodEnd),
+            ];
+            for (date, kind) in boundaries {
+                if date < from || date > to {
+                    continue;
+                }
+                events.push((
+                    date,
+                    UpcomingEvent {
+                        date: Date(date),
+                        kind,
+                        source_id: period.id,
+                        name_enc: None,
+                        name: Some(period.name.clone()),
+                        amount_enc: None,
+                        amount: None,
+                    },
+                ));
+            }
+        }
+
+        events.sort_by_key(|(date, event)| (*date, event.kind, event.source_id));
+        Ok(UpcomingCalendarResponse {
+            from: Date(from),
+            to: Date(to),
+            events: events.into_iter().map(|(_, event)| event).collect(),
+        })
+    }
+}
+
+/// Ciphertext without a DEK, plaintext with one.
+fn reveal_string(dek: Option<&Dek>, envelope: &[u8], aad: &EnvelopeAad<'_>) -> Result<(Option<String>, Option<String>), AppError> {
+    match dek {
+        Some(dek) => Ok((None, Some(dek.decrypt_string_for(envelope, aad)?))),
+        None => Ok((Some(b64(envelope)), None)),
+    }
+}
+
+fn reveal_i64(dek: Option<&Dek>, envelope: &[u8], aad: &EnvelopeAad<'_>) -> Result<(Option<String>, Option<i64>), AppError> {
+    match dek {
+        Some(dek) => Ok((None, Some(dek.decrypt_i64_for(envelope, aad)?))),
+        None => Ok((Some(b64(envelope)), None)),
+    }
+}
+
+/// `day` of every month between `from` and `to`, clamped to the last day
+/// of shorter months.
+fn monthly_dates(day: i32, from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+    if !(1..=31).contains(&day) {
+        return Vec::new();
+    }
+    let mut dates = Vec::new();
+    let (mut year, mut month) = (from.year(), from.month());
+    while (year, month) <= (to.year(), to.month()) {
+        if let Some(date) = base_month_start_date(year, month, day)
+            && date >= from
+            && date <= to
+        {
+            dates.push(date);
+        }
+        (year, month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    }
+    dates
+}
+
+/// Top-up dates of an allowance, with `day` read as [`TopUpCycle`]
+/// documents. Bi-weekly cycles have none.
+fn top_up_dates(cycle: TopUpCycle, day: i32, from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+    match cycle {
+        TopUpCycle::Weekly => TopUpCycle::weekday(day).map(|weekday| weekly_dates(weekday, from, to)).unwrap_or_default(),
+        TopUpCycle::BiWeekly => Vec::new(),
+        TopUpCycle::Monthly => monthly_dates(day, from, to),
+    }
+}
+
+fn weekly_dates(weekday: Weekday, from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+    let offset = (7 + weekday.num_days_from_monday() - from.weekday().num_days_from_monday()) % 7;
+    let mut date = from + Days::new(u64::from(offset));
+    let mut dates = Vec::new();
+    while date <= to {
+        dates.push(date);
+        date = date + Days::new(7);
+    }
+    dates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn monthly_dates_clamp_to_month_end() {
+        assert_eq!(
+            monthly_dates(31, date("2026-01-15"), date("2026-04-30")),
+            [date("2026-01-31"), date("2026-02-28"), date("2026-03-31"), date("2026-04-30")]
+        );
+        assert_eq!(monthly_dates(10, date("2026-12-11"), date("2027-01-10")), [date("2027-01-10")]);
+        assert!(monthly_dates(0, date("2026-01-01"), date("2026-12-31")).is_empty());
+        assert!(monthly_dates(32, date("2026-01-01"), date("2026-12-31")).is_empty());
+    }
+
+    #[test]
+    fn weekly_top_ups_fall_on_the_weekday() {
+        // 2026-03-02 is a Monday.
+        assert_eq!(
+            top_up_dates(TopUpCycle::Weekly, 5, date("2026-03-02"), date("2026-03-20")),
+            [date("2026-03-06"), date("2026-03-13"), date("2026-03-20")]
+        );
+        assert_eq!(
+            top_up_dates(TopUpCycle::Weekly, 1, date("2026-03-02"), date("2026-03-08")),
+            [date("2026-03-02")]
+        );
+        assert!(top_up_dates(TopUpCycle::Weekly, 0, date("2026-03-02"), date("2026-03-08")).is_empty());
+        assert_eq!(
+            top_up_dates(TopUpCycle::Weekly, 7, date("2026-03-02"), date("2026-03-08")),
+            [date("2026-03-08")]
+        );
+        assert!(top_up_dates(TopUpCycle::Weekly, 8, date("2026-03-02"), date("2026-03-31")).is_empty());
+    }
+
+    #[test]
+    fn stored_top_up_cycles_match_the_wire_values() {
+        for cycle in TopUpCycle::ALL {
+            assert_eq!(serde_json::to_value(cycle).unwrap(), cycle.as_str());
+            assert_eq!(TopUpCycle::from_db(cycle.as_str()), Some(cycle));
+        }
+        assert_eq!(TopUpCycle::from_db("daily"), None);
+    }
+
+    #[test]
+    fn fortnightly_top_ups_are_not_projected() {
+        assert!(top_up_dates(TopUpCycle::BiWeekly, 3, date("2026-03-01"), date("2026-04-30")).is_empty());
+    }
+
+    #[test]
+    fn monthly_top_ups_use_the_day_of_month() {
+        assert_eq!(
+            top_up_dates(TopUpCycle::Monthly, 15, date("2026-03-01"), date("2026-04-30")),
+            [date("2026-03-15"), date("2026-04-15")]
+        );
+    }
+}
@@ -0,0 +1,429 @@
+//! Dashboard service — aggregates computed in-process.
+//!
+//! The aggregate tables were dropped with encryption-at-rest, so every
+//! dashboard figure is derived per request: the service fetches the
+//! relevant ciphertext, decrypts it with the session DEK, and reduces it in
+//! memory. Plaintext only lives for the duration of the request; nothing is
+//! written back.
+//!
+//! Classification follows the legacy dashboard queries:
+//!   * spending: Outgoing not paid from an Allowance, plus Transfers into
+//!     an Allowance (money leaving an Allowance was already counted when
+//!     it was topped up)
+//!   * vendor spend: Outgoing with a vendor, not paid from an Allowance
+//!   * inflows / outflows: Incoming / Outgoing; Transfers move money
+//!     between the user's own accounts and count as neither
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::crypto::{Dek, EnvelopeAad};
+use crate::database::dashboard::DashboardTransactionRow;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::common::Date;
+use crate::dto::dashboard::{
+    AccountBalanceResponse, CashFlowResponse, CurrentPeriodResponse, NetPositionResponse, SpendingTrendItem, SpendingTrendResponse, TopVendorItem,
+    TopVendorsResponse,
+};
+use crate::error::app_error::AppError;
+use crate::models::account::AccountType;
+use crate::models::budget_period::BudgetPeriod;
+use crate::models::category::CategoryType;
+
+/// An effective transaction with its amount decrypted.
+struct DecryptedTransaction {
+    occurred_at: NaiveDate,
+    category_type: Option<CategoryType>,
+    from_account_type: AccountType,
+    to_account_type: Option<AccountType>,
+    vendor_id: Option<Uuid>,
+    amount: i64,
+}
+
+impl DecryptedTransaction {
+    fn decrypt(row: DashboardTransactionRow, user_id: &Uuid, dek: &Dek) -> Result<Self, AppError> {
+        let amount = dek.decrypt_i64_for(&row.amount_enc, &EnvelopeAad::new("transaction", "amount_enc", row.id, *user_id))?;
+        Ok(Self {
+            occurred_at: row.occurred_at,
+            category_type: row.category_type,
+            from_account_type: row.from_account_type,
+            to_account_type: row.to_account_type,
+            vendor_id: row.vendor_id,
+            amount,
+        })
+    }
+
+    fn is_spending(&self) -> bool {
+        match self.category_type {
+            Some(CategoryType::Outgoing) => self.from_account_type != AccountType::Allowance,
+            Some(CategoryType::Transfer) => self.to_account_type == Some(AccountType::Allowance),
+            _ => false,
+        }
+    }
+
+    fn spend_vendor(&self) -> Option<Uuid> {
+        match self.category_type {
+            Some(CategoryType::Outgoing) if self.from_account_type != AccountType::Allowance => self.vendor_id,
+            _ => None,
+        }
+    }
+}
+
+pub struct DashboardService<'a> {
+    repository: &'a PostgresRepository,
+}
+
+impl<'a> DashboardService<'a> {
+    pub fn new(repository: &'a PostgresRepository) -> Self {
+        DashboardService { repository }
+    }
+
+    pub async fn current_period(&self, period_id: Option<Uuid>, user_id: &Uuid, dek: &Dek) -> Result<CurrentPeriodResponse, AppError> {
+        let period = self.resolve_period(period_id, user_id).await?;
+        let transactions = self.decrypted_transactions(user_id, dek, period.start_date, period.end_date).await?;
+        let target = self.total_budgeted(user_id, dek).await?;
+
+        let daily_spend = daily_spend(&transactions, period.start_date, period.end_date)?;
+        let spent = checked_sum(daily_spend.iter().copied())?;
+        let today = chrono::Utc::now().date_naive();
+
+        Ok(CurrentPeriodResponse {
+            period_id: period.id,
+            period_name: period.name,
+            start_date: Date(period.start_date),
+            end_date: Date(period.end_date),
+            spent,
+            target,
+            days_in_period: daily_spend.len() as i64,
+            days_remaining: (period.end_date - today).num_days().clamp(0, daily_spend.len() as i64),
+            projected_spend: projected_spend(spent, period.start_date, period.end_date, today)?,
+            daily_spend,
+        })
+    }
+
+    /// Balances of every non-archived account, grouped the way the net
+    /// position card shows them. Credit card balances are debt owed.
+    pub async fn net_position(&self, user_id: &Uuid, dek: &Dek) -> Result<NetPositionResponse, AppError> {
+        let accounts = self.repository.list_accounts(user_id).await?;
+
+        let (mut liquid, mut protected, mut debt) = (0i64, 0i64, 0i64);
+        let mut balances = Vec::with_capacity(accounts.len());
+        for account in accounts.iter().filter(|a| !a.is_archived) {
+            let aad = EnvelopeAad::new("account", "current_balance_enc", account.id, *user_id);
+            let balance = dek.decrypt_i64_for(&account.current_balance_enc, &aad)?;
+            let group = match account.account_type {
+                AccountType::Savings => &mut protected,
+                AccountType::CreditCard => &mut debt,
+                AccountType::Checking | AccountType::Wallet | AccountType::Allowance => &mut liquid,
+            };
+            *group = group.checked_add(balance).ok_or_else(overflow)?;
+            balances.push(AccountBalanceResponse {
+                account_id: account.id,
+                account_type: account.account_type.into(),
+                balance,
+            });
+        }
+
+        let today = chrono::Utc::now().date_naive();
+        let difference_this_period = match self.repository.find_budget_period_covering(user_id, today).await? {
+            Some(period) => {
+                let transactions = self.decrypted_transactions(user_id, dek, period.start_date, period.end_date).await?;
+                let (inflows, outflows) = cash_flow(&transactions)?;
+                Some(inflows.checked_sub(outflows).ok_or_else(overflow)?)
+            }
+            None => None,
+        };
+
+        Ok(NetPositionResponse {
+            total: liquid.checked_add(protected).and_then(|t| t.checked_sub(debt)).ok_or_else(overflow)?,
+            liquid,
+            protected,
+            debt,
+            difference_this_period,
+            accounts: balances,
+        })
+    }
+
+    pub async fn cash_flow(&self, period_id: Option<Uuid>, user_id: &Uuid, dek: &Dek) -> Result<CashFlowResponse, AppError> {
+        let period = self.resolve_period(period_id, user_id).await?;
+        let transactions = self.decrypted_transactions(user_id, dek, period.start_date, period.end_date).await?;
+        let (inflows, outflows) = cash_flow(&transactions)?;
+
+        Ok(CashFlowResponse {
+            period_id: period.id,
+            inflows,
+            outflows,
+            net: inflows.checked_sub(outflows).ok_or_else(overflow)?,
+        })
+    }
+
+    /// Total spending of the last `periods` periods that have started,
+    /// oldest first.
+    pub async fn spending_trend(&self, periods: i64, user_id: &Uuid, dek: &Dek) -> Result<SpendingTrendResponse, AppError> {
+        let today = chrono::Utc::now().date_naive();
+        let periods = self.repository.list_recent_budget_periods(user_id, today, periods).await?;
+        let ranges: Vec<(NaiveDate, NaiveDate)> = periods.iter().map(|p| (p.start_date, p.end_date)).collect();
+        let totals = self.spending_per_period(&ranges, user_id, dek).await?;
+
+        Ok(periods
+            .into_iter()
+            .zip(totals)
+            .map(|(period, total_spend)| SpendingTrendItem {
+                period_id: period.id,
+                period_name: period.name,
+                start_date: Date(period.start_date),
+                end_date: Date(period.end_date),
+                total_spend,
+            })
+            .collect())
+    }
+
+    /// The `limit` vendors with the highest spend in the period.
+    pub async fn top_vendors(&self, period_id: Option<Uuid>, limit: usize, user_id: &Uuid, dek: &Dek) -> Result<TopVendorsResponse, AppError> {
+        let period = self.resolve_period(period_id, user_id).await?;
+        let transactions = self.decrypted_transactions(user_id, dek, period.start_date, period.end_date).await?;
+        let vendors: HashMap<Uuid, Vec<u8>> = self.repository.list_vendors(user_id).await?.into_iter().map(|v| (v.id, v.name_enc)).collect();
+
+        let mut spend: HashMap<Uuid, (i64, i64)> = HashMap::new();
+        for tx in &transactions {
+            if let Some(vendor_id) = tx.spend_vendor()
+                && vendors.contains_key(&vendor_id)
+            {
+                let entry = spend.entry(vendor_id).or_default();
+                entry.0 = entry.0.checked_add(tx.amount).ok_or_else(overflow)?;
+                entry.1 += 1;
+            }
+        }
+        let total = checked_sum(spend.values().map(|(amount, _)| *amount))?;
+
+        let mut ranked: Vec<(Uuid, i64, i64)> = spend.into_iter().map(|(id, (amount, count))| (id, amount, count)).collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranked.truncate(limit);
+
+        ranked
+            .into_iter()
+            .map(|(vendor_id, total_spend, transaction_count)| {
+                let aad = EnvelopeAad::new("vendor", "name_enc", vendor_id, *user_id);
+                Ok(TopVendorItem {
+                    vendor_id,
+                    vendor_name: dek.decrypt_string_for(&vendors[&vendor_id], &aad)?,
+                    total_spend,
+                    transaction_count,
+                    percentage: percentage(total_spend, total),
+                })
+            })
+            .collect()
+    }
+
+    /// Total spending inside each inclusive date range, fetched and
+    /// decrypted in one pass over their union.
+    pub async fn spending_per_period(&self, ranges: &[(NaiveDate, NaiveDate)], user_id: &Uuid, dek: &Dek) -> Result<Vec<i64>, AppError> {
+        let (Some(from), Some(to)) = (ranges.iter().map(|r| r.0).min(), ranges.iter().map(|r| r.1).max()) else {
+            return Ok(Vec::new());
+        };
+        let transactions = self.decrypted_transactions(user_id, dek, from, to).await?;
+
+        ranges
+            .iter()
+            .map(|&(start, end)| {
+                checked_sum(
+                    transactions
+                        .iter()
+                        .filter(|tx| tx.is_spending() && (start..=end).contains(&tx.occurred_at))
+                        .map(|tx| tx.amount),
+                )
+            })
+            .collect()
+    }
+
+    /// Sum of every target that counts towards a period's budget.
+    pub async fn total_budgeted(&self, user_id: &Uuid, dek: &Dek) -> Result<i64, AppError> {
+        let mut total = 0i64;
+        for (id, value_enc) in self.repository.list_active_target_values(user_id).await? {
+            let value = dek.decrypt_i64_for(&value_enc, &EnvelopeAad::new("budget_category", "budgeted_value_enc", id, *user_id))?;
+            total = total.checked_add(value).ok_or_else(overflow)?;
+        }
+        Ok(total)
+    }
+
+    /// The requested period, or the one covering today when none is given.
+    async fn resolve_period(&self, period_id: Option<Uuid>, user_id: &Uuid) -> Result<BudgetPeriod, AppError> {
+        match period_id {
+            Some(id) => self.repository.get_budget_period(&id, user_id).await.map_err(|e| match e {
+                AppError::NotFound(_) => AppError::NotFound("Period not found".to_string()),
+                other => other,
+            }),
+            None => self
+                .repository
+                .find_budget_period_covering(user_id, chrono::Utc::now().date_naive())
+                .await?
+                .ok_or_else(|| AppError::NotFound("No period covers today".to_string())),
+        }
+    }
+
+    async fn decrypted_transactions(&self, user_id: &Uuid, dek: &Dek, from: NaiveDate, to: NaiveDate) -> Result<Vec<DecryptedTransaction>, AppError> {
+        self.repository
+            .list_dashboard_transactions_in_range(user_id, from, to)
+            .await?
+            .into_iter()
+            .map(|row| DecryptedTransaction::decrypt(row, user_id, dek))
+            .collect()
+    }
+}
+
+/// Decrypted amounts are not range checked, so a corrupt one must not wrap
+/// a total.
+fn overflow() -> AppError {
+    AppError::BadRequest("dashboard amount overflow".to_string())
+}
+
+fn checked_sum(amounts: impl IntoIterator<Item = i64>) -> Result<i64, AppError> {
+    amounts.into_iter().try_fold(0i64, |sum, amount| sum.checked_add(amount)).ok_or_else(overflow)
+}
+
+/// Spending per day from `start` to `end` inclusive.
+fn daily_spend(transactions: &[DecryptedTransaction], start: NaiveDate, end: NaiveDate) -> Result<Vec<i64>, AppError> {
+    let mut days = vec![0i64; ((end - start).num_days() + 1).max(0) as usize];
+    for tx in transactions.iter().filter(|tx| tx.is_spending()) {
+        if let Some(day) = days.get_mut((tx.occurred_at - start).num_days() as usize) {
+            *day = day.checked_add(tx.amount).ok_or_else(overflow)?;
+        }
+    }
+    Ok(days)
+}
+
+/// `(inflows, outflows)` over the transactions.
+fn cash_flow(transactions: &[DecryptedTransaction]) -> Result<(i64, i64), AppError> {
+    transactions
+        .iter()
+        .try_fold((0i64, 0i64), |(inflows, outflows), tx| match tx.category_type {
+            Some(CategoryType::Incoming) => Some((inflows.checked_add(tx.amount)?, outflows)),
+            Some(CategoryType::Outgoing) => Some((inflows, outflows.checked_add(tx.amount)?)),
+            _ => Some((inflows, outflows)),
+        })
+        .ok_or_else(overflow)
+}
+
+/// Linear projection of `spent` to the end of the period at the pace so
+/// far. A period that has ended (or not started) projects to what was spent.
+fn projected_spend(spent: i64, start: NaiveDate, end: NaiveDate, today: NaiveDate) -> Result<i64, AppError> {
+    if today < start || today > end {
+        return Ok(spent);
+    }
+    let elapsed = (today - start).num_days() + 1;
+    let length = (end - start).num_days() + 1;
+    spent.checked_mul(length).map(|total| total / elapsed).ok_or_else(overflow)
+}
+
+fn percentage(part: i64, total: i64) -> i64 {
+    if total == 0 {
+        0
+    } else {
+        ((i128::from(part) * 100 + i128::from(total) / 2) / i128::from(total)) as i64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    fn tx(category_type: Option<CategoryType>, from: AccountType, to: Option<AccountType>, day: &str, amount: i64) -> DecryptedTransaction {
+        DecryptedTransaction {
+            occurred_at: date(day),
+            category_type,
+            from_account_type: from,
+            to_account_type: to,
+            vendor_id: Some(Uuid::nil()),
+            amount,
+        }
+    }
+
+    #[test]
+    fn spending_excludes_allowance_purchases_and_counts_allowance_top_ups() {
+        use AccountType::*;
+        use CategoryType::*;
+
+        assert!(tx(Some(Outgoing), Checking, None, "2026-01-01", 1).is_spending());
+        assert!(!tx(Some(Outgoing), Allowance, None, "2026-01-01", 1).is_spending());
+        assert!(tx(Some(Transfer), Checking, Some(Allowance), "2026-01-01", 1).is_spending());
+        assert!(!tx(Some(Transfer), Checking, Some(Savings), "2026-01-01", 1).is_spending());
+        assert!(!tx(Some(Incoming), Checking, None, "2026-01-01", 1).is_spending());
+        assert!(!tx(None, Checking, None, "2026-01-01", 1).is_spending());
+
+        assert!(tx(Some(Outgoing), CreditCard, None, "2026-01-01", 1).spend_vendor().is_some());
+        assert!(tx(Some(Outgoing), Allowance, None, "2026-01-01", 1).spend_vendor().is_none());
+        assert!(tx(Some(Incoming), Checking, None, "2026-01-01", 1).spend_vendor().is_none());
+    }
+
+    #[test]
+    fn daily_spend_buckets_by_day_inclusive_of_both_ends() {
+        use AccountType::*;
+        use CategoryType::*;
+
+        let transactions = [
+            tx(Some(Outgoing), Checking, None, "2026-01-01", 100),
+            tx(Some(Outgoing), Checking, None, "2026-01-03", 250),
+            tx(Some(Outgoing), Checking, None, "2026-01-03", 50),
+            tx(Some(Incoming), Checking, None, "2026-01-02", 9999),
+        ];
+
+        assert_eq!(daily_spend(&transactions, date("2026-01-01"), date("2026-01-03")).unwrap(), vec![100, 0, 300]);
+    }
+
+    #[test]
+    fn sums_reject_overflowing_amounts() {
+        use AccountType::*;
+        use CategoryType::*;
+
+        let transactions = [
+            tx(Some(Outgoing), Checking, None, "2026-01-01", i64::MAX),
+            tx(Some(Outgoing), Checking, None, "2026-01-01", 1),
+        ];
+
+        assert!(matches!(
+            daily_spend(&transactions, date("2026-01-01"), date("2026-01-01")),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(matches!(cash_flow(&transactions), Err(AppError::BadRequest(_))));
+        assert!(checked_sum([i64::MAX, 1]).is_err());
+        assert!(projected_spend(i64::MAX, date("2026-01-01"), date("2026-01-10"), date("2026-01-02")).is_err());
+    }
+
+    #[test]
+    fn cash_flow_ignores_transfers() {
+        use AccountType::*;
+        use CategoryType::*;
+
+        let transactions = [
+            tx(Some(Incoming), Checking, None, "2026-01-01", 5000),
+            tx(Some(Outgoing), CreditCard, None, "2026-01-01", 1200),
+            tx(Some(Transfer), Checking, Some(Savings), "2026-01-01", 700),
+        ];
+
+        assert_eq!(cash_flow(&transactions).unwrap(), (5000, 1200));
+    }
+
+    #[test]
+    fn projected_spend_extrapolates_only_inside_the_period() {
+        let (start, end) = (date("2026-01-01"), date("2026-01-10"));
+
+        assert_eq!(projected_spend(300, start, end, date("2026-01-03")).unwrap(), 1000);
+        assert_eq!(projected_spend(300, start, end, date("2026-01-10")).unwrap(), 300);
+        assert_eq!(projected_spend(300, start, end, date("2026-02-01")).unwrap(), 300);
+        assert_eq!(projected_spend(0, start, end, date("2025-12-31")).unwrap(), 0);
+    }
+
+    #[test]
+    fn percentage_rounds_to_nearest_and_handles_zero_total() {
+        assert_eq!(percentage(1, 3), 33);
+        assert_eq!(percentage(2, 3), 67);
+        assert_eq!(percentage(5, 0), 0);
+        assert_eq!(percentage(i64::MAX, i64::MAX), 100);
+    }
+}
@@ -1,6 +1,7 @@
 use chrono::{Days, Months, NaiveDate};
 use uuid::Uuid;
 
+use crate::crypto::Dek;
 use crate::database::budget_period::{V2PeriodRow, V2ScheduleParams};
 use crate::database::postgres_repository::PostgresRepository;
 use crate::dto::common::{Date, PaginatedResponse};
@@ -10,6 +11,7 @@ use crate::dto::period::{
 };
 use crate::error::app_error::AppError;
 use crate::models::budget_period::{BudgetPeriodRequest, PeriodSchedule};
+use crate::service::dashboard::DashboardService;
 
 pub struct PeriodService<'a> {
     repository: &'a PostgresRepository,
@@ -22,7 +24,7 @@ impl<'a> PeriodService<'a> {
 
     // ===== Period CRUD =====
 
-    pub async fn create_period(&self, request: &CreatePeriodRequest, user_id: &Uuid) -> Result<PeriodResponse, AppError> {
+    pub async fn create_period(&self, request: &CreatePeriodRequest, user_id: &Uuid, dek: Option<&Dek>) -> Result<PeriodResponse, AppError> {
         let (name, start_date, end_date, kind) = extract_period_fields(request)?;
 
         if name.len() < 3 {
@@ -36,6 +38,18 @@ impl<'a> PeriodService<'a> {
         };
         let period_id = self.repository.create_budget_period(&v1_request, user_id).await?;
 
+        // The new range may already hold transactions.
+        let mut row = V2PeriodRow {
+            id: period_id,
+            name: name.clone(),
+            start_date,
+            end_date,
+            transaction_count: None,
+            total_spent: None,
+            total_budgeted: None,
+        };
+        self.fill_totals(std::slice::from_mut(&mut row), user_id, dek).await?;
+
         let status = compute_status(start_date, end_date);
         Ok(PeriodResponse {
             id: period_id,
@@ -44,25 +58,32 @@ impl<'a> PeriodService<'a> {
             length: compute_length(start_date, end_date),
             remaining_days: compute_remaining_days(end_date, status),
             number_of_transactions: 0, // just created, no transactions yet
-            percentage_of_target_used: None,
+            percentage_of_target_used: percentage_of_target_used(&row),
             status: Some(status),
-            total_spent: 0,
-            total_budgeted: 0,
+            total_spent: row.total_spent.unwrap_or(0),
+            total_budgeted: row.total_budgeted.unwrap_or(0),
             kind,
         })
     }
 
-    pub async fn get_period(&self, id: &Uuid, user_id: &Uuid) -> Result<PeriodResponse, AppError> {
-        let row = self
+    pub async fn get_period(&self, id: &Uuid, user_id: &Uuid, dek: Option<&Dek>) -> Result<PeriodResponse, AppError> {
+        let mut row = self
             .repository
             .get_budget_period_v2(id, user_id)
             .await?
             .ok_or_else(|| AppError::NotFound("Period not found".to_string()))?;
+        self.fill_totals(std::slice::from_mut(&mut row), user_id, dek).await?;
 
         Ok(row_to_response(&row))
     }
 
-    pub async fn list_periods(&self, cursor: Option<Uuid>, limit: i64, user_id: &Uuid) -> Result<PaginatedResponse<PeriodResponse>, AppError> {
+    pub async fn list_periods(
+        &self,
+        cursor: Option<Uuid>,
+        limit: i64,
+        user_id: &Uuid,
+        dek: Option<&Dek>,
+    ) -> Result<PaginatedResponse<PeriodResponse>, AppError> {
         let (mut rows, total_count) = self.repository.list_budget_periods_v2(cursor, limit, user_id).await?;
 
         let has_more = rows.len() as i64 > limit;
@@ -70,6 +91,7 @@ impl<'a> PeriodService<'a> {
             rows.truncate(limit as usize);
         }
         let next_cursor = if has_more { rows.last().map(|r| r.id.to_string()) } else { None };
+        self.fill_totals(&mut rows, user_id, dek).await?;
 
         let data: Vec<PeriodResponse> = rows.iter().map(row_to_response).collect();
 
@@ -81,7 +103,7 @@ impl<'a> PeriodService<'a> {
         })
     }
 
-    pub async fn update_period(&self, id: &Uuid, request: &UpdatePeriodRequest, user_id: &Uuid) -> Result<PeriodResponse, AppError> {
+    pub async fn update_period(&self, id: &Uuid, request: &UpdatePeriodRequest, user_id: &Uuid, dek: Option<&Dek>) -> Result<PeriodResponse, AppError> {
         let (name, start_date, end_date, _kind) = extract_period_fields(request)?;
 
         if name.len() < 3 {
@@ -94,11 +116,12 @@ impl<'a> PeriodService<'a> {
         let _updated = self.repository.update_budget_period(id, &v1_request, user_id).await?;
 
         // Re-fetch the period with transaction count
-        let row = self
+        let mut row = self
             .repository
             .get_budget_period_v2(id, user_id)
             .await?
             .ok_or_else(|| AppError::NotFound("Period not found".to_string()))?;
+        self.fill_totals(std::slice::from_mut(&mut row), user_id, dek).await?;
 
         Ok(row_to_response(&row))
     }
@@ -114,6 +137,29 @@ impl<'a> PeriodService<'a> {
         Ok(())
     }
 
+    /// Fill `total_spent` and `total_budgeted` by decrypting the periods'
+    /// transactions and the user's targets. The totals need plaintext, so
+    /// they stay unset (reported as 0) when the session is not unlocked.
+    async fn fill_totals(&self, rows: &mut [V2PeriodRow], user_id: &Uuid, dek: Option<&Dek>) -> Result<(), AppError> {
+        let Some(dek) = dek else {
+            return Ok(());
+        };
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        let dashboard = DashboardService::new(self.repository);
+        let ranges: Vec<(NaiveDate, NaiveDate)> = rows.iter().map(|r| (r.start_date, r.end_date)).collect();
+        let spent = dashboard.spending_per_period(&ranges, user_id, dek).await?;
+        let budgeted = dashboard.total_budgeted(user_id, dek).await?;
+
+        for (row, spent) in rows.iter_mut().zip(spent) {
+            row.total_spent = Some(spent);
+            row.total_budgeted = Some(budgeted);
+        }
+        Ok(())
+    }
+
     // ===== Schedule CRUD =====
 
     pub async fn get_schedule(&self, user_id: &Uuid) -> Result<PeriodScheduleResponse, AppError> {
@@ -250,7 +296,7 @@ fn row_to_response(row: &V2PeriodRow) -> PeriodResponse {
         length: compute_length(row.start_date, row.end_date),
         remaining_days: compute_remaining_days(row.end_date, status),
         number_of_transactions: row.transaction_count.unwrap_or(0),
-        percentage_of_target_used: None,
+        percentage_of_target_used: percentage_of_target_used(row),
         status: Some(status),
         total_spent: row.total_spent.unwrap_or(0),
         total_budgeted: row.total_budgeted.unwrap_or(0),
@@ -260,6 +306,13 @@ fn row_to_response(row: &V2PeriodRow) -> PeriodResponse {
     }
 }
 
+fn percentage_of_target_used(row: &V2PeriodRow) -> Option<i64> {
+    match (row.total_spent, row.total_budgeted) {
+        (Some(spent), Some(budgeted)) if budgeted > 0 => Some(spent * 100 / budgeted),
+        _ => None,
+    }
+}
+
 fn recurrence_method_from_str(s: Option<&str>) -> RecurrenceMethod {
     match s {
         Some("businessDay") => RecurrenceMethod::BusinessDay,
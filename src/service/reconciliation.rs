@@ -0,0 +1,76 @@
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::database::reconciliation::statement_balance_aad;
+use crate::dto::reconciliations::{CreateReconciliationRequest, ReconciliationResponse, to_response};
+use crate::error::app_error::AppError;
+use crate::models::reconciliation::Reconciliation;
+use crate::models::transaction::ClearedStatus;
+use uuid::Uuid;
+
+pub struct ReconciliationService<'a> {
+    repository: &'a PostgresRepository,
+}
+
+impl<'a> ReconciliationService<'a> {
+    pub fn new(repository: &'a PostgresRepository) -> Self {
+        ReconciliationService { repository }
+    }
+
+    pub async fn clear_transaction(&self, id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        self.repository.set_cleared_status(id, user_id, ClearedStatus::Cleared).await
+    }
+
+    pub async fn unclear_transaction(&self, id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        self.repository.set_cleared_status(id, user_id, ClearedStatus::Uncleared).await
+    }
+
+    pub async fn unlock_transaction(&self, id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        self.repository.unlock_transaction(id, user_id).await
+    }
+
+    pub async fn start(&self, account_id: &Uuid, request: &CreateReconciliationRequest, user_id: &Uuid, dek: &Dek) -> Result<ReconciliationResponse, AppError> {
+        let reconciliation = self
+            .repository
+            .create_reconciliation(account_id, user_id, request.statement_date.0, request.statement_balance, dek)
+            .await?;
+        self.report(&reconciliation, user_id, dek).await
+    }
+
+    pub async fn list(&self, account_id: &Uuid, user_id: &Uuid, dek: &Dek) -> Result<Vec<ReconciliationResponse>, AppError> {
+        if self.repository.get_account_by_id(account_id, user_id).await?.is_none() {
+            return Err(AppError::NotFound("Account not found".to_string()));
+        }
+        let mut responses = Vec::new();
+        for reconciliation in self.repository.list_reconciliations(account_id, user_id).await? {
+            responses.push(self.report(&reconciliation, user_id, dek).await?);
+        }
+        Ok(responses)
+    }
+
+    pub async fn get(&self, id: &Uuid, account_id: &Uuid, user_id: &Uuid, dek: &Dek) -> Result<ReconciliationResponse, AppError> {
+        let reconciliation = self.repository.get_reconciliation(id, account_id, user_id).await?;
+        self.report(&reconciliation, user_id, dek).await
+    }
+
+    pub async fn finish(&self, id: &Uuid, account_id: &Uuid, user_id: &Uuid, dek: &Dek) -> Result<ReconciliationResponse, AppError> {
+        let reconciliation = self.repository.finish_reconciliation(id, account_id, user_id, dek).await?;
+        self.report(&reconciliation, user_id, dek).await
+    }
+
+    pub async fn cancel(&self, id: &Uuid, account_id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        self.repository.delete_reconciliation(id, account_id, user_id).await
+    }
+
+    /// An open reconciliation reports the account's live cleared balance.
+    /// A finished one only finished because the two matched, and later
+    /// clearing must not rewrite it, so it reports the statement balance.
+    async fn report(&self, reconciliation: &Reconciliation, user_id: &Uuid, dek: &Dek) -> Result<ReconciliationResponse, AppError> {
+        let statement_balance = dek.decrypt_i64_for(&reconciliation.statement_balance_enc, &statement_balance_aad(&reconciliation.id, user_id))?;
+        let cleared_balance = if reconciliation.finished_at.is_some() {
+            statement_balance
+        } else {
+            self.repository.cleared_balance(&reconciliation.account_id, user_id, dek).await?
+        };
+        Ok(to_response(reconciliation, statement_balance, cleared_balance))
+    }
+}
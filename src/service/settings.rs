@@ -1,11 +1,27 @@
 #![allow(dead_code)]
+use std::collections::HashMap;
+
+use chrono::Utc;
 use uuid::Uuid;
 
+use crate::crypto::{Dek, EnvelopeAad, RowAad};
 use crate::database::postgres_repository::PostgresRepository;
+use crate::database::reconciliation::statement_balance_aad;
+use crate::database::settings::ExportTransactionRow;
+use crate::database::subscription::billing_event_aad;
+use crate::database::transaction::{ledger_aad, split_aad};
+use crate::dto::accounts::TopUpCycle;
+use crate::dto::common::Date;
 use crate::dto::settings::{
-    DateFormat, NumberFormat, PreferencesResponse, ProfileResponse, SessionResponse, Theme, UpdatePreferencesRequest, UpdateProfileRequest,
+    BACKUP_FORMAT, BACKUP_MIN_VERSION, BACKUP_VERSION, BackupAccount, BackupBillingEvent, BackupCategory, BackupDocument, BackupLedgerRow, BackupPeriod,
+    BackupReconciliation, BackupSchedule, BackupSplitLine, BackupSubscription, BackupTag, BackupTarget, BackupVendor, CsvColumn, CsvExportOptions, DateFormat,
+    ImportDataResponse, NumberFormat, PreferencesResponse, ProfileResponse, SessionResponse, Theme, UpdatePreferencesRequest, UpdateProfileRequest,
 };
 use crate::error::app_error::AppError;
+use crate::models::category::CategoryType;
+use crate::models::transaction::ClearedStatus;
+use crate::service::auth::forget_user_deks;
+use crate::session_dek::SessionDekStore;
 
 pub struct SettingsService<'a> {
     pub repository: &'a PostgresRepository,
@@ -86,41 +102,322 @@ impl<'a> SettingsService<'a> {
 
     // ── Export ────────────────────────────────────────────────────────────────
 
-    pub async fn export_transactions_csv(&self, user_id: &Uuid) -> Result<String, AppError> {
-        let rows = self.repository.export_transactions_v2(user_id).await?;
-        let mut csv = String::from("date,description,amount,currency,category,type,from_account,to_account,vendor\n");
-
-        for row in &rows {
-            csv.push_str(&format!(
-                "{},{},{},{},{},{},{},{},{}\n",
-                row.date,
-                escape_csv(&row.description),
-                row.amount,
-                escape_csv(&row.currency),
-                escape_csv(&row.category),
-                escape_csv(&row.tx_type),
-                escape_csv(&row.from_account),
-                escape_csv(&row.to_account),
-                escape_csv(&row.vendor),
-            ));
+    /// Decrypt the account, category and vendor names the CSV export joins
+    /// in. Rows are then streamed through [`TransactionCsvWriter::row`].
+    pub async fn transactions_csv_writer(&self, user_id: &Uuid, dek: Dek, options: CsvExportOptions) -> Result<TransactionCsvWriter, AppError> {
+        let user = *user_id;
+
+        let accounts = self
+            .repository
+            .list_export_accounts(user_id)
+            .await?
+            .into_iter()
+            .map(|a| {
+                let name = dek.decrypt_string_for(&a.name_enc, &EnvelopeAad::new("account", "name_enc", a.id, user))?;
+                Ok((
+                    a.id,
+                    CsvAccount {
+                        name,
+                        currency: a.currency,
+                        decimal_places: a.decimal_places,
+                    },
+                ))
+            })
+            .collect::<Result<HashMap<_, _>, AppError>>()?;
+
+        if let Some(account_id) = options.account_id
+            && !accounts.contains_key(&account_id)
+        {
+            return Err(AppError::NotFound("Account not found".to_string()));
         }
 
-        Ok(csv)
+        let categories = self
+            .repository
+            .list_categories(user_id)
+            .await?
+            .into_iter()
+            .map(|c| {
+                let name = dek.decrypt_string_for(&c.name_enc, &EnvelopeAad::new("category", "name_enc", c.id, user))?;
+                Ok((c.id, (name, c.category_type)))
+            })
+            .collect::<Result<HashMap<_, _>, AppError>>()?;
+
+        let vendors = self
+            .repository
+            .list_vendors(user_id)
+            .await?
+            .into_iter()
+            .map(|v| Ok((v.id, dek.decrypt_string_for(&v.name_enc, &EnvelopeAad::new("vendor", "name_enc", v.id, user))?)))
+            .collect::<Result<HashMap<_, _>, AppError>>()?;
+
+        Ok(TransactionCsvWriter {
+            user_id: user,
+            dek,
+            columns: options.columns,
+            delimiter: options.delimiter.as_char(),
+            accounts,
+            categories,
+            vendors,
+        })
     }
 
-    pub async fn export_data(&self, user_id: &Uuid) -> Result<serde_json::Value, AppError> {
-        self.repository.export_all_data_v2(user_id).await
+    /// Decrypt the user's data into a self-contained backup document.
+    pub async fn export_data(&self, user_id: &Uuid, dek: &Dek) -> Result<BackupDocument, AppError> {
+        let rows = self.repository.export_backup_rows(user_id).await?;
+        let user = *user_id;
+
+        let accounts = rows
+            .accounts
+            .iter()
+            .map(|row| {
+                let a = &row.account;
+                let aad = RowAad::new("account", a.id, user);
+                let decrypt_opt = |value: &Option<Vec<u8>>, column| value.as_deref().map(|v| dek.decrypt_i64_for(v, &aad.column(column))).transpose();
+                Ok(BackupAccount {
+                    id: a.id,
+                    account_type: a.account_type.into(),
+                    currency: row.currency.clone(),
+                    name: dek.decrypt_string_for(&a.name_enc, &aad.column("name_enc"))?,
+                    color: dek.decrypt_string_for(&a.color_enc, &aad.column("color_enc"))?,
+                    balance: dek.decrypt_i64_for(&a.current_balance_enc, &aad.column("current_balance_enc"))?,
+                    is_archived: a.is_archived,
+                    spend_limit: decrypt_opt(&a.spend_limit_enc, "spend_limit_enc")?,
+                    next_transfer_amount: decrypt_opt(&a.next_transfer_amount_enc, "next_transfer_amount_enc")?,
+                    top_up_amount: decrypt_opt(&a.top_up_amount_enc, "top_up_amount_enc")?,
+                    top_up_cycle: a.top_up_cycle.as_deref().and_then(TopUpCycle::from_db),
+                    top_up_day: a.top_up_day.map(i32::from),
+                    statement_close_day: a.statement_close_day.map(i32::from),
+                    payment_due_day: a.payment_due_day.map(i32::from),
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        let categories = rows
+            .categories
+            .iter()
+            .map(|c| {
+                let aad = RowAad::new("category", c.id, user);
+                let decrypt_opt = |value: &Option<Vec<u8>>, column| value.as_deref().map(|v| dek.decrypt_string_for(v, &aad.column(column))).transpose();
+                Ok(BackupCategory {
+                    id: c.id,
+                    category_type: c.category_type.into(),
+                    behavior: c.behavior.map(Into::into),
+                    parent_id: c.parent_id,
+                    is_system: c.is_system,
+                    is_archived: c.is_archived,
+                    name: dek.decrypt_string_for(&c.name_enc, &aad.column("name_enc"))?,
+                    color: decrypt_opt(&c.color_enc, "color_enc")?,
+                    icon: decrypt_opt(&c.icon_enc, "icon_enc")?,
+                    description: decrypt_opt(&c.description_enc, "description_enc")?,
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        let vendors = rows
+            .vendors
+            .iter()
+            .map(|v| {
+                let aad = RowAad::new("vendor", v.id, user);
+                Ok(BackupVendor {
+                    id: v.id,
+                    name: dek.decrypt_string_for(&v.name_enc, &aad.column("name_enc"))?,
+                    description: v
+                        .description_enc
+                        .as_deref()
+                        .map(|d| dek.decrypt_string_for(d, &aad.column("description_enc")))
+                        .transpose()?,
+                    archived: v.archived,
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        let tags = rows
+            .tags
+            .iter()
+            .map(|t| {
+                let aad = RowAad::new("tag", t.id, user);
+                Ok(BackupTag {
+                    id: t.id,
+                    name: dek.decrypt_string_for(&t.name_enc, &aad.column("name_enc"))?,
+                    color: dek.decrypt_string_for(&t.color_enc, &aad.column("color_enc"))?,
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        let targets = rows
+            .targets
+            .iter()
+            .map(|(id, category_id, is_excluded, value_enc)| {
+                Ok(BackupTarget {
+                    id: *id,
+                    category_id: *category_id,
+                    is_excluded: *is_excluded,
+                    value: dek.decrypt_i64_for(value_enc, &EnvelopeAad::new("budget_category", "budgeted_value_enc", *id, user))?,
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        let subscriptions = rows
+            .subscriptions
+            .iter()
+            .map(|s| {
+                let aad = RowAad::new("subscription", s.id, user);
+                let billing_events = rows
+                    .billing_events
+                    .iter()
+                    .filter(|e| e.subscription_id == s.id)
+                    .map(|e| {
+                        let amount = match (&e.amount_enc, e.amount) {
+                            (Some(amount_enc), _) => dek.decrypt_i64_for(amount_enc, &billing_event_aad(&e.id, &user))?,
+                            (None, Some(amount)) => amount,
+                            (None, None) => return Err(AppError::internal(format!("billing event {} has no amount", e.id))),
+                        };
+                        Ok(BackupBillingEvent {
+                            transaction_id: e.transaction_id,
+                            amount,
+                            date: Date(e.date),
+                            detected: e.detected,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, AppError>>()?;
+                Ok(BackupSubscription {
+                    id: s.id,
+                    category_id: s.category_id,
+                    vendor_id: s.vendor_id,
+                    name: dek.decrypt_string_for(&s.name_enc, &aad.column("name_enc"))?,
+                    billing_amount: dek.decrypt_i64_for(&s.billing_amount_enc, &aad.column("billing_amount_enc"))?,
+                    billing_cycle: s.billing_cycle,
+                    billing_day: s.billing_day,
+                    next_charge_date: Date(s.next_charge_date),
+                    status: s.status,
+                    resume_on: s.resume_on.map(Date),
+                    cancelled_at: s.cancelled_at,
+                    auto_post_account_id: s.auto_post_account_id,
+                    last_posted_on: s.last_posted_on.map(Date),
+                    billing_events,
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        let periods = rows
+            .periods
+            .iter()
+            .map(|p| BackupPeriod {
+                id: p.id,
+                name: p.name.clone(),
+                start_date: Date(p.start_date),
+                end_date: Date(p.end_date),
+                is_auto_generated: p.is_auto_generated,
+            })
+            .collect();
+
+        let schedule = rows.schedule.map(|s| BackupSchedule {
+            schedule_type: s.schedule_type,
+            start_day: s.start_day,
+            duration_value: s.duration_value,
+            duration_unit: s.duration_unit,
+            saturday_adjustment: s.saturday_adjustment,
+            sunday_adjustment: s.sunday_adjustment,
+            name_pattern: s.name_pattern,
+            generate_ahead: s.generate_ahead,
+            recurrence_method: s.recurrence_method,
+        });
+
+        let reconciliations = rows
+            .reconciliations
+            .iter()
+            .map(|r| {
+                Ok(BackupReconciliation {
+                    id: r.id,
+                    account_id: r.account_id,
+                    statement_date: Date(r.statement_date),
+                    statement_balance: dek.decrypt_i64_for(&r.statement_balance_enc, &statement_balance_aad(&r.id, user_id))?,
+                    created_at: r.created_at,
+                    finished_at: r.finished_at,
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        let mut splits: HashMap<(Uuid, i64), Vec<BackupSplitLine>> = HashMap::new();
+        for line in &rows.splits {
+            let aad = split_aad(&line.id, user_id);
+            splits.entry((line.transaction_id, line.seq)).or_default().push(BackupSplitLine {
+                category_id: line.category_id,
+                amount: dek.decrypt_i64_for(&line.amount_enc, &aad.column("amount_enc"))?,
+                memo: line
+                    .memo_enc
+                    .as_deref()
+                    .map(|m| dek.decrypt_string_for(m, &aad.column("memo_enc")))
+                    .transpose()?,
+            });
+        }
+
+        // Drained as each transaction's first row is reached, so the links
+        // and cleared status appear once per transaction.
+        let mut transaction_tags: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for (transaction_id, tag_id) in &rows.transaction_tags {
+            transaction_tags.entry(*transaction_id).or_default().push(*tag_id);
+        }
+        let mut cleared: HashMap<Uuid, (ClearedStatus, Option<Uuid>)> = rows
+            .cleared
+            .iter()
+            .map(|(id, status, reconciliation_id)| (*id, (*status, *reconciliation_id)))
+            .collect();
+
+        let transactions = rows
+            .ledger
+            .iter()
+            .map(|t| {
+                let aad = ledger_aad(&t.id, user_id);
+                let (cleared_status, reconciliation_id) = cleared.remove(&t.id).map_or((None, None), |(s, r)| (Some(s), r));
+                Ok(BackupLedgerRow {
+                    id: t.id,
+                    seq: t.seq,
+                    occurred_at: Date(t.occurred_at),
+                    category_id: t.category_id,
+                    from_account_id: t.from_account_id,
+                    to_account_id: t.to_account_id,
+                    vendor_id: t.vendor_id,
+                    amount: dek.decrypt_i64_for(&t.amount_enc, &aad.column("amount_enc"))?,
+                    description: dek.decrypt_string_for(&t.description_enc, &aad.column("description_enc"))?,
+                    kind: t.entry_kind,
+                    created_at: t.created_at,
+                    splits: splits.remove(&(t.id, t.seq)).unwrap_or_default(),
+                    tag_ids: transaction_tags.remove(&t.id).unwrap_or_default(),
+                    cleared_status,
+                    reconciliation_id,
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        Ok(BackupDocument {
+            format: BACKUP_FORMAT.to_string(),
+            version: BACKUP_VERSION,
+            exported_at: Utc::now(),
+            accounts,
+            categories,
+            vendors,
+            tags,
+            targets,
+            subscriptions,
+            periods,
+            schedule,
+            reconciliations,
+            transactions,
+        })
     }
 
-    pub async fn import_data(&self, user_id: &Uuid, data: &serde_json::Value) -> Result<serde_json::Value, AppError> {
-        let (accounts, categories, transactions) = self.repository.import_data_v2(user_id, data).await?;
-        Ok(serde_json::json!({
-            "imported": {
-                "accounts": accounts,
-                "categories": categories,
-                "transactions": transactions,
-            }
-        }))
+    /// Restore a backup document produced by [`Self::export_data`] into the
+    /// user's data, re-encrypted under `dek`.
+    pub async fn import_data(&self, user_id: &Uuid, backup: &BackupDocument, dek: &Dek) -> Result<ImportDataResponse, AppError> {
+        if backup.format != BACKUP_FORMAT {
+            return Err(AppError::BadRequest(format!("Unsupported backup format '{}'", backup.format)));
+        }
+        if !(BACKUP_MIN_VERSION..=BACKUP_VERSION).contains(&backup.version) {
+            return Err(AppError::BadRequest(format!("Unsupported backup version {}", backup.version)));
+        }
+        let imported = self.repository.import_backup(user_id, backup, dek).await?;
+        Ok(ImportDataResponse { imported })
     }
 
     // ── Destructive ──────────────────────────────────────────────────────────
@@ -130,23 +427,123 @@ impl<'a> SettingsService<'a> {
         self.repository.verify_password(&user, password).await.map_err(|_| AppError::InvalidCredentials)
     }
 
-    pub async fn reset_structure(&self, user_id: &Uuid, password: &str, dek: &crate::crypto::Dek) -> Result<(), AppError> {
+    pub async fn reset_structure(&self, user_id: &Uuid, password: &str, dek: &Dek) -> Result<(), AppError> {
         self.verify_password(user_id, password).await?;
         self.repository.reset_structure_v2(user_id, dek).await
     }
 
-    pub async fn delete_account(&self, user_id: &Uuid, password: &str) -> Result<(), AppError> {
+    pub async fn delete_account(&self, dek_store: &dyn SessionDekStore, user_id: &Uuid, password: &str) -> Result<(), AppError> {
         self.verify_password(user_id, password).await?;
         // Must clean up data before deleting user because some FK constraints are RESTRICT
         self.repository.delete_all_user_data(user_id).await?;
-        self.repository.delete_user(user_id).await
+        self.repository.delete_user(user_id).await?;
+        forget_user_deks(dek_store, user_id, None).await;
+        Ok(())
     }
 }
 
-fn escape_csv(s: &str) -> String {
-    if s.contains(',') || s.contains('"') || s.contains('\n') || s.contains('\r') {
+struct CsvAccount {
+    name: String,
+    currency: String,
+    decimal_places: i32,
+}
+
+/// Turns exported ledger rows into CSV lines with the caller's columns and
+/// delimiter. Holds the session DEK for the lifetime of the download.
+pub struct TransactionCsvWriter {
+    user_id: Uuid,
+    dek: Dek,
+    columns: Vec<CsvColumn>,
+    delimiter: char,
+    accounts: HashMap<Uuid, CsvAccount>,
+    categories: HashMap<Uuid, (String, CategoryType)>,
+    vendors: HashMap<Uuid, String>,
+}
+
+impl TransactionCsvWriter {
+    pub fn header(&self) -> String {
+        self.line(self.columns.iter().map(|c| c.name().to_string()))
+    }
+
+    pub fn row(&self, row: &ExportTransactionRow) -> Result<String, AppError> {
+        let aad = ledger_aad(&row.id, &self.user_id);
+        let amount = self.dek.decrypt_i64_for(&row.amount_enc, &aad.column("amount_enc"))?;
+        let description = self.dek.decrypt_string_for(&row.description_enc, &aad.column("description_enc"))?;
+        let from_account = self.accounts.get(&row.from_account_id);
+        let category = row.category_id.and_then(|id| self.categories.get(&id));
+
+        let fields = self.columns.iter().map(|column| match column {
+            CsvColumn::Date => row.occurred_at.format("%Y-%m-%d").to_string(),
+            CsvColumn::Description => description.clone(),
+            CsvColumn::Amount => format_amount(amount, from_account.map_or(2, |a| a.decimal_places)),
+            CsvColumn::Currency => from_account.map(|a| a.currency.clone()).unwrap_or_default(),
+            CsvColumn::Category => category.map(|(name, _)| name.clone()).unwrap_or_default(),
+            CsvColumn::Type => match (row.to_account_id, category) {
+                (Some(_), _) => "transfer".to_string(),
+                (None, Some((_, CategoryType::Incoming))) => "incoming".to_string(),
+                (None, _) => "outgoing".to_string(),
+            },
+            CsvColumn::FromAccount => from_account.map(|a| a.name.clone()).unwrap_or_default(),
+            CsvColumn::ToAccount => row
+                .to_account_id
+                .and_then(|id| self.accounts.get(&id))
+                .map(|a| a.name.clone())
+                .unwrap_or_default(),
+            CsvColumn::Vendor => row.vendor_id.and_then(|id| self.vendors.get(&id)).cloned().unwrap_or_default(),
+        });
+        Ok(self.line(fields))
+    }
+
+    fn line(&self, fields: impl Iterator<Item = String>) -> String {
+        let mut line = fields
+            .map(|f| escape_csv(&f, self.delimiter))
+            .collect::<Vec<_>>()
+            .join(&self.delimiter.to_string());
+        line.push('\n');
+        line
+    }
+}
+
+/// Format minor units with the currency's number of decimal places, e.g.
+/// `-1234` with 2 places as `-12.34`.
+fn format_amount(amount: i64, decimal_places: i32) -> String {
+    if decimal_places <= 0 {
+        return amount.to_string();
+    }
+    let places = decimal_places as usize;
+    let digits = format!("{:0>width$}", amount.unsigned_abs(), width = places + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - places);
+    let sign = if amount < 0 { "-" } else { "" };
+    format!("{sign}{whole}.{fraction}")
+}
+
+fn escape_csv(s: &str, delimiter: char) -> String {
+    if s.contains(delimiter) || s.contains('"') || s.contains('\n') || s.contains('\r') {
         format!("\"{}\"", s.replace('"', "\"\""))
     } else {
         s.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_amount_uses_currency_decimal_places() {
+        assert_eq!(format_amount(123_456, 2), "1234.56");
+        assert_eq!(format_amount(-5, 2), "-0.05");
+        assert_eq!(format_amount(7, 3), "0.007");
+        assert_eq!(format_amount(1500, 0), "1500");
+        assert_eq!(format_amount(i64::MIN, 2), "-92233720368547758.08");
+    }
+
+    #[test]
+    fn escape_csv_quotes_the_active_delimiter() {
+        assert_eq!(escape_csv("a,b", ','), "\"a,b\"");
+        assert_eq!(escape_csv("a,b", ';'), "a,b");
+        assert_eq!(escape_csv("a;b", ';'), "\"a;b\"");
+        assert_eq!(escape_csv("a\tb", '\t'), "\"a\tb\"");
+        assert_eq!(escape_csv("say \"hi\"", ','), "\"say \"\"hi\"\"\"");
+    }
+}
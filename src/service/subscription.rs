@@ -1,9 +1,12 @@
-use chrono::NaiveDate;
+use chrono::{NaiveDate, Utc};
 use uuid::Uuid;
 
 use crate::crypto::Dek;
 use crate::database::postgres_repository::{PostgresRepository, is_foreign_key_violation};
-use crate::dto::subscriptions::{CreateSubscriptionRequest, EncryptedSubscriptionResponse, SubscriptionListResponse, UpdateSubscriptionRequest};
+use crate::dto::subscriptions::{
+    AutoPostResponse, BillingEventResponse, CreateSubscriptionRequest, EncryptedSubscriptionResponse, SubscriptionAlertResponse, SubscriptionDetailResponse,
+    SubscriptionListResponse, UpdateSubscriptionRequest,
+};
 use crate::error::app_error::AppError;
 
 pub struct SubscriptionService<'a> {
@@ -19,6 +22,14 @@ impl<'a> SubscriptionService<'a> {
         self.repository.list_subscriptions(user_id).await
     }
 
+    pub async fn alerts(&self, user_id: &Uuid, dek: &Dek) -> Result<Vec<SubscriptionAlertResponse>, AppError> {
+        self.repository.list_subscription_alerts(user_id, dek).await
+    }
+
+    pub async fn get(&self, id: &Uuid, user_id: &Uuid, dek: &Dek) -> Result<SubscriptionDetailResponse, AppError> {
+        self.repository.get_subscription(id, user_id, dek).await
+    }
+
     pub async fn create(&self, req: &CreateSubscriptionRequest, user_id: &Uuid, dek: &Dek) -> Result<EncryptedSubscriptionResponse, AppError> {
         self.repository.create_subscription(req, user_id, dek).await.map_err(map_fk_violation)
     }
@@ -31,6 +42,34 @@ impl<'a> SubscriptionService<'a> {
         self.repository.delete_subscription(id, user_id).await
     }
 
+    pub async fn pause(&self, id: &Uuid, user_id: &Uuid, resume_on: Option<NaiveDate>) -> Result<EncryptedSubscriptionResponse, AppError> {
+        if let Some(resume_on) = resume_on
+            && resume_on <= Utc::now().date_naive()
+        {
+            return Err(AppError::BadRequest("resumeOn must be in the future".to_string()));
+        }
+        self.repository.pause_subscription(id, user_id, resume_on).await
+    }
+
+    pub async fn resume(&self, id: &Uuid, user_id: &Uuid) -> Result<EncryptedSubscriptionResponse, AppError> {
+        self.repository.resume_subscription(id, user_id, Utc::now().date_naive()).await
+    }
+
+    pub async fn link_transaction(&self, id: &Uuid, transaction_id: &Uuid, user_id: &Uuid, dek: &Dek) -> Result<BillingEventResponse, AppError> {
+        self.repository.link_billing_transaction(id, transaction_id, user_id, dek).await
+    }
+
+    pub async fn unlink_transaction(&self, id: &Uuid, transaction_id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        self.repository.unlink_billing_transaction(id, transaction_id, user_id).await
+    }
+
+    /// Post the charges of auto-post subscriptions that fell due since
+    /// they were last posted. Called once a session's DEK is stored.
+    pub async fn post_due_charges(&self, user_id: &Uuid, dek: &Dek) -> Result<AutoPostResponse, AppError> {
+        let posted_charges = self.repository.post_due_subscription_charges(user_id, dek, Utc::now().date_naive()).await?;
+        Ok(AutoPostResponse { posted_charges })
+    }
+
     pub async fn cancel(&self, id: &Uuid, user_id: &Uuid, cancellation_date: Option<&NaiveDate>) -> Result<EncryptedSubscriptionResponse, AppError> {
         self.repository.cancel_subscription(id, user_id, cancellation_date).await
     }
@@ -0,0 +1,42 @@
+use crate::crypto::Dek;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::dto::common::PaginatedResponse;
+use crate::dto::tags::{CreateTagRequest, EncryptedTagResponse, TagListResponse, UpdateTagRequest, to_encrypted_response};
+use crate::error::app_error::AppError;
+use uuid::Uuid;
+
+pub struct TagService<'a> {
+    repository: &'a PostgresRepository,
+}
+
+impl<'a> TagService<'a> {
+    pub fn new(repository: &'a PostgresRepository) -> Self {
+        TagService { repository }
+    }
+
+    pub async fn list_tags(&self, user_id: &Uuid) -> Result<TagListResponse, AppError> {
+        let tags = self.repository.list_tags(user_id).await?;
+        let total_count = tags.len() as i64;
+        let data: Vec<EncryptedTagResponse> = tags.iter().map(to_encrypted_response).collect();
+        Ok(PaginatedResponse {
+            data,
+            total_count,
+            has_more: false,
+            next_cursor: None,
+        })
+    }
+
+    pub async fn create_tag(&self, request: &CreateTagRequest, user_id: &Uuid, dek: &Dek) -> Result<EncryptedTagResponse, AppError> {
+        let tag = self.repository.create_tag(request, user_id, dek).await?;
+        Ok(to_encrypted_response(&tag))
+    }
+
+    pub async fn update_tag(&self, id: &Uuid, request: &UpdateTagRequest, user_id: &Uuid, dek: &Dek) -> Result<EncryptedTagResponse, AppError> {
+        let tag = self.repository.update_tag(id, request, user_id, dek).await?;
+        Ok(to_encrypted_response(&tag))
+    }
+
+    pub async fn delete_tag(&self, id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        self.repository.delete_tag(id, user_id).await
+    }
+}
@@ -11,10 +11,11 @@
 
 use crate::crypto::Dek;
 use crate::database::postgres_repository::PostgresRepository;
-use crate::dto::transactions::{CreateTransactionRequest, EncryptedTransactionResponse};
+use crate::dto::common::PaginatedResponse;
+use crate::dto::transactions::{CreateTransactionRequest, EncryptedTransactionResponse, SplitLineRequest, TransactionHistoryEntry, TransactionListResponse};
 use crate::error::app_error::AppError;
-use crate::models::pagination::TransactionDirection;
-use crate::models::transaction::TransactionRequest as V1TransactionRequest;
+use crate::models::pagination::{CursorParams, TransactionDirection, TransactionFilters};
+use crate::models::transaction::{MAX_SPLIT_LINES, TransactionRequest as V1TransactionRequest, TransactionSplit};
 use chrono::NaiveDate;
 use uuid::Uuid;
 
@@ -61,6 +62,51 @@ impl<'a> TransactionService<'a> {
         self.repository.delete_transaction(id, user_id, dek).await
     }
 
+    pub async fn restore_transaction(&self, id: &Uuid, user_id: &Uuid, dek: &Dek) -> Result<EncryptedTransactionResponse, AppError> {
+        let result = self.repository.restore_transaction(id, user_id, dek).await?;
+        Ok(result.into())
+    }
+
+    /// Every ledger row of a logical transaction, voided ones included.
+    pub async fn history(&self, id: &Uuid, user_id: &Uuid) -> Result<Vec<TransactionHistoryEntry>, AppError> {
+        let rows = self.repository.list_transaction_history(id, user_id).await?;
+        if rows.is_empty() {
+            return Err(AppError::NotFound("Transaction not found".to_string()));
+        }
+        Ok(rows.into_iter().map(TransactionHistoryEntry::from).collect())
+    }
+
+    /// One filtered page of effective transactions, newest first.
+    /// `period_id` narrows the filters to that period's dates.
+    pub async fn list_page(
+        &self,
+        mut filters: TransactionFilters,
+        period_id: Option<&Uuid>,
+        params: &CursorParams,
+        user_id: &Uuid,
+    ) -> Result<TransactionListResponse, AppError> {
+        if let Some(period_id) = period_id {
+            let period = self.repository.get_budget_period(period_id, user_id).await?;
+            filters.date_from = Some(period.start_date);
+            filters.date_to = Some(period.end_date);
+        }
+
+        let (mut rows, total_count) = self.repository.list_transactions_page(user_id, &filters, params).await?;
+        let limit = params.effective_limit() as usize;
+        let has_more = rows.len() > limit;
+        if has_more {
+            rows.truncate(limit);
+        }
+        let next_cursor = if has_more { rows.last().map(|r| r.id.to_string()) } else { None };
+
+        Ok(PaginatedResponse {
+            data: rows.into_iter().map(EncryptedTransactionResponse::from).collect(),
+            total_count,
+            has_more,
+            next_cursor,
+        })
+    }
+
     /// List every effective logical transaction whose latest-row date
     /// falls inside a period. Returns ciphertext for the client to
     /// decrypt and reduce locally into dashboards, breakdowns, etc.
@@ -83,9 +129,7 @@ impl<'a> TransactionService<'a> {
 }
 
 /// Converts the V2 direction string (from query param) to the V1 TransactionDirection
-/// which maps to DB category_type values. Kept for the filter-plumbing in
-/// dto/pagination until Phase 3 retires the filter machinery entirely.
-#[allow(dead_code)]
+/// which maps to DB category_type values.
 pub fn parse_direction(direction: &str) -> Result<TransactionDirection, AppError> {
     match direction {
         "income" => Ok(TransactionDirection::Incoming),
@@ -100,7 +144,7 @@ pub fn parse_direction(direction: &str) -> Result<TransactionDirection, AppError
 
 /// Validates and converts a V2 CreateTransactionRequest into a V1 TransactionRequest.
 fn to_v1_request(request: &CreateTransactionRequest) -> Result<V1TransactionRequest, AppError> {
-    let (date, description, amount, from_account_id, category_id, vendor_id, to_account_id) = match request {
+    let (date, description, amount, from_account_id, category_id, vendor_id, to_account_id, splits, tag_ids, subscription_id) = match request {
         CreateTransactionRequest::Regular {
             date,
             description,
@@ -108,7 +152,21 @@ fn to_v1_request(request: &CreateTransactionRequest) -> Result<V1TransactionRequ
             from_account_id,
             category_id,
             vendor_id,
-        } => (date, description, *amount, *from_account_id, *category_id, vendor_id.as_ref().copied(), None),
+            splits,
+            tag_ids,
+            subscription_id,
+        } => (
+            date,
+            description,
+            *amount,
+            *from_account_id,
+            *category_id,
+            vendor_id.as_ref().copied(),
+            None,
+            splits.as_slice(),
+            tag_ids,
+            *subscription_id,
+        ),
         CreateTransactionRequest::Transfer {
             date,
             description,
@@ -117,6 +175,8 @@ fn to_v1_request(request: &CreateTransactionRequest) -> Result<V1TransactionRequ
             category_id,
             vendor_id,
             to_account_id,
+            tag_ids,
+            subscription_id,
         } => (
             date,
             description,
@@ -125,6 +185,9 @@ fn to_v1_request(request: &CreateTransactionRequest) -> Result<V1TransactionRequ
             *category_id,
             vendor_id.as_ref().copied(),
             Some(*to_account_id),
+            &[][..],
+            tag_ids,
+            *subscription_id,
         ),
     };
 
@@ -134,6 +197,7 @@ fn to_v1_request(request: &CreateTransactionRequest) -> Result<V1TransactionRequ
     if description.len() < 3 {
         return Err(AppError::BadRequest("description must be at least 3 characters".to_string()));
     }
+    validate_splits(amount, splits)?;
 
     Ok(V1TransactionRequest {
         amount,
@@ -143,11 +207,46 @@ fn to_v1_request(request: &CreateTransactionRequest) -> Result<V1TransactionRequ
         from_account_id,
         to_account_id,
         vendor_id,
+        splits: splits
+            .iter()
+            .map(|line| TransactionSplit {
+                category_id: line.category_id,
+                amount: line.amount,
+                memo: line.memo.clone(),
+            })
+            .collect(),
+        tag_ids: tag_ids.clone(),
+        subscription_id,
     })
 }
 
+/// A split needs at least two, and at most `MAX_SPLIT_LINES`, positive
+/// lines that add up to the transaction amount. No lines at all means an
+/// unsplit transaction.
+fn validate_splits(amount: i64, splits: &[SplitLineRequest]) -> Result<(), AppError> {
+    if splits.is_empty() {
+        return Ok(());
+    }
+    if splits.len() < 2 {
+        return Err(AppError::BadRequest("splits must have at least 2 lines".to_string()));
+    }
+    if splits.len() > MAX_SPLIT_LINES {
+        return Err(AppError::BadRequest(format!("splits must have at most {MAX_SPLIT_LINES} lines")));
+    }
+    if splits.iter().any(|line| line.amount <= 0) {
+        return Err(AppError::BadRequest("split amounts must be > 0".to_string()));
+    }
+    let total = splits
+        .iter()
+        .try_fold(0i64, |sum, line| sum.checked_add(line.amount))
+        .ok_or_else(|| AppError::BadRequest("split amounts overflow".to_string()))?;
+    if total != amount {
+        return Err(AppError::BadRequest(format!("split amounts sum to {total}, expected {amount}")));
+    }
+    Ok(())
+}
+
 /// Parse a date string in YYYY-MM-DD format.
-#[allow(dead_code)]
 pub fn parse_date(s: &str) -> Result<NaiveDate, AppError> {
     NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| AppError::BadRequest(format!("Invalid date format '{}'. Expected YYYY-MM-DD", s)))
 }
@@ -7,6 +7,7 @@ use crate::error::app_error::AppError;
 use crate::models::audit::audit_events;
 use crate::models::user::User;
 use crate::service::auth::AuthService;
+use crate::session_dek::SessionDekStore;
 use chrono::Utc;
 use sha2::{Digest, Sha256};
 use uuid::Uuid;
@@ -21,6 +22,15 @@ impl<'a> TwoFactorService<'a> {
         TwoFactorService { repo, config }
     }
 
+    /// Revoke every bearer token of a user and lock the DEKs cached for them.
+    async fn revoke_bearer_tokens(&self, dek_store: &dyn SessionDekStore, user_id: &Uuid) {
+        let token_ids: Vec<Uuid> = self.repo.find_by_user(user_id).await.unwrap_or_default().into_iter().map(|t| t.id).collect();
+        let _ = self.repo.revoke_all_for_user(user_id).await;
+        if let Err(e) = dek_store.remove_many(&token_ids).await {
+            tracing::warn!(error = %e, "Failed to drop session DEKs for revoked tokens of user {}", user_id);
+        }
+    }
+
     /// Get the current 2FA status for a user.
     pub async fn get_status(&self, user_id: &Uuid) -> Result<TwoFactorStatusResponse, AppError> {
         let two_factor = self.repo.get_two_factor_by_user(user_id).await?;
@@ -72,7 +82,14 @@ impl<'a> TwoFactorService<'a> {
 
     /// Verify a TOTP code and enable 2FA for the user.
     /// Returns the backup codes so they can be shown to the user once.
-    pub async fn verify_setup(&self, user_id: &Uuid, code: &str, client_ip: Option<String>, user_agent: Option<String>) -> Result<Vec<String>, AppError> {
+    pub async fn verify_setup(
+        &self,
+        dek_store: &dyn SessionDekStore,
+        user_id: &Uuid,
+        code: &str,
+        client_ip: Option<String>,
+        user_agent: Option<String>,
+    ) -> Result<Vec<String>, AppError> {
         let two_factor = self
             .repo
             .get_two_factor_by_user(user_id)
@@ -95,7 +112,7 @@ impl<'a> TwoFactorService<'a> {
         self.repo.verify_and_enable_two_factor(user_id).await?;
 
         // Revoke all API tokens
-        let _ = self.repo.revoke_all_for_user(user_id).await;
+        self.revoke_bearer_tokens(dek_store, user_id).await;
 
         let _ = self
             .repo
@@ -174,7 +191,14 @@ impl<'a> TwoFactorService<'a> {
     }
 
     /// Disable 2FA for a user (V2: requires only a TOTP/backup code, not password).
-    pub async fn disable(&self, user_id: &Uuid, code: &str, client_ip: Option<String>, user_agent: Option<String>) -> Result<(), AppError> {
+    pub async fn disable(
+        &self,
+        dek_store: &dyn SessionDekStore,
+        user_id: &Uuid,
+        code: &str,
+        client_ip: Option<String>,
+        user_agent: Option<String>,
+    ) -> Result<(), AppError> {
         let two_factor = self
             .repo
             .get_two_factor_by_user(user_id)
@@ -193,7 +217,7 @@ impl<'a> TwoFactorService<'a> {
         self.repo.disable_two_factor(user_id).await?;
 
         // Revoke all API tokens
-        let _ = self.repo.revoke_all_for_user(user_id).await;
+        self.revoke_bearer_tokens(dek_store, user_id).await;
 
         let _ = self
             .repo
@@ -1,11 +1,24 @@
 //! Per-session DEK store and request guard.
 //!
 //! See `.kiro/specs/encryption-at-rest/design.md` §"DEK transport" for the
-//! design. The v1 implementation is an in-process `Arc<RwLock<HashMap>>`
-//! keyed by the per-device auth principal id — session_id for cookie auth
-//! (web), api_token_id for bearer auth (mobile). It is registered as Rocket
-//! managed state by `build_rocket`. Phase 5 replaces the in-process store
-//! with a Redis-backed one.
+//! design. The store maps the per-device auth principal id — session_id for
+//! cookie auth (web), api_token_id for bearer auth (mobile) — to the
+//! unlocked DEK, and indexes each entry by its owning user so every device
+//! of a user can be locked at once. `SessionDekStore` is the backend trait;
+//! `build_rocket` registers the backend selected by `Config::dek_store` as a
+//! `DekStore` managed state:
+//!
+//!   * `in_memory` — `InMemoryDekStore`, an in-process map. Entries are lost on
+//!     restart and are not shared between instances.
+//!   * `redis` — `RedisDekStore`, any Redis-protocol server. DEKs are sealed
+//!     with the server-side `dek_store.sealing_key` before they leave the
+//!     process, so the store itself never holds a usable key.
+//!
+//! An entry lives until the earlier of the expiry of the principal it
+//! belongs to (session `expires_at`, or the bearer token's
+//! `refresh_expires_at`) and `dek_store.idle_timeout_seconds` without a
+//! lookup. An unlocked DEK therefore never outlives the credential that
+//! unlocked it, and an idle device has to unlock again.
 //!
 //! Flow:
 //!   1. User logs in → cookie session or bearer token is issued. No DEK yet.
@@ -16,79 +29,538 @@
 //!      `Dek` parameter via the `FromRequest` guard below, which resolves
 //!      `CurrentUser` and looks up the DEK by that same principal id.
 //!   4. Logout / session revoke deletes the store entry alongside the
-//!      session/token row.
+//!      session/token row. Password change, password reset, bearer token
+//!      revocation and account deletion drop every entry of the user via
+//!      `remove_all_for_user` (or `remove_many` for token-only revocation).
 //!
 //! The `Dek` type is zeroize-on-drop, so clones from the store are cleaned
 //! up automatically when the handler returns.
 
 use crate::auth::CurrentUser;
+use crate::config::{DekStoreBackend, DekStoreConfig};
 use crate::crypto::Dek;
 use crate::error::app_error::AppError;
+use chrono::{DateTime, Duration, Utc};
+use redis::aio::ConnectionManager;
+use rocket::fairing::AdHoc;
 use rocket::http::Status;
 use rocket::outcome::Outcome;
 use rocket::request::{FromRequest, Outcome as RequestOutcome, Request};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{OnceCell, RwLock};
 use uuid::Uuid;
+use zeroize::Zeroizing;
+
+/// How often the eviction task sweeps expired and idle entries.
+const EVICTION_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Principal → DEK mapping. The principal id is either a session_id
+/// (cookie auth) or an api_token_id (bearer auth); both are random UUIDs
+/// drawn from non-overlapping tables.
+#[rocket::async_trait]
+pub trait SessionDekStore: Send + Sync {
+    /// Store a DEK for the given principal of `user_id` until `expires_at`
+    /// (or until it sits idle for the configured timeout). Overwrites any
+    /// existing entry.
+    async fn put(&self, principal_id: Uuid, user_id: Uuid, dek: Dek, expires_at: DateTime<Utc>) -> Result<(), AppError>;
+
+    /// Return an owned clone of the DEK for the given principal if present,
+    /// not expired and not idle, and restart its idle clock. The clone is a
+    /// fresh `Dek` that the caller owns; it is zeroed on drop.
+    async fn get_cloned(&self, principal_id: &Uuid) -> Result<Option<Dek>, AppError>;
+
+    /// Remove the DEK entry for a principal. Used on logout and session
+    /// revoke.
+    async fn remove(&self, principal_id: &Uuid) -> Result<(), AppError>;
+
+    /// Remove the entries for every listed principal. Used when only some
+    /// kinds of principal are revoked (e.g. bearer tokens when 2FA changes).
+    async fn remove_many(&self, principal_ids: &[Uuid]) -> Result<(), AppError>;
+
+    /// Remove every entry belonging to `user_id`, except `keep` (the
+    /// caller's own session, when it survives the operation).
+    async fn remove_all_for_user(&self, user_id: &Uuid, keep: Option<&Uuid>) -> Result<(), AppError>;
+
+    /// Replace the DEK of every entry belonging to `user_id`, keeping each
+    /// entry's expiry. Used after a DEK rotation so devices that were
+    /// already unlocked keep working without a fresh unlock; locked devices
+    /// stay locked.
+    async fn replace_for_user(&self, user_id: &Uuid, dek: &Dek) -> Result<(), AppError>;
+
+    /// Stop every entry belonging to `user_id` from accepting legacy
+    /// envelopes. Called once the legacy envelope sweep has set
+    /// `users.envelopes_upgraded_at`, so unlocked devices pick it up
+    /// without a fresh unlock.
+    async fn mark_envelopes_upgraded(&self, user_id: &Uuid) -> Result<(), AppError>;
+
+    /// Drop entries that are expired or idle. Returns the number removed.
+    /// Backends with native expiry have nothing to do.
+    async fn evict_expired(&self) -> Result<usize, AppError>;
+
+    /// Move an entry to a new principal id with a new expiry. Used when a
+    /// cookie session is refreshed, which replaces the session row.
+    async fn transfer(&self, from: &Uuid, to: Uuid, user_id: Uuid, expires_at: DateTime<Utc>) -> Result<(), AppError> {
+        if let Some(dek) = self.get_cloned(from).await? {
+            self.put(to, user_id, dek, expires_at).await?;
+        }
+        self.remove(from).await
+    }
+}
+
+/// Managed state type registered by `build_rocket`. Route handlers take
+/// `&State<DekStore>`.
+pub type DekStore = Arc<dyn SessionDekStore>;
+
+/// Construct the backend selected by `config.backend`. The networked
+/// backend connects lazily on first use, so this never blocks.
+pub fn build_dek_store(config: &DekStoreConfig) -> Result<DekStore, String> {
+    if config.idle_timeout_seconds <= 0 {
+        return Err("dek_store.idle_timeout_seconds must be positive".to_string());
+    }
+    let idle_timeout = Duration::seconds(config.idle_timeout_seconds);
+
+    match config.backend {
+        DekStoreBackend::InMemory => Ok(Arc::new(InMemoryDekStore::new(idle_timeout))),
+        DekStoreBackend::Redis => {
+            let sealing_key = config.parse_sealing_key()?;
+            let store = RedisDekStore::new(&config.redis_url, sealing_key, &config.redis_key_prefix, idle_timeout)
+                .map_err(|e| format!("Invalid dek_store.redis_url: {}", e))?;
+            Ok(Arc::new(store))
+        }
+    }
+}
+
+/// Fairing that periodically evicts expired and idle entries from the
+/// managed `DekStore` once the server is running.
+pub fn stage_dek_store_eviction() -> AdHoc {
+    AdHoc::on_liftoff("Session DEK eviction", |rocket| {
+        Box::pin(async move {
+            let Some(store) = rocket.state::<DekStore>().cloned() else {
+                return;
+            };
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(EVICTION_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    match store.evict_expired().await {
+                        Ok(0) => {}
+                        Ok(evicted) => tracing::debug!(evicted, "Evicted idle session DEKs"),
+                        Err(e) => tracing::warn!(error = %e, "Failed to evict idle session DEKs"),
+                    }
+                }
+            });
+        })
+    })
+}
+
+struct MemoryEntry {
+    user_id: Uuid,
+    dek: Dek,
+    expires_at: DateTime<Utc>,
+    last_used: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct MemoryState {
+    entries: HashMap<Uuid, MemoryEntry>,
+    by_user: HashMap<Uuid, HashSet<Uuid>>,
+}
+
+impl MemoryState {
+    /// Remove an entry and its index slot. The Dek is zeroed on drop.
+    fn take(&mut self, principal_id: &Uuid) -> Option<MemoryEntry> {
+        let entry = self.entries.remove(principal_id)?;
+        if let Some(principals) = self.by_user.get_mut(&entry.user_id) {
+            principals.remove(principal_id);
+            if principals.is_empty() {
+                self.by_user.remove(&entry.user_id);
+            }
+        }
+        Some(entry)
+    }
+
+    fn principals_of(&self, user_id: &Uuid) -> Vec<Uuid> {
+        self.by_user.get(user_id).map(|p| p.iter().copied().collect()).unwrap_or_default()
+    }
+}
 
-/// In-process principal → DEK mapping. The principal id is either a
-/// session_id (cookie auth) or an api_token_id (bearer auth); both are
-/// random UUIDs drawn from non-overlapping tables.
-#[derive(Clone)]
-pub struct SessionDekStore {
-    inner: Arc<RwLock<HashMap<Uuid, Dek>>>,
+/// In-process backend. Expired and idle entries are dropped on lookup and
+/// by the periodic `evict_expired` sweep.
+pub struct InMemoryDekStore {
+    inner: RwLock<MemoryState>,
+    idle_timeout: Duration,
 }
 
-impl SessionDekStore {
-    pub fn new() -> Self {
+impl InMemoryDekStore {
+    pub fn new(idle_timeout: Duration) -> Self {
         Self {
-            inner: Arc::new(RwLock::new(HashMap::new())),
+            inner: RwLock::new(MemoryState::default()),
+            idle_timeout,
         }
     }
 
-    /// Store a DEK for the given principal. Overwrites any existing entry.
-    /// The old Dek is zeroed on drop automatically.
-    pub async fn put(&self, principal_id: Uuid, dek: Dek) {
-        let mut guard = self.inner.write().await;
-        guard.insert(principal_id, dek);
+    fn is_live(&self, entry: &MemoryEntry, now: DateTime<Utc>) -> bool {
+        entry.expires_at > now && entry.last_used + self.idle_timeout > now
+    }
+}
+
+#[rocket::async_trait]
+impl SessionDekStore for InMemoryDekStore {
+    async fn put(&self, principal_id: Uuid, user_id: Uuid, dek: Dek, expires_at: DateTime<Utc>) -> Result<(), AppError> {
+        let now = Utc::now();
+        let mut state = self.inner.write().await;
+        state.take(&principal_id);
+        if expires_at > now {
+            state.entries.insert(
+                principal_id,
+                MemoryEntry {
+                    user_id,
+                    dek,
+                    expires_at,
+                    last_used: now,
+                },
+            );
+            state.by_user.entry(user_id).or_default().insert(principal_id);
+        }
+        Ok(())
     }
 
-    /// Return an owned clone of the DEK for the given principal if present.
-    /// The clone is a fresh `Dek` that the caller owns; it is zeroed on drop.
-    pub async fn get_cloned(&self, principal_id: &Uuid) -> Option<Dek> {
-        let guard = self.inner.read().await;
-        guard.get(principal_id).map(|d| d.clone_for_request())
+    async fn get_cloned(&self, principal_id: &Uuid) -> Result<Option<Dek>, AppError> {
+        let now = Utc::now();
+        let mut state = self.inner.write().await;
+        let live = match state.entries.get(principal_id) {
+            Some(entry) => self.is_live(entry, now),
+            None => return Ok(None),
+        };
+        if !live {
+            state.take(principal_id);
+            return Ok(None);
+        }
+        Ok(state.entries.get_mut(principal_id).map(|entry| {
+            entry.last_used = now;
+            entry.dek.clone_for_request()
+        }))
     }
 
-    /// Remove the DEK entry for a principal. Used on logout, session
-    /// revoke, and session expiry. The removed Dek is dropped (and zeroed)
-    /// synchronously.
-    pub async fn remove(&self, principal_id: &Uuid) {
-        let mut guard = self.inner.write().await;
-        guard.remove(principal_id);
+    async fn remove(&self, principal_id: &Uuid) -> Result<(), AppError> {
+        self.inner.write().await.take(principal_id);
+        Ok(())
     }
 
-    /// Remove every entry for a given user. Used during password change to
-    /// invalidate DEK caches on other devices, and during
-    /// `delete_all_user_data`. We can't key by user_id directly because the
-    /// map is principal-keyed, so callers must pass the list of principal
-    /// ids (session ids and/or api token ids) they want invalidated.
-    ///
-    /// This helper is a placeholder until Phase 5 adds a secondary
-    /// `principal_id → user_id` index to the store.
-    #[allow(dead_code)]
-    pub async fn remove_many(&self, principal_ids: &[Uuid]) {
-        let mut guard = self.inner.write().await;
+    async fn remove_many(&self, principal_ids: &[Uuid]) -> Result<(), AppError> {
+        let mut state = self.inner.write().await;
         for id in principal_ids {
-            guard.remove(id);
+            state.take(id);
+        }
+        Ok(())
+    }
+
+    async fn remove_all_for_user(&self, user_id: &Uuid, keep: Option<&Uuid>) -> Result<(), AppError> {
+        let mut state = self.inner.write().await;
+        for id in state.principals_of(user_id) {
+            if Some(&id) != keep {
+                state.take(&id);
+            }
+        }
+        Ok(())
+    }
+
+    async fn replace_for_user(&self, user_id: &Uuid, dek: &Dek) -> Result<(), AppError> {
+        let mut state = self.inner.write().await;
+        for id in state.principals_of(user_id) {
+            if let Some(entry) = state.entries.get_mut(&id) {
+                entry.dek = dek.clone_for_request();
+            }
         }
+        Ok(())
+    }
+
+    async fn mark_envelopes_upgraded(&self, user_id: &Uuid) -> Result<(), AppError> {
+        let mut state = self.inner.write().await;
+        for id in state.principals_of(user_id) {
+            if let Some(entry) = state.entries.get_mut(&id) {
+                entry.dek = entry.dek.clone_for_request().with_legacy_envelopes(false);
+            }
+        }
+        Ok(())
+    }
+
+    async fn evict_expired(&self) -> Result<usize, AppError> {
+        let now = Utc::now();
+        let mut state = self.inner.write().await;
+        let stale: Vec<Uuid> = state.entries.iter().filter(|(_, entry)| !self.is_live(entry, now)).map(|(id, _)| *id).collect();
+        for id in &stale {
+            state.take(id);
+        }
+        Ok(stale.len())
+    }
+}
+
+/// Plaintext layout sealed into each networked entry:
+/// `principal_id (16) || user_id (16) || expires_at unix seconds (8, BE) || dek (32) || legacy envelopes (1)`.
+const SEALED_ENTRY_LEN: usize = 73;
+
+/// Length of entries sealed before the legacy envelope byte was added.
+/// They are read as still accepting legacy envelopes.
+const SEALED_ENTRY_LEN_V0: usize = 72;
+
+/// A networked entry after unsealing.
+struct SealedEntry {
+    user_id: Uuid,
+    expires_at: DateTime<Utc>,
+    dek: Dek,
+}
+
+/// Redis-protocol backend. Each principal key holds the sealed entry with
+/// an `EX` TTL of the remaining idle timeout (capped at the principal's
+/// expiry), refreshed on every lookup; a per-user set indexes the
+/// principals of a user. Binding the principal id into the sealed value
+/// stops an entry copied to another key from unlocking a different
+/// session. Requires Redis 7.0 or newer (`EXPIRE ... NX`/`GT`).
+pub struct RedisDekStore {
+    client: redis::Client,
+    connection: OnceCell<ConnectionManager>,
+    sealing_key: Dek,
+    key_prefix: String,
+    idle_timeout: Duration,
+}
+
+impl RedisDekStore {
+    pub fn new(redis_url: &str, sealing_key: [u8; 32], key_prefix: &str, idle_timeout: Duration) -> Result<Self, redis::RedisError> {
+        Ok(Self {
+            client: redis::Client::open(redis_url)?,
+            connection: OnceCell::new(),
+            sealing_key: Dek::from_bytes(sealing_key),
+            key_prefix: key_prefix.to_string(),
+            idle_timeout,
+        })
+    }
+
+    async fn connection(&self) -> Result<ConnectionManager, AppError> {
+        let manager = self
+            .connection
+            .get_or_try_init(|| ConnectionManager::new(self.client.clone()))
+            .await
+            .map_err(redis_error)?;
+        Ok(manager.clone())
+    }
+
+    fn key(&self, principal_id: &Uuid) -> String {
+        format!("{}{}", self.key_prefix, principal_id)
+    }
+
+    fn user_key(&self, user_id: &Uuid) -> String {
+        format!("{}user:{}", self.key_prefix, user_id)
+    }
+
+    /// Seconds an entry may live from now: the idle timeout, capped at the
+    /// principal's expiry. Zero or negative means already expired.
+    fn entry_ttl(&self, expires_at: DateTime<Utc>) -> i64 {
+        (expires_at - Utc::now()).num_seconds().min(self.idle_timeout.num_seconds())
+    }
+
+    fn seal(&self, principal_id: &Uuid, user_id: &Uuid, expires_at: DateTime<Utc>, dek: &Dek) -> Result<Vec<u8>, AppError> {
+        let mut plaintext = Zeroizing::new(Vec::with_capacity(SEALED_ENTRY_LEN));
+        plaintext.extend_from_slice(principal_id.as_bytes());
+        plaintext.extend_from_slice(user_id.as_bytes());
+        plaintext.extend_from_slice(&expires_at.timestamp().to_be_bytes());
+        plaintext.extend_from_slice(dek.as_bytes());
+        plaintext.push(u8::from(dek.accepts_legacy_envelopes()));
+        Ok(self.sealing_key.encrypt_bytes(&plaintext)?)
+    }
+
+    fn unseal(&self, principal_id: &Uuid, sealed: &[u8]) -> Result<SealedEntry, AppError> {
+        let plaintext = Zeroizing::new(self.sealing_key.decrypt_bytes(sealed)?);
+        if !matches!(plaintext.len(), SEALED_ENTRY_LEN | SEALED_ENTRY_LEN_V0) || &plaintext[..16] != principal_id.as_bytes() {
+            return Err(AppError::internal("Sealed DEK does not belong to this principal"));
+        }
+
+        let user_id = Uuid::from_slice(&plaintext[16..32]).map_err(|e| AppError::uuid("Invalid sealed DEK owner", e))?;
+        let mut timestamp = [0u8; 8];
+        timestamp.copy_from_slice(&plaintext[32..40]);
+        let expires_at = DateTime::from_timestamp(i64::from_be_bytes(timestamp), 0).ok_or_else(|| AppError::internal("Invalid sealed DEK expiry"))?;
+        let mut bytes = Zeroizing::new([0u8; 32]);
+        bytes.copy_from_slice(&plaintext[40..72]);
+        let legacy_envelopes = plaintext.get(72).is_none_or(|&flag| flag != 0);
+
+        Ok(SealedEntry {
+            user_id,
+            expires_at,
+            dek: Dek::from_bytes(*bytes).with_legacy_envelopes(legacy_envelopes),
+        })
+    }
+
+    /// Reseal every entry of `user_id` with the DEK `replace` derives from
+    /// the stored one.
+    async fn reseal_for_user(&self, user_id: &Uuid, replace: impl Fn(&Dek) -> Dek + Send) -> Result<(), AppError> {
+        let mut conn = self.connection().await?;
+        for id in self.principals_of(&mut conn, user_id).await? {
+            let sealed: Option<Vec<u8>> = redis::cmd("GET").arg(self.key(&id)).query_async(&mut conn).await.map_err(redis_error)?;
+            let Some(sealed) = sealed else {
+                continue;
+            };
+            let entry = self.unseal(&id, &sealed)?;
+
+            // XX: skip entries that expired meanwhile; KEEPTTL: a reseal
+            // neither extends nor shortens the entry.
+            redis::cmd("SET")
+                .arg(self.key(&id))
+                .arg(self.seal(&id, &entry.user_id, entry.expires_at, &replace(&entry.dek))?)
+                .arg("XX")
+                .arg("KEEPTTL")
+                .query_async::<()>(&mut conn)
+                .await
+                .map_err(redis_error)?;
+        }
+        Ok(())
+    }
+
+    async fn principals_of(&self, conn: &mut ConnectionManager, user_id: &Uuid) -> Result<Vec<Uuid>, AppError> {
+        let members: Vec<String> = redis::cmd("SMEMBERS")
+            .arg(self.user_key(user_id))
+            .query_async(conn)
+            .await
+            .map_err(redis_error)?;
+        Ok(members.iter().filter_map(|m| Uuid::parse_str(m).ok()).collect())
     }
 }
 
-impl Default for SessionDekStore {
-    fn default() -> Self {
-        Self::new()
+fn redis_error(e: redis::RedisError) -> AppError {
+    AppError::internal(format!("Session DEK store unavailable: {}", e))
+}
+
+#[rocket::async_trait]
+impl SessionDekStore for RedisDekStore {
+    async fn put(&self, principal_id: Uuid, user_id: Uuid, dek: Dek, expires_at: DateTime<Utc>) -> Result<(), AppError> {
+        let ttl_seconds = self.entry_ttl(expires_at);
+        if ttl_seconds <= 0 {
+            return self.remove(&principal_id).await;
+        }
+
+        let sealed = self.seal(&principal_id, &user_id, expires_at, &dek)?;
+        // The index outlives every entry it lists: NX sets a TTL on a new
+        // set, GT only ever extends it.
+        let index_ttl_seconds = (expires_at - Utc::now()).num_seconds();
+        let user_key = self.user_key(&user_id);
+
+        let mut conn = self.connection().await?;
+        redis::pipe()
+            .atomic()
+            .cmd("SET")
+            .arg(self.key(&principal_id))
+            .arg(sealed)
+            .arg("EX")
+            .arg(ttl_seconds)
+            .ignore()
+            .cmd("SADD")
+            .arg(&user_key)
+            .arg(principal_id.to_string())
+            .ignore()
+            .cmd("EXPIRE")
+            .arg(&user_key)
+            .arg(index_ttl_seconds)
+            .arg("NX")
+            .ignore()
+            .cmd("EXPIRE")
+            .arg(&user_key)
+            .arg(index_ttl_seconds)
+            .arg("GT")
+            .ignore()
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(redis_error)
+    }
+
+    async fn get_cloned(&self, principal_id: &Uuid) -> Result<Option<Dek>, AppError> {
+        let mut conn = self.connection().await?;
+        let sealed: Option<Vec<u8>> = redis::cmd("GET")
+            .arg(self.key(principal_id))
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_error)?;
+        let Some(sealed) = sealed else {
+            return Ok(None);
+        };
+
+        let entry = self.unseal(principal_id, &sealed)?;
+        let ttl_seconds = self.entry_ttl(entry.expires_at);
+        if ttl_seconds <= 0 {
+            self.remove(principal_id).await?;
+            return Ok(None);
+        }
+
+        // Restart the idle clock.
+        redis::cmd("EXPIRE")
+            .arg(self.key(principal_id))
+            .arg(ttl_seconds)
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(redis_error)?;
+
+        Ok(Some(entry.dek))
+    }
+
+    async fn remove(&self, principal_id: &Uuid) -> Result<(), AppError> {
+        self.remove_many(std::slice::from_ref(principal_id)).await
+    }
+
+    async fn remove_many(&self, principal_ids: &[Uuid]) -> Result<(), AppError> {
+        if principal_ids.is_empty() {
+            return Ok(());
+        }
+
+        // Index members left behind are harmless: deleting a missing key
+        // is a no-op, and the index expires with the user's last principal.
+        let mut conn = self.connection().await?;
+        let mut cmd = redis::cmd("DEL");
+        for id in principal_ids {
+            cmd.arg(self.key(id));
+        }
+        cmd.query_async::<()>(&mut conn).await.map_err(redis_error)
+    }
+
+    async fn remove_all_for_user(&self, user_id: &Uuid, keep: Option<&Uuid>) -> Result<(), AppError> {
+        let mut conn = self.connection().await?;
+        let doomed: Vec<Uuid> = self
+            .principals_of(&mut conn, user_id)
+            .await?
+            .into_iter()
+            .filter(|id| Some(id) != keep)
+            .collect();
+        if doomed.is_empty() {
+            return Ok(());
+        }
+
+        let mut del = redis::cmd("DEL");
+        let mut srem = redis::cmd("SREM");
+        srem.arg(self.user_key(user_id));
+        for id in &doomed {
+            del.arg(self.key(id));
+            srem.arg(id.to_string());
+        }
+        redis::pipe()
+            .atomic()
+            .add_command(del)
+            .ignore()
+            .add_command(srem)
+            .ignore()
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(redis_error)
+    }
+
+    async fn replace_for_user(&self, user_id: &Uuid, dek: &Dek) -> Result<(), AppError> {
+        self.reseal_for_user(user_id, |_| dek.clone_for_request()).await
+    }
+
+    async fn mark_envelopes_upgraded(&self, user_id: &Uuid) -> Result<(), AppError> {
+        self.reseal_for_user(user_id, |dek| dek.clone_for_request().with_legacy_envelopes(false)).await
+    }
+
+    async fn evict_expired(&self) -> Result<usize, AppError> {
+        // Key TTLs expire entries server-side.
+        Ok(0)
     }
 }
 
@@ -100,14 +572,18 @@ impl Default for SessionDekStore {
 /// the DEK by that principal's id. Rejects with 401 if:
 ///   * the caller is not authenticated, or
 ///   * the caller is authenticated but has not yet completed `/v2/auth/unlock`.
+///
+/// The yielded DEK accepts legacy envelopes only if the user's envelopes
+/// had not all been upgraded at unlock; the flag lives on the store entry
+/// and is cleared by `mark_envelopes_upgraded`.
 #[rocket::async_trait]
 impl<'r> FromRequest<'r> for Dek {
     type Error = AppError;
 
     async fn from_request(req: &'r Request<'_>) -> RequestOutcome<Self, Self::Error> {
-        let store = match req.rocket().state::<SessionDekStore>() {
+        let store = match req.rocket().state::<DekStore>() {
             Some(s) => s,
-            None => return Outcome::Error((Status::InternalServerError, AppError::internal("SessionDekStore not registered"))),
+            None => return Outcome::Error((Status::InternalServerError, AppError::internal("DekStore not registered"))),
         };
 
         let user = match req.guard::<CurrentUser>().await {
@@ -121,8 +597,289 @@ impl<'r> FromRequest<'r> for Dek {
         };
 
         match store.get_cloned(&principal_id).await {
-            Some(dek) => Outcome::Success(dek),
-            None => Outcome::Error((Status::Unauthorized, AppError::Unauthorized)),
+            Ok(Some(dek)) => Outcome::Success(dek),
+            Ok(None) => Outcome::Error((Status::Unauthorized, AppError::Unauthorized)),
+            Err(e) => Outcome::Error((Status::InternalServerError, e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory_store() -> InMemoryDekStore {
+        InMemoryDekStore::new(Duration::minutes(30))
+    }
+
+    fn redis_store() -> RedisDekStore {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        RedisDekStore::new(&url, [7u8; 32], &format!("piggy_pulse_test:{}:", Uuid::new_v4()), Duration::minutes(30)).expect("valid redis url")
+    }
+
+    fn in_an_hour() -> DateTime<Utc> {
+        Utc::now() + Duration::hours(1)
+    }
+
+    #[tokio::test]
+    async fn memory_store_round_trips_until_expiry() {
+        let store = memory_store();
+        let user = Uuid::new_v4();
+        let live = Uuid::new_v4();
+        let expired = Uuid::new_v4();
+        let dek = Dek::generate();
+
+        store.put(live, user, dek.clone_for_request(), in_an_hour()).await.unwrap();
+        store
+            .put(expired, user, dek.clone_for_request(), Utc::now() - Duration::seconds(1))
+            .await
+            .unwrap();
+
+        let fetched = store.get_cloned(&live).await.unwrap().expect("entry present");
+        assert_eq!(fetched.as_bytes(), dek.as_bytes());
+        assert!(store.get_cloned(&expired).await.unwrap().is_none());
+
+        store.remove(&live).await.unwrap();
+        assert!(store.get_cloned(&live).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn memory_store_evicts_idle_entries() {
+        let store = InMemoryDekStore::new(Duration::milliseconds(50));
+        let user = Uuid::new_v4();
+        let idle = Uuid::new_v4();
+        let busy = Uuid::new_v4();
+
+        store.put(idle, user, Dek::generate(), in_an_hour()).await.unwrap();
+        store.put(busy, user, Dek::generate(), in_an_hour()).await.unwrap();
+
+        for _ in 0..3 {
+            tokio::time::sleep(std::time::Duration::from_millis(30)).await;
+            assert!(store.get_cloned(&busy).await.unwrap().is_some(), "lookups keep an entry alive");
+        }
+
+        assert_eq!(store.evict_expired().await.unwrap(), 1);
+        assert!(store.get_cloned(&idle).await.unwrap().is_none());
+        assert!(store.inner.read().await.by_user[&user].contains(&busy));
+    }
+
+    #[tokio::test]
+    async fn memory_store_remove_all_for_user_keeps_other_users_and_current_session() {
+        let store = memory_store();
+        let user = Uuid::new_v4();
+        let other_user = Uuid::new_v4();
+        let (current, other_device, foreign) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        store.put(current, user, Dek::generate(), in_an_hour()).await.unwrap();
+        store.put(other_device, user, Dek::generate(), in_an_hour()).await.unwrap();
+        store.put(foreign, other_user, Dek::generate(), in_an_hour()).await.unwrap();
+
+        store.remove_all_for_user(&user, Some(&current)).await.unwrap();
+        assert!(store.get_cloned(&current).await.unwrap().is_some());
+        assert!(store.get_cloned(&other_device).await.unwrap().is_none());
+        assert!(store.get_cloned(&foreign).await.unwrap().is_some());
+
+        store.remove_all_for_user(&user, None).await.unwrap();
+        assert!(store.get_cloned(&current).await.unwrap().is_none());
+        assert!(!store.inner.read().await.by_user.contains_key(&user));
+    }
+
+    #[tokio::test]
+    async fn memory_store_replace_for_user_only_touches_that_user() {
+        let store = memory_store();
+        let user = Uuid::new_v4();
+        let other_user = Uuid::new_v4();
+        let (mine, foreign) = (Uuid::new_v4(), Uuid::new_v4());
+        let foreign_dek = Dek::generate();
+        let new_dek = Dek::generate();
+
+        store.put(mine, user, Dek::generate(), in_an_hour()).await.unwrap();
+        store.put(foreign, other_user, foreign_dek.clone_for_request(), in_an_hour()).await.unwrap();
+        store.replace_for_user(&user, &new_dek).await.unwrap();
+
+        assert_eq!(store.get_cloned(&mine).await.unwrap().unwrap().as_bytes(), new_dek.as_bytes());
+        assert_eq!(store.get_cloned(&foreign).await.unwrap().unwrap().as_bytes(), foreign_dek.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn memory_store_mark_envelopes_upgraded_only_touches_that_user() {
+        let store = memory_store();
+        let user = Uuid::new_v4();
+        let other_user = Uuid::new_v4();
+        let (mine, foreign) = (Uuid::new_v4(), Uuid::new_v4());
+        let dek = Dek::generate().with_legacy_envelopes(true);
+
+        store.put(mine, user, dek.clone_for_request(), in_an_hour()).await.unwrap();
+        store
+            .put(foreign, other_user, Dek::generate().with_legacy_envelopes(true), in_an_hour())
+            .await
+            .unwrap();
+        store.mark_envelopes_upgraded(&user).await.unwrap();
+
+        let upgraded = store.get_cloned(&mine).await.unwrap().unwrap();
+        assert_eq!(upgraded.as_bytes(), dek.as_bytes());
+        assert!(!upgraded.accepts_legacy_envelopes());
+        assert!(store.get_cloned(&foreign).await.unwrap().unwrap().accepts_legacy_envelopes());
+    }
+
+    #[tokio::test]
+    async fn memory_store_transfer_moves_entry() {
+        let store = memory_store();
+        let user = Uuid::new_v4();
+        let old = Uuid::new_v4();
+        let new = Uuid::new_v4();
+        let dek = Dek::generate();
+
+        store.put(old, user, dek.clone_for_request(), in_an_hour()).await.unwrap();
+        store.transfer(&old, new, user, Utc::now() + Duration::hours(2)).await.unwrap();
+
+        assert!(store.get_cloned(&old).await.unwrap().is_none());
+        assert_eq!(store.get_cloned(&new).await.unwrap().unwrap().as_bytes(), dek.as_bytes());
+        assert_eq!(store.inner.read().await.by_user[&user].len(), 1);
+    }
+
+    #[test]
+    fn sealed_entry_is_bound_to_its_principal() {
+        let store = redis_store();
+        let principal = Uuid::new_v4();
+        let user = Uuid::new_v4();
+        let expires_at = DateTime::from_timestamp(in_an_hour().timestamp(), 0).unwrap();
+        let dek = Dek::generate();
+
+        let sealed = store.seal(&principal, &user, expires_at, &dek).unwrap();
+
+        assert!(!sealed.windows(32).any(|w| w == dek.as_bytes()));
+        let entry = store.unseal(&principal, &sealed).unwrap();
+        assert_eq!(entry.dek.as_bytes(), dek.as_bytes());
+        assert_eq!(entry.user_id, user);
+        assert_eq!(entry.expires_at, expires_at);
+        assert!(store.unseal(&Uuid::new_v4(), &sealed).is_err());
+    }
+
+    #[test]
+    fn sealed_entry_keeps_the_legacy_envelope_flag() {
+        let store = redis_store();
+        let principal = Uuid::new_v4();
+        let user = Uuid::new_v4();
+
+        for legacy in [false, true] {
+            let dek = Dek::generate().with_legacy_envelopes(legacy);
+            let sealed = store.seal(&principal, &user, in_an_hour(), &dek).unwrap();
+            assert_eq!(store.unseal(&principal, &sealed).unwrap().dek.accepts_legacy_envelopes(), legacy);
         }
+
+        // Entries sealed before the flag existed still accept legacy envelopes.
+        let mut plaintext = Vec::with_capacity(SEALED_ENTRY_LEN_V0);
+        plaintext.extend_from_slice(principal.as_bytes());
+        plaintext.extend_from_slice(user.as_bytes());
+        plaintext.extend_from_slice(&in_an_hour().timestamp().to_be_bytes());
+        plaintext.extend_from_slice(Dek::generate().as_bytes());
+        let sealed = store.sealing_key.encrypt_bytes(&plaintext).unwrap();
+        assert!(store.unseal(&principal, &sealed).unwrap().dek.accepts_legacy_envelopes());
+    }
+
+    #[test]
+    fn sealed_entry_requires_the_sealing_key() {
+        let store = redis_store();
+        let other = RedisDekStore::new("redis://127.0.0.1:6379", [8u8; 32], "piggy_pulse_test:", Duration::minutes(30)).unwrap();
+        let principal = Uuid::new_v4();
+
+        let sealed = store.seal(&principal, &Uuid::new_v4(), in_an_hour(), &Dek::generate()).unwrap();
+
+        assert!(other.unseal(&principal, &sealed).is_err());
+    }
+
+    #[test]
+    fn entry_ttl_is_capped_by_idle_timeout_and_expiry() {
+        let store = redis_store();
+
+        assert_eq!(store.entry_ttl(Utc::now() + Duration::days(30)), 30 * 60);
+        assert!(store.entry_ttl(Utc::now() + Duration::seconds(90)) <= 90);
+        assert!(store.entry_ttl(Utc::now() - Duration::seconds(1)) <= 0);
+    }
+
+    async fn redis_ttl(store: &RedisDekStore, key: &str) -> i64 {
+        let mut conn = store.connection().await.unwrap();
+        redis::cmd("TTL").arg(key).query_async(&mut conn).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires redis"]
+    async fn redis_store_round_trips_with_ttl() {
+        let store = redis_store();
+        let principal = Uuid::new_v4();
+        let user = Uuid::new_v4();
+        let dek = Dek::generate();
+
+        store
+            .put(principal, user, dek.clone_for_request(), Utc::now() + Duration::seconds(120))
+            .await
+            .unwrap();
+        assert_eq!(store.get_cloned(&principal).await.unwrap().unwrap().as_bytes(), dek.as_bytes());
+
+        let ttl = redis_ttl(&store, &store.key(&principal)).await;
+        assert!((1..=120).contains(&ttl), "unexpected ttl {ttl}");
+        let index_ttl = redis_ttl(&store, &store.user_key(&user)).await;
+        assert!((1..=120).contains(&index_ttl), "unexpected index ttl {index_ttl}");
+
+        let mut conn = store.connection().await.unwrap();
+        let raw: Vec<u8> = redis::cmd("GET").arg(store.key(&principal)).query_async(&mut conn).await.unwrap();
+        assert!(!raw.windows(32).any(|w| w == dek.as_bytes()));
+
+        store.remove(&principal).await.unwrap();
+        assert!(store.get_cloned(&principal).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires redis"]
+    async fn redis_store_idle_timeout_caps_ttl() {
+        let store = redis_store();
+        let principal = Uuid::new_v4();
+
+        store
+            .put(principal, Uuid::new_v4(), Dek::generate(), Utc::now() + Duration::days(30))
+            .await
+            .unwrap();
+
+        let ttl = redis_ttl(&store, &store.key(&principal)).await;
+        assert!((1..=30 * 60).contains(&ttl), "unexpected ttl {ttl}");
+
+        store.remove(&principal).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires redis"]
+    async fn redis_store_remove_all_for_user_keeps_current_session() {
+        let store = redis_store();
+        let user = Uuid::new_v4();
+        let (current, other_device) = (Uuid::new_v4(), Uuid::new_v4());
+
+        store.put(current, user, Dek::generate(), in_an_hour()).await.unwrap();
+        store.put(other_device, user, Dek::generate(), in_an_hour()).await.unwrap();
+
+        store.remove_all_for_user(&user, Some(&current)).await.unwrap();
+        assert!(store.get_cloned(&current).await.unwrap().is_some());
+        assert!(store.get_cloned(&other_device).await.unwrap().is_none());
+
+        store.remove_all_for_user(&user, None).await.unwrap();
+        assert!(store.get_cloned(&current).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires redis"]
+    async fn redis_store_replace_for_user_keeps_ttl() {
+        let store = redis_store();
+        let user = Uuid::new_v4();
+        let unlocked = Uuid::new_v4();
+        let new_dek = Dek::generate();
+
+        store.put(unlocked, user, Dek::generate(), Utc::now() + Duration::seconds(120)).await.unwrap();
+        store.replace_for_user(&user, &new_dek).await.unwrap();
+
+        assert_eq!(store.get_cloned(&unlocked).await.unwrap().unwrap().as_bytes(), new_dek.as_bytes());
+        let ttl = redis_ttl(&store, &store.key(&unlocked)).await;
+        assert!((1..=120).contains(&ttl), "unexpected ttl {ttl}");
+
+        store.remove_all_for_user(&user, None).await.unwrap();
     }
 }
@@ -23,7 +23,8 @@ pub async fn get_eur_currency_id_unauthenticated(client: &Client) -> String {
 
 /// Unlocks the session with a test DEK (all zeros for integration tests).
 /// This must be called after login/register for any authenticated operations.
-pub async fn unlock_session(client: &Client) {
+/// Returns the response body listing the auto-posted subscription charges.
+pub async fn unlock_session(client: &Client) -> Value {
     // Generate a test DEK (32 bytes of zeros, base64-encoded)
     let test_dek = BASE64.encode([0u8; 32]);
 
@@ -38,7 +39,8 @@ pub async fn unlock_session(client: &Client) {
         .dispatch()
         .await;
 
-    assert_eq!(resp.status(), Status::NoContent, "unlock failed: {:?}", resp.status());
+    assert_eq!(resp.status(), Status::Ok, "unlock failed: {:?}", resp.status());
+    serde_json::from_str(&resp.into_string().await.unwrap()).unwrap()
 }
 
 /// Creates a unique user via V2 register, sets currency (EUR), and returns `(user_id, email)`.
@@ -97,3 +99,48 @@ pub async fn create_user_and_login(client: &Client) -> (String, String) {
 
     (user_id, email)
 }
+
+/// Simulates another unlocked device of `user_id` by caching a DEK under a
+/// fresh principal id directly in the app's DEK store.
+#[allow(dead_code)]
+pub async fn seed_other_device_dek(client: &Client, user_id: &str) -> Uuid {
+    let store = client.rocket().state::<piggy_pulse::session_dek::DekStore>().expect("DEK store registered");
+    let principal_id = Uuid::new_v4();
+    let user_id = Uuid::parse_str(user_id).expect("valid user id");
+    store
+        .put(
+            principal_id,
+            user_id,
+            piggy_pulse::crypto::Dek::generate(),
+            chrono::Utc::now() + chrono::Duration::hours(1),
+        )
+        .await
+        .expect("seed DEK");
+    principal_id
+}
+
+/// Whether the app's DEK store still holds a DEK for `principal_id`.
+#[allow(dead_code)]
+pub async fn has_cached_dek(client: &Client, principal_id: &Uuid) -> bool {
+    let store = client.rocket().state::<piggy_pulse::session_dek::DekStore>().expect("DEK store registered");
+    store.get_cloned(principal_id).await.expect("DEK store lookup").is_some()
+}
+
+/// Inserts a fresh password reset token for `user_id` directly in the database
+/// (the real token is only ever delivered by email) and returns the plain token.
+#[allow(dead_code)]
+pub async fn insert_reset_token(user_id: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    let url = std::env::var("DATABASE_URL").unwrap_or_else(|_| super::TEST_DB_URL.to_string());
+    let pool = sqlx::PgPool::connect(&url).await.expect("connect to test db");
+    let token = Uuid::new_v4().simple().to_string();
+    let token_hash = hex::encode(Sha256::digest(token.as_bytes()));
+    sqlx::query("INSERT INTO password_resets (user_id, token_hash, expires_at) VALUES ($1, $2, NOW() + INTERVAL '1 hour')")
+        .bind(Uuid::parse_str(user_id).unwrap())
+        .bind(&token_hash)
+        .execute(&pool)
+        .await
+        .expect("insert password reset");
+    token
+}
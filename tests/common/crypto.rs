@@ -1,22 +1,68 @@
+use aes_gcm::aes::Aes256;
+use aes_gcm::aes::cipher::{Array, BlockCipherEncrypt, KeyInit as BlockKeyInit};
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD as BASE64;
 
 /// Test DEK — must match the one sent in unlock_session (32 zero bytes).
 const TEST_DEK_BYTES: [u8; 32] = [0u8; 32];
 
-/// Decrypt an AES-GCM envelope (base64-encoded nonce+ciphertext) with the test DEK.
+/// Version + algorithm header of a v1 envelope (v1, AES-256-GCM).
+const ENVELOPE_V1_HEADER: [u8; 2] = [0x01, 0x01];
+
+/// Decrypt an envelope (base64) with the test DEK without knowing which
+/// cell it belongs to. v1 envelopes authenticate their cell through the GCM
+/// tag, which this helper cannot check, so it recovers the plaintext from
+/// the CTR keystream instead. Tests that care about the binding use
+/// `try_decrypt_cell_with`.
 #[allow(dead_code)]
 pub fn decrypt_envelope(envelope_b64: &str) -> Vec<u8> {
-    use aes_gcm::aead::{Aead, KeyInit};
+    let envelope = BASE64.decode(envelope_b64.as_bytes()).expect("valid base64");
+    let body = envelope.strip_prefix(ENVELOPE_V1_HEADER.as_slice()).expect("v1 envelope header");
+    assert!(body.len() >= 12 + 16, "envelope too short");
+    let (nonce, rest) = body.split_at(12);
+    let ciphertext = &rest[..rest.len() - 16];
+
+    let cipher = Aes256::new(&TEST_DEK_BYTES.into());
+    ciphertext
+        .chunks(16)
+        .enumerate()
+        .flat_map(|(i, chunk)| {
+            // GCM encrypts with counter blocks nonce || be32(2 + i).
+            let mut block = Array::default();
+            block[..12].copy_from_slice(nonce);
+            block[12..].copy_from_slice(&(2 + i as u32).to_be_bytes());
+            cipher.encrypt_block(&mut block);
+            chunk.iter().zip(block).map(|(c, k)| c ^ k).collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Decrypt a v1 envelope with an arbitrary key, authenticating it against
+/// the cell it was read from. Returns None if the tag does not verify
+/// (wrong key, wrong cell, or corrupted ciphertext).
+#[allow(dead_code)]
+pub fn try_decrypt_cell_with(key: &[u8; 32], table: &str, column: &str, row_id: &str, user_id: &str, envelope_b64: &str) -> Option<Vec<u8>> {
+    use aes_gcm::aead::{Aead, KeyInit, Payload};
     use aes_gcm::{Aes256Gcm, Nonce};
+
     let envelope = BASE64.decode(envelope_b64.as_bytes()).expect("valid base64");
-    if envelope.len() < 12 + 16 {
+    let body = envelope.strip_prefix(ENVELOPE_V1_HEADER.as_slice())?;
+    if body.len() < 12 + 16 {
         panic!("envelope too short");
     }
-    let (nonce_bytes, ciphertext) = envelope.split_at(12);
-    let cipher = Aes256Gcm::new_from_slice(&TEST_DEK_BYTES).expect("valid key");
+
+    let mut aad = ENVELOPE_V1_HEADER.to_vec();
+    for part in [table, column] {
+        aad.extend_from_slice(&(part.len() as u16).to_be_bytes());
+        aad.extend_from_slice(part.as_bytes());
+    }
+    aad.extend_from_slice(uuid::Uuid::parse_str(row_id).expect("row id").as_bytes());
+    aad.extend_from_slice(uuid::Uuid::parse_str(user_id).expect("user id").as_bytes());
+
+    let (nonce_bytes, ciphertext) = body.split_at(12);
+    let cipher = Aes256Gcm::new_from_slice(key).expect("valid key");
     let nonce = Nonce::try_from(nonce_bytes).expect("nonce is always 12 bytes");
-    cipher.decrypt(&nonce, ciphertext).expect("decrypt with test DEK")
+    cipher.decrypt(&nonce, Payload { msg: ciphertext, aad: &aad }).ok()
 }
 
 /// Decrypt an encrypted i64 field from an AES-GCM envelope.
@@ -69,6 +69,25 @@ pub async fn create_vendor(client: &Client, name: &str) -> String {
     body["id"].as_str().expect("vendor id").to_string()
 }
 
+/// Creates a tag via V2 POST /tags. Returns the tag ID.
+pub async fn create_tag(client: &Client, name: &str) -> String {
+    let payload = serde_json::json!({
+        "name": name,
+        "color": "#228BE6"
+    });
+
+    let resp = client
+        .post(format!("{}/tags", super::V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created, "create_tag failed");
+
+    let body: Value = serde_json::from_str(&resp.into_string().await.expect("tag body")).expect("valid json");
+    body["id"].as_str().expect("tag id").to_string()
+}
+
 /// Creates a period via V2 POST /periods. Returns the period ID.
 pub async fn create_period(client: &Client, start: &str, end: &str) -> String {
     let payload = serde_json::json!({
@@ -38,6 +38,11 @@ pub fn test_config() -> Config {
     config.session.cookie_secure = false;
     // Set a valid 2FA encryption key for tests (insecure, debug-only)
     config.two_factor.encryption_key = "0000000000000000000000000000000000000000000000000000000000000000".to_string();
+    // Run the suites against the networked DEK store when a redis server is provided
+    if let Ok(url) = std::env::var("TEST_DEK_STORE_REDIS_URL") {
+        config.dek_store.backend = piggy_pulse::DekStoreBackend::Redis;
+        config.dek_store.redis_url = url;
+    }
     config
 }
 
@@ -1,7 +1,5 @@
 mod common;
 
-use base64::Engine;
-use base64::engine::general_purpose::STANDARD as BASE64;
 use common::auth::{create_user_and_login, get_eur_currency_id};
 use common::entities::{create_account, create_category, create_period, create_target, create_transaction};
 use common::{TEST_PASSWORD, V2_BASE, test_client};
@@ -9,22 +7,9 @@ use rocket::http::{ContentType, Status};
 use rocket::local::asynchronous::Client;
 use serde_json::Value;
 
-// Test DEK — must match the one sent in unlock_session (32 zero bytes, base64-encoded).
-const TEST_DEK_BYTES: [u8; 32] = [0u8; 32];
-const _TEST_DEK_B64: &str = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
-
 /// Helper to decrypt an AES-GCM envelope with the test DEK (all zeros).
 fn decrypt_test_dek(envelope_b64: &str) -> Vec<u8> {
-    use aes_gcm::aead::{Aead, KeyInit};
-    use aes_gcm::{Aes256Gcm, Nonce};
-    let envelope = BASE64.decode(envelope_b64.as_bytes()).expect("valid base64");
-    if envelope.len() < 12 + 16 {
-        panic!("envelope too short");
-    }
-    let (nonce_bytes, ciphertext) = envelope.split_at(12);
-    let cipher = Aes256Gcm::new_from_slice(&TEST_DEK_BYTES).expect("valid key");
-    let nonce = Nonce::try_from(nonce_bytes).expect("nonce is always 12 bytes");
-    cipher.decrypt(&nonce, ciphertext).expect("decrypt with test DEK")
+    common::crypto::decrypt_envelope(envelope_b64)
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -674,7 +659,8 @@ async fn test_reset_structure_allows_rebuilding_data() {
 async fn test_delete_account_removes_all_user_data() {
     common::clear_login_rate_limits().await;
     let client = test_client().await;
-    let (_user_id, email) = create_user_and_login(&client).await;
+    let (user_id, email) = create_user_and_login(&client).await;
+    let other_device = common::auth::seed_other_device_dek(&client, &user_id).await;
 
     // Create some data
     create_account(&client, "DI DelUser Acct", 100_000).await;
@@ -689,6 +675,9 @@ async fn test_delete_account_removes_all_user_data() {
         .await;
     assert_eq!(resp.status(), Status::NoContent);
 
+    // Assert: no device of the deleted user keeps an unlocked DEK
+    assert!(!common::auth::has_cached_dek(&client, &other_device).await);
+
     // Assert: login with same credentials fails
     let client2 = test_client().await;
     let login_payload = serde_json::json!({
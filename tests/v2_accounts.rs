@@ -1,7 +1,7 @@
 mod common;
 
 use common::auth::create_user_and_login;
-use common::crypto::{decrypt_i64, decrypt_string};
+use common::crypto::{decrypt_i64, decrypt_string, try_decrypt_cell_with};
 use common::{V2_BASE, test_client};
 use rocket::http::{ContentType, Status};
 use serde_json::{Value, json};
@@ -114,6 +114,111 @@ async fn test_create_allowance_with_null_spend_limit() {
     assert_eq!(body["currencyId"], eur_id);
 }
 
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_create_allowance_validates_top_up_cycle() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let eur_id = common::auth::get_eur_currency_id(&client).await;
+
+    let mut payload = json!({
+        "accountType": "allowance",
+        "name": "Pocket Money",
+        "color": "#ff00ff",
+        "initialBalance": 0,
+        "currencyId": eur_id,
+        "topUpAmount": 1500,
+        "topUpCycle": "bi-weekly",
+        "topUpDay": 5
+    });
+    let resp = client
+        .post(format!("{}/accounts", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(body["topUpCycle"], "bi-weekly");
+
+    for cycle in ["biweekly", "daily", "Weekly"] {
+        payload["name"] = json!(format!("Pocket Money {cycle}"));
+        payload["topUpCycle"] = json!(cycle);
+        let resp = client
+            .post(format!("{}/accounts", V2_BASE))
+            .header(ContentType::JSON)
+            .body(payload.to_string())
+            .dispatch()
+            .await;
+        assert_eq!(resp.status(), Status::UnprocessableEntity, "{cycle}");
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_allowance_top_up_day_follows_its_cycle() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let eur_id = common::auth::get_eur_currency_id(&client).await;
+
+    let payload = |cycle: &str, day: i32| {
+        json!({
+            "accountType": "allowance",
+            "name": format!("Pocket {cycle} {day}"),
+            "color": "#ff00ff",
+            "initialBalance": 0,
+            "currencyId": eur_id,
+            "topUpAmount": 1500,
+            "topUpCycle": cycle,
+            "topUpDay": day
+        })
+    };
+
+    // Weekly days are ISO weekdays, 1 (Monday) to 7 (Sunday); monthly
+    // days are days of the month.
+    let mut created = None;
+    for (cycle, day, status) in [
+        ("weekly", 1, Status::Created),
+        ("weekly", 7, Status::Created),
+        ("bi-weekly", 7, Status::Created),
+        ("monthly", 31, Status::Created),
+        ("weekly", 0, Status::BadRequest),
+        ("weekly", 8, Status::BadRequest),
+        ("bi-weekly", 0, Status::BadRequest),
+        ("monthly", 0, Status::BadRequest),
+        ("monthly", 32, Status::BadRequest),
+    ] {
+        let resp = client
+            .post(format!("{}/accounts", V2_BASE))
+            .header(ContentType::JSON)
+            .body(payload(cycle, day).to_string())
+            .dispatch()
+            .await;
+        assert_eq!(resp.status(), status, "{cycle} {day}");
+        if status == Status::Created && created.is_none() {
+            let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+            created = body["id"].as_str().map(str::to_string);
+        }
+    }
+
+    // Updates are checked the same way.
+    let account_id = created.unwrap();
+    let resp = client
+        .put(format!("{}/accounts/{}", V2_BASE, account_id))
+        .header(ContentType::JSON)
+        .body(payload("weekly", 0).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::BadRequest);
+    let resp = client
+        .put(format!("{}/accounts/{}", V2_BASE, account_id))
+        .header(ContentType::JSON)
+        .body(payload("monthly", 15).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+}
+
 #[rocket::async_test]
 #[ignore = "requires database"]
 async fn test_create_account_no_auth() {
@@ -683,6 +788,96 @@ async fn test_adjust_balance_downward() {
     assert_eq!(balance, 5000);
 }
 
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_adjust_balance_records_ledger_entry() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "Adjust Ledger", 10000).await;
+    let category_id = common::entities::create_category(&client, "Adjust Groceries", "expense").await;
+    common::entities::create_transaction(&client, &account_id, &category_id, 2000, "2026-03-10").await;
+
+    let adjust = |new_balance: i64| {
+        let client = &client;
+        let account_id = &account_id;
+        async move {
+            let resp = client
+                .post(format!("{}/accounts/{}/adjust-balance", V2_BASE, account_id))
+                .header(ContentType::JSON)
+                .body(json!({ "newBalance": new_balance }).to_string())
+                .dispatch()
+                .await;
+            assert_eq!(resp.status(), Status::Ok);
+            let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+            decrypt_i64(body["currentBalanceEnc"].as_str().unwrap())
+        }
+    };
+    let adjustments = || {
+        let client = &client;
+        let account_id = &account_id;
+        async move {
+            let resp = client.get(format!("{}/transactions/page?accountId={}", V2_BASE, account_id)).dispatch().await;
+            assert_eq!(resp.status(), Status::Ok);
+            let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+            body["data"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|t| t["isAdjustment"] == true)
+                .cloned()
+                .collect::<Vec<Value>>()
+        }
+    };
+
+    assert_eq!(adjust(9500).await, 9500);
+    let rows = adjustments().await;
+    assert_eq!(rows.len(), 1);
+    assert_eq!(decrypt_i64(rows[0]["amountEnc"].as_str().unwrap()), 1500);
+    assert_eq!(decrypt_string(rows[0]["descriptionEnc"].as_str().unwrap()), "Balance adjustment");
+    assert!(rows[0]["categoryId"].is_null());
+    assert_eq!(rows[0]["clearedStatus"], "cleared");
+    let adjustment_id = rows[0]["id"].as_str().unwrap().to_string();
+
+    // Setting the balance it already has writes nothing.
+    assert_eq!(adjust(9500).await, 9500);
+    assert_eq!(adjustments().await.len(), 1);
+
+    assert_eq!(adjust(9000).await, 9000);
+    let amounts: Vec<i64> = adjustments().await.iter().map(|t| decrypt_i64(t["amountEnc"].as_str().unwrap())).collect();
+    assert_eq!(amounts.iter().sum::<i64>(), 1000);
+    assert!(amounts.contains(&-500));
+
+    let resp = client.get(format!("{}/transactions/{}/history", V2_BASE, adjustment_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    let history: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(history[0]["kind"], "adjustment");
+
+    // Adjustments are not work for the uncategorized inbox, and stay put.
+    let resp = client.get(format!("{}/transactions/page?uncategorized=true", V2_BASE)).dispatch().await;
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(body["totalCount"], 0);
+    let resp = client.delete(format!("{}/transactions/{}", V2_BASE, adjustment_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::Conflict);
+    let resp = client
+        .put(format!("{}/transactions/{}", V2_BASE, adjustment_id))
+        .header(ContentType::JSON)
+        .body(
+            json!({
+                "transactionType": "Regular",
+                "date": "2026-03-10",
+                "description": "Recategorized",
+                "amount": 1500,
+                "fromAccountId": account_id,
+                "categoryId": category_id,
+                "vendorId": null
+            })
+            .to_string(),
+        )
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Conflict);
+}
+
 #[rocket::async_test]
 #[ignore = "requires database"]
 async fn test_adjust_balance_not_found() {
@@ -891,3 +1086,617 @@ async fn test_update_account_bad_currency_returns_400() {
     eprintln!("STATUS={} BODY={}", status, body);
     assert_ne!(status, Status::InternalServerError, "got 500: {}", body);
 }
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Envelope format
+// ═══════════════════════════════════════════════════════════════════════════════
+
+async fn db_pool() -> sqlx::PgPool {
+    let url = std::env::var("DATABASE_URL").unwrap_or_else(|_| common::TEST_DB_URL.to_string());
+    sqlx::PgPool::connect(&url).await.expect("connect to test db")
+}
+
+/// Overwrite an account's balance with an unbound legacy envelope, as rows
+/// written before versioned envelopes existed look on disk, and mark the
+/// owner's envelopes as not yet upgraded. The owner's sessions are re-cached
+/// as an unlock would cache them then, without starting the sweep.
+async fn store_legacy_balance(client: &rocket::local::asynchronous::Client, account_id: &str, balance: i64) {
+    plant_legacy_balance(account_id, balance).await;
+    let pool = db_pool().await;
+    sqlx::query("UPDATE users SET envelopes_upgraded_at = NULL WHERE id = (SELECT user_id FROM account WHERE id = $1)")
+        .bind(Uuid::parse_str(account_id).unwrap())
+        .execute(&pool)
+        .await
+        .expect("mark envelopes not upgraded");
+
+    let sessions: Vec<(Uuid, Uuid, chrono::DateTime<chrono::Utc>)> =
+        sqlx::query_as("SELECT s.id, s.user_id, s.expires_at FROM user_session s JOIN account a ON a.user_id = s.user_id WHERE a.id = $1")
+            .bind(Uuid::parse_str(account_id).unwrap())
+            .fetch_all(&pool)
+            .await
+            .expect("load sessions");
+    let store = client.rocket().state::<piggy_pulse::session_dek::DekStore>().expect("DEK store registered");
+    for (session_id, user_id, expires_at) in sessions {
+        let dek = piggy_pulse::crypto::Dek::from_bytes([0u8; 32]).with_legacy_envelopes(true);
+        store.put(session_id, user_id, dek, expires_at).await.expect("cache legacy-accepting DEK");
+    }
+}
+
+/// Whether every cached session DEK of `user_id` has stopped accepting
+/// legacy envelopes, polled until the background sweep gets there.
+async fn wait_for_envelope_upgrade(client: &rocket::local::asynchronous::Client, user_id: &str) -> bool {
+    let sessions: Vec<(Uuid,)> = sqlx::query_as("SELECT id FROM user_session WHERE user_id = $1")
+        .bind(Uuid::parse_str(user_id).unwrap())
+        .fetch_all(&db_pool().await)
+        .await
+        .expect("load sessions");
+    let store = client.rocket().state::<piggy_pulse::session_dek::DekStore>().expect("DEK store registered");
+    for _ in 0..100 {
+        let mut upgraded = true;
+        for (session_id,) in &sessions {
+            if let Some(dek) = store.get_cloned(session_id).await.expect("DEK store lookup") {
+                upgraded &= !dek.accepts_legacy_envelopes();
+            }
+        }
+        if upgraded {
+            return true;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    false
+}
+
+/// Overwrite an account's balance with an unbound legacy envelope and
+/// nothing else.
+async fn plant_legacy_balance(account_id: &str, balance: i64) {
+    let legacy = piggy_pulse::crypto::Dek::from_bytes([0u8; 32]).encrypt_bytes(&balance.to_le_bytes()).unwrap();
+    sqlx::query("UPDATE account SET current_balance_enc = $1 WHERE id = $2")
+        .bind(legacy)
+        .bind(Uuid::parse_str(account_id).unwrap())
+        .execute(&db_pool().await)
+        .await
+        .expect("store legacy envelope");
+}
+
+async fn get_account(client: &rocket::local::asynchronous::Client, account_id: &str) -> Value {
+    let resp = client.get(format!("{}/accounts/{}", V2_BASE, account_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    serde_json::from_str(&resp.into_string().await.unwrap()).unwrap()
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_account_envelopes_are_bound_to_their_cell() {
+    let client = test_client().await;
+    let (user_id, _email) = create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "Bound Checking", 10000).await;
+    let other_id = common::entities::create_account(&client, "Other Checking", 10000).await;
+
+    let body = get_account(&client, &account_id).await;
+    let name = body["nameEnc"].as_str().unwrap();
+    let key = [0u8; 32];
+
+    assert_eq!(
+        try_decrypt_cell_with(&key, "account", "name_enc", &account_id, &user_id, name).as_deref(),
+        Some("Bound Checking".as_bytes())
+    );
+    assert!(try_decrypt_cell_with(&key, "account", "color_enc", &account_id, &user_id, name).is_none());
+    assert!(try_decrypt_cell_with(&key, "account", "name_enc", &other_id, &user_id, name).is_none());
+    assert!(try_decrypt_cell_with(&key, "account", "name_enc", &account_id, &Uuid::new_v4().to_string(), name).is_none());
+    assert!(try_decrypt_cell_with(&key, "vendor", "name_enc", &account_id, &user_id, name).is_none());
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_legacy_balance_is_upgraded_on_account_update() {
+    let client = test_client().await;
+    let (user_id, _email) = create_user_and_login(&client).await;
+    let eur_id = common::auth::get_eur_currency_id(&client).await;
+    let account_id = common::entities::create_account(&client, "Legacy Checking", 10000).await;
+    store_legacy_balance(&client, &account_id, 20000).await;
+
+    let payload = json!({
+        "accountType": "checking",
+        "name": "Legacy Checking",
+        "color": "#abcdef",
+        "initialBalance": 0,
+        "currencyId": eur_id,
+        "spendLimit": null
+    });
+    let resp = client
+        .put(format!("{}/accounts/{}", V2_BASE, account_id))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    // The untouched balance column was rewritten as a bound v1 envelope.
+    let body = get_account(&client, &account_id).await;
+    let balance = try_decrypt_cell_with(
+        &[0u8; 32],
+        "account",
+        "current_balance_enc",
+        &account_id,
+        &user_id,
+        body["currentBalanceEnc"].as_str().unwrap(),
+    )
+    .expect("balance upgraded to a bound envelope");
+    assert_eq!(i64::from_le_bytes(balance.try_into().unwrap()), 20000);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_legacy_balance_is_read_by_transaction_write() {
+    let client = test_client().await;
+    let (user_id, _email) = create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "Legacy Wallet", 10000).await;
+    let category_id = common::entities::create_category(&client, "Legacy Groceries", "expense").await;
+    store_legacy_balance(&client, &account_id, 20000).await;
+
+    common::entities::create_transaction(&client, &account_id, &category_id, 2500, "2026-03-01").await;
+
+    let body = get_account(&client, &account_id).await;
+    let balance = try_decrypt_cell_with(
+        &[0u8; 32],
+        "account",
+        "current_balance_enc",
+        &account_id,
+        &user_id,
+        body["currentBalanceEnc"].as_str().unwrap(),
+    )
+    .expect("balance rewritten as a bound envelope");
+    assert_eq!(i64::from_le_bytes(balance.try_into().unwrap()), 17500);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_legacy_envelopes_are_upgraded_on_unlock_then_rejected() {
+    let client = test_client().await;
+    let (user_id, _email) = create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "Sweep Wallet", 10000).await;
+    let category_id = common::entities::create_category(&client, "Sweep Groceries", "expense").await;
+    store_legacy_balance(&client, &account_id, 20000).await;
+
+    // The sweep runs after the unlock response, then stops the session
+    // from accepting legacy envelopes.
+    common::auth::unlock_session(&client).await;
+    assert!(wait_for_envelope_upgrade(&client, &user_id).await, "sweep did not finish");
+
+    let body = get_account(&client, &account_id).await;
+    let balance = try_decrypt_cell_with(
+        &[0u8; 32],
+        "account",
+        "current_balance_enc",
+        &account_id,
+        &user_id,
+        body["currentBalanceEnc"].as_str().unwrap(),
+    )
+    .expect("balance bound to its cell on unlock");
+    assert_eq!(i64::from_le_bytes(balance.try_into().unwrap()), 20000);
+
+    // Once everything is upgraded, a legacy envelope put back from an old
+    // snapshot no longer decrypts.
+    plant_legacy_balance(&account_id, 90000).await;
+    let payload = json!({
+        "transactionType": "Regular",
+        "date": "2026-03-01",
+        "description": "After sweep",
+        "amount": 2500,
+        "fromAccountId": account_id,
+        "categoryId": category_id,
+        "vendorId": null
+    });
+    let resp = client
+        .post(format!("{}/transactions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::InternalServerError);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// GET /accounts/{id}/balance-history, GET /accounts/balance-history
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_balance_history_closing_balances_per_day() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "History Checking", 100_000).await;
+    let groceries = common::entities::create_category(&client, "History Groceries", "expense").await;
+    let salary = common::entities::create_category(&client, "History Salary", "income").await;
+    common::entities::create_transaction(&client, &account_id, &groceries, 2000, "2026-03-10").await;
+    common::entities::create_transaction(&client, &account_id, &salary, 5000, "2026-03-12").await;
+    common::entities::create_transaction(&client, &account_id, &groceries, 1000, "2026-03-20").await;
+
+    let resp = client
+        .get(format!("{}/accounts/{}/balance-history?from=2026-03-09&to=2026-03-13", V2_BASE, account_id))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let points: Vec<(&str, i64, i64)> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| {
+            (
+                p["date"].as_str().unwrap(),
+                p["balance"].as_i64().unwrap(),
+                p["transactionCount"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        points,
+        [
+            ("2026-03-09", 100_000, 0),
+            ("2026-03-10", 98_000, 1),
+            ("2026-03-11", 98_000, 0),
+            ("2026-03-12", 103_000, 1),
+            ("2026-03-13", 103_000, 0),
+        ]
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_balance_history_batch_covers_every_account() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let checking = common::entities::create_account(&client, "Batch History Checking", 10_000).await;
+    let groceries = common::entities::create_category(&client, "Batch History Groceries", "expense").await;
+    let payoff = common::entities::create_category(&client, "Batch History Payoff", "transfer").await;
+    let eur_id = common::auth::get_eur_currency_id(&client).await;
+    let resp = client
+        .post(format!("{}/accounts", V2_BASE))
+        .header(ContentType::JSON)
+        .body(
+            json!({
+                "accountType": "creditcard",
+                "name": "Batch History Card",
+                "color": "#000000",
+                "currencyId": eur_id,
+                "initialBalance": 0,
+                "spendLimit": 100_000
+            })
+            .to_string(),
+        )
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let card = body["id"].as_str().unwrap().to_string();
+
+    common::entities::create_transaction(&client, &card, &groceries, 1500, "2026-03-02").await;
+    let resp = client
+        .post(format!("{}/transactions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(
+            json!({
+                "transactionType": "Transfer",
+                "date": "2026-03-03",
+                "description": "Card payoff",
+                "amount": 1000,
+                "fromAccountId": checking,
+                "toAccountId": card,
+                "categoryId": payoff,
+                "vendorId": null
+            })
+            .to_string(),
+        )
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+
+    let resp = client
+        .get(format!("{}/accounts/balance-history?from=2026-03-01&to=2026-03-03", V2_BASE))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let balances = |account_id: &str| -> Vec<i64> {
+        let item = body.as_array().unwrap().iter().find(|i| i["accountId"] == account_id).unwrap();
+        item["history"].as_array().unwrap().iter().map(|p| p["balance"].as_i64().unwrap()).collect()
+    };
+    assert_eq!(balances(&checking), [10_000, 10_000, 9_000]);
+    // Card balances are debt owed: purchases raise it, payments lower it.
+    assert_eq!(balances(&card), [0, 1_500, 500]);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_balance_history_validation() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "History Validation", 0).await;
+
+    for query in [
+        "from=2026-03-01",
+        "from=2026-03-05&to=2026-03-01",
+        "from=2025-01-01&to=2026-03-01",
+        "from=march&to=2026-03-01",
+    ] {
+        let resp = client
+            .get(format!("{}/accounts/{}/balance-history?{}", V2_BASE, account_id, query))
+            .dispatch()
+            .await;
+        assert_eq!(resp.status(), Status::BadRequest, "{}", query);
+    }
+
+    let resp = client
+        .get(format!("{}/accounts/{}/balance-history?from=2026-03-01&to=2026-03-02", V2_BASE, Uuid::new_v4()))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Ledger verification
+// ═══════════════════════════════════════════════════════════════════════════════
+
+async fn verify_ledger(client: &rocket::local::asynchronous::Client, repair: bool) -> Value {
+    let resp = client
+        .post(format!("{}/accounts/verify", V2_BASE))
+        .header(ContentType::JSON)
+        .body(json!({ "repair": repair }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    serde_json::from_str(&resp.into_string().await.unwrap()).unwrap()
+}
+
+fn account_check<'a>(body: &'a Value, account_id: &str) -> &'a Value {
+    body["accounts"].as_array().unwrap().iter().find(|a| a["accountId"] == account_id).unwrap()
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_verify_ledger_consistent_after_edits() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let checking = common::entities::create_account(&client, "Verify Checking", 50_000).await;
+    let savings = common::entities::create_account(&client, "Verify Savings", 0).await;
+    let groceries = common::entities::create_category(&client, "Verify Groceries", "expense").await;
+    let moves = common::entities::create_category(&client, "Verify Moves", "transfer").await;
+
+    let edited = common::entities::create_transaction(&client, &checking, &groceries, 2_000, "2026-03-02").await;
+    let deleted = common::entities::create_transaction(&client, &checking, &groceries, 700, "2026-03-03").await;
+    let resp = client
+        .post(format!("{}/transactions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(
+            json!({
+                "transactionType": "Transfer",
+                "date": "2026-03-04",
+                "description": "To savings",
+                "amount": 10_000,
+                "fromAccountId": checking,
+                "toAccountId": savings,
+                "categoryId": moves,
+                "vendorId": null
+            })
+            .to_string(),
+        )
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let resp = client
+        .put(format!("{}/transactions/{}", V2_BASE, edited))
+        .header(ContentType::JSON)
+        .body(
+            json!({
+                "transactionType": "Regular",
+                "date": "2026-03-02",
+                "description": "Groceries",
+                "amount": 2_500,
+                "fromAccountId": checking,
+                "categoryId": groceries,
+                "vendorId": null
+            })
+            .to_string(),
+        )
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.delete(format!("{}/transactions/{}", V2_BASE, deleted)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+    let resp = client
+        .post(format!("{}/accounts/{}/adjust-balance", V2_BASE, savings))
+        .header(ContentType::JSON)
+        .body(json!({ "newBalance": 12_000 }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    let body = verify_ledger(&client, false).await;
+    assert_eq!(body["consistent"], true);
+    assert_eq!(body["repaired"], false);
+    assert_eq!(body["transactions"], json!([]));
+    assert_eq!(body["envelopeFailures"], json!([]));
+    assert!(body["envelopesChecked"].as_u64().unwrap() > 0);
+
+    let checking = account_check(&body, &checking);
+    assert_eq!(checking["storedBalance"], 37_500);
+    assert_eq!(checking["ledgerBalance"], 37_500);
+    assert_eq!(checking["drift"], 0);
+    assert_eq!(checking["baselined"], false);
+    let savings = account_check(&body, &savings);
+    assert_eq!(savings["ledgerBalance"], 12_000);
+    assert_eq!(savings["drift"], 0);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_verify_ledger_reports_and_repairs_balance_drift() {
+    let client = test_client().await;
+    let (user_id, _email) = create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "Drift Checking", 10_000).await;
+    let groceries = common::entities::create_category(&client, "Drift Groceries", "expense").await;
+    common::entities::create_transaction(&client, &account_id, &groceries, 1_200, "2026-03-02").await;
+
+    // A readable balance the ledger does not account for.
+    store_legacy_balance(&client, &account_id, 9_999).await;
+
+    let body = verify_ledger(&client, false).await;
+    assert_eq!(body["consistent"], false);
+    assert_eq!(body["repaired"], false);
+    let check = account_check(&body, &account_id);
+    assert_eq!(check["storedBalance"], 9_999);
+    assert_eq!(check["ledgerBalance"], 8_800);
+    assert_eq!(check["drift"], 1_199);
+
+    assert!(check.get("adjustmentId").is_none());
+
+    // The repair keeps the stored balance and books the drift as a
+    // cleared adjustment the ledger can account for.
+    let body = verify_ledger(&client, true).await;
+    assert_eq!(body["repaired"], true);
+    let check = account_check(&body, &account_id);
+    assert_eq!(check["drift"], 1_199);
+    let adjustment_id = check["adjustmentId"].as_str().unwrap().to_string();
+
+    let resp = client.get(format!("{}/transactions/page?accountId={}", V2_BASE, account_id)).dispatch().await;
+    let page: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let adjustment = page["data"].as_array().unwrap().iter().find(|t| t["id"] == adjustment_id.as_str()).unwrap();
+    assert_eq!(decrypt_i64(adjustment["amountEnc"].as_str().unwrap()), 1_199);
+    assert_eq!(adjustment["isAdjustment"], true);
+    assert_eq!(adjustment["clearedStatus"], "cleared");
+
+    let audited: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM security_audit_log WHERE user_id = $1 AND event_type = 'ledger_repaired' AND metadata->'account_ids' ? $2 AND metadata->'adjustment_ids' ? $3",
+    )
+    .bind(Uuid::parse_str(&user_id).unwrap())
+    .bind(&account_id)
+    .bind(&adjustment_id)
+    .fetch_one(&db_pool().await)
+    .await
+    .unwrap();
+    assert_eq!(audited, 1);
+
+    let body = verify_ledger(&client, true).await;
+    assert_eq!(body["consistent"], true);
+    assert_eq!(body["repaired"], false);
+    let check = account_check(&body, &account_id);
+    assert_eq!(check["storedBalance"], 9_999);
+    assert_eq!(check["ledgerBalance"], 9_999);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_verify_ledger_repairs_transaction_state() {
+    let client = test_client().await;
+    let (user_id, _email) = create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "State Checking", 10_000).await;
+    let groceries = common::entities::create_category(&client, "State Groceries", "expense").await;
+    let tx_id = common::entities::create_transaction(&client, &account_id, &groceries, 1_200, "2026-03-02").await;
+
+    let tx_uuid = Uuid::parse_str(&tx_id).unwrap();
+    let aad = piggy_pulse::crypto::EnvelopeAad::new("logical_transaction_state", "current_sum_enc", tx_uuid, Uuid::parse_str(&user_id).unwrap());
+    let wrong_sum = piggy_pulse::crypto::Dek::from_bytes([0u8; 32]).encrypt_i64_for(0, &aad).unwrap();
+    sqlx::query("UPDATE logical_transaction_state SET current_sum_enc = $1, is_effective = false WHERE id = $2")
+        .bind(wrong_sum)
+        .bind(tx_uuid)
+        .execute(&db_pool().await)
+        .await
+        .unwrap();
+
+    let body = verify_ledger(&client, false).await;
+    assert_eq!(body["consistent"], false);
+    assert_eq!(body["transactions"], json!([{ "transactionId": tx_id, "storedSum": 0, "ledgerSum": 1_200 }]));
+    assert_eq!(account_check(&body, &account_id)["drift"], 0);
+
+    // Once repaired the transaction is effective again and can be deleted.
+    verify_ledger(&client, true).await;
+    assert_eq!(verify_ledger(&client, false).await["consistent"], true);
+    let resp = client.delete(format!("{}/transactions/{}", V2_BASE, tx_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+    assert_eq!(
+        decrypt_i64(get_account(&client, &account_id).await["currentBalanceEnc"].as_str().unwrap()),
+        10_000
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_verify_ledger_baselines_accounts_without_opening_balance() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "Baseline Checking", 10_000).await;
+    let groceries = common::entities::create_category(&client, "Baseline Groceries", "expense").await;
+    common::entities::create_transaction(&client, &account_id, &groceries, 1_000, "2026-03-02").await;
+
+    sqlx::query("UPDATE account SET opening_balance_enc = NULL WHERE id = $1")
+        .bind(Uuid::parse_str(&account_id).unwrap())
+        .execute(&db_pool().await)
+        .await
+        .unwrap();
+
+    let body = verify_ledger(&client, false).await;
+    let check = account_check(&body, &account_id);
+    assert_eq!(check["baselined"], true);
+    assert_eq!(check["ledgerBalance"], 9_000);
+    assert_eq!(check["drift"], 0);
+
+    // A plain verify writes nothing.
+    let opening: Option<Vec<u8>> = sqlx::query_scalar("SELECT opening_balance_enc FROM account WHERE id = $1")
+        .bind(Uuid::parse_str(&account_id).unwrap())
+        .fetch_one(&db_pool().await)
+        .await
+        .unwrap();
+    assert!(opening.is_none());
+    let body = verify_ledger(&client, false).await;
+    assert_eq!(account_check(&body, &account_id)["baselined"], true);
+
+    // A repair saves the derived opening balance, so later drift shows up.
+    let body = verify_ledger(&client, true).await;
+    assert_eq!(account_check(&body, &account_id)["baselined"], true);
+    assert_eq!(body["repaired"], false);
+    store_legacy_balance(&client, &account_id, 9_500).await;
+    let body = verify_ledger(&client, false).await;
+    let check = account_check(&body, &account_id);
+    assert_eq!(check["baselined"], false);
+    assert_eq!(check["drift"], 500);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_verify_ledger_reports_undecryptable_envelopes() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "Envelope Checking", 10_000).await;
+    let category_id = common::entities::create_category(&client, "Envelope Groceries", "expense").await;
+
+    // Another cell's valid ciphertext does not open here.
+    sqlx::query("UPDATE category SET name_enc = (SELECT name_enc FROM account WHERE id = $1) WHERE id = $2")
+        .bind(Uuid::parse_str(&account_id).unwrap())
+        .bind(Uuid::parse_str(&category_id).unwrap())
+        .execute(&db_pool().await)
+        .await
+        .unwrap();
+
+    let body = verify_ledger(&client, true).await;
+    assert_eq!(body["consistent"], false);
+    assert_eq!(body["repaired"], false);
+    assert_eq!(
+        body["envelopeFailures"],
+        json!([{ "table": "category", "column": "name_enc", "rowId": category_id }])
+    );
+    assert_eq!(account_check(&body, &account_id)["drift"], 0);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_verify_ledger_no_auth() {
+    let client = test_client().await;
+    let resp = client
+        .post(format!("{}/accounts/verify", V2_BASE))
+        .header(ContentType::JSON)
+        .body("{}")
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
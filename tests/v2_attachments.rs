@@ -0,0 +1,338 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use common::auth::create_user_and_login;
+use common::crypto::{decrypt_string, try_decrypt_cell_with};
+use common::entities::{create_account, create_category, create_transaction};
+use common::{TEST_DB_URL, TEST_PASSWORD, V2_BASE, test_client, test_config};
+use piggy_pulse::{AttachmentBackend, build_rocket};
+use rocket::http::{ContentType, Status};
+use rocket::local::asynchronous::Client;
+use serde_json::Value;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Helpers
+// ═══════════════════════════════════════════════════════════════════════════════
+
+const BOUNDARY: &str = "piggy-pulse-attachment-boundary";
+
+/// A client storing attachments as files under a fresh directory.
+async fn filesystem_client(max_size_bytes: Option<u64>) -> (Client, PathBuf) {
+    let root = std::env::temp_dir().join(format!("piggy-pulse-attachments-{}", uuid::Uuid::new_v4()));
+    let mut config = test_config();
+    config.attachments.backend = AttachmentBackend::Filesystem;
+    config.attachments.path = root.to_string_lossy().into_owned();
+    if let Some(max) = max_size_bytes {
+        config.attachments.max_size_bytes = max;
+    }
+    let client = Client::tracked(build_rocket(config)).await.expect("valid rocket instance");
+    (client, root)
+}
+
+async fn db_pool() -> sqlx::PgPool {
+    let url = std::env::var("DATABASE_URL").unwrap_or_else(|_| TEST_DB_URL.to_string());
+    sqlx::PgPool::connect(&url).await.expect("connect to test db")
+}
+
+fn files_under(dir: &Path) -> usize {
+    match std::fs::read_dir(dir) {
+        Ok(entries) => entries
+            .map(|e| e.unwrap())
+            .map(|e| if e.file_type().unwrap().is_dir() { files_under(&e.path()) } else { 1 })
+            .sum(),
+        Err(_) => 0,
+    }
+}
+
+/// Deterministic, non-repeating-looking content spanning several chunks.
+fn receipt_bytes(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+async fn upload(client: &Client, tx_id: &str, file_name: &str, content_type: &str, content: &[u8]) -> (Status, Value) {
+    let mut body =
+        format!("--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{file_name}\"\r\nContent-Type: {content_type}\r\n\r\n").into_bytes();
+    body.extend_from_slice(content);
+    body.extend_from_slice(format!("\r\n--{BOUNDARY}--\r\n").as_bytes());
+
+    let resp = client
+        .post(format!("{}/transactions/{}/attachments", V2_BASE, tx_id))
+        .header(ContentType::new("multipart", "form-data").with_params(("boundary", BOUNDARY)))
+        .body(body)
+        .dispatch()
+        .await;
+    let status = resp.status();
+    let body = resp.into_string().await.unwrap_or_default();
+    (status, serde_json::from_str(&body).unwrap_or(Value::Null))
+}
+
+async fn seed_transaction(client: &Client) -> String {
+    let account_id = create_account(client, "Attachment Checking", 100_000).await;
+    let category_id = create_category(client, "Attachment Groceries", "expense").await;
+    create_transaction(client, &account_id, &category_id, 4_200, "2026-03-10").await
+}
+
+async fn list(client: &Client, tx_id: &str) -> Vec<Value> {
+    let resp = client.get(format!("{}/transactions/{}/attachments", V2_BASE, tx_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    serde_json::from_str::<Value>(&resp.into_string().await.unwrap())
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .clone()
+}
+
+async fn download(client: &Client, tx_id: &str, attachment_id: &str) -> (Status, Option<ContentType>, Option<String>, Vec<u8>) {
+    let resp = client
+        .get(format!("{}/transactions/{}/attachments/{}", V2_BASE, tx_id, attachment_id))
+        .dispatch()
+        .await;
+    let status = resp.status();
+    let content_type = resp.content_type();
+    let disposition = resp.headers().get_one("Content-Disposition").map(str::to_string);
+    (status, content_type, disposition, resp.into_bytes().await.unwrap_or_default())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// POST/GET/DELETE /transactions/<id>/attachments
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_attachment_round_trips_through_postgres_chunks() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let tx_id = seed_transaction(&client).await;
+    let content = receipt_bytes(600 * 1024);
+
+    let (status, created) = upload(&client, &tx_id, "Reçu mars.pdf", "application/pdf", &content).await;
+    assert_eq!(status, Status::Created, "{created}");
+    assert_eq!(created["transactionId"], tx_id.as_str());
+    assert_eq!(created["sizeBytes"], content.len());
+    assert_eq!(decrypt_string(created["fileNameEnc"].as_str().unwrap()), "Reçu mars.pdf");
+    assert_eq!(decrypt_string(created["contentTypeEnc"].as_str().unwrap()), "application/pdf");
+    let attachment_id = created["id"].as_str().unwrap();
+
+    let pool = db_pool().await;
+    let chunks: Vec<Vec<u8>> = sqlx::query_scalar("SELECT ciphertext FROM attachment_chunk WHERE attachment_id = $1::uuid ORDER BY idx")
+        .bind(attachment_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(chunks.len(), 3, "600 KiB is stored as three 256 KiB chunks");
+    assert!(chunks.iter().all(|c| !c.windows(64).any(|w| w == &content[..64])), "chunks are encrypted");
+
+    let listed = list(&client, &tx_id).await;
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["id"], attachment_id);
+
+    let (status, content_type, disposition, bytes) = download(&client, &tx_id, attachment_id).await;
+    assert_eq!(status, Status::Ok);
+    assert_eq!(content_type, Some(ContentType::PDF));
+    assert_eq!(
+        disposition.as_deref(),
+        Some("attachment; filename=\"Re_u mars.pdf\"; filename*=UTF-8''Re%C3%A7u%20mars.pdf")
+    );
+    assert!(bytes == content, "downloaded content matches the upload");
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_attachment_round_trips_through_filesystem() {
+    let (client, root) = filesystem_client(None).await;
+    create_user_and_login(&client).await;
+    let tx_id = seed_transaction(&client).await;
+    let content = receipt_bytes(300 * 1024);
+
+    let (status, created) = upload(&client, &tx_id, "invoice.png", "image/png", &content).await;
+    assert_eq!(status, Status::Created, "{created}");
+    let attachment_id = created["id"].as_str().unwrap();
+    assert_eq!(files_under(&root), 1);
+
+    let pool = db_pool().await;
+    let chunk_rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM attachment_chunk WHERE attachment_id = $1::uuid")
+        .bind(attachment_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(chunk_rows, 0);
+
+    let (status, content_type, _, bytes) = download(&client, &tx_id, attachment_id).await;
+    assert_eq!(status, Status::Ok);
+    assert_eq!(content_type, Some(ContentType::PNG));
+    assert!(bytes == content, "downloaded content matches the upload");
+
+    let resp = client
+        .delete(format!("{}/transactions/{}/attachments/{}", V2_BASE, tx_id, attachment_id))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::NoContent);
+    assert_eq!(files_under(&root), 0, "deleting the attachment removes its file");
+    assert!(list(&client, &tx_id).await.is_empty());
+
+    let (status, ..) = download(&client, &tx_id, attachment_id).await;
+    assert_eq!(status, Status::NotFound);
+    let _ = std::fs::remove_dir_all(root);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_attachment_rejects_bad_uploads() {
+    let (client, root) = filesystem_client(Some(1024)).await;
+    create_user_and_login(&client).await;
+    let tx_id = seed_transaction(&client).await;
+
+    let (status, _) = upload(&client, &tx_id, "empty.txt", "text/plain", b"").await;
+    assert_eq!(status, Status::BadRequest);
+
+    let (status, _) = upload(&client, &tx_id, "large.bin", "application/octet-stream", &receipt_bytes(4096)).await;
+    assert_eq!(status, Status::PayloadTooLarge);
+
+    let (status, _) = upload(&client, &uuid::Uuid::new_v4().to_string(), "receipt.txt", "text/plain", b"total 42").await;
+    assert_eq!(status, Status::NotFound);
+
+    let (status, _) = upload(&client, "not-a-uuid", "receipt.txt", "text/plain", b"total 42").await;
+    assert_eq!(status, Status::BadRequest);
+
+    assert_eq!(files_under(&root), 0, "rejected uploads leave no files behind");
+    let _ = std::fs::remove_dir_all(root);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_attachments_are_scoped_to_their_owner() {
+    let owner = test_client().await;
+    create_user_and_login(&owner).await;
+    let tx_id = seed_transaction(&owner).await;
+    let (status, created) = upload(&owner, &tx_id, "receipt.txt", "text/plain", b"total 42").await;
+    assert_eq!(status, Status::Created);
+    let attachment_id = created["id"].as_str().unwrap();
+
+    let other = test_client().await;
+    create_user_and_login(&other).await;
+    let resp = other.get(format!("{}/transactions/{}/attachments", V2_BASE, tx_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::NotFound);
+    let (status, ..) = download(&other, &tx_id, attachment_id).await;
+    assert_eq!(status, Status::NotFound);
+    let resp = other
+        .delete(format!("{}/transactions/{}/attachments/{}", V2_BASE, tx_id, attachment_id))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::NotFound);
+
+    assert_eq!(list(&owner, &tx_id).await.len(), 1);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_attachment_survives_dek_rotation() {
+    let (client, root) = filesystem_client(None).await;
+    let (user_id, _) = create_user_and_login(&client).await;
+    let tx_id = seed_transaction(&client).await;
+    let content = receipt_bytes(300 * 1024);
+    let (status, created) = upload(&client, &tx_id, "invoice.pdf", "application/pdf", &content).await;
+    assert_eq!(status, Status::Created);
+    let attachment_id = created["id"].as_str().unwrap();
+
+    let new_dek = [7u8; 32];
+    let payload = serde_json::json!({
+        "newDek": BASE64.encode(new_dek),
+        "wrappedDek": "bmV3LWRlay13cmFw",
+        "dekWrapParams": {"salt": "rotated"}
+    });
+    let resp = client
+        .post(format!("{}/auth/rotate-dek", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Only the sealed metadata was re-encrypted; the file opens as before.
+    let listed = list(&client, &tx_id).await;
+    let file_name = try_decrypt_cell_with(
+        &new_dek,
+        "attachment",
+        "file_name_enc",
+        attachment_id,
+        &user_id,
+        listed[0]["fileNameEnc"].as_str().unwrap(),
+    );
+    assert_eq!(file_name.as_deref(), Some(b"invoice.pdf".as_slice()));
+    let (status, _, _, bytes) = download(&client, &tx_id, attachment_id).await;
+    assert_eq!(status, Status::Ok);
+    assert!(bytes == content, "downloaded content matches the upload");
+    let _ = std::fs::remove_dir_all(root);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_reset_structure_removes_attachments_and_files() {
+    let (client, root) = filesystem_client(None).await;
+    let (user_id, _) = create_user_and_login(&client).await;
+    let tx_id = seed_transaction(&client).await;
+    for name in ["one.txt", "two.txt"] {
+        let (status, _) = upload(&client, &tx_id, name, "text/plain", b"receipt").await;
+        assert_eq!(status, Status::Created);
+    }
+    assert_eq!(files_under(&root), 2);
+
+    let resp = client
+        .post(format!("{}/settings/reset-structure", V2_BASE))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "password": TEST_PASSWORD }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::NoContent);
+
+    let pool = db_pool().await;
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM attachment WHERE user_id = $1::uuid")
+        .bind(&user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, 0);
+    assert_eq!(files_under(&root), 0);
+    let _ = std::fs::remove_dir_all(root);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_delete_account_removes_attachments() {
+    common::clear_login_rate_limits().await;
+    let client = test_client().await;
+    let (user_id, _) = create_user_and_login(&client).await;
+    let tx_id = seed_transaction(&client).await;
+    let (status, _) = upload(&client, &tx_id, "receipt.txt", "text/plain", b"total 42").await;
+    assert_eq!(status, Status::Created);
+
+    let resp = client
+        .delete(format!("{}/settings/account", V2_BASE))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "password": TEST_PASSWORD }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::NoContent);
+
+    let pool = db_pool().await;
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM attachment WHERE user_id = $1::uuid")
+        .bind(&user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, 0);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_attachments_require_auth() {
+    let client = test_client().await;
+    let resp = client
+        .get(format!("{}/transactions/{}/attachments", V2_BASE, uuid::Uuid::new_v4()))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
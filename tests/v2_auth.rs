@@ -337,6 +337,20 @@ async fn test_refresh_happy() {
     );
 }
 
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_refresh_keeps_session_unlocked() {
+    let client = test_client().await;
+    common::auth::create_user_and_login(&client).await;
+
+    let resp = client.post(format!("{}/auth/refresh", V2_BASE)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    // The refreshed session is a new principal; encrypted writes must still work
+    // without a second unlock.
+    common::entities::create_account(&client, "After Refresh", 1000).await;
+}
+
 #[rocket::async_test]
 #[ignore = "requires database"]
 async fn test_refresh_no_auth() {
@@ -357,6 +371,62 @@ async fn test_change_password_happy() {
     let client = test_client().await;
     common::auth::create_user_and_login(&client).await;
 
+    let payload = serde_json::json!({
+        "currentPassword": TEST_PASSWORD,
+        "newPassword": "NewSecurePassword!2026abc",
+        "wrappedDek": "bmV3LXBhc3N3b3JkLXdyYXA=",
+        "dekWrapParams": {"salt": "new"}
+    });
+
+    let resp = client
+        .put(format!("{}/auth/password", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(resp.status(), Status::Ok);
+
+    // The re-wrapped DEK was stored together with the new password
+    let resp = client.get(format!("{}/auth/wrapped-dek", V2_BASE)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(body["wrappedDek"], "bmV3LXBhc3N3b3JkLXdyYXA=");
+    assert_eq!(body["dekWrapParams"]["salt"], "new");
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_change_password_locks_other_devices() {
+    let client = test_client().await;
+    let (user_id, _) = common::auth::create_user_and_login(&client).await;
+    let other_device = common::auth::seed_other_device_dek(&client, &user_id).await;
+
+    let payload = serde_json::json!({
+        "currentPassword": TEST_PASSWORD,
+        "newPassword": "NewSecurePassword!2026abc",
+        "wrappedDek": "bmV3LXBhc3N3b3JkLXdyYXA=",
+        "dekWrapParams": {"salt": "new"}
+    });
+    let resp = client
+        .put(format!("{}/auth/password", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    assert!(!common::auth::has_cached_dek(&client, &other_device).await);
+    // The session that changed the password stays unlocked
+    common::entities::create_account(&client, "After Password Change", 1000).await;
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_change_password_missing_wrapped_dek() {
+    let client = test_client().await;
+    let (_user_id, email) = common::auth::create_user_and_login(&client).await;
+
     let payload = serde_json::json!({
         "currentPassword": TEST_PASSWORD,
         "newPassword": "NewSecurePassword!2026abc"
@@ -369,6 +439,16 @@ async fn test_change_password_happy() {
         .dispatch()
         .await;
 
+    assert_eq!(resp.status(), Status::BadRequest);
+
+    // The old password must still work
+    common::clear_login_rate_limits().await;
+    let resp = client
+        .post(format!("{}/auth/login", V2_BASE))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "email": email, "password": TEST_PASSWORD }).to_string())
+        .dispatch()
+        .await;
     assert_eq!(resp.status(), Status::Ok);
 }
 
@@ -380,7 +460,9 @@ async fn test_change_password_wrong_current() {
 
     let payload = serde_json::json!({
         "currentPassword": "WrongCurrentPassword!2026",
-        "newPassword": "NewSecurePassword!2026abc"
+        "newPassword": "NewSecurePassword!2026abc",
+        "wrappedDek": "bmV3LXBhc3N3b3JkLXdyYXA=",
+        "dekWrapParams": {"salt": "new"}
     });
 
     let resp = client
@@ -507,6 +589,185 @@ async fn test_reset_password_invalid_token() {
     );
 }
 
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_reset_password_with_recovery_key_rewraps_dek() {
+    let client = test_client().await;
+    let email = format!("recovery.{}@example.com", Uuid::new_v4());
+
+    let reg = serde_json::json!({
+        "email": email,
+        "password": TEST_PASSWORD,
+        "name": "Recovery User",
+        "wrappedDek": "b2xkLXBhc3N3b3JkLXdyYXA=",
+        "dekWrapParams": {"salt": "old"},
+        "recoveryWrappedDek": "cmVjb3Zlcnkta2V5LXdyYXA=",
+        "recoveryWrapParams": {"salt": "recovery"}
+    });
+    let resp = client
+        .post(format!("{}/auth/register", V2_BASE))
+        .header(ContentType::JSON)
+        .body(reg.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let user_id = body["user"]["id"].as_str().unwrap().to_string();
+
+    let token = common::auth::insert_reset_token(&user_id).await;
+
+    // The reset token unlocks the recovery wrap without consuming the token
+    let resp = client
+        .post(format!("{}/auth/reset-password/recovery-key", V2_BASE))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "token": token }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(body["recoveryWrappedDek"], "cmVjb3Zlcnkta2V5LXdyYXA=");
+    assert_eq!(body["recoveryWrapParams"]["salt"], "recovery");
+
+    let new_password = "NewSecurePassword!2026abc";
+    let resp = client
+        .post(format!("{}/auth/reset-password", V2_BASE))
+        .header(ContentType::JSON)
+        .body(
+            serde_json::json!({
+                "token": token,
+                "password": new_password,
+                "wrappedDek": "bmV3LXBhc3N3b3JkLXdyYXA=",
+                "dekWrapParams": {"salt": "new"}
+            })
+            .to_string(),
+        )
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    common::clear_login_rate_limits().await;
+    let resp = client
+        .post(format!("{}/auth/login", V2_BASE))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "email": email, "password": new_password }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(body["wrappedDek"], "bmV3LXBhc3N3b3JkLXdyYXA=");
+    assert_eq!(body["dekWrapParams"]["salt"], "new");
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_reset_password_locks_all_devices() {
+    common::clear_login_rate_limits().await;
+    let client = test_client().await;
+    let (user_id, _) = common::auth::create_user_and_login(&client).await;
+    let other_device = common::auth::seed_other_device_dek(&client, &user_id).await;
+    let token = common::auth::insert_reset_token(&user_id).await;
+
+    let payload = serde_json::json!({ "token": token, "password": "ResetSecurePassword!2026xyz" });
+    let resp = client
+        .post(format!("{}/auth/reset-password", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    assert!(!common::auth::has_cached_dek(&client, &other_device).await);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_reset_password_wrapped_dek_without_params() {
+    let client = test_client().await;
+    let (user_id, _email) = common::auth::create_user_and_login(&client).await;
+    let token = common::auth::insert_reset_token(&user_id).await;
+
+    let resp = client
+        .post(format!("{}/auth/reset-password", V2_BASE))
+        .header(ContentType::JSON)
+        .body(
+            serde_json::json!({
+                "token": token,
+                "password": "NewSecurePassword!2026abc",
+                "wrappedDek": "bmV3LXBhc3N3b3JkLXdyYXA="
+            })
+            .to_string(),
+        )
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::BadRequest);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_recovery_key_for_reset_not_configured() {
+    let client = test_client().await;
+    let (user_id, _email) = common::auth::create_user_and_login(&client).await;
+    let token = common::auth::insert_reset_token(&user_id).await;
+
+    let resp = client
+        .post(format!("{}/auth/reset-password/recovery-key", V2_BASE))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "token": token }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// GET/PUT/DELETE /auth/recovery-key
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_recovery_key_roundtrip() {
+    let client = test_client().await;
+    common::auth::create_user_and_login(&client).await;
+
+    let resp = client.get(format!("{}/auth/recovery-key", V2_BASE)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert!(body["recoveryWrappedDek"].is_null());
+
+    let resp = client
+        .put(format!("{}/auth/recovery-key", V2_BASE))
+        .header(ContentType::JSON)
+        .body(
+            serde_json::json!({
+                "recoveryWrappedDek": "cmVjb3Zlcnkta2V5LXdyYXA=",
+                "recoveryWrapParams": {"salt": "recovery"}
+            })
+            .to_string(),
+        )
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client.get(format!("{}/auth/recovery-key", V2_BASE)).dispatch().await;
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(body["recoveryWrappedDek"], "cmVjb3Zlcnkta2V5LXdyYXA=");
+    assert_eq!(body["recoveryWrapParams"]["salt"], "recovery");
+
+    let resp = client.delete(format!("{}/auth/recovery-key", V2_BASE)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+
+    let resp = client.get(format!("{}/auth/recovery-key", V2_BASE)).dispatch().await;
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert!(body["recoveryWrappedDek"].is_null());
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_recovery_key_no_auth() {
+    let client = test_client().await;
+    let resp = client.get(format!("{}/auth/recovery-key", V2_BASE)).dispatch().await;
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // 2FA endpoints
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -943,7 +1204,9 @@ async fn test_bearer_token_revoked_after_password_change() {
     // Change password (using session cookie from register)
     let payload = serde_json::json!({
         "currentPassword": TEST_PASSWORD,
-        "newPassword": "NewSecurePassword!2026abc"
+        "newPassword": "NewSecurePassword!2026abc",
+        "wrappedDek": "bmV3LXBhc3N3b3JkLXdyYXA=",
+        "dekWrapParams": {"salt": "new"}
     });
     let resp = client
         .put(format!("{}/auth/password", V2_BASE))
@@ -0,0 +1,508 @@
+mod common;
+
+use common::auth::create_user_and_login;
+use common::entities::{
+    create_account, create_category, create_period, create_subscription, create_tag, create_target, create_transaction, create_transaction_with_vendor,
+    create_vendor,
+};
+use common::{V2_BASE, test_client};
+use rocket::http::{ContentType, Status};
+use rocket::local::asynchronous::Client;
+use serde_json::Value;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Helpers
+// ═══════════════════════════════════════════════════════════════════════════════
+
+async fn export(client: &Client) -> Value {
+    let resp = client.get(format!("{}/settings/export/data", V2_BASE)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    serde_json::from_str(&resp.into_string().await.unwrap()).unwrap()
+}
+
+async fn import(client: &Client, backup: &Value) -> (Status, Value) {
+    let resp = client
+        .post(format!("{}/settings/import/data", V2_BASE))
+        .header(ContentType::JSON)
+        .body(backup.to_string())
+        .dispatch()
+        .await;
+    let status = resp.status();
+    let body = resp.into_string().await.unwrap_or_default();
+    (status, serde_json::from_str(&body).unwrap_or(Value::Null))
+}
+
+/// Populate a user with one of everything, plus a transaction that was
+/// edited (three ledger rows) and one that was deleted (sum zero).
+async fn seed(client: &Client) -> (String, String) {
+    let account_id = create_account(client, "Backup Checking", 250_000).await;
+    let category_id = create_category(client, "Backup Groceries", "expense").await;
+    let vendor_id = create_vendor(client, "Backup Market").await;
+    create_target(client, &category_id, 40_000).await;
+    create_period(client, "2026-03-01", "2026-03-31").await;
+    let subscription_id = create_subscription(client, "Backup Streaming", &category_id, 1299, "monthly", "2026-04-05").await;
+
+    let edited = create_transaction_with_vendor(client, &account_id, &category_id, 5000, "2026-03-10", &vendor_id).await;
+    let payload = serde_json::json!({
+        "transactionType": "Regular",
+        "date": "2026-03-11",
+        "description": "Weekly shop",
+        "amount": 7500,
+        "fromAccountId": account_id,
+        "categoryId": category_id,
+        "vendorId": vendor_id
+    });
+    let resp = client
+        .put(format!("{}/transactions/{}", V2_BASE, edited))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    let resp = client
+        .post(format!("{}/subscriptions/{}/transactions", V2_BASE, subscription_id))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "transactionId": edited }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+
+    let deleted = create_transaction_with_vendor(client, &account_id, &category_id, 900, "2026-03-12", &vendor_id).await;
+    let resp = client.delete(format!("{}/transactions/{}", V2_BASE, deleted)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+
+    (account_id, category_id)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// GET /settings/export/data
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_export_decrypts_everything() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let (account_id, category_id) = seed(&client).await;
+
+    let backup = export(&client).await;
+    assert_eq!(backup["format"], "piggy-pulse-backup");
+    assert_eq!(backup["version"], 3);
+
+    let account = backup["accounts"].as_array().unwrap().iter().find(|a| a["id"] == account_id.as_str()).unwrap();
+    assert_eq!(account["name"], "Backup Checking");
+    assert_eq!(account["currency"], "EUR");
+
+    let category = backup["categories"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["id"] == category_id.as_str())
+        .unwrap();
+    assert_eq!(category["name"], "Backup Groceries");
+    assert_eq!(category["type"], "expense");
+
+    assert_eq!(backup["vendors"][0]["name"], "Backup Market");
+    assert_eq!(backup["targets"][0]["value"], 40_000);
+    assert_eq!(backup["subscriptions"][0]["name"], "Backup Streaming");
+    assert_eq!(backup["subscriptions"][0]["billingAmount"], 1299);
+    assert_eq!(backup["subscriptions"][0]["billingEvents"][0]["amount"], 7500);
+    assert_eq!(backup["subscriptions"][0]["billingEvents"][0]["date"], "2026-03-11");
+    assert_eq!(backup["periods"].as_array().unwrap().len(), 1);
+
+    // create + (reversal, replacement) + (create, reversal)
+    let ledger = backup["transactions"].as_array().unwrap();
+    assert_eq!(ledger.len(), 5);
+    let seqs: Vec<i64> = ledger.iter().map(|r| r["seq"].as_i64().unwrap()).collect();
+    assert!(seqs.windows(2).all(|w| w[0] < w[1]), "ledger rows come in seq order");
+    assert!(ledger.iter().any(|r| r["description"] == "Weekly shop" && r["amount"] == 7500));
+    assert!(ledger.iter().any(|r| r["amount"] == -5000));
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_export_requires_auth() {
+    let client = test_client().await;
+    let resp = client.get(format!("{}/settings/export/data", V2_BASE)).dispatch().await;
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// POST /settings/import/data
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_import_round_trips_into_fresh_user() {
+    let source = test_client().await;
+    create_user_and_login(&source).await;
+    seed(&source).await;
+    let original = export(&source).await;
+
+    let target = test_client().await;
+    create_user_and_login(&target).await;
+    let (status, body) = import(&target, &original).await;
+    assert_eq!(status, Status::Ok, "{body}");
+    assert_eq!(body["imported"]["accounts"], 1);
+    assert_eq!(body["imported"]["vendors"], 1);
+    assert_eq!(body["imported"]["targets"], 1);
+    assert_eq!(body["imported"]["subscriptions"], 1);
+    assert_eq!(body["imported"]["periods"], 1);
+    assert_eq!(body["imported"]["transactions"], 2);
+    assert_eq!(body["imported"]["ledgerRows"], 5);
+
+    let restored = export(&target).await;
+    assert_eq!(restored["accounts"][0]["name"], "Backup Checking");
+    assert_eq!(restored["accounts"][0]["balance"], original["accounts"][0]["balance"]);
+    assert_ne!(restored["accounts"][0]["id"], original["accounts"][0]["id"]);
+
+    let amounts = |doc: &Value| {
+        doc["transactions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["amount"].as_i64().unwrap())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(amounts(&restored), amounts(&original));
+
+    // Only the edited transaction is still effective, at its latest amount.
+    let resp = target
+        .get(format!("{}/transactions/range?from=2026-03-01&to=2026-03-31", V2_BASE))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let list: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let items = list.get("data").unwrap_or(&list).as_array().unwrap().clone();
+    assert_eq!(items.len(), 1);
+
+    let subscription = &restored["subscriptions"][0];
+    let category = restored["categories"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["name"] == "Backup Groceries")
+        .unwrap();
+    assert_eq!(subscription["categoryId"], category["id"]);
+    assert_eq!(subscription["billingAmount"], 1299);
+    assert_eq!(subscription["billingEvents"][0]["amount"], 7500);
+    assert_eq!(subscription["billingEvents"][0]["transactionId"], items[0]["id"]);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_import_round_trips_split_lines() {
+    let source = test_client().await;
+    create_user_and_login(&source).await;
+    let account_id = create_account(&source, "Split Backup Checking", 100_000).await;
+    let groceries = create_category(&source, "Split Backup Groceries", "expense").await;
+    let household = create_category(&source, "Split Backup Household", "expense").await;
+    let payload = serde_json::json!({
+        "transactionType": "Regular",
+        "date": "2026-03-10",
+        "description": "Supermarket run",
+        "amount": 5000,
+        "fromAccountId": account_id,
+        "categoryId": groceries,
+        "vendorId": null,
+        "splits": [
+            { "categoryId": groceries, "amount": 3000, "memo": "Food" },
+            { "categoryId": household, "amount": 2000 }
+        ]
+    });
+    let resp = source
+        .post(format!("{}/transactions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let original = export(&source).await;
+    let splits = &original["transactions"][0]["splits"];
+    assert_eq!(splits[0]["amount"], 3000);
+    assert_eq!(splits[0]["memo"], "Food");
+    assert_eq!(splits[1]["categoryId"], household.as_str());
+
+    let target = test_client().await;
+    create_user_and_login(&target).await;
+    let (status, body) = import(&target, &original).await;
+    assert_eq!(status, Status::Ok, "{body}");
+
+    let restored = export(&target).await;
+    let category_name = |doc: &Value, id: &Value| doc["categories"].as_array().unwrap().iter().find(|c| &c["id"] == id).unwrap()["name"].clone();
+    let lines = restored["transactions"][0]["splits"].as_array().unwrap();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["amount"], 3000);
+    assert_eq!(lines[0]["memo"], "Food");
+    assert_eq!(category_name(&restored, &lines[1]["categoryId"]), "Split Backup Household");
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_import_round_trips_tags() {
+    let source = test_client().await;
+    create_user_and_login(&source).await;
+    let account_id = create_account(&source, "Tag Backup Checking", 100_000).await;
+    let category_id = create_category(&source, "Tag Backup Groceries", "expense").await;
+    let holiday = create_tag(&source, "Backup Holiday").await;
+    create_tag(&source, "Backup Unused").await;
+    let payload = serde_json::json!({
+        "transactionType": "Regular",
+        "date": "2026-03-10",
+        "description": "Beach snacks",
+        "amount": 1500,
+        "fromAccountId": account_id,
+        "categoryId": category_id,
+        "vendorId": null,
+        "tagIds": [holiday]
+    });
+    let resp = source
+        .post(format!("{}/transactions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let created: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+
+    // A correction appends rows; the links still travel once, on the first.
+    let mut edit = payload.clone();
+    edit["amount"] = serde_json::json!(1800);
+    edit.as_object_mut().unwrap().remove("tagIds");
+    let resp = source
+        .put(format!("{}/transactions/{}", V2_BASE, created["id"].as_str().unwrap()))
+        .header(ContentType::JSON)
+        .body(edit.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    let original = export(&source).await;
+    assert_eq!(original["tags"].as_array().unwrap().len(), 2);
+    let ledger = original["transactions"].as_array().unwrap();
+    assert_eq!(ledger.len(), 3);
+    assert_eq!(ledger[0]["tagIds"], serde_json::json!([holiday]));
+    assert!(ledger[1..].iter().all(|r| r.get("tagIds").is_none()));
+
+    let target = test_client().await;
+    create_user_and_login(&target).await;
+    let (status, body) = import(&target, &original).await;
+    assert_eq!(status, Status::Ok, "{body}");
+    assert_eq!(body["imported"]["tags"], 2);
+
+    let restored = export(&target).await;
+    let restored_holiday = restored["tags"].as_array().unwrap().iter().find(|t| t["name"] == "Backup Holiday").unwrap();
+    assert_eq!(restored_holiday["color"], "#228BE6");
+    assert_ne!(restored_holiday["id"].as_str().unwrap(), holiday);
+
+    let resp = target
+        .get(format!("{}/transactions/range?from=2026-03-01&to=2026-03-31", V2_BASE))
+        .dispatch()
+        .await;
+    let list: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let items = list.get("data").unwrap_or(&list).as_array().unwrap().clone();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["tagIds"], serde_json::json!([restored_holiday["id"]]));
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_import_round_trips_cleared_status_and_reconciliations() {
+    let source = test_client().await;
+    create_user_and_login(&source).await;
+    let account_id = create_account(&source, "Recon Backup Checking", 100_000).await;
+    let category_id = create_category(&source, "Recon Backup Groceries", "expense").await;
+    let reconciled = create_transaction(&source, &account_id, &category_id, 2000, "2026-03-10").await;
+    let cleared = create_transaction(&source, &account_id, &category_id, 500, "2026-03-11").await;
+    create_transaction(&source, &account_id, &category_id, 300, "2026-03-12").await;
+
+    let resp = source.post(format!("{}/transactions/{}/clear", V2_BASE, reconciled)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+    let resp = source
+        .post(format!("{}/accounts/{}/reconciliations", V2_BASE, account_id))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "statementDate": "2026-03-31", "statementBalance": 98_000 }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let started: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let resp = source
+        .post(format!(
+            "{}/accounts/{}/reconciliations/{}/finish",
+            V2_BASE,
+            account_id,
+            started["id"].as_str().unwrap()
+        ))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = source.post(format!("{}/transactions/{}/clear", V2_BASE, cleared)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+
+    let original = export(&source).await;
+    assert_eq!(original["reconciliations"][0]["statementBalance"], 98_000);
+    assert!(original["reconciliations"][0]["finishedAt"].is_string());
+    let ledger = original["transactions"].as_array().unwrap();
+    assert_eq!(ledger[0]["clearedStatus"], "reconciled");
+    assert_eq!(ledger[0]["reconciliationId"], started["id"]);
+    assert_eq!(ledger[1]["clearedStatus"], "cleared");
+    assert!(ledger[2].get("clearedStatus").is_none());
+
+    let target = test_client().await;
+    create_user_and_login(&target).await;
+    let (status, body) = import(&target, &original).await;
+    assert_eq!(status, Status::Ok, "{body}");
+    assert_eq!(body["imported"]["reconciliations"], 1);
+
+    let restored = export(&target).await;
+    let restored_account = restored["accounts"][0]["id"].as_str().unwrap();
+    let reconciliation = &restored["reconciliations"][0];
+    assert_ne!(reconciliation["id"], started["id"]);
+    assert_eq!(reconciliation["accountId"], restored_account);
+    assert_eq!(reconciliation["statementDate"], "2026-03-31");
+    assert_eq!(reconciliation["finishedAt"], original["reconciliations"][0]["finishedAt"]);
+
+    let resp = target
+        .get(format!("{}/transactions/page?accountId={}", V2_BASE, restored_account))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let page: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let mut statuses: Vec<&str> = page["data"].as_array().unwrap().iter().map(|t| t["clearedStatus"].as_str().unwrap()).collect();
+    statuses.sort();
+    assert_eq!(statuses, ["cleared", "reconciled", "uncleared"]);
+
+    let restored_ledger = restored["transactions"].as_array().unwrap();
+    assert_eq!(restored_ledger[0]["reconciliationId"], reconciliation["id"]);
+
+    let resp = target
+        .get(format!(
+            "{}/accounts/{}/reconciliations/{}",
+            V2_BASE,
+            restored_account,
+            reconciliation["id"].as_str().unwrap()
+        ))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(body["statementBalance"], 98_000);
+    assert_eq!(body["status"], "finished");
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_import_accepts_version_1_without_tags() {
+    let source = test_client().await;
+    create_user_and_login(&source).await;
+    seed(&source).await;
+    let mut backup = export(&source).await;
+    backup["version"] = serde_json::json!(1);
+    backup.as_object_mut().unwrap().remove("tags");
+    backup.as_object_mut().unwrap().remove("reconciliations");
+
+    let target = test_client().await;
+    create_user_and_login(&target).await;
+    let (status, body) = import(&target, &backup).await;
+    assert_eq!(status, Status::Ok, "{body}");
+    assert_eq!(body["imported"]["tags"], 0);
+    assert_eq!(body["imported"]["ledgerRows"], 5);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_import_rejects_dangling_tag_link() {
+    let source = test_client().await;
+    create_user_and_login(&source).await;
+    let account_id = create_account(&source, "Tag Link Checking", 100_000).await;
+    let vendor_id = create_vendor(&source, "Tag Link Market").await;
+    let category_id = create_category(&source, "Tag Link Groceries", "expense").await;
+    create_transaction_with_vendor(&source, &account_id, &category_id, 5000, "2026-03-10", &vendor_id).await;
+    let mut backup = export(&source).await;
+    backup["transactions"][0]["tagIds"] = serde_json::json!([uuid::Uuid::new_v4()]);
+
+    let target = test_client().await;
+    create_user_and_login(&target).await;
+    let (status, _) = import(&target, &backup).await;
+    assert_eq!(status, Status::BadRequest);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_import_rejects_split_lines_not_summing_to_amount() {
+    let source = test_client().await;
+    create_user_and_login(&source).await;
+    let account_id = create_account(&source, "Split Sum Checking", 100_000).await;
+    let vendor_id = create_vendor(&source, "Split Sum Market").await;
+    let category_id = create_category(&source, "Split Sum Groceries", "expense").await;
+    create_transaction_with_vendor(&source, &account_id, &category_id, 5000, "2026-03-10", &vendor_id).await;
+    let mut backup = export(&source).await;
+    backup["transactions"][0]["splits"] = serde_json::json!([
+        { "categoryId": category_id, "amount": 1000 },
+        { "categoryId": category_id, "amount": 2000 }
+    ]);
+
+    let target = test_client().await;
+    create_user_and_login(&target).await;
+    let (status, _) = import(&target, &backup).await;
+    assert_eq!(status, Status::BadRequest);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_import_rejects_unknown_version() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let mut backup = export(&client).await;
+    backup["version"] = serde_json::json!(99);
+
+    let (status, _) = import(&client, &backup).await;
+    assert_eq!(status, Status::BadRequest);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_import_rejects_unknown_format() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let mut backup = export(&client).await;
+    backup["format"] = serde_json::json!("something-else");
+
+    let (status, _) = import(&client, &backup).await;
+    assert_eq!(status, Status::BadRequest);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_import_rejects_dangling_reference() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let mut backup = export(&client).await;
+    backup["targets"] = serde_json::json!([{
+        "id": uuid::Uuid::new_v4(),
+        "categoryId": uuid::Uuid::new_v4(),
+        "isExcluded": false,
+        "value": 100
+    }]);
+
+    let (status, _) = import(&client, &backup).await;
+    assert_eq!(status, Status::BadRequest);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_import_conflict_rolls_back() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    seed(&client).await;
+    let mut backup = export(&client).await;
+    // Rename the account so it imports, leaving the category name to clash.
+    backup["accounts"][0]["name"] = serde_json::json!("Backup Savings");
+
+    let (status, _) = import(&client, &backup).await;
+    assert_eq!(status, Status::Conflict);
+
+    let after = export(&client).await;
+    assert_eq!(after["accounts"].as_array().unwrap().len(), 1, "the renamed account must be rolled back");
+}
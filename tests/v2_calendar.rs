@@ -0,0 +1,202 @@
+mod common;
+
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use common::auth::{create_user_and_login, get_eur_currency_id};
+use common::crypto::{decrypt_i64, decrypt_string};
+use common::entities::{create_category, create_period, create_subscription};
+use common::{TEST_PASSWORD, V2_BASE, test_client};
+use rocket::http::{ContentType, Status};
+use rocket::local::asynchronous::Client;
+use serde_json::Value;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Helpers
+// ═══════════════════════════════════════════════════════════════════════════════
+
+fn today() -> NaiveDate {
+    chrono::Utc::now().date_naive()
+}
+
+/// First of the month that is not before today.
+fn next_first_of_month() -> NaiveDate {
+    let today = today();
+    if today.day() == 1 {
+        today
+    } else {
+        today.with_day(1).unwrap().checked_add_months(chrono::Months::new(1)).unwrap()
+    }
+}
+
+async fn create_scheduled_account(client: &Client, payload: Value) -> String {
+    let mut payload = payload;
+    payload["currencyId"] = Value::String(get_eur_currency_id(client).await);
+    payload["color"] = Value::String("#1a2b3c".to_string());
+    payload["initialBalance"] = Value::from(0);
+    let resp = client
+        .post(format!("{}/accounts", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    body["id"].as_str().unwrap().to_string()
+}
+
+async fn upcoming(client: &Client, query: &str) -> Value {
+    let resp = client.get(format!("{}/calendar/upcoming{}", V2_BASE, query)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    serde_json::from_str(&resp.into_string().await.unwrap()).unwrap()
+}
+
+fn events_of<'a>(body: &'a Value, kind: &str, source_id: &str) -> Vec<&'a Value> {
+    body["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|e| e["kind"] == kind && e["sourceId"] == source_id)
+        .collect()
+}
+
+fn date_of(event: &Value) -> NaiveDate {
+    event["date"].as_str().unwrap().parse().unwrap()
+}
+
+/// Seeds one event source of every kind; returns their ids as
+/// `(subscription, credit card, allowance, period)`.
+async fn seed_sources(client: &Client) -> (String, String, String, String) {
+    let cat_id = create_category(client, "Calendar Streaming", "expense").await;
+    let sub_id = create_subscription(client, "Calendar Stream", &cat_id, 1299, "monthly", &next_first_of_month().to_string()).await;
+    let card_id = create_scheduled_account(
+        client,
+        serde_json::json!({
+            "accountType": "creditcard",
+            "name": "Calendar Card",
+            "spendLimit": 200_000,
+            "statementCloseDay": 10,
+            "paymentDueDay": 25
+        }),
+    )
+    .await;
+    let allowance_id = create_scheduled_account(
+        client,
+        serde_json::json!({
+            "accountType": "allowance",
+            "name": "Calendar Allowance",
+            "topUpAmount": 5000,
+            "topUpCycle": "weekly",
+            "topUpDay": 1
+        }),
+    )
+    .await;
+    let period_id = create_period(client, &today().to_string(), &(today() + Days::new(6)).to_string()).await;
+    (sub_id, card_id, allowance_id, period_id)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// GET /calendar/upcoming
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_upcoming_merges_every_source_in_date_order() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let (sub_id, card_id, allowance_id, period_id) = seed_sources(&client).await;
+
+    let body = upcoming(&client, "?days=31").await;
+    assert_eq!(body["from"], today().to_string());
+    assert_eq!(body["to"], (today() + Days::new(30)).to_string());
+
+    let dates: Vec<NaiveDate> = body["events"].as_array().unwrap().iter().map(date_of).collect();
+    assert!(dates.windows(2).all(|w| w[0] <= w[1]));
+
+    let charges = events_of(&body, "subscriptionCharge", &sub_id);
+    assert_eq!(charges.len(), 1);
+    assert_eq!(date_of(charges[0]), next_first_of_month());
+    assert_eq!(charges[0]["name"], "Calendar Stream");
+    assert_eq!(charges[0]["amount"], 1299);
+    assert!(charges[0].get("nameEnc").is_none());
+    assert!(charges[0].get("amountEnc").is_none());
+
+    let closes = events_of(&body, "statementClose", &card_id);
+    assert!(!closes.is_empty());
+    assert!(
+        closes
+            .iter()
+            .all(|e| date_of(e).day() == 10 && e["name"] == "Calendar Card" && e.get("amount").is_none())
+    );
+    let dues = events_of(&body, "paymentDue", &card_id);
+    assert!(!dues.is_empty());
+    assert!(dues.iter().all(|e| date_of(e).day() == 25));
+
+    let top_ups = events_of(&body, "allowanceTopUp", &allowance_id);
+    assert!(top_ups.len() >= 4);
+    assert!(top_ups.iter().all(|e| date_of(e).weekday() == Weekday::Mon && e["amount"] == 5000));
+
+    let starts = events_of(&body, "periodStart", &period_id);
+    assert_eq!(starts.len(), 1);
+    assert_eq!(date_of(starts[0]), today());
+    let ends = events_of(&body, "periodEnd", &period_id);
+    assert_eq!(ends.len(), 1);
+    assert_eq!(date_of(ends[0]), today() + Days::new(6));
+    assert!(ends[0]["name"].is_string());
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_upcoming_returns_ciphertext_without_dek() {
+    common::clear_login_rate_limits().await;
+    let client = test_client().await;
+    let (_, email) = create_user_and_login(&client).await;
+    let (sub_id, _, allowance_id, period_id) = seed_sources(&client).await;
+
+    // A fresh login that never unlocked has no DEK.
+    let locked = test_client().await;
+    let resp = locked
+        .post(format!("{}/auth/login", V2_BASE))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "email": email, "password": TEST_PASSWORD }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    let body = upcoming(&locked, "?days=31").await;
+
+    let charge = events_of(&body, "subscriptionCharge", &sub_id)[0];
+    assert!(charge.get("name").is_none());
+    assert!(charge.get("amount").is_none());
+    assert_eq!(decrypt_string(charge["nameEnc"].as_str().unwrap()), "Calendar Stream");
+    assert_eq!(decrypt_i64(charge["amountEnc"].as_str().unwrap()), 1299);
+
+    let top_up = events_of(&body, "allowanceTopUp", &allowance_id)[0];
+    assert_eq!(decrypt_i64(top_up["amountEnc"].as_str().unwrap()), 5000);
+
+    // Period names are not encrypted.
+    assert!(events_of(&body, "periodStart", &period_id)[0]["name"].is_string());
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_upcoming_window_defaults_and_clamps() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+
+    let body = upcoming(&client, "").await;
+    assert_eq!(body["to"], (today() + Days::new(29)).to_string());
+    assert!(body["events"].as_array().unwrap().is_empty());
+
+    let body = upcoming(&client, "?days=0").await;
+    assert_eq!(body["to"], today().to_string());
+
+    let body = upcoming(&client, "?days=5000").await;
+    assert_eq!(body["to"], (today() + Days::new(365)).to_string());
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_upcoming_unauthenticated_returns_401() {
+    let client = test_client().await;
+    let resp = client.get(format!("{}/calendar/upcoming", V2_BASE)).dispatch().await;
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
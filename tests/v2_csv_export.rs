@@ -0,0 +1,112 @@
+mod common;
+
+use common::auth::create_user_and_login;
+use common::entities::{create_account, create_category, create_transaction, create_transaction_with_vendor, create_vendor};
+use common::{V2_BASE, test_client};
+use rocket::http::{ContentType, Status};
+use rocket::local::asynchronous::Client;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Helpers
+// ═══════════════════════════════════════════════════════════════════════════════
+
+async fn export_csv(client: &Client, query: &str) -> String {
+    let resp = client.get(format!("{}/settings/export/transactions.csv{}", V2_BASE, query)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    assert_eq!(resp.content_type(), Some(ContentType::CSV));
+    resp.into_string().await.unwrap()
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// GET /settings/export/transactions.csv
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_csv_export_decrypts_rows_and_labels() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = create_account(&client, "CSV Checking", 100_000).await;
+    let category_id = create_category(&client, "CSV, Groceries", "expense").await;
+    let vendor_id = create_vendor(&client, "CSV Market").await;
+    create_transaction_with_vendor(&client, &account_id, &category_id, 1234, "2026-03-10", &vendor_id).await;
+    let deleted = create_transaction(&client, &account_id, &category_id, 999, "2026-03-11").await;
+    let resp = client.delete(format!("{}/transactions/{}", V2_BASE, deleted)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+
+    let csv = export_csv(&client, "").await;
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "date,description,amount,currency,category,type,from_account,to_account,vendor");
+    assert_eq!(lines.len(), 2, "deleted transactions are not exported: {csv}");
+    assert_eq!(
+        lines[1],
+        "2026-03-10,Test transaction,12.34,EUR,\"CSV, Groceries\",outgoing,CSV Checking,,CSV Market"
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_csv_export_columns_and_delimiter() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = create_account(&client, "CSV Delim", 100_000).await;
+    let category_id = create_category(&client, "CSV; Food", "expense").await;
+    create_transaction(&client, &account_id, &category_id, 500, "2026-03-10").await;
+
+    let csv = export_csv(&client, "?columns=amount,category,date&delimiter=semicolon").await;
+    assert_eq!(csv, "amount;category;date\n5.00;\"CSV; Food\";2026-03-10\n");
+
+    let csv = export_csv(&client, "?columns=amount&delimiter=tab").await;
+    assert_eq!(csv, "amount\n5.00\n");
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_csv_export_filters_by_range_and_account() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let first = create_account(&client, "CSV First", 100_000).await;
+    let second = create_account(&client, "CSV Second", 100_000).await;
+    let category_id = create_category(&client, "CSV Filter", "expense").await;
+    create_transaction(&client, &first, &category_id, 100, "2026-02-28").await;
+    create_transaction(&client, &first, &category_id, 200, "2026-03-01").await;
+    create_transaction(&client, &second, &category_id, 300, "2026-03-15").await;
+
+    let csv = export_csv(&client, "?from=2026-03-01&to=2026-03-31&columns=amount").await;
+    assert_eq!(csv, "amount\n2.00\n3.00\n");
+
+    let csv = export_csv(&client, &format!("?accountId={}&columns=amount", first)).await;
+    assert_eq!(csv, "amount\n1.00\n2.00\n");
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_csv_export_rejects_bad_parameters() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+
+    for query in [
+        "?columns=amount,balance",
+        "?columns=amount,amount",
+        "?delimiter=pipe",
+        "?from=03/01/2026",
+        "?from=2026-03-02&to=2026-03-01",
+    ] {
+        let resp = client.get(format!("{}/settings/export/transactions.csv{}", V2_BASE, query)).dispatch().await;
+        assert_eq!(resp.status(), Status::BadRequest, "{query}");
+    }
+
+    let resp = client
+        .get(format!("{}/settings/export/transactions.csv?accountId={}", V2_BASE, uuid::Uuid::new_v4()))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_csv_export_requires_auth() {
+    let client = test_client().await;
+    let resp = client.get(format!("{}/settings/export/transactions.csv", V2_BASE)).dispatch().await;
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
@@ -0,0 +1,336 @@
+mod common;
+
+use common::auth::{create_user_and_login, get_eur_currency_id};
+use common::entities::{create_account, create_category, create_period, create_target, create_transaction, create_transaction_with_vendor, create_vendor};
+use common::{V2_BASE, test_client};
+use rocket::http::{ContentType, Status};
+use rocket::local::asynchronous::Client;
+use serde_json::Value;
+
+fn date_after(days: i64) -> String {
+    (chrono::Utc::now().date_naive() + chrono::Duration::days(days)).format("%Y-%m-%d").to_string()
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Helpers
+// ═══════════════════════════════════════════════════════════════════════════════
+
+async fn get_json(client: &Client, url: &str) -> Value {
+    let resp = client.get(url.to_string()).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok, "GET {} failed with {}", url, resp.status());
+    serde_json::from_str(&resp.into_string().await.unwrap()).unwrap()
+}
+
+async fn create_typed_account(client: &Client, account_type: &str, name: &str, balance: i64) -> String {
+    let payload = serde_json::json!({
+        "accountType": account_type,
+        "name": name,
+        "color": "#1a2b3c",
+        "initialBalance": balance,
+        "currencyId": get_eur_currency_id(client).await,
+        "spendLimit": null
+    });
+    let resp = client
+        .post(format!("{}/accounts", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    body["id"].as_str().unwrap().to_string()
+}
+
+async fn create_transfer(client: &Client, from_account_id: &str, to_account_id: &str, category_id: &str, amount: i64, date: &str) {
+    let payload = serde_json::json!({
+        "transactionType": "Transfer",
+        "date": date,
+        "description": "Move funds",
+        "amount": amount,
+        "fromAccountId": from_account_id,
+        "toAccountId": to_account_id,
+        "categoryId": category_id,
+        "vendorId": null
+    });
+    let resp = client
+        .post(format!("{}/transactions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// GET /dashboard/current-period
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_current_period_reports_spend_target_and_daily_series() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = create_account(&client, "Dashboard Checking", 100_000).await;
+    let allowance_id = create_typed_account(&client, "allowance", "Pocket Money", 0).await;
+    let groceries = create_category(&client, "Groceries", "expense").await;
+    let salary = create_category(&client, "Salary", "income").await;
+    let transfer = create_category(&client, "Top Up", "transfer").await;
+    create_target(&client, &groceries, 40_000).await;
+
+    let period_id = create_period(&client, &date_after(-2), &date_after(7)).await;
+    create_transaction(&client, &account_id, &groceries, 1_500, &date_after(-2)).await;
+    create_transaction(&client, &account_id, &groceries, 2_500, &date_after(0)).await;
+    create_transaction(&client, &account_id, &salary, 300_000, &date_after(-1)).await;
+    create_transfer(&client, &account_id, &allowance_id, &transfer, 5_000, &date_after(-1)).await;
+    // Paid from the allowance: already counted by the top-up above.
+    create_transaction(&client, &allowance_id, &groceries, 900, &date_after(0)).await;
+    // Outside the period.
+    create_transaction(&client, &account_id, &groceries, 7_777, &date_after(-3)).await;
+
+    let body = get_json(&client, &format!("{}/dashboard/current-period", V2_BASE)).await;
+
+    assert_eq!(body["periodId"], period_id);
+    assert_eq!(body["startDate"], date_after(-2));
+    assert_eq!(body["endDate"], date_after(7));
+    assert_eq!(body["spent"], 9_000);
+    assert_eq!(body["target"], 40_000);
+    assert_eq!(body["daysInPeriod"], 10);
+    assert_eq!(body["daysRemaining"], 7);
+    assert_eq!(body["projectedSpend"], 30_000);
+    let daily: Vec<i64> = body["dailySpend"].as_array().unwrap().iter().map(|v| v.as_i64().unwrap()).collect();
+    assert_eq!(daily, vec![1_500, 5_000, 2_500, 0, 0, 0, 0, 0, 0, 0]);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_current_period_without_covering_period_returns_404() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    create_period(&client, &date_after(10), &date_after(20)).await;
+
+    let resp = client.get(format!("{}/dashboard/current-period", V2_BASE)).dispatch().await;
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_current_period_accepts_explicit_period() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = create_account(&client, "Past Checking", 100_000).await;
+    let groceries = create_category(&client, "Groceries", "expense").await;
+    let past = create_period(&client, &date_after(-40), &date_after(-31)).await;
+    create_transaction(&client, &account_id, &groceries, 4_200, &date_after(-35)).await;
+
+    let body = get_json(&client, &format!("{}/dashboard/current-period?periodId={}", V2_BASE, past)).await;
+    assert_eq!(body["periodId"], past);
+    assert_eq!(body["spent"], 4_200);
+    assert_eq!(body["daysRemaining"], 0);
+    assert_eq!(body["projectedSpend"], 4_200);
+
+    let resp = client
+        .get(format!("{}/dashboard/current-period?periodId={}", V2_BASE, uuid::Uuid::new_v4()))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_dashboard_requires_auth() {
+    let client = test_client().await;
+
+    for card in ["current-period", "net-position", "cash-flow", "spending-trend", "top-vendors"] {
+        let resp = client.get(format!("{}/dashboard/{}", V2_BASE, card)).dispatch().await;
+        assert_eq!(resp.status(), Status::Unauthorized, "{card}");
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// GET /dashboard/net-position
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_net_position_groups_balances_by_account_type() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let checking = create_account(&client, "Net Checking", 50_000).await;
+    create_typed_account(&client, "savings", "Net Savings", 200_000).await;
+    let card = create_typed_account(&client, "creditcard", "Net Card", 0).await;
+    let archived = create_account(&client, "Net Archived", 999_999).await;
+    let resp = client.post(format!("{}/accounts/{}/archive", V2_BASE, archived)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+
+    let groceries = create_category(&client, "Groceries", "expense").await;
+    let salary = create_category(&client, "Salary", "income").await;
+    create_period(&client, &date_after(-5), &date_after(5)).await;
+    create_transaction(&client, &card, &groceries, 12_000, &date_after(0)).await;
+    create_transaction(&client, &checking, &salary, 30_000, &date_after(-1)).await;
+
+    let body = get_json(&client, &format!("{}/dashboard/net-position", V2_BASE)).await;
+
+    assert_eq!(body["liquid"], 80_000);
+    assert_eq!(body["protected"], 200_000);
+    assert_eq!(body["debt"], 12_000);
+    assert_eq!(body["total"], 268_000);
+    assert_eq!(body["differenceThisPeriod"], 18_000);
+    let accounts = body["accounts"].as_array().unwrap();
+    assert_eq!(accounts.len(), 3);
+    let card_entry = accounts.iter().find(|a| a["accountId"] == card).unwrap();
+    assert_eq!(card_entry["accountType"], "creditcard");
+    assert_eq!(card_entry["balance"], 12_000);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_net_position_without_period_has_null_difference() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    create_account(&client, "Lonely Checking", 1_000).await;
+
+    let body = get_json(&client, &format!("{}/dashboard/net-position", V2_BASE)).await;
+    assert_eq!(body["total"], 1_000);
+    assert!(body["differenceThisPeriod"].is_null());
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// GET /dashboard/cash-flow
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_cash_flow_sums_income_and_expenses_but_not_transfers() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let checking = create_account(&client, "Flow Checking", 100_000).await;
+    let savings = create_typed_account(&client, "savings", "Flow Savings", 0).await;
+    let groceries = create_category(&client, "Groceries", "expense").await;
+    let salary = create_category(&client, "Salary", "income").await;
+    let transfer = create_category(&client, "Saving", "transfer").await;
+    let period_id = create_period(&client, &date_after(-5), &date_after(5)).await;
+
+    create_transaction(&client, &checking, &salary, 250_000, &date_after(-4)).await;
+    create_transaction(&client, &checking, &groceries, 3_000, &date_after(-3)).await;
+    create_transaction(&client, &checking, &groceries, 4_000, &date_after(-2)).await;
+    create_transfer(&client, &checking, &savings, &transfer, 50_000, &date_after(-1)).await;
+
+    let body = get_json(&client, &format!("{}/dashboard/cash-flow", V2_BASE)).await;
+    assert_eq!(body["periodId"], period_id);
+    assert_eq!(body["inflows"], 250_000);
+    assert_eq!(body["outflows"], 7_000);
+    assert_eq!(body["net"], 243_000);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_cash_flow_ignores_voided_transactions() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let checking = create_account(&client, "Void Checking", 100_000).await;
+    let groceries = create_category(&client, "Groceries", "expense").await;
+    create_period(&client, &date_after(-5), &date_after(5)).await;
+    create_transaction(&client, &checking, &groceries, 3_000, &date_after(0)).await;
+    let voided = create_transaction(&client, &checking, &groceries, 8_000, &date_after(0)).await;
+    let resp = client.delete(format!("{}/transactions/{}", V2_BASE, voided)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+
+    let body = get_json(&client, &format!("{}/dashboard/cash-flow", V2_BASE)).await;
+    assert_eq!(body["outflows"], 3_000);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// GET /dashboard/spending-trend
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_spending_trend_lists_started_periods_oldest_first() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let checking = create_account(&client, "Trend Checking", 100_000).await;
+    let groceries = create_category(&client, "Groceries", "expense").await;
+
+    let oldest = create_period(&client, &date_after(-30), &date_after(-21)).await;
+    let older = create_period(&client, &date_after(-20), &date_after(-11)).await;
+    let current = create_period(&client, &date_after(-10), &date_after(5)).await;
+    create_period(&client, &date_after(6), &date_after(15)).await;
+
+    create_transaction(&client, &checking, &groceries, 1_000, &date_after(-25)).await;
+    create_transaction(&client, &checking, &groceries, 2_000, &date_after(-15)).await;
+    create_transaction(&client, &checking, &groceries, 500, &date_after(-14)).await;
+    create_transaction(&client, &checking, &groceries, 3_000, &date_after(0)).await;
+
+    let body = get_json(&client, &format!("{}/dashboard/spending-trend?periods=2", V2_BASE)).await;
+    let items = body.as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["periodId"], older);
+    assert_eq!(items[0]["totalSpend"], 2_500);
+    assert_eq!(items[1]["periodId"], current);
+    assert_eq!(items[1]["totalSpend"], 3_000);
+
+    let body = get_json(&client, &format!("{}/dashboard/spending-trend", V2_BASE)).await;
+    let items = body.as_array().unwrap();
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[0]["periodId"], oldest);
+    assert_eq!(items[0]["totalSpend"], 1_000);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// GET /dashboard/top-vendors
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_top_vendors_ranks_by_spend_with_decrypted_names() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let checking = create_account(&client, "Vendor Checking", 100_000).await;
+    let groceries = create_category(&client, "Groceries", "expense").await;
+    let refunds = create_category(&client, "Refunds", "income").await;
+    let market = create_vendor(&client, "Market").await;
+    let bakery = create_vendor(&client, "Bakery").await;
+    let kiosk = create_vendor(&client, "Kiosk").await;
+    create_period(&client, &date_after(-5), &date_after(5)).await;
+
+    create_transaction_with_vendor(&client, &checking, &groceries, 6_000, &date_after(-1), &market).await;
+    create_transaction_with_vendor(&client, &checking, &groceries, 1_500, &date_after(0), &market).await;
+    create_transaction_with_vendor(&client, &checking, &groceries, 2_000, &date_after(0), &bakery).await;
+    create_transaction_with_vendor(&client, &checking, &groceries, 500, &date_after(0), &kiosk).await;
+    // Refunds are not vendor spend.
+    create_transaction_with_vendor(&client, &checking, &refunds, 9_000, &date_after(0), &kiosk).await;
+
+    let body = get_json(&client, &format!("{}/dashboard/top-vendors?limit=2", V2_BASE)).await;
+    let items = body.as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["vendorId"], market);
+    assert_eq!(items[0]["vendorName"], "Market");
+    assert_eq!(items[0]["totalSpend"], 7_500);
+    assert_eq!(items[0]["transactionCount"], 2);
+    assert_eq!(items[0]["percentage"], 75);
+    assert_eq!(items[1]["vendorId"], bakery);
+    assert_eq!(items[1]["vendorName"], "Bakery");
+    assert_eq!(items[1]["percentage"], 20);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Isolation
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_dashboard_only_sees_own_transactions() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let checking = create_account(&client, "Other Checking", 100_000).await;
+    let groceries = create_category(&client, "Groceries", "expense").await;
+    create_period(&client, &date_after(-5), &date_after(5)).await;
+    create_transaction(&client, &checking, &groceries, 5_000, &date_after(0)).await;
+
+    let other = test_client().await;
+    create_user_and_login(&other).await;
+    create_period(&other, &date_after(-5), &date_after(5)).await;
+
+    let body = get_json(&other, &format!("{}/dashboard/current-period", V2_BASE)).await;
+    assert_eq!(body["spent"], 0);
+}
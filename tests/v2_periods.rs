@@ -344,6 +344,36 @@ async fn test_get_period_all_fields_match() {
     common::assertions::assert_uuid(&body["id"]);
 }
 
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_get_period_reports_decrypted_totals() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "Totals Checking", 100_000).await;
+    let groceries = common::entities::create_category(&client, "Groceries", "expense").await;
+    let salary = common::entities::create_category(&client, "Salary", "income").await;
+    common::entities::create_target(&client, &groceries, 20_000).await;
+    common::entities::create_transaction(&client, &account_id, &groceries, 3_000, "2026-06-05").await;
+    common::entities::create_transaction(&client, &account_id, &groceries, 2_000, "2026-06-20").await;
+    common::entities::create_transaction(&client, &account_id, &salary, 90_000, "2026-06-10").await;
+    common::entities::create_transaction(&client, &account_id, &groceries, 7_000, "2026-07-01").await;
+
+    let period_id = common::entities::create_period(&client, "2026-06-01", "2026-06-30").await;
+
+    let get_resp = client.get(format!("{}/periods/{}", V2_BASE, period_id)).dispatch().await;
+    assert_eq!(get_resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&get_resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(body["totalSpent"], 5_000);
+    assert_eq!(body["totalBudgeted"], 20_000);
+    assert_eq!(body["percentageOfTargetUsed"], 25);
+
+    let list_resp = client.get(format!("{}/periods", V2_BASE)).dispatch().await;
+    let list: Value = serde_json::from_str(&list_resp.into_string().await.unwrap()).unwrap();
+    let listed = list["data"].as_array().unwrap().iter().find(|p| p["id"] == period_id.as_str()).unwrap();
+    assert_eq!(listed["totalSpent"], 5_000);
+    assert_eq!(listed["totalBudgeted"], 20_000);
+}
+
 #[rocket::async_test]
 #[ignore = "requires database"]
 async fn test_get_period_not_found() {
@@ -0,0 +1,308 @@
+mod common;
+
+use common::auth::create_user_and_login;
+use common::entities::{create_account, create_category, create_transaction};
+use common::{V2_BASE, test_client};
+use rocket::http::{ContentType, Status};
+use rocket::local::asynchronous::Client;
+use serde_json::Value;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Helpers
+// ═══════════════════════════════════════════════════════════════════════════════
+
+async fn post_status(client: &Client, transaction_id: &str, action: &str) -> Status {
+    client
+        .post(format!("{}/transactions/{}/{}", V2_BASE, transaction_id, action))
+        .dispatch()
+        .await
+        .status()
+}
+
+async fn cleared_status(client: &Client, account_id: &str, transaction_id: &str) -> String {
+    let resp = client.get(format!("{}/transactions/page?accountId={}", V2_BASE, account_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let row = body["data"].as_array().unwrap().iter().find(|t| t["id"] == transaction_id).unwrap();
+    row["clearedStatus"].as_str().unwrap().to_string()
+}
+
+async fn start(client: &Client, account_id: &str, statement_balance: i64) -> (Status, Value) {
+    let resp = client
+        .post(format!("{}/accounts/{}/reconciliations", V2_BASE, account_id))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "statementDate": "2026-03-31", "statementBalance": statement_balance }).to_string())
+        .dispatch()
+        .await;
+    let status = resp.status();
+    (status, serde_json::from_str(&resp.into_string().await.unwrap()).unwrap_or(Value::Null))
+}
+
+async fn finish(client: &Client, account_id: &str, reconciliation_id: &str) -> (Status, Value) {
+    let resp = client
+        .post(format!("{}/accounts/{}/reconciliations/{}/finish", V2_BASE, account_id, reconciliation_id))
+        .dispatch()
+        .await;
+    let status = resp.status();
+    (status, serde_json::from_str(&resp.into_string().await.unwrap()).unwrap_or(Value::Null))
+}
+
+fn update_payload(account_id: &str, category_id: &str, amount: i64) -> String {
+    serde_json::json!({
+        "transactionType": "Regular",
+        "date": "2026-03-10",
+        "description": "Corrected purchase",
+        "amount": amount,
+        "fromAccountId": account_id,
+        "categoryId": category_id,
+        "vendorId": null
+    })
+    .to_string()
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Cleared status
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_clear_and_unclear_transaction() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = create_account(&client, "Clear Checking", 100_000).await;
+    let category_id = create_category(&client, "Clear Groceries", "expense").await;
+    let tx_id = create_transaction(&client, &account_id, &category_id, 2000, "2026-03-10").await;
+    assert_eq!(cleared_status(&client, &account_id, &tx_id).await, "uncleared");
+
+    assert_eq!(post_status(&client, &tx_id, "clear").await, Status::NoContent);
+    assert_eq!(cleared_status(&client, &account_id, &tx_id).await, "cleared");
+    assert_eq!(post_status(&client, &tx_id, "clear").await, Status::NoContent, "clearing twice is a no-op");
+
+    // Corrections keep the status.
+    let resp = client
+        .put(format!("{}/transactions/{}", V2_BASE, tx_id))
+        .header(ContentType::JSON)
+        .body(update_payload(&account_id, &category_id, 2100))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(body["clearedStatus"], "cleared");
+
+    assert_eq!(post_status(&client, &tx_id, "unclear").await, Status::NoContent);
+    assert_eq!(cleared_status(&client, &account_id, &tx_id).await, "uncleared");
+    assert_eq!(post_status(&client, &tx_id, "unlock").await, Status::Conflict);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_clear_unknown_or_deleted_transaction() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = create_account(&client, "Clear Missing Checking", 100_000).await;
+    let category_id = create_category(&client, "Clear Missing Groceries", "expense").await;
+
+    assert_eq!(post_status(&client, &uuid::Uuid::new_v4().to_string(), "clear").await, Status::NotFound);
+
+    let tx_id = create_transaction(&client, &account_id, &category_id, 2000, "2026-03-10").await;
+    let resp = client.delete(format!("{}/transactions/{}", V2_BASE, tx_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+    assert_eq!(post_status(&client, &tx_id, "clear").await, Status::Conflict);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Reconciliation
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_reconciliation_reports_cleared_balance_and_locks_on_finish() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = create_account(&client, "Recon Checking", 100_000).await;
+    let groceries = create_category(&client, "Recon Groceries", "expense").await;
+    let salary = create_category(&client, "Recon Salary", "income").await;
+
+    let cleared = create_transaction(&client, &account_id, &groceries, 2000, "2026-03-10").await;
+    let income = create_transaction(&client, &account_id, &salary, 5000, "2026-03-12").await;
+    let pending = create_transaction(&client, &account_id, &groceries, 500, "2026-03-30").await;
+    assert_eq!(post_status(&client, &cleared, "clear").await, Status::NoContent);
+    assert_eq!(post_status(&client, &income, "clear").await, Status::NoContent);
+
+    let (status, started) = start(&client, &account_id, 103_000).await;
+    assert_eq!(status, Status::Created);
+    assert_eq!(started["status"], "open");
+    assert_eq!(started["statementDate"], "2026-03-31");
+    assert_eq!(started["statementBalance"], 103_000);
+    assert_eq!(started["clearedBalance"], 103_000, "pending purchase is left out");
+    assert_eq!(started["difference"], 0);
+    assert!(started["finishedAt"].is_null());
+    let reconciliation_id = started["id"].as_str().unwrap();
+
+    let (status, finished) = finish(&client, &account_id, reconciliation_id).await;
+    assert_eq!(status, Status::Ok);
+    assert_eq!(finished["status"], "finished");
+    assert!(finished["finishedAt"].is_string());
+    assert_eq!(cleared_status(&client, &account_id, &cleared).await, "reconciled");
+    assert_eq!(cleared_status(&client, &account_id, &income).await, "reconciled");
+    assert_eq!(cleared_status(&client, &account_id, &pending).await, "uncleared");
+
+    // Reconciled transactions are locked until unlocked.
+    let resp = client
+        .put(format!("{}/transactions/{}", V2_BASE, cleared))
+        .header(ContentType::JSON)
+        .body(update_payload(&account_id, &groceries, 2500))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Conflict);
+    let resp = client.delete(format!("{}/transactions/{}", V2_BASE, cleared)).dispatch().await;
+    assert_eq!(resp.status(), Status::Conflict);
+    assert_eq!(post_status(&client, &cleared, "unclear").await, Status::Conflict);
+
+    assert_eq!(post_status(&client, &cleared, "unlock").await, Status::NoContent);
+    assert_eq!(cleared_status(&client, &account_id, &cleared).await, "cleared");
+    let resp = client
+        .put(format!("{}/transactions/{}", V2_BASE, cleared))
+        .header(ContentType::JSON)
+        .body(update_payload(&account_id, &groceries, 2500))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    // The finished reconciliation keeps the figures it finished with.
+    let resp = client
+        .get(format!("{}/accounts/{}/reconciliations/{}", V2_BASE, account_id, reconciliation_id))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(body["clearedBalance"], 103_000);
+    assert_eq!(body["difference"], 0);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_reconciliation_cannot_finish_with_difference() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = create_account(&client, "Recon Diff Checking", 50_000).await;
+    let category_id = create_category(&client, "Recon Diff Groceries", "expense").await;
+    let tx_id = create_transaction(&client, &account_id, &category_id, 1000, "2026-03-10").await;
+    assert_eq!(post_status(&client, &tx_id, "clear").await, Status::NoContent);
+
+    let (status, started) = start(&client, &account_id, 48_500).await;
+    assert_eq!(status, Status::Created);
+    assert_eq!(started["clearedBalance"], 49_000);
+    assert_eq!(started["difference"], -500);
+    let reconciliation_id = started["id"].as_str().unwrap();
+
+    let (status, _) = finish(&client, &account_id, reconciliation_id).await;
+    assert_eq!(status, Status::Conflict);
+    assert_eq!(cleared_status(&client, &account_id, &tx_id).await, "cleared");
+
+    // Only one open reconciliation per account.
+    let (status, _) = start(&client, &account_id, 49_000).await;
+    assert_eq!(status, Status::Conflict);
+
+    let resp = client
+        .delete(format!("{}/accounts/{}/reconciliations/{}", V2_BASE, account_id, reconciliation_id))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::NoContent);
+
+    let (status, restarted) = start(&client, &account_id, 49_000).await;
+    assert_eq!(status, Status::Created);
+    let (status, _) = finish(&client, &account_id, restarted["id"].as_str().unwrap()).await;
+    assert_eq!(status, Status::Ok);
+
+    let resp = client
+        .delete(format!(
+            "{}/accounts/{}/reconciliations/{}",
+            V2_BASE,
+            account_id,
+            restarted["id"].as_str().unwrap()
+        ))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Conflict, "finished reconciliations stay");
+
+    let resp = client.get(format!("{}/accounts/{}/reconciliations", V2_BASE, account_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    let list: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(list.as_array().unwrap().len(), 1);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_reconciliation_counts_transfers_on_receiving_account() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let checking = create_account(&client, "Recon Transfer Checking", 100_000).await;
+    let savings = create_account(&client, "Recon Transfer Savings", 10_000).await;
+    let transfer = create_category(&client, "Recon Saving", "transfer").await;
+
+    let payload = serde_json::json!({
+        "transactionType": "Transfer",
+        "date": "2026-03-15",
+        "description": "Move funds",
+        "amount": 3000,
+        "fromAccountId": checking,
+        "toAccountId": savings,
+        "categoryId": transfer,
+        "vendorId": null
+    });
+    let resp = client
+        .post(format!("{}/transactions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let tx_id = body["id"].as_str().unwrap();
+
+    let (_, uncleared) = start(&client, &savings, 13_000).await;
+    assert_eq!(uncleared["clearedBalance"], 10_000);
+    let resp = client
+        .delete(format!(
+            "{}/accounts/{}/reconciliations/{}",
+            V2_BASE,
+            savings,
+            uncleared["id"].as_str().unwrap()
+        ))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::NoContent);
+
+    assert_eq!(post_status(&client, tx_id, "clear").await, Status::NoContent);
+    let (_, cleared) = start(&client, &savings, 13_000).await;
+    assert_eq!(cleared["clearedBalance"], 13_000);
+    let (_, checking_report) = start(&client, &checking, 97_000).await;
+    assert_eq!(checking_report["clearedBalance"], 97_000);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_reconciliation_requires_own_account() {
+    let owner = test_client().await;
+    create_user_and_login(&owner).await;
+    let account_id = create_account(&owner, "Recon Owner Checking", 10_000).await;
+    let (_, started) = start(&owner, &account_id, 10_000).await;
+
+    let other = test_client().await;
+    create_user_and_login(&other).await;
+    let (status, _) = start(&other, &account_id, 10_000).await;
+    assert_eq!(status, Status::NotFound);
+    let resp = other
+        .get(format!(
+            "{}/accounts/{}/reconciliations/{}",
+            V2_BASE,
+            account_id,
+            started["id"].as_str().unwrap()
+        ))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::NotFound);
+    let (status, _) = finish(&other, &account_id, started["id"].as_str().unwrap()).await;
+    assert_eq!(status, Status::NotFound);
+}
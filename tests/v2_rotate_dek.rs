@@ -0,0 +1,300 @@
+mod common;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use common::crypto::try_decrypt_cell_with;
+use common::{V2_BASE, test_client};
+use rocket::http::{ContentType, Status};
+use rocket::local::asynchronous::Client;
+use serde_json::Value;
+
+const OLD_DEK: [u8; 32] = [0u8; 32];
+const NEW_DEK: [u8; 32] = [7u8; 32];
+
+fn rotation_payload(new_dek: [u8; 32]) -> Value {
+    serde_json::json!({
+        "newDek": BASE64.encode(new_dek),
+        "wrappedDek": "bmV3LWRlay13cmFw",
+        "dekWrapParams": {"salt": "rotated"}
+    })
+}
+
+async fn rotate(client: &Client, new_dek: [u8; 32]) -> rocket::local::asynchronous::LocalResponse<'_> {
+    post_rotation(client, rotation_payload(new_dek)).await
+}
+
+async fn post_rotation(client: &Client, payload: Value) -> rocket::local::asynchronous::LocalResponse<'_> {
+    client
+        .post(format!("{}/auth/rotate-dek", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await
+}
+
+async fn get_json(client: &Client, path: &str) -> Value {
+    let resp = client.get(format!("{}{}", V2_BASE, path)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok, "GET {} failed", path);
+    serde_json::from_str(&resp.into_string().await.unwrap()).unwrap()
+}
+
+/// The cell an envelope was read from: (table, column, row id, user id).
+type Cell<'a> = (&'a str, &'a str, &'a str, &'a str);
+
+fn decrypt_string(key: &[u8; 32], cell: Cell<'_>, value: &Value) -> Option<String> {
+    let (table, column, row_id, user_id) = cell;
+    try_decrypt_cell_with(key, table, column, row_id, user_id, value.as_str().expect("envelope string")).map(|b| String::from_utf8(b).unwrap())
+}
+
+fn decrypt_i64(key: &[u8; 32], cell: Cell<'_>, value: &Value) -> Option<i64> {
+    let (table, column, row_id, user_id) = cell;
+    try_decrypt_cell_with(key, table, column, row_id, user_id, value.as_str().expect("envelope string")).map(|b| i64::from_le_bytes(b.try_into().unwrap()))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// POST /auth/rotate-dek
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_rotate_dek_reencrypts_user_data() {
+    let client = test_client().await;
+    let (user_id, _email) = common::auth::create_user_and_login(&client).await;
+
+    let account_id = common::entities::create_account(&client, "Rotated Checking", 10_000).await;
+    let category_id = common::entities::create_category(&client, "Rotated Groceries", "expense").await;
+    let tx_id = common::entities::create_transaction(&client, &account_id, &category_id, 2_500, "2026-03-01").await;
+
+    let resp = rotate(&client, NEW_DEK).await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert!(body["rowsReencrypted"].as_u64().unwrap() >= 4);
+    assert_eq!(body["recoveryKeyCleared"], false);
+
+    // The session's DEK was swapped in place, so reads keep working and all
+    // ciphertext now opens only under the new key.
+    let account = get_json(&client, &format!("/accounts/{}", account_id)).await;
+    let account_cell = |column| ("account", column, account_id.as_str(), user_id.as_str());
+    assert_eq!(
+        decrypt_string(&NEW_DEK, account_cell("name_enc"), &account["nameEnc"]).as_deref(),
+        Some("Rotated Checking")
+    );
+    assert_eq!(
+        decrypt_i64(&NEW_DEK, account_cell("current_balance_enc"), &account["currentBalanceEnc"]),
+        Some(7_500)
+    );
+    assert!(decrypt_string(&OLD_DEK, account_cell("name_enc"), &account["nameEnc"]).is_none());
+
+    let txs = get_json(&client, "/transactions/range?from=2026-03-01&to=2026-03-31").await;
+    let tx = txs.as_array().unwrap().iter().find(|t| t["id"] == tx_id.as_str()).expect("transaction listed");
+    let tx_cell = |column| ("transaction", column, tx_id.as_str(), user_id.as_str());
+    assert_eq!(decrypt_i64(&NEW_DEK, tx_cell("amount_enc"), &tx["amountEnc"]), Some(2_500));
+    assert_eq!(
+        decrypt_string(&NEW_DEK, tx_cell("description_enc"), &tx["descriptionEnc"]).as_deref(),
+        Some("Test transaction")
+    );
+
+    let wrapped = get_json(&client, "/auth/wrapped-dek").await;
+    assert_eq!(wrapped["wrappedDek"], "bmV3LWRlay13cmFw");
+    assert_eq!(wrapped["dekWrapParams"]["salt"], "rotated");
+    assert!(wrapped.get("pendingWrappedDek").is_none());
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_rotate_dek_reencrypts_split_lines() {
+    let client = test_client().await;
+    let (user_id, _email) = common::auth::create_user_and_login(&client).await;
+
+    let account_id = common::entities::create_account(&client, "Rotated Split Checking", 10_000).await;
+    let groceries = common::entities::create_category(&client, "Rotated Split Groceries", "expense").await;
+    let household = common::entities::create_category(&client, "Rotated Split Household", "expense").await;
+    let payload = serde_json::json!({
+        "transactionType": "Regular",
+        "date": "2026-03-01",
+        "description": "Split purchase",
+        "amount": 2_500,
+        "fromAccountId": account_id,
+        "categoryId": groceries,
+        "vendorId": null,
+        "splits": [
+            { "categoryId": groceries, "amount": 1_500, "memo": "Food" },
+            { "categoryId": household, "amount": 1_000 }
+        ]
+    });
+    let resp = client
+        .post(format!("{}/transactions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+
+    let resp = rotate(&client, NEW_DEK).await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    let txs = get_json(&client, "/transactions/range?from=2026-03-01&to=2026-03-31").await;
+    let line = &txs[0]["splits"][0];
+    let split_id = line["id"].as_str().unwrap();
+    let split_cell = |column| ("transaction_split", column, split_id, user_id.as_str());
+    assert_eq!(decrypt_i64(&NEW_DEK, split_cell("amount_enc"), &line["amountEnc"]), Some(1_500));
+    assert_eq!(decrypt_string(&NEW_DEK, split_cell("memo_enc"), &line["memoEnc"]).as_deref(), Some("Food"));
+    assert!(decrypt_i64(&OLD_DEK, split_cell("amount_enc"), &line["amountEnc"]).is_none());
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_rotate_dek_keeps_names_unique() {
+    let client = test_client().await;
+    common::auth::create_user_and_login(&client).await;
+    common::entities::create_account(&client, "Rotated Savings", 0).await;
+
+    let resp = rotate(&client, NEW_DEK).await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Blind indexes are re-derived under the new DEK, so the existing name
+    // still blocks a duplicate.
+    let eur_id = common::auth::get_eur_currency_id(&client).await;
+    let payload = serde_json::json!({
+        "accountType": "savings",
+        "name": "rotated savings",
+        "color": "#1a2b3c",
+        "initialBalance": 0,
+        "currencyId": eur_id,
+        "spendLimit": null
+    });
+    let resp = client
+        .post(format!("{}/accounts", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Conflict);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_rotate_dek_same_key_rejected() {
+    let client = test_client().await;
+    common::auth::create_user_and_login(&client).await;
+
+    let resp = rotate(&client, OLD_DEK).await;
+    assert_eq!(resp.status(), Status::BadRequest);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_rotate_dek_requires_unlock() {
+    let client = test_client().await;
+
+    let resp = rotate(&client, NEW_DEK).await;
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_rotate_dek_conflicts_with_pending_rotation_to_other_key() {
+    let client = test_client().await;
+    let (user_id, _email) = common::auth::create_user_and_login(&client).await;
+
+    // Simulate an interrupted rotation to some other key
+    let url = std::env::var("DATABASE_URL").unwrap_or_else(|_| common::TEST_DB_URL.to_string());
+    let pool = sqlx::PgPool::connect(&url).await.expect("connect to test db");
+    sqlx::query(
+        "INSERT INTO dek_rotation (user_id, new_wrapped_dek, new_dek_wrap_params, new_dek_check) VALUES ($1, '\\x01'::bytea, '{\"salt\": \"pending\"}', '\\x00'::bytea)",
+    )
+    .bind(uuid::Uuid::parse_str(&user_id).unwrap())
+    .execute(&pool)
+    .await
+    .expect("insert pending rotation");
+
+    let wrapped = get_json(&client, "/auth/wrapped-dek").await;
+    assert_eq!(wrapped["pendingWrappedDek"], "AQ==");
+    assert_eq!(wrapped["pendingDekWrapParams"]["salt"], "pending");
+
+    let resp = rotate(&client, NEW_DEK).await;
+    assert_eq!(resp.status(), Status::Conflict);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_rotate_dek_rewraps_recovery_key_for_reset() {
+    common::clear_login_rate_limits().await;
+    let client = test_client().await;
+    let (user_id, email) = common::auth::create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "Recovered Checking", 10_000).await;
+
+    let resp = client
+        .put(format!("{}/auth/recovery-key", V2_BASE))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "recoveryWrappedDek": "b2xkLXJlY292ZXJ5", "recoveryWrapParams": {"salt": "recovery"} }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    // The recovery wrap of the old DEK would be dead after the rotation.
+    let resp = rotate(&client, NEW_DEK).await;
+    assert_eq!(resp.status(), Status::BadRequest);
+
+    let mut payload = rotation_payload(NEW_DEK);
+    payload["recoveryWrappedDek"] = Value::from("bmV3LXJlY292ZXJ5");
+    payload["recoveryWrapParams"] = serde_json::json!({"salt": "new-recovery"});
+    let resp = post_rotation(&client, payload).await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(body["recoveryKeyCleared"], false);
+
+    // A recovery-key reset hands out the wrap of the new DEK.
+    let token = common::auth::insert_reset_token(&user_id).await;
+    let resp = client
+        .post(format!("{}/auth/reset-password/recovery-key", V2_BASE))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "token": token }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(body["recoveryWrappedDek"], "bmV3LXJlY292ZXJ5");
+    assert_eq!(body["recoveryWrapParams"]["salt"], "new-recovery");
+
+    let new_password = "RecoveredSecurePassword!2026abc";
+    let resp = client
+        .post(format!("{}/auth/reset-password", V2_BASE))
+        .header(ContentType::JSON)
+        .body(
+            serde_json::json!({
+                "token": token,
+                "password": new_password,
+                "wrappedDek": "cmVzZXQtd3JhcA==",
+                "dekWrapParams": {"salt": "reset"}
+            })
+            .to_string(),
+        )
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    // Unlocking with the recovered (new) DEK reads the rotated data.
+    let device = test_client().await;
+    let resp = device
+        .post(format!("{}/auth/login", V2_BASE))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "email": email, "password": new_password }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = device
+        .post(format!("{}/auth/unlock", V2_BASE))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "dek": BASE64.encode(NEW_DEK) }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    let account = get_json(&device, &format!("/accounts/{}", account_id)).await;
+    let account_cell = |column| ("account", column, account_id.as_str(), user_id.as_str());
+    assert_eq!(
+        decrypt_string(&NEW_DEK, account_cell("name_enc"), &account["nameEnc"]).as_deref(),
+        Some("Recovered Checking")
+    );
+}
@@ -1,8 +1,8 @@
 mod common;
 
-use common::auth::create_user_and_login;
+use common::auth::{create_user_and_login, unlock_session};
 use common::crypto::{decrypt_i64, decrypt_string};
-use common::entities::{create_category, create_subscription, create_vendor};
+use common::entities::{create_account, create_category, create_subscription, create_transaction, create_transaction_with_vendor, create_vendor};
 use common::{V2_BASE, test_client};
 use rocket::http::{ContentType, Status};
 use serde_json::Value;
@@ -444,3 +444,642 @@ async fn test_cancel_subscription_unauthenticated_returns_401() {
 
     assert_eq!(resp.status(), Status::Unauthorized);
 }
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// POST /subscriptions/{id}/pause and /resume
+// ═══════════════════════════════════════════════════════════════════════════════
+
+async fn post_action(client: &rocket::local::asynchronous::Client, sub_id: &str, action: &str, body: &str) -> (Status, Value) {
+    let resp = client
+        .post(format!("{}/subscriptions/{}/{}", V2_BASE, sub_id, action))
+        .header(ContentType::JSON)
+        .body(body)
+        .dispatch()
+        .await;
+    let status = resp.status();
+    let body = resp.into_string().await.unwrap_or_default();
+    (status, serde_json::from_str(&body).unwrap_or(Value::Null))
+}
+
+/// First charge on the 1st of a month that is not before today, as the
+/// test helper creates subscriptions billed on day 1.
+fn next_first_of_month() -> String {
+    use chrono::Datelike;
+    let today = chrono::Utc::now().date_naive();
+    let next = if today.day() == 1 {
+        today
+    } else {
+        today.with_day(1).unwrap().checked_add_months(chrono::Months::new(1)).unwrap()
+    };
+    next.format("%Y-%m-%d").to_string()
+}
+
+async fn db_pool() -> sqlx::PgPool {
+    let url = std::env::var("DATABASE_URL").unwrap_or_else(|_| common::TEST_DB_URL.to_string());
+    sqlx::PgPool::connect(&url).await.expect("connect to test db")
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_pause_and_resume_subscription() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let cat_id = create_category(&client, "Pause Streaming", "expense").await;
+    let sub_id = create_subscription(&client, "Paused Stream", &cat_id, 999, "monthly", "2026-01-01").await;
+
+    let (status, body) = post_action(&client, &sub_id, "pause", r#"{"resumeOn": "2099-06-01"}"#).await;
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["status"], "paused");
+    assert_eq!(body["resumeOn"], "2099-06-01");
+
+    // Pausing again replaces the resume date.
+    let (status, body) = post_action(&client, &sub_id, "pause", "{}").await;
+    assert_eq!(status, Status::Ok);
+    assert!(body["resumeOn"].is_null());
+
+    // Charges missed while paused are skipped.
+    let (status, body) = post_action(&client, &sub_id, "resume", "").await;
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["status"], "active");
+    assert!(body["resumeOn"].is_null());
+    assert_eq!(body["nextChargeDate"], next_first_of_month());
+
+    // Resuming an active subscription is a no-op.
+    let (status, body) = post_action(&client, &sub_id, "resume", "").await;
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["status"], "active");
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_pause_subscription_rejections() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let cat_id = create_category(&client, "Pause Rejections", "expense").await;
+    let sub_id = create_subscription(&client, "Rejected Pause", &cat_id, 500, "monthly", "2026-04-01").await;
+
+    let (status, _) = post_action(&client, &sub_id, "pause", r#"{"resumeOn": "2020-01-01"}"#).await;
+    assert_eq!(status, Status::BadRequest);
+
+    let (status, _) = post_action(&client, "00000000-0000-0000-0000-000000000099", "pause", "{}").await;
+    assert_eq!(status, Status::NotFound);
+
+    post_action(&client, &sub_id, "cancel", "{}").await;
+    let (status, _) = post_action(&client, &sub_id, "pause", "{}").await;
+    assert_eq!(status, Status::Conflict);
+    let (status, _) = post_action(&client, &sub_id, "resume", "").await;
+    assert_eq!(status, Status::Conflict);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_pause_subscription_unauthenticated_returns_401() {
+    let client = test_client().await;
+
+    let (status, _) = post_action(&client, "00000000-0000-0000-0000-000000000001", "pause", "{}").await;
+    assert_eq!(status, Status::Unauthorized);
+    let (status, _) = post_action(&client, "00000000-0000-0000-0000-000000000001", "resume", "").await;
+    assert_eq!(status, Status::Unauthorized);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// advance-subscriptions cron job
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_advance_subscriptions_rolls_dates_and_resumes() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let cat_id = create_category(&client, "Cron Streaming", "expense").await;
+    let stale = create_subscription(&client, "Stale Charge", &cat_id, 999, "monthly", "2026-01-01").await;
+    let future = create_subscription(&client, "Future Charge", &cat_id, 999, "yearly", "2099-01-01").await;
+    let paused = create_subscription(&client, "Due To Resume", &cat_id, 999, "monthly", "2026-01-01").await;
+    let held = create_subscription(&client, "Held Paused", &cat_id, 999, "monthly", "2026-01-01").await;
+
+    post_action(&client, &paused, "pause", r#"{"resumeOn": "2099-01-01"}"#).await;
+    post_action(&client, &held, "pause", "{}").await;
+    sqlx::query("UPDATE subscription SET resume_on = '2026-01-15' WHERE id = $1")
+        .bind(uuid::Uuid::parse_str(&paused).unwrap())
+        .execute(&db_pool().await)
+        .await
+        .unwrap();
+
+    let result = piggy_pulse::advance_subscriptions(&common::test_config()).await.expect("cron run");
+    assert!(result.subscriptions_resumed >= 1);
+    assert!(result.subscriptions_advanced >= 2);
+
+    let resp = client.get(format!("{}/subscriptions", V2_BASE)).dispatch().await;
+    let list: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let find = |id: &str| list.as_array().unwrap().iter().find(|s| s["id"] == id).unwrap().clone();
+
+    assert_eq!(find(&stale)["nextChargeDate"], next_first_of_month());
+    assert_eq!(find(&future)["nextChargeDate"], "2099-01-01");
+    assert_eq!(find(&paused)["status"], "active");
+    assert!(find(&paused)["resumeOn"].is_null());
+    assert_eq!(find(&paused)["nextChargeDate"], next_first_of_month());
+    assert_eq!(find(&held)["status"], "paused");
+    assert_eq!(find(&held)["nextChargeDate"], "2026-01-01");
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Billing history
+// ═══════════════════════════════════════════════════════════════════════════════
+
+async fn billing_history(client: &rocket::local::asynchronous::Client, sub_id: &str) -> Vec<Value> {
+    let resp = client.get(format!("{}/subscriptions/{}", V2_BASE, sub_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(body["id"], sub_id);
+    body["billingHistory"].as_array().unwrap().clone()
+}
+
+fn subscription_payment(account_id: &str, category_id: &str, amount: i64, date: &str, sub_id: &str) -> Value {
+    serde_json::json!({
+        "transactionType": "Regular",
+        "date": date,
+        "description": "Subscription charge",
+        "amount": amount,
+        "fromAccountId": account_id,
+        "categoryId": category_id,
+        "vendorId": null,
+        "subscriptionId": sub_id
+    })
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_transaction_with_subscription_records_billing_event() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = create_account(&client, "Billing Checking", 100_000).await;
+    let cat_id = create_category(&client, "Billing Streaming", "expense").await;
+    let sub_id = create_subscription(&client, "Billed Stream", &cat_id, 1499, "monthly", "2026-03-01").await;
+
+    assert!(billing_history(&client, &sub_id).await.is_empty());
+
+    let resp = client
+        .post(format!("{}/transactions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(subscription_payment(&account_id, &cat_id, 1499, "2026-03-01", &sub_id).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let created: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let tx_id = created["id"].as_str().unwrap();
+
+    let history = billing_history(&client, &sub_id).await;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0]["transactionId"], tx_id);
+    assert_eq!(history[0]["subscriptionId"], sub_id.as_str());
+    assert_eq!(history[0]["billingDate"], "2026-03-01");
+    assert_eq!(history[0]["detected"], false);
+    assert_eq!(decrypt_i64(history[0]["amountEnc"].as_str().unwrap()), 1499);
+
+    // A correction carries over to the event.
+    let mut payload = subscription_payment(&account_id, &cat_id, 1599, "2026-03-02", &sub_id);
+    payload.as_object_mut().unwrap().remove("subscriptionId");
+    let resp = client
+        .put(format!("{}/transactions/{}", V2_BASE, tx_id))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+
+    let history = billing_history(&client, &sub_id).await;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0]["billingDate"], "2026-03-02");
+    assert_eq!(decrypt_i64(history[0]["amountEnc"].as_str().unwrap()), 1599);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_link_and_unlink_billing_transaction() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = create_account(&client, "Link Checking", 100_000).await;
+    let cat_id = create_category(&client, "Link Streaming", "expense").await;
+    let sub_id = create_subscription(&client, "Linked Stream", &cat_id, 999, "monthly", "2026-03-01").await;
+    // Booked elsewhere, so they are not matched to the subscription on create.
+    let other_cat = create_category(&client, "Link Misc", "expense").await;
+    let march = create_transaction(&client, &account_id, &other_cat, 999, "2026-03-01").await;
+    let april = create_transaction(&client, &account_id, &other_cat, 1099, "2026-04-01").await;
+
+    let link = |tx_id: String| {
+        let client = &client;
+        let sub_id = &sub_id;
+        async move { post_action(client, sub_id, "transactions", &serde_json::json!({ "transactionId": tx_id }).to_string()).await }
+    };
+
+    let (status, event) = link(march.clone()).await;
+    assert_eq!(status, Status::Created);
+    assert_eq!(event["transactionId"], march.as_str());
+    assert_eq!(event["billingDate"], "2026-03-01");
+    assert_eq!(decrypt_i64(event["amountEnc"].as_str().unwrap()), 999);
+    let (status, _) = link(april.clone()).await;
+    assert_eq!(status, Status::Created);
+
+    let (status, _) = link(march.clone()).await;
+    assert_eq!(status, Status::Conflict);
+    let (status, _) = link(uuid::Uuid::new_v4().to_string()).await;
+    assert_eq!(status, Status::NotFound);
+
+    // Newest first.
+    let history = billing_history(&client, &sub_id).await;
+    let linked: Vec<&str> = history.iter().map(|e| e["transactionId"].as_str().unwrap()).collect();
+    assert_eq!(linked, [april.as_str(), march.as_str()]);
+
+    let resp = client
+        .delete(format!("{}/subscriptions/{}/transactions/{}", V2_BASE, sub_id, march))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::NoContent);
+    let resp = client
+        .delete(format!("{}/subscriptions/{}/transactions/{}", V2_BASE, sub_id, march))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::NotFound);
+    assert_eq!(billing_history(&client, &sub_id).await.len(), 1);
+
+    // Voided transactions cannot be billed.
+    let resp = client.delete(format!("{}/transactions/{}", V2_BASE, march)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+    let (status, _) = link(march.clone()).await;
+    assert_eq!(status, Status::Conflict);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_billing_history_is_per_user() {
+    let owner = test_client().await;
+    create_user_and_login(&owner).await;
+    let owner_cat = create_category(&owner, "Owner Streaming", "expense").await;
+    let sub_id = create_subscription(&owner, "Owner Stream", &owner_cat, 999, "monthly", "2026-03-01").await;
+
+    let other = test_client().await;
+    create_user_and_login(&other).await;
+    let account_id = create_account(&other, "Other Checking", 100_000).await;
+    let cat_id = create_category(&other, "Other Streaming", "expense").await;
+    let tx_id = create_transaction(&other, &account_id, &cat_id, 999, "2026-03-01").await;
+
+    let resp = other.get(format!("{}/subscriptions/{}", V2_BASE, sub_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::NotFound);
+
+    let resp = other
+        .post(format!("{}/transactions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(subscription_payment(&account_id, &cat_id, 999, "2026-03-01", &sub_id).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::BadRequest);
+
+    let (status, _) = post_action(&other, &sub_id, "transactions", &serde_json::json!({ "transactionId": tx_id }).to_string()).await;
+    assert_eq!(status, Status::NotFound);
+    let (status, _) = post_action(&owner, &sub_id, "transactions", &serde_json::json!({ "transactionId": tx_id }).to_string()).await;
+    assert_eq!(status, Status::NotFound);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_plaintext_billing_event_amount_is_encrypted_on_read() {
+    let client = test_client().await;
+    let (user_id, _) = create_user_and_login(&client).await;
+    let cat_id = create_category(&client, "Legacy Streaming", "expense").await;
+    let sub_id = create_subscription(&client, "Legacy Stream", &cat_id, 799, "monthly", "2026-03-01").await;
+
+    let pool = db_pool().await;
+    let event_id: uuid::Uuid =
+        sqlx::query_scalar("INSERT INTO subscription_billing_event (subscription_id, user_id, amount, date) VALUES ($1, $2, 799, '2026-02-01') RETURNING id")
+            .bind(uuid::Uuid::parse_str(&sub_id).unwrap())
+            .bind(uuid::Uuid::parse_str(&user_id).unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+    let history = billing_history(&client, &sub_id).await;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0]["id"], event_id.to_string());
+    assert!(history[0]["transactionId"].is_null());
+    assert_eq!(decrypt_i64(history[0]["amountEnc"].as_str().unwrap()), 799);
+
+    let amount: Option<i64> = sqlx::query_scalar("SELECT amount FROM subscription_billing_event WHERE id = $1")
+        .bind(event_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert!(amount.is_none());
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Charge detection and /subscriptions/alerts
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_matching_transaction_is_detected_as_billing_event() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = create_account(&client, "Detect Checking", 100_000).await;
+    let cat_id = create_category(&client, "Detect Streaming", "expense").await;
+    let vendor_id = create_vendor(&client, "Detect Vendor").await;
+    let payload = serde_json::json!({
+        "name": "Detected Stream",
+        "categoryId": cat_id,
+        "vendorId": vendor_id,
+        "billingAmount": 1000,
+        "billingCycle": "monthly",
+        "billingDay": 1,
+        "nextChargeDate": "2026-03-01"
+    });
+    let resp = client
+        .post(format!("{}/subscriptions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let sub: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let sub_id = sub["id"].as_str().unwrap();
+
+    let matched = create_transaction_with_vendor(&client, &account_id, &cat_id, 1050, "2026-03-01", &vendor_id).await;
+    // Too far off the billing amount, and missing the vendor.
+    create_transaction_with_vendor(&client, &account_id, &cat_id, 1200, "2026-03-02", &vendor_id).await;
+    create_transaction(&client, &account_id, &cat_id, 1000, "2026-03-03").await;
+
+    let history = billing_history(&client, sub_id).await;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0]["transactionId"], matched.as_str());
+    assert_eq!(history[0]["detected"], true);
+    assert_eq!(decrypt_i64(history[0]["amountEnc"].as_str().unwrap()), 1050);
+
+    // Once billed, a correction does not match the transaction again.
+    let resp = client
+        .put(format!("{}/transactions/{}", V2_BASE, matched))
+        .header(ContentType::JSON)
+        .body(
+            serde_json::json!({
+                "transactionType": "Regular",
+                "date": "2026-03-01",
+                "description": "Test transaction",
+                "amount": 990,
+                "fromAccountId": account_id,
+                "categoryId": cat_id,
+                "vendorId": vendor_id
+            })
+            .to_string(),
+        )
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let history = billing_history(&client, sub_id).await;
+    assert_eq!(history.len(), 1);
+    assert_eq!(decrypt_i64(history[0]["amountEnc"].as_str().unwrap()), 990);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_charge_after_cancellation_is_flagged() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = create_account(&client, "Alert Checking", 100_000).await;
+    let cat_id = create_category(&client, "Alert Streaming", "expense").await;
+    let sub_id = create_subscription(&client, "Cancelled Stream", &cat_id, 500, "monthly", "2026-02-01").await;
+    let (status, _) = post_action(&client, &sub_id, "cancel", r#"{"cancellationDate": "2026-03-01"}"#).await;
+    assert_eq!(status, Status::Ok);
+
+    let charge = |amount: i64, date: &'static str| {
+        let client = &client;
+        let payload = serde_json::json!({
+            "transactionType": "Regular",
+            "date": date,
+            "description": "Streaming charge",
+            "amount": amount,
+            "fromAccountId": account_id,
+            "categoryId": cat_id,
+            "vendorId": null
+        });
+        async move {
+            let resp = client
+                .post(format!("{}/transactions", V2_BASE))
+                .header(ContentType::JSON)
+                .body(payload.to_string())
+                .dispatch()
+                .await;
+            assert_eq!(resp.status(), Status::Created);
+            serde_json::from_str::<Value>(&resp.into_string().await.unwrap()).unwrap()
+        }
+    };
+
+    let before = charge(500, "2026-02-01").await;
+    assert_eq!(before["chargedAfterCancellation"], false);
+    let after = charge(500, "2026-03-05").await;
+    assert_eq!(after["chargedAfterCancellation"], true);
+    let after_id = after["id"].as_str().unwrap();
+
+    let resp = client.get(format!("{}/transactions/page?accountId={}", V2_BASE, account_id)).dispatch().await;
+    let page: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let flagged: Vec<&str> = page["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|t| t["chargedAfterCancellation"] == true)
+        .map(|t| t["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(flagged, [after_id]);
+
+    let resp = client.get(format!("{}/subscriptions/alerts", V2_BASE)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    let alerts: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let alerts = alerts.as_array().unwrap();
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0]["subscriptionId"], sub_id.as_str());
+    assert_eq!(alerts[0]["transactionId"], after_id);
+    assert_eq!(alerts[0]["billingDate"], "2026-03-05");
+    assert_eq!(alerts[0]["detected"], true);
+    assert!(alerts[0]["cancelledAt"].as_str().unwrap().starts_with("2026-03-01"));
+
+    // Deleting the charge clears the alert.
+    let resp = client.delete(format!("{}/transactions/{}", V2_BASE, after_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+    let resp = client.get(format!("{}/subscriptions/alerts", V2_BASE)).dispatch().await;
+    let alerts: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert!(alerts.as_array().unwrap().is_empty());
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Auto-post on unlock
+// ═══════════════════════════════════════════════════════════════════════════════
+
+fn first_of_month(months_from_now: i32) -> chrono::NaiveDate {
+    use chrono::Datelike;
+    let first = chrono::Utc::now().date_naive().with_day(1).unwrap();
+    if months_from_now >= 0 {
+        first.checked_add_months(chrono::Months::new(months_from_now as u32)).unwrap()
+    } else {
+        first.checked_sub_months(chrono::Months::new(months_from_now.unsigned_abs())).unwrap()
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_unlock_posts_due_auto_post_charges() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = create_account(&client, "Auto Post Checking", 100_000).await;
+    let cat_id = create_category(&client, "Auto Post Streaming", "expense").await;
+    let first_charge = first_of_month(-2);
+
+    let payload = serde_json::json!({
+        "name": "Auto Stream",
+        "categoryId": cat_id,
+        "vendorId": null,
+        "billingAmount": 1299,
+        "billingCycle": "monthly",
+        "billingDay": 1,
+        "nextChargeDate": first_charge.to_string(),
+        "autoPostAccountId": account_id
+    });
+    let resp = client
+        .post(format!("{}/subscriptions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(body["autoPostAccountId"], account_id.as_str());
+    assert!(body["lastPostedOn"].is_null());
+    let sub_id = body["id"].as_str().unwrap().to_string();
+
+    // The cron job leaves unposted charges for the next unlock.
+    piggy_pulse::advance_subscriptions(&common::test_config()).await.expect("cron run");
+
+    let posted = unlock_session(&client).await;
+    let posted = posted["postedCharges"].as_array().unwrap();
+    let expected_dates: Vec<String> = (-2..=0).map(|m| first_of_month(m).to_string()).collect();
+    let dates: Vec<&str> = posted.iter().map(|e| e["billingDate"].as_str().unwrap()).collect();
+    assert_eq!(dates, expected_dates);
+    for event in posted {
+        assert_eq!(event["subscriptionId"], sub_id.as_str());
+        assert_eq!(event["detected"], false);
+        assert_eq!(decrypt_i64(event["amountEnc"].as_str().unwrap()), 1299);
+    }
+
+    let history = billing_history(&client, &sub_id).await;
+    assert_eq!(history.len(), 3);
+
+    let resp = client.get(format!("{}/transactions/page?accountId={}", V2_BASE, account_id)).dispatch().await;
+    let page: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let transactions = page["data"].as_array().unwrap();
+    assert_eq!(transactions.len(), 3);
+    for tx in transactions {
+        assert!(posted.iter().any(|e| e["transactionId"] == tx["id"]));
+        assert_eq!(decrypt_string(tx["descriptionEnc"].as_str().unwrap()), "Auto Stream");
+    }
+
+    let resp = client.get(format!("{}/subscriptions", V2_BASE)).dispatch().await;
+    let list: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let sub = list.as_array().unwrap().iter().find(|s| s["id"] == sub_id.as_str()).unwrap();
+    assert_eq!(sub["nextChargeDate"], first_of_month(1).to_string());
+    assert_eq!(sub["lastPostedOn"], first_of_month(0).to_string());
+
+    // Nothing is due on the next unlock.
+    let again = unlock_session(&client).await;
+    assert!(again["postedCharges"].as_array().unwrap().is_empty());
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_concurrent_unlocks_post_each_charge_once() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = create_account(&client, "Race Checking", 100_000).await;
+    let cat_id = create_category(&client, "Race Streaming", "expense").await;
+
+    let payload = serde_json::json!({
+        "name": "Race Stream",
+        "categoryId": cat_id,
+        "vendorId": null,
+        "billingAmount": 500,
+        "billingCycle": "monthly",
+        "billingDay": 1,
+        "nextChargeDate": first_of_month(-1).to_string(),
+        "autoPostAccountId": account_id
+    });
+    let resp = client
+        .post(format!("{}/subscriptions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let sub_id = body["id"].as_str().unwrap().to_string();
+
+    let (first, second) = rocket::futures::future::join(unlock_session(&client), unlock_session(&client)).await;
+    let posted = first["postedCharges"].as_array().unwrap().len() + second["postedCharges"].as_array().unwrap().len();
+    assert_eq!(posted, 2);
+    assert_eq!(billing_history(&client, &sub_id).await.len(), 2);
+
+    let resp = client.get(format!("{}/transactions/page?accountId={}", V2_BASE, account_id)).dispatch().await;
+    let page: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(page["data"].as_array().unwrap().len(), 2);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_unlock_skips_subscriptions_without_auto_post() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = create_account(&client, "Manual Checking", 100_000).await;
+    let cat_id = create_category(&client, "Manual Streaming", "expense").await;
+    let sub_id = create_subscription(&client, "Manual Stream", &cat_id, 800, "monthly", &first_of_month(-1).to_string()).await;
+
+    let posted = unlock_session(&client).await;
+    assert!(posted["postedCharges"].as_array().unwrap().is_empty());
+    assert!(billing_history(&client, &sub_id).await.is_empty());
+
+    let resp = client.get(format!("{}/transactions/page?accountId={}", V2_BASE, account_id)).dispatch().await;
+    let page: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert!(page["data"].as_array().unwrap().is_empty());
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_auto_post_account_must_belong_to_user() {
+    let other = test_client().await;
+    create_user_and_login(&other).await;
+    let foreign_account = create_account(&other, "Foreign Checking", 100_000).await;
+
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let cat_id = create_category(&client, "Foreign Streaming", "expense").await;
+    let sub_id = create_subscription(&client, "Foreign Stream", &cat_id, 800, "monthly", "2099-01-01").await;
+
+    let payload = serde_json::json!({
+        "name": "Foreign Stream",
+        "categoryId": cat_id,
+        "vendorId": null,
+        "billingAmount": 800,
+        "billingCycle": "monthly",
+        "billingDay": 1,
+        "nextChargeDate": "2099-01-01",
+        "autoPostAccountId": foreign_account
+    });
+    let resp = client
+        .post(format!("{}/subscriptions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::BadRequest);
+
+    let resp = client
+        .put(format!("{}/subscriptions/{}", V2_BASE, sub_id))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::BadRequest);
+}
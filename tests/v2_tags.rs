@@ -0,0 +1,277 @@
+mod common;
+
+use common::auth::create_user_and_login;
+use common::crypto::decrypt_string;
+use common::entities::{create_account, create_category, create_tag, create_transaction};
+use common::{V2_BASE, test_client};
+use rocket::http::{ContentType, Status};
+use rocket::local::asynchronous::Client;
+use serde_json::Value;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Helpers
+// ═══════════════════════════════════════════════════════════════════════════════
+
+async fn post_transaction(client: &Client, payload: &Value) -> Value {
+    let resp = client
+        .post(format!("{}/transactions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    serde_json::from_str(&resp.into_string().await.unwrap()).unwrap()
+}
+
+async fn page_ids(client: &Client, query: &str) -> Vec<String> {
+    let resp = client.get(format!("{}/transactions/page?{}", V2_BASE, query)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    body["data"].as_array().unwrap().iter().map(|t| t["id"].as_str().unwrap().to_string()).collect()
+}
+
+fn regular(account_id: &str, category_id: &str, amount: i64, date: &str) -> Value {
+    serde_json::json!({
+        "transactionType": "Regular",
+        "date": date,
+        "description": "Tagged purchase",
+        "amount": amount,
+        "fromAccountId": account_id,
+        "categoryId": category_id,
+        "vendorId": null
+    })
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// /tags CRUD
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_create_and_list_tags() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+
+    let resp = client
+        .post(format!("{}/tags", V2_BASE))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "name": "Holiday", "color": "#FAB005" }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(decrypt_string(body["nameEnc"].as_str().unwrap()), "Holiday");
+    assert_eq!(decrypt_string(body["colorEnc"].as_str().unwrap()), "#FAB005");
+    common::assertions::assert_uuid(&body["id"]);
+
+    create_tag(&client, "Business").await;
+
+    let resp = client.get(format!("{}/tags", V2_BASE)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    let list: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(list["totalCount"], 2);
+    let names: Vec<String> = list["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| decrypt_string(t["nameEnc"].as_str().unwrap()))
+        .collect();
+    assert_eq!(names, ["Holiday", "Business"]);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_create_tag_empty_name_rejected() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+
+    let resp = client
+        .post(format!("{}/tags", V2_BASE))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "name": "", "color": "#FAB005" }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::BadRequest);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_create_tag_duplicate_name_conflicts() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    create_tag(&client, "Reimbursable").await;
+
+    let resp = client
+        .post(format!("{}/tags", V2_BASE))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "name": "reimbursable", "color": "#000000" }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Conflict);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_update_tag() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let tag_id = create_tag(&client, "Trip").await;
+
+    let resp = client
+        .put(format!("{}/tags/{}", V2_BASE, tag_id))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "name": "Road trip", "color": "#40C057" }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(body["id"], tag_id.as_str());
+    assert_eq!(decrypt_string(body["nameEnc"].as_str().unwrap()), "Road trip");
+    assert_eq!(decrypt_string(body["colorEnc"].as_str().unwrap()), "#40C057");
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_update_tag_not_found() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+
+    let resp = client
+        .put(format!("{}/tags/{}", V2_BASE, uuid::Uuid::new_v4()))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "name": "Ghost", "color": "#000000" }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_delete_tag_unlinks_transactions() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = create_account(&client, "Tag Delete Checking", 100_000).await;
+    let category_id = create_category(&client, "Tag Delete Groceries", "expense").await;
+    let tag_id = create_tag(&client, "Temporary").await;
+
+    let mut payload = regular(&account_id, &category_id, 1500, "2026-03-05");
+    payload["tagIds"] = serde_json::json!([tag_id]);
+    let created = post_transaction(&client, &payload).await;
+
+    let resp = client.delete(format!("{}/tags/{}", V2_BASE, tag_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+
+    let resp = client.get(format!("{}/transactions/page?accountId={}", V2_BASE, account_id)).dispatch().await;
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(body["data"][0]["id"], created["id"]);
+    assert_eq!(body["data"][0]["tagIds"], serde_json::json!([]));
+
+    let resp = client.delete(format!("{}/tags/{}", V2_BASE, tag_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_tags_are_per_user() {
+    let owner = test_client().await;
+    create_user_and_login(&owner).await;
+    let tag_id = create_tag(&owner, "Private").await;
+
+    let other = test_client().await;
+    create_user_and_login(&other).await;
+    let account_id = create_account(&other, "Other Checking", 100_000).await;
+    let category_id = create_category(&other, "Other Groceries", "expense").await;
+
+    let resp = other.delete(format!("{}/tags/{}", V2_BASE, tag_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::NotFound);
+
+    let mut payload = regular(&account_id, &category_id, 1500, "2026-03-05");
+    payload["tagIds"] = serde_json::json!([tag_id]);
+    let resp = other
+        .post(format!("{}/transactions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::BadRequest);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Tags on transactions
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_tags_survive_correction_and_can_be_replaced() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = create_account(&client, "Tag Edit Checking", 100_000).await;
+    let category_id = create_category(&client, "Tag Edit Groceries", "expense").await;
+    let holiday = create_tag(&client, "Edit Holiday").await;
+    let work = create_tag(&client, "Edit Work").await;
+
+    let mut payload = regular(&account_id, &category_id, 2000, "2026-03-05");
+    payload["tagIds"] = serde_json::json!([holiday, holiday]);
+    let created = post_transaction(&client, &payload).await;
+    assert_eq!(created["tagIds"], serde_json::json!([holiday]));
+    let id = created["id"].as_str().unwrap();
+
+    // An update without tagIds keeps the tags.
+    let resp = client
+        .put(format!("{}/transactions/{}", V2_BASE, id))
+        .header(ContentType::JSON)
+        .body(regular(&account_id, &category_id, 2500, "2026-03-06").to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let updated: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(updated["tagIds"], serde_json::json!([holiday]));
+
+    // One with tagIds replaces them.
+    let mut payload = regular(&account_id, &category_id, 2500, "2026-03-06");
+    payload["tagIds"] = serde_json::json!([work]);
+    let resp = client
+        .put(format!("{}/transactions/{}", V2_BASE, id))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let updated: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(updated["tagIds"], serde_json::json!([work]));
+
+    // Delete and restore keep them too.
+    let resp = client.delete(format!("{}/transactions/{}", V2_BASE, id)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+    let resp = client.post(format!("{}/transactions/{}/restore", V2_BASE, id)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    let restored: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(restored["tagIds"], serde_json::json!([work]));
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_page_filters_by_tag() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = create_account(&client, "Tag Filter Checking", 100_000).await;
+    let category_id = create_category(&client, "Tag Filter Groceries", "expense").await;
+    let holiday = create_tag(&client, "Filter Holiday").await;
+    let unused = create_tag(&client, "Filter Unused").await;
+
+    let mut payload = regular(&account_id, &category_id, 1000, "2026-03-05");
+    payload["tagIds"] = serde_json::json!([holiday]);
+    let tagged = post_transaction(&client, &payload).await;
+    create_transaction(&client, &account_id, &category_id, 2000, "2026-03-06").await;
+
+    let ids = page_ids(&client, &format!("tagId={}", holiday)).await;
+    assert_eq!(ids, [tagged["id"].as_str().unwrap()]);
+
+    let ids = page_ids(&client, &format!("tagId={}", unused)).await;
+    assert!(ids.is_empty());
+
+    assert_eq!(page_ids(&client, &format!("accountId={}", account_id)).await.len(), 2);
+
+    let resp = client.get(format!("{}/transactions/page?tagId=not-a-uuid", V2_BASE)).dispatch().await;
+    assert_eq!(resp.status(), Status::BadRequest);
+}
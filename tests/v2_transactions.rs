@@ -315,9 +315,7 @@ async fn test_create_transaction_unauthenticated_returns_401() {
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // GET /transactions — list (plain array of EncryptedTransactionResponse)
-// Filter query params (accountId, categoryId, vendorId, direction, limit,
-// cursor, fromDate, toDate) are not yet wired in the encrypted service layer.
-// Only periodId filtering is active.
+// Only periodId filtering; filtered, paginated access is GET /transactions/page.
 // ═══════════════════════════════════════════════════════════════════════════════
 
 #[rocket::async_test]
@@ -374,6 +372,188 @@ async fn test_list_transactions_empty_period_returns_empty() {
     assert!(data.is_empty(), "empty period should return empty array");
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// GET /transactions/page — filtered, cursor-paginated list
+// ═══════════════════════════════════════════════════════════════════════════════
+
+async fn get_tx_page(client: &rocket::local::asynchronous::Client, query: &str) -> Value {
+    let resp = client.get(format!("{}/transactions/page{}", V2_BASE, query)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok, "GET /transactions/page{} failed", query);
+    serde_json::from_str(&resp.into_string().await.unwrap()).unwrap()
+}
+
+fn page_ids(page: &Value) -> Vec<String> {
+    page["data"].as_array().unwrap().iter().map(|t| t["id"].as_str().unwrap().to_string()).collect()
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_page_walks_newest_first_with_cursor() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "Page Acct", 100_000).await;
+    let category_id = common::entities::create_category(&client, "Page Cat", "expense").await;
+    for (amount, date) in [
+        (100, "2026-03-01"),
+        (200, "2026-03-02"),
+        (300, "2026-03-02"),
+        (400, "2026-03-03"),
+        (500, "2026-03-04"),
+    ] {
+        common::entities::create_transaction(&client, &account_id, &category_id, amount, date).await;
+    }
+
+    let mut seen = Vec::new();
+    let mut dates = Vec::new();
+    let mut cursor = String::new();
+    for expected_len in [2, 2, 1] {
+        let page = get_tx_page(&client, &format!("?limit=2{}", cursor)).await;
+        assert_eq!(page["totalCount"], 5);
+        assert_eq!(page["data"].as_array().unwrap().len(), expected_len);
+        assert_eq!(page["hasMore"], expected_len == 2);
+        seen.extend(page_ids(&page));
+        dates.extend(page["data"].as_array().unwrap().iter().map(|t| t["date"].as_str().unwrap().to_string()));
+        cursor = match page["nextCursor"].as_str() {
+            Some(next) => format!("&cursor={}", next),
+            None => String::new(),
+        };
+    }
+    assert!(cursor.is_empty(), "last page has no cursor");
+
+    let mut unique = seen.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), 5, "no transaction repeats across pages");
+    assert_eq!(dates, ["2026-03-04", "2026-03-03", "2026-03-02", "2026-03-02", "2026-03-01"]);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_page_filters_by_account_category_vendor_and_direction() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let checking = common::entities::create_account(&client, "Page Filter Checking", 100_000).await;
+    let savings = common::entities::create_account(&client, "Page Filter Savings", 0).await;
+    let groceries = common::entities::create_category(&client, "Page Filter Groceries", "expense").await;
+    let household = common::entities::create_category(&client, "Page Filter Household", "expense").await;
+    let salary = common::entities::create_category(&client, "Page Filter Salary", "income").await;
+    let moves = common::entities::create_category(&client, "Page Filter Moves", "transfer").await;
+    let vendor_id = common::entities::create_vendor(&client, "Page Filter Market").await;
+
+    let shop = common::entities::create_transaction_with_vendor(&client, &checking, &groceries, 1000, "2026-03-05", &vendor_id).await;
+    let pay = common::entities::create_transaction(&client, &checking, &salary, 50_000, "2026-03-06").await;
+    let payload = serde_json::json!({
+        "transactionType": "Transfer",
+        "date": "2026-03-07",
+        "description": "To savings",
+        "amount": 5000,
+        "fromAccountId": checking,
+        "categoryId": moves,
+        "vendorId": null,
+        "toAccountId": savings
+    });
+    let resp = client
+        .post(format!("{}/transactions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let transfer: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let transfer = transfer["id"].as_str().unwrap().to_string();
+    let payload = split_payload(
+        &checking,
+        &groceries,
+        3000,
+        serde_json::json!([
+            { "categoryId": groceries, "amount": 2000 },
+            { "categoryId": household, "amount": 1000 }
+        ]),
+    );
+    let resp = client
+        .post(format!("{}/transactions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let split: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let split = split["id"].as_str().unwrap().to_string();
+
+    let page = get_tx_page(&client, &format!("?accountId={}", savings)).await;
+    assert_eq!(page_ids(&page), [transfer.as_str()], "to-side of a transfer matches");
+    assert_eq!(page["totalCount"], 1);
+
+    let page = get_tx_page(&client, &format!("?categoryId={}", household)).await;
+    assert_eq!(page_ids(&page), [split.as_str()], "split lines match their category");
+
+    let page = get_tx_page(&client, &format!("?vendorId={}", vendor_id)).await;
+    assert_eq!(page_ids(&page), [shop.as_str()]);
+
+    let page = get_tx_page(&client, "?direction=income").await;
+    assert_eq!(page_ids(&page), [pay]);
+
+    let page = get_tx_page(&client, &format!("?accountId={}&direction=expense&from=2026-03-06", checking)).await;
+    assert_eq!(page_ids(&page), [split]);
+
+    let page = get_tx_page(&client, "?uncategorized=true").await;
+    assert!(page_ids(&page).is_empty());
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_page_scopes_to_period() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "Page Period Acct", 100_000).await;
+    let category_id = common::entities::create_category(&client, "Page Period Cat", "expense").await;
+    let period_id = common::entities::create_period(&client, "2026-03-01", "2026-03-31").await;
+    common::entities::create_transaction(&client, &account_id, &category_id, 100, "2026-02-28").await;
+    let inside = common::entities::create_transaction(&client, &account_id, &category_id, 200, "2026-03-01").await;
+
+    let page = get_tx_page(&client, &format!("?periodId={}", period_id)).await;
+    assert_eq!(page_ids(&page), [inside]);
+
+    let resp = client
+        .get(format!("{}/transactions/page?periodId={}", V2_BASE, Uuid::new_v4()))
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_page_rejects_bad_parameters() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let id = Uuid::new_v4();
+
+    for query in [
+        "?accountId=nope".to_string(),
+        "?cursor=nope".to_string(),
+        "?direction=sideways".to_string(),
+        "?from=03/01/2026".to_string(),
+        "?from=2026-03-02&to=2026-03-01".to_string(),
+        "?uncategorized=maybe".to_string(),
+        "?limit=0".to_string(),
+        format!("?uncategorized=true&categoryId={}", id),
+        format!("?periodId={}&from=2026-03-01", id),
+    ] {
+        let resp = client.get(format!("{}/transactions/page{}", V2_BASE, query)).dispatch().await;
+        assert_eq!(resp.status(), Status::BadRequest, "{query}");
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_page_unauthenticated_returns_401() {
+    let client = test_client().await;
+
+    let resp = client.get(format!("{}/transactions/page", V2_BASE)).dispatch().await;
+
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // PUT /transactions/{id} — update
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -552,6 +732,359 @@ async fn test_delete_unauthenticated_returns_401() {
     assert_eq!(resp.status(), Status::Unauthorized);
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// Split transactions
+// ═══════════════════════════════════════════════════════════════════════════════
+
+fn split_payload(account_id: &str, category_id: &str, amount: i64, splits: Value) -> Value {
+    serde_json::json!({
+        "transactionType": "Regular",
+        "date": "2026-03-10",
+        "description": "Supermarket run",
+        "amount": amount,
+        "fromAccountId": account_id,
+        "categoryId": category_id,
+        "vendorId": null,
+        "splits": splits
+    })
+}
+
+fn split_amounts(body: &Value) -> Vec<(String, i64)> {
+    body["splits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| (s["categoryId"].as_str().unwrap().to_string(), decrypt_i64(s["amountEnc"].as_str().unwrap())))
+        .collect()
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_create_split_transaction_returns_lines() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "Split Acct", 100_000).await;
+    let groceries = common::entities::create_category(&client, "Split Groceries", "expense").await;
+    let household = common::entities::create_category(&client, "Split Household", "expense").await;
+
+    let payload = split_payload(
+        &account_id,
+        &groceries,
+        5000,
+        serde_json::json!([
+            { "categoryId": groceries, "amount": 3000, "memo": "Food" },
+            { "categoryId": household, "amount": 2000 }
+        ]),
+    );
+    let resp = client
+        .post(format!("{}/transactions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(decrypt_i64(body["amountEnc"].as_str().unwrap()), 5000);
+    assert_eq!(split_amounts(&body), [(groceries.clone(), 3000), (household.clone(), 2000)]);
+    assert_eq!(decrypt_string(body["splits"][0]["memoEnc"].as_str().unwrap()), "Food");
+    assert!(body["splits"][1]["memoEnc"].is_null());
+    assert_eq!(account_balance(&client, &account_id).await, 95_000, "balance moves once for the total");
+
+    let listed = get_tx_list(&client, &format!("{}/transactions/range?from=2026-03-01&to=2026-03-31", V2_BASE)).await;
+    let listed = listed.iter().find(|t| t["id"] == body["id"]).unwrap();
+    assert_eq!(split_amounts(listed), [(groceries, 3000), (household, 2000)]);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_split_lines_follow_corrections_voids_and_restores() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "Split Edit Acct", 100_000).await;
+    let groceries = common::entities::create_category(&client, "Split Edit Groceries", "expense").await;
+    let household = common::entities::create_category(&client, "Split Edit Household", "expense").await;
+
+    let payload = split_payload(
+        &account_id,
+        &groceries,
+        5000,
+        serde_json::json!([
+            { "categoryId": groceries, "amount": 3000 },
+            { "categoryId": household, "amount": 2000 }
+        ]),
+    );
+    let resp = client
+        .post(format!("{}/transactions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    let tx_id = body["id"].as_str().unwrap().to_string();
+
+    let payload = split_payload(
+        &account_id,
+        &groceries,
+        6000,
+        serde_json::json!([
+            { "categoryId": groceries, "amount": 1000 },
+            { "categoryId": household, "amount": 5000, "memo": "Detergent" }
+        ]),
+    );
+    let resp = client
+        .put(format!("{}/transactions/{}", V2_BASE, tx_id))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(split_amounts(&body), [(groceries.clone(), 1000), (household.clone(), 5000)]);
+    assert_eq!(account_balance(&client, &account_id).await, 94_000);
+
+    let resp = client.delete(format!("{}/transactions/{}", V2_BASE, tx_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+    let resp = client.post(format!("{}/transactions/{}/restore", V2_BASE, tx_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(split_amounts(&body), [(groceries.clone(), 1000), (household.clone(), 5000)]);
+    assert_eq!(decrypt_string(body["splits"][1]["memoEnc"].as_str().unwrap()), "Detergent");
+    assert_eq!(account_balance(&client, &account_id).await, 94_000);
+
+    // Removing the lines on a later correction leaves an unsplit transaction.
+    let payload = split_payload(&account_id, &groceries, 6000, serde_json::json!([]));
+    let resp = client
+        .put(format!("{}/transactions/{}", V2_BASE, tx_id))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert!(body["splits"].as_array().unwrap().is_empty());
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_split_validation_returns_400() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "Split Bad Acct", 100_000).await;
+    let groceries = common::entities::create_category(&client, "Split Bad Groceries", "expense").await;
+    let household = common::entities::create_category(&client, "Split Bad Household", "expense").await;
+    let salary = common::entities::create_category(&client, "Split Bad Salary", "income").await;
+
+    let other = test_client().await;
+    create_user_and_login(&other).await;
+    let foreign = common::entities::create_category(&other, "Split Foreign", "expense").await;
+
+    for splits in [
+        serde_json::json!([{ "categoryId": groceries, "amount": 3000 }, { "categoryId": household, "amount": 1000 }]),
+        serde_json::json!([{ "categoryId": groceries, "amount": 5000 }]),
+        serde_json::json!([{ "categoryId": groceries, "amount": 5000 }, { "categoryId": household, "amount": 0 }]),
+        serde_json::json!([{ "categoryId": groceries, "amount": 3000 }, { "categoryId": salary, "amount": 2000 }]),
+        serde_json::json!([{ "categoryId": groceries, "amount": 3000 }, { "categoryId": foreign, "amount": 2000 }]),
+        // Over the line cap, even though the 101 lines sum to the amount.
+        serde_json::Value::Array(
+            std::iter::once(serde_json::json!({ "categoryId": household, "amount": 100 }))
+                .chain(std::iter::repeat_n(serde_json::json!({ "categoryId": groceries, "amount": 49 }), 100))
+                .collect(),
+        ),
+    ] {
+        let payload = split_payload(&account_id, &groceries, 5000, splits.clone());
+        let resp = client
+            .post(format!("{}/transactions", V2_BASE))
+            .header(ContentType::JSON)
+            .body(payload.to_string())
+            .dispatch()
+            .await;
+        assert_eq!(resp.status(), Status::BadRequest, "{splits}");
+    }
+    assert_eq!(account_balance(&client, &account_id).await, 100_000);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_delete_category_used_by_split_line_returns_409() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "Split Cat Acct", 100_000).await;
+    let groceries = common::entities::create_category(&client, "Split Cat Groceries", "expense").await;
+    let household = common::entities::create_category(&client, "Split Cat Household", "expense").await;
+
+    let payload = split_payload(
+        &account_id,
+        &groceries,
+        5000,
+        serde_json::json!([
+            { "categoryId": groceries, "amount": 3000 },
+            { "categoryId": household, "amount": 2000 }
+        ]),
+    );
+    let resp = client
+        .post(format!("{}/transactions", V2_BASE))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Created);
+
+    let resp = client.delete(format!("{}/categories/{}", V2_BASE, household)).dispatch().await;
+    assert_eq!(resp.status(), Status::Conflict);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// POST /transactions/{id}/restore
+// ═══════════════════════════════════════════════════════════════════════════════
+
+async fn account_balance(client: &rocket::local::asynchronous::Client, account_id: &str) -> i64 {
+    let resp = client.get(format!("{}/accounts/{}", V2_BASE, account_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    decrypt_i64(body["currentBalanceEnc"].as_str().unwrap())
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_restore_reapplies_deleted_transaction() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "Restore Acct", 100_000).await;
+    let category_id = common::entities::create_category(&client, "Restore Cat", "expense").await;
+    let tx_id = common::entities::create_transaction(&client, &account_id, &category_id, 2500, "2026-03-10").await;
+    let resp = client.delete(format!("{}/transactions/{}", V2_BASE, tx_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+    assert_eq!(account_balance(&client, &account_id).await, 100_000);
+
+    let resp = client.post(format!("{}/transactions/{}/restore", V2_BASE, tx_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+    assert_eq!(body["id"], tx_id.as_str());
+    assert_eq!(decrypt_i64(body["amountEnc"].as_str().unwrap()), 2500);
+    assert_eq!(decrypt_string(body["descriptionEnc"].as_str().unwrap()), "Test transaction");
+    assert_eq!(body["date"], "2026-03-10");
+    assert_eq!(account_balance(&client, &account_id).await, 97_500);
+
+    let listed = get_tx_list(&client, &format!("{}/transactions/range?from=2026-03-01&to=2026-03-31", V2_BASE)).await;
+    assert!(listed.iter().any(|t| t["id"] == tx_id.as_str()), "restored transaction is listed again");
+
+    let history = get_tx_list(&client, &format!("{}/transactions/{}/history", V2_BASE, tx_id)).await;
+    let kinds: Vec<&str> = history.iter().map(|r| r["kind"].as_str().unwrap()).collect();
+    assert_eq!(kinds, ["original", "void", "restore"]);
+
+    // A restored transaction can be edited and deleted again.
+    let resp = client.delete(format!("{}/transactions/{}", V2_BASE, tx_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+    assert_eq!(account_balance(&client, &account_id).await, 100_000);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_restore_effective_transaction_returns_409() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "Restore Live Acct", 100_000).await;
+    let category_id = common::entities::create_category(&client, "Restore Live Cat", "expense").await;
+    let tx_id = common::entities::create_transaction(&client, &account_id, &category_id, 2500, "2026-03-10").await;
+
+    let resp = client.post(format!("{}/transactions/{}/restore", V2_BASE, tx_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::Conflict);
+    assert_eq!(account_balance(&client, &account_id).await, 97_500);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_restore_other_users_transaction_returns_404() {
+    let owner = test_client().await;
+    create_user_and_login(&owner).await;
+    let account_id = common::entities::create_account(&owner, "Restore Owner Acct", 100_000).await;
+    let category_id = common::entities::create_category(&owner, "Restore Owner Cat", "expense").await;
+    let tx_id = common::entities::create_transaction(&owner, &account_id, &category_id, 2500, "2026-03-10").await;
+    let resp = owner.delete(format!("{}/transactions/{}", V2_BASE, tx_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+
+    let other = test_client().await;
+    create_user_and_login(&other).await;
+    let resp = other.post(format!("{}/transactions/{}/restore", V2_BASE, tx_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::NotFound);
+
+    let resp = other.post(format!("{}/transactions/{}/restore", V2_BASE, Uuid::new_v4())).dispatch().await;
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// GET /transactions/{id}/history
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_history_labels_every_ledger_row() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+    let account_id = common::entities::create_account(&client, "Hist Acct", 100_000).await;
+    let category_id = common::entities::create_category(&client, "Hist Cat", "expense").await;
+    let tx_id = common::entities::create_transaction(&client, &account_id, &category_id, 5000, "2026-03-10").await;
+
+    let payload = serde_json::json!({
+        "transactionType": "Regular",
+        "date": "2026-03-11",
+        "description": "Corrected purchase",
+        "amount": 7000,
+        "fromAccountId": account_id,
+        "categoryId": category_id,
+        "vendorId": null
+    });
+    let resp = client
+        .put(format!("{}/transactions/{}", V2_BASE, tx_id))
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.delete(format!("{}/transactions/{}", V2_BASE, tx_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::NoContent);
+
+    let history = get_tx_list(&client, &format!("{}/transactions/{}/history", V2_BASE, tx_id)).await;
+    let kinds: Vec<&str> = history.iter().map(|r| r["kind"].as_str().unwrap()).collect();
+    assert_eq!(kinds, ["original", "reversal", "correction", "void"]);
+    let amounts: Vec<i64> = history.iter().map(|r| decrypt_i64(r["amountEnc"].as_str().unwrap())).collect();
+    assert_eq!(amounts, [5000, -5000, 7000, -7000]);
+    assert_eq!(decrypt_string(history[2]["descriptionEnc"].as_str().unwrap()), "Corrected purchase");
+    assert_eq!(history[2]["date"], "2026-03-11");
+    assert!(history.iter().all(|r| r["id"] == tx_id.as_str()));
+    assert!(history.windows(2).all(|w| w[0]["seq"].as_i64() < w[1]["seq"].as_i64()));
+    assert_eq!(history[1]["createdAt"], history[2]["createdAt"], "reversal and correction are written together");
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_history_of_other_users_transaction_returns_404() {
+    let owner = test_client().await;
+    create_user_and_login(&owner).await;
+    let account_id = common::entities::create_account(&owner, "Hist Owner Acct", 100_000).await;
+    let category_id = common::entities::create_category(&owner, "Hist Owner Cat", "expense").await;
+    let tx_id = common::entities::create_transaction(&owner, &account_id, &category_id, 5000, "2026-03-10").await;
+
+    let other = test_client().await;
+    create_user_and_login(&other).await;
+    let resp = other.get(format!("{}/transactions/{}/history", V2_BASE, tx_id)).dispatch().await;
+    assert_eq!(resp.status(), Status::NotFound);
+
+    let resp = other.get(format!("{}/transactions/{}/history", V2_BASE, Uuid::new_v4())).dispatch().await;
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_history_unauthenticated_returns_401() {
+    let client = test_client().await;
+
+    let resp = client.get(format!("{}/transactions/{}/history", V2_BASE, Uuid::new_v4())).dispatch().await;
+
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Cross-domain isolation
 // ═══════════════════════════════════════════════════════════════════════════════
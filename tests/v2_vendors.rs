@@ -111,6 +111,67 @@ async fn test_create_vendor_unauthenticated() {
     assert_eq!(resp.status(), Status::Unauthorized);
 }
 
+async fn post_vendor(client: &rocket::local::asynchronous::Client, name: &str) -> Status {
+    client
+        .post(format!("{}/vendors", V2_BASE))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({ "name": name, "description": null }).to_string())
+        .dispatch()
+        .await
+        .status()
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_create_vendor_duplicate_name_conflict() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+
+    common::entities::create_vendor(&client, "Corner Shop").await;
+
+    assert_eq!(post_vendor(&client, "Corner Shop").await, Status::Conflict);
+    assert_eq!(post_vendor(&client, "CORNER shop").await, Status::Conflict);
+    assert_eq!(post_vendor(&client, "Corner Shop 2").await, Status::Created);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_create_vendor_same_name_as_other_user() {
+    let client_a = test_client().await;
+    create_user_and_login(&client_a).await;
+    common::entities::create_vendor(&client_a, "Shared Name").await;
+
+    let client_b = test_client().await;
+    create_user_and_login(&client_b).await;
+    assert_eq!(post_vendor(&client_b, "Shared Name").await, Status::Created);
+}
+
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_duplicate_name_detected_for_vendor_without_blind_index() {
+    let client = test_client().await;
+    let (user_id, _email) = create_user_and_login(&client).await;
+    let vendor_id = common::entities::create_vendor(&client, "Old Vendor").await;
+
+    // Rows written before blind indexes existed have no name_bidx.
+    let url = std::env::var("DATABASE_URL").unwrap_or_else(|_| common::TEST_DB_URL.to_string());
+    let pool = sqlx::PgPool::connect(&url).await.expect("connect to test db");
+    sqlx::query("UPDATE vendor SET name_bidx = NULL WHERE user_id = $1")
+        .bind(Uuid::parse_str(&user_id).unwrap())
+        .execute(&pool)
+        .await
+        .expect("clear blind index");
+
+    assert_eq!(post_vendor(&client, "old vendor").await, Status::Conflict);
+
+    let (bidx,): (Option<Vec<u8>>,) = sqlx::query_as("SELECT name_bidx FROM vendor WHERE id = $1")
+        .bind(Uuid::parse_str(&vendor_id).unwrap())
+        .fetch_one(&pool)
+        .await
+        .expect("read blind index");
+    assert!(bidx.is_some(), "blind index should be backfilled");
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // GET /vendors (list)
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -262,6 +323,27 @@ async fn test_update_vendor_persists_via_get() {
     assert_eq!(decrypt_string(vendor["descriptionEnc"].as_str().unwrap()), "New description");
 }
 
+#[rocket::async_test]
+#[ignore = "requires database"]
+async fn test_update_vendor_to_existing_name_conflict() {
+    let client = test_client().await;
+    create_user_and_login(&client).await;
+
+    common::entities::create_vendor(&client, "Bakery").await;
+    let vendor_id = common::entities::create_vendor(&client, "Butcher").await;
+
+    let rename = |name: &'static str| {
+        client
+            .put(format!("{}/vendors/{}", V2_BASE, vendor_id))
+            .header(ContentType::JSON)
+            .body(serde_json::json!({ "name": name, "description": null }).to_string())
+            .dispatch()
+    };
+    assert_eq!(rename("bakery").await.status(), Status::Conflict);
+    // Keeping its own name is not a conflict.
+    assert_eq!(rename("BUTCHER").await.status(), Status::Ok);
+}
+
 #[rocket::async_test]
 #[ignore = "requires database"]
 async fn test_update_vendor_not_found() {